@@ -0,0 +1,31 @@
+#![feature(test)]
+
+extern crate phoebe;
+extern crate test;
+
+use phoebe::Interpreter;
+use test::Bencher;
+
+fn run(interpreter: &mut Interpreter, source: &str) -> Vec<u8> {
+    let mut input = source.as_bytes();
+    let mut output = Vec::new();
+    let mut error = Vec::new();
+    interpreter
+        .repl(&mut input, &mut output, &mut error, false)
+        .unwrap();
+    assert!(error.is_empty(), "{}", String::from_utf8_lossy(&error));
+    output
+}
+
+/// A hundred nested calls, each of which builds a stack frame,
+/// evaluates an `if` in its parent's environment and calls two
+/// builtins.
+#[bench]
+fn nested_calls(b: &mut Bencher) {
+    let mut interpreter = Interpreter::new();
+    run(
+        &mut interpreter,
+        "(defun count-down (n) (if (= n 0) 'done (count-down (- n 1))))",
+    );
+    b.iter(|| assert_eq!(run(&mut interpreter, "(count-down 100)"), b"done\n"));
+}
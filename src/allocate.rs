@@ -8,10 +8,17 @@
 use crate::gc::{self, GarbageCollected};
 use crate::types::{ExpandedObject, Object};
 use std::{
+    cell::Cell,
     sync::{self, atomic, mpsc, Mutex},
     thread,
 };
 
+/// The number of heap objects every thread has allocated.
+static OBJECTS_ALLOCATED: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+/// The number of heap objects the garbage collector has deallocated.
+static OBJECTS_FREED: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
 /// The allocator's stack size, in bytes. This doesn't need to be
 /// particularly large; the 2MiB default is excessive.
 const ALLOCATOR_THREAD_STACK_SIZE: usize = 16 * 1024;
@@ -38,7 +45,7 @@ lazy_static! {
     /// we don't want any thread which allocates anything to
     /// block. The solution is a special allocator thread
     static ref JUST_ALLOCATED: Mutex<mpsc::Sender<Object>> = {
-        let (sender, receiver) = mpsc::channel();
+        let (sender, receiver) = mpsc::channel::<Object>();
         thread::Builder::new()
             .name("Allocator".to_owned())
             .stack_size(ALLOCATOR_THREAD_STACK_SIZE)
@@ -46,6 +53,7 @@ lazy_static! {
                 for o in receiver.iter() {
                     let ct = {
                         let mut alloced = ALLOCED_OBJECTS.lock().unwrap();
+                        o.whiten(gc::white());
                         alloced.push(o);
                         alloced.len()
                     };
@@ -71,15 +79,38 @@ thread_local! {
     static JUST_ALLOCATED_SENDER: mpsc::Sender<Object> = {
         JUST_ALLOCATED.lock().unwrap().clone()
     };
+    static ALLOCATION_COUNT: Cell<usize> = { Cell::new(0) };
 }
 
 /// Every time we allocate an `Object` with heap data, we call
 /// `add_to_alloced` on the new `Object`. That puts it into the
 /// `ALLOCED_OBJECTS` so that the garbage collector can find it.
 pub fn add_to_alloced(obj: Object) {
+    ALLOCATION_COUNT.with(|c| c.set(c.get() + 1));
+    OBJECTS_ALLOCATED.fetch_add(1, atomic::Ordering::Relaxed);
     JUST_ALLOCATED_SENDER.with(|s| s.send(obj).unwrap());
 }
 
+/// The number of heap objects this thread has allocated.
+pub fn allocation_count() -> usize {
+    ALLOCATION_COUNT.with(Cell::get)
+}
+
+/// The number of heap objects every thread has allocated.
+pub fn total_allocation_count() -> usize {
+    OBJECTS_ALLOCATED.load(atomic::Ordering::Relaxed)
+}
+
+/// The number of heap objects which have been allocated and not yet
+/// deallocated, including any which haven't reached
+/// `ALLOCED_OBJECTS`.
+pub fn live_object_count() -> usize {
+    // Every object is counted as allocated before it's counted as
+    // freed, so read the frees first.
+    let freed = OBJECTS_FREED.load(atomic::Ordering::Relaxed);
+    total_allocation_count().saturating_sub(freed)
+}
+
 #[derive(Fail, Debug)]
 /// Represents errors that may occur while deallocating an object.
 ///
@@ -101,11 +132,20 @@ pub unsafe fn deallocate(obj: Object) -> Result<(), DeallocError> {
             Err(DeallocError::ImmediateType)?
         }
         ExpandedObject::Symbol(s) => GarbageCollected::deallocate(s),
+        ExpandedObject::String(s) => GarbageCollected::deallocate(s),
         ExpandedObject::Cons(c) => GarbageCollected::deallocate(c),
         ExpandedObject::Namespace(n) => GarbageCollected::deallocate(n),
         ExpandedObject::HeapObject(h) => GarbageCollected::deallocate(h),
+        ExpandedObject::Vector(v) => GarbageCollected::deallocate(v),
+        ExpandedObject::HashTable(h) => GarbageCollected::deallocate(h),
+        ExpandedObject::Ratio(r) => GarbageCollected::deallocate(r),
+        ExpandedObject::Structure(s) => GarbageCollected::deallocate(s),
+        ExpandedObject::Bytes(b) => GarbageCollected::deallocate(b),
+        ExpandedObject::WeakRef(w) => GarbageCollected::deallocate(w),
+        ExpandedObject::Native(n) => GarbageCollected::deallocate(n),
         ExpandedObject::Function(f) => GarbageCollected::deallocate(f),
         ExpandedObject::QuietError(e) => GarbageCollected::deallocate(e),
     }
+    OBJECTS_FREED.fetch_add(1, atomic::Ordering::Relaxed);
     Ok(())
 }
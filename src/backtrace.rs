@@ -0,0 +1,101 @@
+//! A record of which functions each thread is in the middle of
+//! calling, so that an error raised deep inside a program can be
+//! reported along with how it got there. `Function::apply` enters a
+//! frame for the duration of each call, and `backtrace` takes a
+//! snapshot of the current thread's frames, which stays valid after
+//! they've returned.
+
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::fmt;
+
+thread_local! {
+    /// The frames of every call this thread is inside of, outermost
+    /// first. The functions they belong to are alive for as long as
+    /// they're being called, and so are their names.
+    static ACTIVE_FRAMES: RefCell<Vec<ActiveFrame>> = { RefCell::new(Vec::new()) };
+}
+
+struct ActiveFrame {
+    name: Option<GcRef<Symbol>>,
+    arg_count: usize,
+}
+
+/// Held by `Function::apply` while it calls a function, and pops
+/// that function's frame when dropped.
+pub(crate) struct CallFrame {
+    _private: (),
+}
+
+impl CallFrame {
+    pub(crate) fn enter(name: Option<GcRef<Symbol>>, arg_count: usize) -> CallFrame {
+        ACTIVE_FRAMES.with(|f| f.borrow_mut().push(ActiveFrame { name, arg_count }));
+        CallFrame { _private: () }
+    }
+}
+
+impl Drop for CallFrame {
+    fn drop(&mut self) {
+        ACTIVE_FRAMES.with(|f| {
+            let _pop = f.borrow_mut().pop();
+            debug_assert!(_pop.is_some());
+        });
+    }
+}
+
+/// A single function call.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    /// The function's name, or `None` for a `lambda`.
+    pub name: Option<String>,
+    /// How many arguments it was passed.
+    pub arg_count: usize,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "{}", name)?,
+            None => write!(f, "[anonymous function]")?,
+        }
+        let plural = if self.arg_count == 1 { "" } else { "s" };
+        write!(f, " with {} argument{}", self.arg_count, plural)
+    }
+}
+
+/// The calls a thread was inside of when `backtrace` was called.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Backtrace {
+    /// Innermost first.
+    pub frames: Vec<Frame>,
+}
+
+impl fmt::Display for Backtrace {
+    /// One frame per line, innermost first, like:
+    ///
+    /// ```text
+    ///   0: inner with 2 arguments
+    ///   1: outer with 1 argument
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, frame) in self.frames.iter().enumerate() {
+            writeln!(f, "{:>3}: {}", i, frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// A snapshot of the current thread's active function calls.
+pub fn backtrace() -> Backtrace {
+    let frames = ACTIVE_FRAMES.with(|f| {
+        f.borrow()
+            .iter()
+            .rev()
+            .map(|frame| Frame {
+                name: frame.name.map(|name| format!("{}", *name)),
+                arg_count: frame.arg_count,
+            })
+            .collect()
+    });
+    Backtrace { frames }
+}
@@ -1,12 +1,120 @@
+extern crate atty;
+extern crate ctrlc;
 extern crate env_logger;
 extern crate phoebe;
 
+use atty::Stream;
+use phoebe::interrupt::interrupt_handle;
+use phoebe::load::{LoadError, Loader};
+use phoebe::repl::{Color, Colors};
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, stderr, stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Load each of `scripts` in order, returning the exit status: zero
+/// if every one loaded without errors.
+fn run_scripts(scripts: &[OsString]) -> i32 {
+    let loader = Loader::new();
+    let mut status = 0;
+    for script in scripts {
+        match loader.load_file(script) {
+            Ok(()) => (),
+            // The loader has already reported each erroring form,
+            // with its file and line.
+            Err(LoadError::Forms(_)) => status = 1,
+            Err(e) => {
+                eprintln!("{}: {}", Path::new(script).display(), e);
+                status = 1;
+            }
+        }
+    }
+    let _ = io::stdout().flush();
+    status
+}
+
+/// The file to load before the first prompt: the one `PHOEBE_INIT`
+/// names if it's set, or else `~/.phoeberc` if there is one.
+fn init_file() -> Option<PathBuf> {
+    match env::var_os("PHOEBE_INIT") {
+        Some(ref path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => env::var_os("HOME")
+            .map(|home| Path::new(&home).join(".phoeberc"))
+            .filter(|path| path.is_file()),
+    }
+}
+
+/// Load the init file, if there is one, into the global namespace, so
+/// that users can define helpers of their own for every session.
+/// Errors are reported, but the REPL starts anyway.
+fn load_init_file() {
+    if let Some(path) = init_file() {
+        match Loader::new().load_file(&path) {
+            Ok(()) | Err(LoadError::Forms(_)) => (),
+            Err(e) => eprintln!("{}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Color the prompt and errors only when they're printed to a
+/// terminal, unless `--no-color` was passed or `NO_COLOR` is set. The
+/// prompt is green unless `PHOEBE_PROMPT_COLOR` names another color.
+fn colors(no_color: bool) -> Colors {
+    if no_color || env::var_os("NO_COLOR").is_some() {
+        return Colors::default();
+    }
+    let prompt = env::var("PHOEBE_PROMPT_COLOR")
+        .ok()
+        .and_then(|name| Color::named(&name))
+        .unwrap_or(Color::Green);
+    let colors = Colors::with_prompt(prompt);
+    Colors {
+        prompt: colors.prompt.filter(|_| atty::is(Stream::Stdout)),
+        error: colors.error.filter(|_| atty::is(Stream::Stderr)),
+    }
+}
+
 fn main() {
     env_logger::init();
-    use std::io::{stderr, stdin, stdout};
+
+    let (flags, scripts): (Vec<OsString>, Vec<OsString>) = env::args_os()
+        .skip(1)
+        .partition(|arg| arg == "--no-color" || arg == "--no-banner");
+    let no_color = flags.iter().any(|flag| flag == "--no-color");
+    let banner = !flags.iter().any(|flag| flag == "--no-banner");
+    if !scripts.is_empty() {
+        process::exit(run_scripts(&scripts));
+    }
+
     let mut err = stderr();
-    let mut input = stdin();
     let mut output = stdout();
 
-    phoebe::repl::repl(&mut input, &mut output, &mut err, true).unwrap();
+    let colors = colors(no_color);
+    let mut interpreter = phoebe::Interpreter::new()
+        .with_banner(banner)
+        .with_colors(colors);
+    load_init_file();
+
+    // Ctrl-C interrupts whatever the REPL is evaluating, rather than
+    // killing the process. Ctrl-D at the prompt quits.
+    let handle = interrupt_handle();
+    if let Err(e) = ctrlc::set_handler(move || handle.interrupt()) {
+        eprintln!("Couldn't install the Ctrl-C handler: {}", e);
+    }
+
+    #[cfg(feature = "line-editing")]
+    {
+        let mut input = phoebe::repl::LineEditor::new().with_prompt_color(colors.prompt);
+        interpreter
+            .repl(&mut input, &mut output, &mut err, false)
+            .unwrap();
+    }
+    #[cfg(not(feature = "line-editing"))]
+    {
+        let mut input = io::stdin();
+        interpreter
+            .repl(&mut input, &mut output, &mut err, true)
+            .unwrap();
+    }
 }
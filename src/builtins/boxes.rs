@@ -0,0 +1,46 @@
+//! Boxes are mutable cells holding a single value. They're a built-in
+//! structure type, as if defined by `(defstruct box value)`, so
+//! `make-box`, `box-p` and `box-value` are also defined, and boxes
+//! print as `#S(box :value 1)`.
+
+use super::structures::{define_function, define_structure, structure_of_type};
+use crate::prelude::*;
+
+lazy_static! {
+    static ref BOX: GcRef<Symbol> = { symbol_lookup::make_symbol(b"box") };
+    static ref VALUE: GcRef<Symbol> = { symbol_lookup::make_symbol(b"value") };
+}
+
+pub fn make_box_builtins() {
+    // The list `(value)` is both the box type's slot names and the
+    // arglist of `box`.
+    let slot_names: List = [Object::from(*VALUE)].iter().cloned().collect();
+    define_structure(*BOX, slot_names).unwrap();
+    let constructor =
+        Function::make_closure(slot_names, vec![Object::from(slot_names)], |captured| {
+            let slot_names = List::try_convert_from(captured[0])?;
+            let value = *symbol_lookup::lookup_symbol(*VALUE)?;
+            Object::from(Structure::allocate(Structure::new(
+                *BOX,
+                slot_names,
+                vec![value],
+            )))
+        })
+        .unwrap();
+    define_function("box", constructor);
+
+    builtin_functions! {
+        // The value in `cell`, which `setf` can assign through.
+        "unbox" (cell) -> {
+            let mut cell = structure_of_type(*cell, *BOX)?;
+            Object::from(cell.ref_slot(0))
+        };
+        // Replace the value in `cell` with `value`, returning
+        // `value`.
+        "set-box!" (cell value) -> {
+            let mut cell = structure_of_type(*cell, *BOX)?;
+            cell.set_slot(0, *value);
+            *value
+        };
+    }
+}
@@ -0,0 +1,29 @@
+//! Builtin functions which convert and compare characters.
+
+use crate::prelude::*;
+
+pub fn make_character_builtins() {
+    builtin_functions! {
+        "char-code" (character) -> {
+            let character = char::try_convert_from(*character)?;
+            Object::from(character as i32)
+        };
+        // Returns `nil` if `code` is not a Unicode scalar value.
+        "code-char" (code) -> {
+            let code = i32::try_convert_from(*code)?;
+            match ::std::char::from_u32(code as u32) {
+                Some(c) if code >= 0 => Object::from(c),
+                _ => Object::nil(),
+            }
+        };
+        "char=" (character &rest others) -> {
+            let character = char::try_convert_from(*character)?;
+            for other in List::try_convert_from(*others)? {
+                if char::try_convert_from(other)? != character {
+                    return Object::from(false);
+                }
+            }
+            Object::from(true)
+        };
+    }
+}
@@ -0,0 +1,191 @@
+//! `defclass` and `make-instance`, a simple object system with single
+//! inheritance. Classes are a built-in structure type, as if defined
+//! by `(defstruct class name superclass slots)`, where `superclass` is
+//! a class or `nil` and `slots` lists the class's own slots after
+//! those it inherits. Each class is stored on its name's plist under
+//! `class`. Instances are structures whose type is the class's name,
+//! so they print as `#S(point :x 1 :y 2)` and are accessed with
+//! `slot-value`.
+
+use super::structures::{define_structure, make_structure, structure_of_type};
+use crate::gc::Rooted;
+use crate::prelude::*;
+
+lazy_static! {
+    static ref CLASS: GcRef<Symbol> = { symbol_lookup::make_symbol(b"class") };
+}
+
+/// The class named `name`, if `defclass` has defined one.
+pub(super) fn find_class(name: GcRef<Symbol>) -> Option<GcRef<Structure>> {
+    name.get(Object::from(*CLASS))
+        .and_then(<GcRef<Structure>>::maybe_from)
+}
+
+/// `obj` as a class, or the class it names.
+pub(super) fn class_designated_by(obj: Object) -> Result<GcRef<Structure>, GcRef<Error>> {
+    match <GcRef<Symbol>>::maybe_from(obj) {
+        Some(name) => find_class(name).ok_or_else(|| Error::type_error(*CLASS)),
+        None => structure_of_type(obj, *CLASS),
+    }
+}
+
+/// The index of the slot named `slot` in `structure`.
+fn slot_index(structure: GcRef<Structure>, slot: GcRef<Symbol>) -> Result<usize, GcRef<Error>> {
+    structure
+        .slot_names()
+        .position(|s| s == Object::from(slot))
+        .ok_or_else(|| {
+            EvaluatorError::NoSuchSlot {
+                object: Object::from(structure),
+                slot,
+            }
+            .into()
+        })
+}
+
+/// True if `class` is `superclass` or inherits from it.
+pub(super) fn subclassp(class: GcRef<Structure>, superclass: GcRef<Structure>) -> bool {
+    let mut class = Object::from(class);
+    while let Some(c) = <GcRef<Structure>>::maybe_from(class) {
+        if c == superclass {
+            return true;
+        }
+        class = c.slots()[1];
+    }
+    false
+}
+
+/// Define the class `name`, whose instances have the slots of
+/// `superclass`, if any, followed by `slots`.
+pub(super) fn define_class(
+    mut name: GcRef<Symbol>,
+    superclass: Option<GcRef<Structure>>,
+    slots: List,
+) -> Result<GcRef<Structure>, GcRef<Error>> {
+    let mut all_slots = match superclass {
+        Some(superclass) => List::try_convert_from(superclass.slots()[2])?.collect::<Vec<Object>>(),
+        None => Vec::new(),
+    };
+    for slot in slots {
+        <GcRef<Symbol>>::try_convert_from(slot)?;
+        if !all_slots.contains(&slot) {
+            all_slots.push(slot);
+        }
+    }
+    let all_slots: List = all_slots.into_iter().collect();
+    let class = make_structure(
+        *CLASS,
+        vec![
+            Object::from(name),
+            superclass.map(Object::from).unwrap_or_else(Object::nil),
+            Object::from(all_slots),
+        ],
+    )?;
+    name.put(Object::from(*CLASS), Object::from(class));
+    Ok(class)
+}
+
+/// An instance of `class` whose slots are initialized from the
+/// alternating keywords and values in `initargs`, and otherwise hold
+/// `nil`.
+pub(super) fn make_instance(
+    class: GcRef<Structure>,
+    initargs: List,
+) -> Result<GcRef<Structure>, GcRef<Error>> {
+    let name = <GcRef<Symbol>>::try_convert_from(class.slots()[0])?;
+    let slot_names = List::try_convert_from(class.slots()[2])?;
+    let mut instance = Structure::allocate(Structure::new(
+        name,
+        slot_names,
+        vec![Object::nil(); slot_names.count()],
+    ));
+    // Nothing else refers to the instance until it's returned, and
+    // interning the slots' names may allocate.
+    let _rooted = Rooted::new(instance);
+    let mut pairs = initargs;
+    let mut initialized = Vec::new();
+    while let Some(key) = pairs.next() {
+        let value = match pairs.next() {
+            Some(value) => value,
+            None => {
+                return Err(EvaluatorError::OddArgCount {
+                    found: initargs.count(),
+                }
+                .into());
+            }
+        };
+        let slot = symbol_lookup::make_symbol(Keyword::try_convert_from(key)?.name());
+        // As with `&key` arguments, the leftmost value for a slot
+        // wins.
+        let index = slot_index(instance, slot)?;
+        if !initialized.contains(&index) {
+            initialized.push(index);
+            instance.set_slot(index, value);
+        }
+    }
+    Ok(instance)
+}
+
+pub fn make_class_builtins() {
+    let slot_names: List = [&b"name"[..], b"superclass", b"slots"]
+        .iter()
+        .map(|name| Object::from(symbol_lookup::make_symbol(name)))
+        .collect();
+    define_structure(*CLASS, slot_names).unwrap();
+
+    special_forms! {
+        // `(defclass point3 (point) (z))` defines the class `point3`,
+        // whose instances have the slots of `point` followed by `z`.
+        // Phoebe has only single inheritance, so the list of
+        // superclasses may name at most one. Returns the class.
+        "defclass" (name superclasses slots) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            let mut superclasses = List::try_convert_from(*superclasses)?;
+            if superclasses.count() > 1 {
+                return EvaluatorError::MultipleSuperclasses { class: name }.into();
+            }
+            let superclass = match superclasses.next() {
+                Some(superclass) => Some(class_designated_by(superclass)?),
+                None => None,
+            };
+            Object::from(define_class(name, superclass, List::try_convert_from(*slots)?)?)
+        };
+    }
+
+    builtin_functions! {
+        // A class or `nil`.
+        "find-class" (name) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            find_class(name).map(Object::from).unwrap_or_else(Object::nil)
+        };
+        // The class `obj` is an instance of, or `nil` if it isn't an
+        // instance.
+        "class-of" (obj) -> {
+            match <GcRef<Structure>>::maybe_from(*obj) {
+                Some(s) => find_class(s.name()).map(Object::from).unwrap_or_else(Object::nil),
+                None => Object::nil(),
+            }
+        };
+        // True if `class` is `superclass` or inherits from it. Either
+        // may be a class or a class name.
+        "subclassp" (class superclass) -> {
+            let superclass = class_designated_by(*superclass)?;
+            Object::from(subclassp(class_designated_by(*class)?, superclass))
+        };
+        // `(make-instance 'point :x 1)` makes an instance of `point`
+        // whose slot `x` holds 1 and whose other slots hold `nil`.
+        // `class` may be a class or a class name.
+        "make-instance" (class &rest initargs) -> {
+            let class = class_designated_by(*class)?;
+            Object::from(make_instance(class, List::try_convert_from(*initargs)?)?)
+        };
+        // The value of `obj`'s slot named `slot`, which `setf` can
+        // assign through. `obj` may be any structure.
+        "slot-value" (obj slot) -> {
+            let mut structure = <GcRef<Structure>>::try_convert_from(*obj)?;
+            let slot = <GcRef<Symbol>>::try_convert_from(*slot)?;
+            let index = slot_index(structure, slot)?;
+            Object::from(structure.ref_slot(index))
+        };
+    }
+}
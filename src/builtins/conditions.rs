@@ -0,0 +1,118 @@
+//! `define-condition`, which defines structured error types, and
+//! `handler-case`, which catches errors by type. Condition types are
+//! classes which inherit from the built-in class `condition`, and a
+//! condition is an `Error` holding an instance of one. Catching a
+//! condition type also catches its subtypes.
+
+use super::classes::{class_designated_by, define_class, find_class, make_instance, subclassp};
+use crate::gc::Rooted;
+use crate::prelude::*;
+use std::ops::Try;
+
+lazy_static! {
+    static ref CONDITION: GcRef<Symbol> = { symbol_lookup::make_symbol(b"condition") };
+}
+
+/// The class every condition type inherits from.
+fn root() -> GcRef<Structure> {
+    find_class(*CONDITION).unwrap()
+}
+
+/// True if `error` is of the type `type_name`, which is either `t`,
+/// the name of a builtin error like `type-error`, or a condition type.
+/// Every error is a `condition`.
+fn error_is_of_type(error: &Error, type_name: Object) -> bool {
+    if type_name == Object::t() || type_name == Object::from(*CONDITION) {
+        return true;
+    }
+    let type_name = match <GcRef<Symbol>>::maybe_from(type_name) {
+        Some(t) => t,
+        None => return false,
+    };
+    let name = error.name();
+    if name == type_name {
+        return true;
+    }
+    match (find_class(name), find_class(type_name)) {
+        (Some(class), Some(superclass)) => subclassp(class, superclass),
+        _ => false,
+    }
+}
+
+pub fn make_condition_builtins() {
+    define_class(*CONDITION, None, List::nil()).unwrap();
+
+    special_forms! {
+        // `(define-condition file-missing (file-error) (path))`
+        // defines the condition type `file-missing`, with the slots
+        // of `file-error` followed by `path`. With no parent, the
+        // type's parent is `condition`. Returns the type's class.
+        "define-condition" (name parents slots) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            let mut parents = List::try_convert_from(*parents)?;
+            if parents.count() > 1 {
+                return EvaluatorError::MultipleSuperclasses { class: name }.into();
+            }
+            let parent = match parents.next() {
+                Some(parent) => class_designated_by(parent)?,
+                None => root(),
+            };
+            if !subclassp(parent, root()) {
+                return Error::type_error(*CONDITION).into();
+            }
+            Object::from(define_class(name, Some(parent), List::try_convert_from(*slots)?)?)
+        };
+        // `(handler-case form (type-error (e) ...) (file-missing () ...))`
+        // evaluates `form`. If it signals an error, the body of the
+        // first clause whose type the error is of is evaluated, with
+        // the error bound to the clause's variable, if it names one.
+        // Errors which no clause matches are signalled again.
+        "handler-case" (form &rest clauses) -> {
+            let form = *form;
+            let clauses = List::try_convert_from(*clauses)?;
+            let error = match symbol_lookup::in_parent_env(|| form.evaluate()).into_result() {
+                Ok(o) => return o,
+                Err(e) => e,
+            };
+            // Nothing refers to the error until a clause binds it.
+            let error = Rooted::new(error);
+            for clause in clauses {
+                let mut clause = List::try_convert_from(clause)?;
+                let type_name = clause.next().unwrap_or_else(Object::nil);
+                if !error_is_of_type(&error, type_name) {
+                    continue;
+                }
+                let mut var = List::try_convert_from(clause.next().unwrap_or_else(Object::nil))?;
+                let bindings = match var.next() {
+                    Some(var) => vec![(var.try_convert_into()?, Object::quiet_error(*error))],
+                    None => Vec::new(),
+                };
+                let env = symbol_lookup::in_parent_env(|| {
+                    Object::from(Namespace::create_let_env(&bindings))
+                });
+                let env = <GcRef<Namespace>>::try_convert_from(env)?;
+                return symbol_lookup::with_env(env, || {
+                    let mut res = Object::nil();
+                    for body_clause in clause {
+                        res = body_clause.evaluate()?;
+                    }
+                    res
+                });
+            }
+            (*error).into()
+        };
+    }
+
+    builtin_functions! {
+        // `(make-condition 'file-missing :path 'foo)` is an error,
+        // like the ones `error` makes, which `throw` can signal.
+        "make-condition" (name &rest initargs) -> {
+            let class = class_designated_by(*name)?;
+            if !subclassp(class, root()) {
+                return Error::type_error(*CONDITION).into();
+            }
+            let condition = make_instance(class, List::try_convert_from(*initargs)?)?;
+            Object::quiet_error(Error::condition(condition))
+        };
+    }
+}
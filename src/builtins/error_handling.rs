@@ -1,9 +1,27 @@
 //! Builtin functions and special forms related to throwing, catching
 //! and handling errors.
 
+use crate::debug_mode::debug_mode;
 use crate::prelude::*;
 use std::ops::Try;
 
+/// Evaluate `form` in the caller's environment, and if it is `nil`,
+/// signal an `assertion-error` holding `form` and the evaluated
+/// `message`.
+fn check_assertion(form: Object, message: Object) -> Object {
+    symbol_lookup::in_parent_env(|| {
+        if bool::from(form.evaluate()?) {
+            return Object::nil();
+        }
+        let message = if message.definedp() {
+            message.evaluate()?
+        } else {
+            Object::nil()
+        };
+        EvaluatorError::AssertionFailed { form, message }.into()
+    })
+}
+
 pub fn make_error_builtins() {
     builtin_functions! {
         "throw" (error) -> {
@@ -21,8 +39,32 @@ pub fn make_error_builtins() {
         "not-a-reference-error" () -> {
             Object::quiet_error(Error::cannot_be_referenced())
         };
+        // The type of `error`, like `type-error`, or the name it was
+        // made with.
+        "error-name" (error) -> {
+            let error = <GcRef<Error>>::try_convert_from(*error)?;
+            Object::from(error.name())
+        };
+        // The object `error` was made with, or for a condition, the
+        // structure holding its slots. `nil` for builtin errors.
+        "error-payload" (error) -> {
+            let error = <GcRef<Error>>::try_convert_from(*error)?;
+            error.payload().unwrap_or_else(Object::nil)
+        };
     }
     special_forms! {
+        "assert" (form &optional message) -> {
+            check_assertion(*form, *message)
+        };
+        // Like `assert`, but outside of debug mode, `form` isn't even
+        // evaluated.
+        "debug-assert" (form &optional message) -> {
+            if debug_mode() {
+                check_assertion(*form, *message)
+            } else {
+                Object::nil()
+            }
+        };
         "catch-error" (r#try bind &rest catch) -> {
             let bind: GcRef<Symbol> = (*bind).try_convert_into()?;
             let catch = List::try_convert_from(*catch)?;
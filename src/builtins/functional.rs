@@ -0,0 +1,97 @@
+//! Builtin functions which make and combine other functions.
+//!
+//! Functions returned by these builtins are closures made with
+//! `Function::make_closure`. Each takes any number of arguments,
+//! which its body finds bound to `args`.
+
+use crate::prelude::*;
+use crate::types::function::REST;
+
+lazy_static! {
+    static ref ARGS: GcRef<Symbol> = { symbol_lookup::make_symbol(b"args") };
+}
+
+/// The arguments a closure made by `make_variadic_closure` was
+/// called with.
+fn closure_args() -> Result<List, GcRef<Error>> {
+    let args = symbol_lookup::lookup_symbol(*ARGS)?;
+    Ok(List::try_convert_from(*args)?)
+}
+
+/// Allocate a closure over `captured` which takes any number of
+/// arguments and returns the result of `body`.
+fn make_variadic_closure<F>(captured: Vec<Object>, body: F) -> Object
+where
+    F: Fn(&[Object]) -> Object + 'static,
+{
+    let arglist: List = [Object::from(*REST), Object::from(*ARGS)]
+        .iter()
+        .cloned()
+        .collect();
+    Object::from(Function::allocate(Function::make_closure(
+        arglist, captured, body,
+    )?))
+}
+
+/// Call the function `function` on `args`, which have already been
+/// evaluated.
+fn apply(function: Object, args: List) -> Object {
+    <GcRef<Function>>::try_convert_from(function)?.apply(args)
+}
+
+pub fn make_functional_builtins() {
+    builtin_functions! {
+        // Calls a function held in a variable, which is how Lisp-2
+        // mode calls functions which aren't in function cells.
+        "funcall" (function &rest args) -> {
+            apply(*function, List::try_convert_from(*args)?)
+        };
+        "identity" (obj) -> {
+            *obj
+        };
+        "constantly" (value) -> {
+            make_variadic_closure(vec![*value], |captured| captured[0])
+        };
+        "complement" (function) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            make_variadic_closure(vec![Object::from(function)], |captured| {
+                Object::from(!bool::from(apply(captured[0], closure_args()?)?))
+            })
+        };
+        // `((compose f g h) x)` is `(f (g (h x)))`. The last function
+        // receives all of the arguments; each of the others receives
+        // the result of the one after it.
+        "compose" (&rest functions) -> {
+            let functions = List::try_convert_from(*functions)?;
+            for function in functions {
+                <GcRef<Function>>::try_convert_from(function)?;
+            }
+            make_variadic_closure(functions.collect(), |captured| {
+                let mut args = closure_args()?;
+                let mut functions = captured.iter().rev();
+                let mut res = match functions.next() {
+                    Some(&function) => apply(function, args)?,
+                    None => return args.next().unwrap_or_else(Object::nil),
+                };
+                for &function in functions {
+                    res = apply(function, [res].iter().cloned().collect())?;
+                }
+                res
+            })
+        };
+        // `((partial f a b) c d)` is `(f a b c d)`.
+        "partial" (function &rest args) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            let mut captured = vec![Object::from(function)];
+            captured.extend(List::try_convert_from(*args)?);
+            make_variadic_closure(captured, |captured| {
+                let args: List = captured[1..]
+                    .iter()
+                    .cloned()
+                    .chain(closure_args()?)
+                    .collect();
+                apply(captured[0], args)
+            })
+        };
+    }
+}
@@ -0,0 +1,126 @@
+//! `defgeneric` and `defmethod`, which define functions that dispatch
+//! on the types of their arguments. A generic function is bound like
+//! any other function, to a closure which looks up the most specific
+//! applicable method in its `GenericFunction` and applies it. The
+//! `GenericFunction` is also stored on its name's plist, where
+//! `defmethod` finds it, under an uninterned symbol, so that Phoebe
+//! code can't replace it with something else.
+
+use crate::prelude::*;
+use crate::types::generic_function::GenericFunction;
+
+lazy_static! {
+    static ref GENERIC_FUNCTION: GcRef<Symbol> =
+        { symbol_lookup::make_uninterned_symbol(b"generic-function") };
+}
+
+/// Mark the plist key, which, being uninterned, nothing else keeps
+/// alive. Called by `gc::roots::gc_mark_roots`.
+pub(crate) fn gc_mark_plist_key(m: bool) {
+    GENERIC_FUNCTION.gc_mark(m);
+}
+
+/// The `GenericFunction` in `table`, or a `type-error` if it holds
+/// something else.
+fn generic_function(table: &mut Native) -> Result<&mut GenericFunction, ConversionError> {
+    table
+        .downcast_mut::<GenericFunction>()
+        .ok_or_else(|| ConversionError::wanted(GenericFunction::type_name()))
+}
+
+/// Bind `name` to a new generic function with no methods, which takes
+/// the arguments named by `params`.
+fn define_generic(mut name: GcRef<Symbol>, params: List) -> Result<Object, GcRef<Error>> {
+    let table = Native::allocate(Native::new(GenericFunction::new(name)));
+    name.put(Object::from(*GENERIC_FUNCTION), Object::from(table));
+
+    let captured = vec![Object::from(table), Object::from(params)];
+    let function = Function::make_closure(params, captured, |captured| {
+        let mut table = <GcRef<Native>>::try_convert_from(captured[0])?;
+        let params = List::try_convert_from(captured[1])?;
+        let mut args = Vec::new();
+        for param in params {
+            args.push(*symbol_lookup::lookup_symbol(param.try_convert_into()?)?);
+        }
+        let table = generic_function(&mut table)?;
+        match table.dispatch(&args) {
+            Some(method) => method.apply(args.iter().cloned().collect()),
+            None => EvaluatorError::NoApplicableMethod {
+                name: table.name(),
+                args: args.iter().cloned().collect(),
+            }
+            .into(),
+        }
+    })?;
+    let function = Object::from(Function::allocate(function.with_name(name)));
+    symbol_lookup::make_function_from_global_namespace(name).set(function);
+    Ok(function)
+}
+
+pub fn make_generic_function_builtins() {
+    // The collector marks the key from its own thread, which mustn't
+    // be the one to allocate it.
+    lazy_static::initialize(&GENERIC_FUNCTION);
+    special_forms! {
+        // `(defgeneric area (shape))` binds `area` to a generic
+        // function of one argument with no methods, discarding any
+        // methods an earlier definition had. Generic functions
+        // dispatch on every argument, so `params` may not contain
+        // `&optional`, `&rest` or `&key`.
+        "defgeneric" (name params) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            let params = List::try_convert_from(*params)?;
+            for param in params {
+                <GcRef<Symbol>>::try_convert_from(param)?;
+            }
+            define_generic(name, params)?
+        };
+        // `(defmethod area ((shape circle)) ...)` adds a method to
+        // `area` for when its argument is of type `circle`, replacing
+        // any method with the same specializers. Parameters written
+        // as bare symbols, or specialized on `t`, accept any type. If
+        // `name` isn't yet a generic function, it's defined as if by
+        // `defgeneric`. Returns the method.
+        "defmethod" (name params &rest body) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            let mut arglist = Vec::new();
+            let mut specializers = Vec::new();
+            for param in List::try_convert_from(*params)? {
+                let (param, specializer) = match List::maybe_from(param) {
+                    Some(mut pair) if !param.nilp() => {
+                        let param = pair.next().unwrap_or_else(Object::nil);
+                        let specializer = pair.next().unwrap_or_else(Object::nil);
+                        (param, specializer)
+                    }
+                    _ => (param, Object::t()),
+                };
+                arglist.push(Object::from(<GcRef<Symbol>>::try_convert_from(param)?));
+                specializers.push(if specializer == Object::t() {
+                    None
+                } else {
+                    Some(<GcRef<Symbol>>::try_convert_from(specializer)?)
+                });
+            }
+            let arglist: List = arglist.into_iter().collect();
+
+            let method = Function::allocate(
+                Function::make_lambda(
+                    arglist,
+                    List::try_convert_from(*body)?,
+                    symbol_lookup::scope_for_a_new_function(),
+                )?.with_name(name)
+            );
+
+            let table = match name.get(Object::from(*GENERIC_FUNCTION)) {
+                Some(table) => table,
+                None => {
+                    define_generic(name, arglist)?;
+                    name.get(Object::from(*GENERIC_FUNCTION)).unwrap()
+                }
+            };
+            let mut table = <GcRef<Native>>::try_convert_from(table)?;
+            generic_function(&mut table)?.add_method(specializers, method);
+            Object::from(method)
+        };
+    }
+}
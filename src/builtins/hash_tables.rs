@@ -0,0 +1,75 @@
+//! Builtin functions which make and manipulate hash tables.
+
+use crate::evaluator::should_eval_to_reference;
+use crate::gc::roots;
+use crate::prelude::*;
+use crate::types::hash_table::Test;
+
+pub fn make_hash_table_builtins() {
+    builtin_functions! {
+        // `test` names the function keys are compared with, either
+        // `eql`, the default, or `equal`.
+        "make-hash-table" (&key test) -> {
+            let test = if (*test).definedp() {
+                let name = <GcRef<Symbol>>::try_convert_from(*test)?;
+                match Test::from_name(name) {
+                    Some(test) => test,
+                    None => {
+                        return Error::type_error(
+                            symbol_lookup::make_symbol(b"hash-table-test")
+                        ).into();
+                    }
+                }
+            } else {
+                Test::Eql
+            };
+            Object::from(HashTable::allocate(HashTable::new(test)))
+        };
+        // Under `setf`, returns a reference to the value associated
+        // with `key`, adding `key` to the table if it is absent, so
+        // that `(setf (gethash key table) value)` works.
+        "gethash" (key table &optional default) -> {
+            let mut table = <GcRef<HashTable>>::try_convert_from(*table)?;
+            if should_eval_to_reference() {
+                Object::from(table.ref_value(*key))
+            } else if let Some(value) = table.get(*key) {
+                value
+            } else if (*default).definedp() {
+                *default
+            } else {
+                Object::nil()
+            }
+        };
+        // Returns `t` if `key` was present, or `nil` otherwise.
+        "remhash" (key table) -> {
+            let mut table = <GcRef<HashTable>>::try_convert_from(*table)?;
+            Object::from(table.remove(*key).is_some())
+        };
+        "hash-table-count" (table) -> {
+            let table = <GcRef<HashTable>>::try_convert_from(*table)?;
+            Object::from(table.len() as i32)
+        };
+        // Calls `function` on each key in `table` and its value, in
+        // no particular order. Keys which `function` adds or removes
+        // do not change which keys it is called on.
+        "maphash" (function table) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            let table = <GcRef<HashTable>>::try_convert_from(*table)?;
+            // `function` may remove pairs it hasn't been called on yet
+            // from the table, so they're rooted until it has.
+            let pairs = table.pairs();
+            for &(key, value) in &pairs {
+                roots::push_temporary_root(key);
+                roots::push_temporary_root(value);
+            }
+            let res = (|| {
+                for &(key, value) in &pairs {
+                    function.apply([key, value].iter().cloned().collect())?;
+                }
+                Object::nil()
+            })();
+            roots::pop_temporary_roots(2 * pairs.len());
+            res
+        };
+    }
+}
@@ -0,0 +1,155 @@
+//! Builtin functions and variables which describe the running
+//! Phoebe.
+
+use crate::allocate::allocation_count;
+use crate::documentation;
+use crate::gc::{self, gc_count};
+use crate::metrics::metrics;
+use crate::prelude::*;
+use crate::standard_streams::{with_standard_error, with_standard_output};
+use crate::types::graph;
+use crate::version::version_info;
+use std::io::Write;
+use std::time::Instant;
+
+pub fn make_introspection_builtins() {
+    let features: List = version_info()
+        .features
+        .iter()
+        .map(|f| symbol_lookup::make_symbol(f.as_bytes()))
+        .collect();
+    symbol_lookup::add_to_global(
+        symbol_lookup::make_symbol(b"*features*"),
+        Object::from(features),
+    );
+
+    special_forms! {
+        // Reports how long evaluating `form` took to the standard
+        // error. The garbage collector runs in its own thread, so the
+        // collections it reports may have been triggered by other
+        // threads' allocations.
+        "time" (form) -> {
+            let form = *form;
+            let allocations = allocation_count();
+            let collections = gc_count();
+            let start = Instant::now();
+            let res = symbol_lookup::in_parent_env(|| form.evaluate());
+            let elapsed = start.elapsed();
+            with_standard_error(|e| {
+                writeln!(
+                    e,
+                    "Evaluation took {}.{:06} seconds of real time, {} allocations and {} garbage collections.",
+                    elapsed.as_secs(),
+                    elapsed.subsec_micros(),
+                    allocation_count() - allocations,
+                    gc_count() - collections,
+                )
+            })?;
+            res
+        };
+    }
+
+    builtin_functions! {
+        // Phoebe doesn't have strings yet, so the version is returned
+        // as a symbol.
+        "lisp-implementation-version" () -> {
+            Object::from(symbol_lookup::make_symbol(version_info().version.as_bytes()))
+        };
+        // An alist describing this build, like
+        // `((version . 0.1.0) (features phoebe threads))`. See
+        // `phoebe::version_info()`.
+        "version" () -> {
+            let info = version_info();
+            let features: List = info
+                .features
+                .iter()
+                .map(|f| Object::from(symbol_lookup::make_symbol(f.as_bytes())))
+                .collect();
+            let pairs: List = vec![
+                Object::from(Cons::allocate(Cons::new(
+                    Object::from(symbol_lookup::make_symbol(b"version")),
+                    Object::from(symbol_lookup::make_symbol(info.version.as_bytes())),
+                ))),
+                Object::from(Cons::allocate(Cons::new(
+                    Object::from(symbol_lookup::make_symbol(b"features")),
+                    Object::from(features),
+                ))),
+            ]
+            .into_iter()
+            .collect();
+            Object::from(pairs)
+        };
+        // An alist of the counters in `phoebe::metrics()`, like
+        // `((objects-live . 1234) (gc-count . 5) ...)`.
+        "runtime-stats" () -> {
+            let stats: List = metrics()
+                .pairs()
+                .iter()
+                .map(|&(name, value)| {
+                    Object::from(Cons::allocate(Cons::new(
+                        Object::from(symbol_lookup::make_symbol(name.as_bytes())),
+                        Object::from(value as i32),
+                    )))
+                })
+                .collect();
+            Object::from(stats)
+        };
+        // Run a complete garbage collection and return a list of the
+        // number of objects it freed and the number which remain.
+        "gc" () -> {
+            let summary = gc::collect();
+            let counts: List = vec![summary.freed as i32, summary.live as i32]
+                .into_iter()
+                .collect();
+            Object::from(counts)
+        };
+        // Prints a summary of `phoebe::gc::stats()`.
+        "room" () -> {
+            with_standard_output(|o| write!(o, "{}", gc::stats()))?;
+            Object::nil()
+        };
+        // The number of distinct heap objects reachable from `obj`,
+        // or with `:bytes t`, the bytes allocated for them.
+        "object-size" (obj &key bytes) -> {
+            let size = graph::size(*obj);
+            if (*bytes).definedp() && bool::from(*bytes) {
+                Object::from(size.bytes as i32)
+            } else {
+                Object::from(size.objects as i32)
+            }
+        };
+        // Documentation is usually the docstring a `defun` or
+        // `lambda` begins with, or the third argument to `defvar`,
+        // but any object can be attached with `set-documentation`.
+        "documentation" (name) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            documentation::documentation(name).unwrap_or_else(Object::nil)
+        };
+        "set-documentation" (name documentation) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            documentation::set_documentation(name, *documentation);
+            *documentation
+        };
+        "object-depth" (obj) -> {
+            Object::from(graph::depth(*obj) as i32)
+        };
+        // `nil` for anonymous functions.
+        "function-name" (function) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            function.name().map(Object::from).unwrap_or_else(Object::nil)
+        };
+        "function-arglist" (function) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            Object::from(function.arglist())
+        };
+        // `nil` for builtins, whose bodies are written in Rust.
+        "function-body" (function) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            function.body().map(Object::from).unwrap_or_else(Object::nil)
+        };
+        "function-environment" (function) -> {
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            Object::from(function.env())
+        };
+    }
+}
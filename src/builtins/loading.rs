@@ -0,0 +1,83 @@
+//! The `load` builtin, which evaluates a file with `load::Loader`, and
+//! the `require` builtin, which provides modules built into Phoebe.
+
+use super::scheme_compat::make_scheme_compat_builtins;
+use crate::gc::safepoint;
+use crate::load::Loader;
+use crate::prelude::*;
+use crate::types::pathname::path_designated_by;
+use std::path::PathBuf;
+use std::str;
+use std::sync::Mutex;
+
+/// Each module `require` can provide, and the function which defines
+/// its contents.
+const MODULES: &[(&[u8], fn())] = &[(b"scheme-compat", make_scheme_compat_builtins)];
+
+lazy_static! {
+    /// Held while providing a module, so that two threads can't both
+    /// provide it.
+    static ref REQUIRE_LOCK: Mutex<()> = { Mutex::new(()) };
+}
+
+/// Push `name` onto the list bound to `list` in the default global
+/// namespace.
+fn push_to_global_list(list: &[u8], name: GcRef<Symbol>) {
+    let mut place =
+        symbol_lookup::make_from_default_global_namespace(symbol_lookup::make_symbol(list));
+    let list = Object::from(Cons::allocate(Cons::new(Object::from(name), *place)));
+    place.set(list);
+}
+
+/// Provide the module `name`, returning `false` if it had already
+/// been provided.
+fn require(name: GcRef<Symbol>) -> Result<bool, EvaluatorError> {
+    // Another thread may be evaluating a module while it holds the
+    // lock.
+    let _lock = safepoint::blocking(|| REQUIRE_LOCK.lock().unwrap());
+    let modules =
+        symbol_lookup::make_from_default_global_namespace(symbol_lookup::make_symbol(b"*modules*"));
+    if let Some(mut loaded) = List::maybe_from(*modules) {
+        if loaded.any(|m| m == Object::from(name)) {
+            return Ok(false);
+        }
+    }
+    let provide = MODULES
+        .iter()
+        .find(|&&(module, _)| module == (*name).as_ref())
+        .map(|&(_, provide)| provide)
+        .ok_or(EvaluatorError::UnknownModule { name })?;
+    provide();
+    push_to_global_list(b"*modules*", name);
+    push_to_global_list(b"*features*", name);
+    Ok(true)
+}
+
+pub fn make_load_builtins() {
+    symbol_lookup::add_to_global(symbol_lookup::make_symbol(b"*modules*"), Object::nil());
+
+    builtin_functions! {
+        // `path` is a pathname or a string. For compatibility with
+        // code written before Phoebe had strings, it may also be a
+        // symbol, as in `(load (quote path/to/file.phoebe))`.
+        "load" (path) -> {
+            let path = match <GcRef<Symbol>>::maybe_from(*path) {
+                Some(sym) => match str::from_utf8((*sym).as_ref()) {
+                    Ok(p) => PathBuf::from(p),
+                    Err(_) => return Error::type_error(<GcRef<Symbol>>::type_name()).into(),
+                },
+                None => path_designated_by(*path)?,
+            };
+            symbol_lookup::with_env(symbol_lookup::global_env(), || {
+                Loader::new().load_file(&path)
+            })?;
+            Object::t()
+        };
+        // Returns `t` if the module was provided by this call, or
+        // `nil` if it already had been.
+        "require" (name) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            Object::from(require(name)?)
+        };
+    }
+}
@@ -1,3 +1,12 @@
+use crate::prelude::*;
+
+/// The symbol naming the argument `name` of a builtin. Arguments are
+/// Rust identifiers, which can't contain `-`, so `_` stands in for
+/// it, as in `junk_allowed` for `:junk-allowed`.
+pub fn arg_symbol(name: &str) -> GcRef<Symbol> {
+    symbol_lookup::make_symbol(name.replace('_', "-").as_bytes())
+}
+
 macro_rules! special_form {
     ($name:expr ; ($($arg:tt)*) -> $blk:block) => {
         {
@@ -54,24 +63,24 @@ macro_rules! builtin_func {
 
 macro_rules! make_arg_syms {
     ($($arg:ident)*) => {
-        $(let $arg = $crate::symbol_lookup::make_symbol(stringify!($arg).as_ref());)*;
+        $(let $arg = $crate::builtins::macros::arg_symbol(stringify!($arg));)*;
     };
     ($($arg:ident)* &optional $($oarg:ident)*) => {
-        $(let $arg = $crate::symbol_lookup::make_symbol(stringify!($arg).as_ref());)*;
-        $(let $oarg = $crate::symbol_lookup::make_symbol(stringify!($oarg).as_ref());)*;
+        $(let $arg = $crate::builtins::macros::arg_symbol(stringify!($arg));)*;
+        $(let $oarg = $crate::builtins::macros::arg_symbol(stringify!($oarg));)*;
     };
     ($($arg:ident)* &rest $($rarg:ident)*) => {
-        $(let $arg = $crate::symbol_lookup::make_symbol(stringify!($arg).as_ref());)*;
-        $(let $rarg = $crate::symbol_lookup::make_symbol(stringify!($rarg).as_ref());)*;
+        $(let $arg = $crate::builtins::macros::arg_symbol(stringify!($arg));)*;
+        $(let $rarg = $crate::builtins::macros::arg_symbol(stringify!($rarg));)*;
     };
     ($($arg:ident)* &optional $($oarg:ident)* &rest $($rarg:ident)*) => {
-        $(let $arg = $crate::symbol_lookup::make_symbol(stringify!($arg).as_ref());)*;
-        $(let $oarg = $crate::symbol_lookup::make_symbol(stringify!($oarg).as_ref());)*;
-        $(let $rarg = $crate::symbol_lookup::make_symbol(stringify!($rarg).as_ref());)*;
+        $(let $arg = $crate::builtins::macros::arg_symbol(stringify!($arg));)*;
+        $(let $oarg = $crate::builtins::macros::arg_symbol(stringify!($oarg));)*;
+        $(let $rarg = $crate::builtins::macros::arg_symbol(stringify!($rarg));)*;
     };
     ($($arg:ident)* &key $($karg:ident)*) => {
-        $(let $arg = $crate::symbol_lookup::make_symbol(stringify!($arg).as_ref());)*;
-        $(let $karg = $crate::symbol_lookup::make_symbol(stringify!($karg).as_ref());)*;
+        $(let $arg = $crate::builtins::macros::arg_symbol(stringify!($arg));)*;
+        $(let $karg = $crate::builtins::macros::arg_symbol(stringify!($karg));)*;
     };
 }
 
@@ -1,6 +1,57 @@
 //! Builtin functions related to mathematical and arithmetic ops.
 
 use crate::prelude::*;
+use crate::random;
+use crate::reader::{parse_float_prefix, parse_integer_prefix};
+
+/// Parse the name of the symbol `text`, less any surrounding
+/// whitespace, with `parse_prefix`, as `parse-integer` and
+/// `parse-float` do. Unless `junk_allowed`, the whole name must be a
+/// number; otherwise, the number may be followed by anything, and if
+/// there isn't one at all the result is `nil`.
+fn parse_number<T, F>(text: Object, junk_allowed: bool, wanted: &[u8], parse_prefix: F) -> Object
+where
+    T: Into<Object>,
+    F: FnOnce(&[u8]) -> Option<(T, usize)>,
+{
+    let sym = <GcRef<Symbol>>::try_convert_from(text)?;
+    let name: &[u8] = (*sym).as_ref();
+    let start = name
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or_else(|| name.len());
+    let end = name
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    let trimmed = &name[start..end];
+    match parse_prefix(trimmed) {
+        Some((n, len)) if junk_allowed || len == trimmed.len() => n.into(),
+        None if junk_allowed => Object::nil(),
+        _ => EvaluatorError::Parse {
+            text,
+            wanted: symbol_lookup::make_symbol(wanted),
+        }
+        .into(),
+    }
+}
+
+/// Returns `t` if `test` holds for each adjacent pair of numbers in
+/// the list `nums`, as in `(< 1 2 3)`.
+fn compare_chain(nums: Object, test: fn(&PhoebeNumber, &PhoebeNumber) -> bool) -> Object {
+    let mut nums = List::try_convert_from(nums)?;
+    if let Some(first) = nums.next() {
+        let mut prev = PhoebeNumber::try_convert_from(first)?;
+        for n in nums {
+            let n = PhoebeNumber::try_convert_from(n)?;
+            if !test(&prev, &n) {
+                return Object::from(false);
+            }
+            prev = n;
+        }
+    }
+    Object::from(true)
+}
 
 /// This function is called by `make_builtins`. It does no checking
 /// for whether these functions have already been built, so calling it
@@ -25,6 +76,18 @@ pub fn make_math_builtins() {
             }
             Object::from(true)
         };
+        "<" (&rest nums) -> {
+            compare_chain(*nums, PartialOrd::lt)
+        };
+        ">" (&rest nums) -> {
+            compare_chain(*nums, PartialOrd::gt)
+        };
+        "<=" (&rest nums) -> {
+            compare_chain(*nums, PartialOrd::le)
+        };
+        ">=" (&rest nums) -> {
+            compare_chain(*nums, PartialOrd::ge)
+        };
         "+" (&rest nums) -> {
             let mut result = PhoebeNumber::from(0);
             let nums = List::try_convert_from(*nums)?;
@@ -69,5 +132,59 @@ pub fn make_math_builtins() {
                 Object::from(number)
             }
         };
+        "random" (limit) -> {
+            // A random number of the same type as `limit`, at least
+            // zero and less than `limit`.
+            match PhoebeNumber::try_convert_from(*limit)? {
+                PhoebeNumber::Integer(n) if n > 0 => {
+                    Object::from(random::below(n as u64) as i32)
+                }
+                PhoebeNumber::Float(f) if f > 0.0 => {
+                    Object::from(random::unit_float() * f)
+                }
+                _ => Error::type_error(symbol_lookup::make_symbol(b"positive-number")).into(),
+            }
+        };
+        // Phoebe doesn't have strings yet, so these parse the name
+        // of a symbol, like those returned by `read-line`.
+        "parse-integer" (text &key radix junk_allowed) -> {
+            let radix = if (*radix).definedp() {
+                match i32::try_convert_from(*radix)? {
+                    r if 2 <= r && r <= 36 => r as u32,
+                    _ => return Error::type_error(symbol_lookup::make_symbol(b"radix")).into(),
+                }
+            } else {
+                10
+            };
+            let junk_allowed = (*junk_allowed).definedp() && bool::from(*junk_allowed);
+            parse_number(*text, junk_allowed, b"integer", |s| parse_integer_prefix(s, radix))
+        };
+        "parse-float" (text &key junk_allowed) -> {
+            let junk_allowed = (*junk_allowed).definedp() && bool::from(*junk_allowed);
+            parse_number(*text, junk_allowed, b"float", parse_float_prefix)
+        };
+        "min" (number &rest others) -> {
+            let mut min = PhoebeNumber::try_convert_from(*number)?;
+            for n in List::try_convert_from(*others)? {
+                let n = PhoebeNumber::try_convert_from(n)?;
+                if n < min {
+                    min = n;
+                }
+            }
+            Object::from(min)
+        };
+        "max" (number &rest others) -> {
+            let mut max = PhoebeNumber::try_convert_from(*number)?;
+            for n in List::try_convert_from(*others)? {
+                let n = PhoebeNumber::try_convert_from(n)?;
+                if n > max {
+                    max = n;
+                }
+            }
+            Object::from(max)
+        };
+        "abs" (number) -> {
+            Object::from(PhoebeNumber::try_convert_from(*number)?.abs())
+        };
     }
 }
@@ -2,8 +2,15 @@
 //! functions and special forms. Phoebe is largely useless until that
 //! function is called.
 
+use crate::documentation;
 use crate::evaluator::eval_to_reference;
+use crate::gc::{roots, Rooted};
+use crate::lisp2::lisp2;
 use crate::prelude::*;
+use crate::shadowing;
+use crate::types::equality;
+use crate::watchpoints;
+use std::ops;
 use std::sync::{Once, ONCE_INIT};
 
 static ONCE_BUILTINS: Once = ONCE_INIT;
@@ -11,9 +18,31 @@ static ONCE_BUILTINS: Once = ONCE_INIT;
 #[macro_use]
 mod macros;
 
+mod boxes;
+mod characters;
+mod classes;
+mod conditions;
 mod error_handling;
+mod functional;
+pub(crate) mod generic_functions;
+mod hash_tables;
+mod introspection;
+mod loading;
 mod math_builtins;
 mod namespacing;
+mod pathnames;
+mod printing;
+mod promises;
+mod reading;
+mod regexes;
+mod scheme_compat;
+mod streams;
+mod structures;
+mod symbols;
+mod timestamps;
+mod vectors;
+mod watching;
+mod weak_refs;
 
 /// Any new thread which could be spawned before or during sourcing
 /// builtins should call this function as its first act. Calling it
@@ -31,6 +60,123 @@ pub fn make_builtins_once() {
     ONCE_BUILTINS.call_once(make_builtins);
 }
 
+lazy_static! {
+    static ref SETF_FUNCTION: GcRef<Symbol> = { symbol_lookup::make_symbol(b"setf-function") };
+}
+
+/// Somewhere `setf` and its relatives can store a value.
+enum Place {
+    /// Most places, like variables and `(aref v 0)`, evaluate to a
+    /// `Reference`.
+    Reference(Reference),
+    /// A call to a function like `byte-ref`, whose values are not
+    /// `Object`s and so can't be referred to. Its symbol's
+    /// `setf-function` property is a function which is called with
+    /// the same arguments followed by the new value, and which stores
+    /// that value. `args` have already been evaluated, and are rooted
+    /// while the new value is.
+    Call {
+        getter: GcRef<Function>,
+        setter: GcRef<Function>,
+        args: Vec<Rooted<Object>>,
+    },
+}
+
+impl Place {
+    /// Evaluate the place form `place`.
+    fn evaluate(place: Object) -> Result<Place, GcRef<Error>> {
+        if let Some(c) = <GcRef<Cons>>::maybe_from(place) {
+            let Cons { car, cdr, .. } = *c;
+            let setter = <GcRef<Symbol>>::maybe_from(car)
+                .and_then(|sym| sym.get(Object::from(*SETF_FUNCTION)));
+            if let Some(setter) = setter {
+                let setter = <GcRef<Function>>::try_convert_from(setter)?;
+                let getter = symbol_lookup::in_parent_env(|| car.evaluate())?;
+                let getter = <GcRef<Function>>::try_convert_from(getter)?;
+                let mut args = Vec::new();
+                for arg in List::try_convert_from(cdr)? {
+                    args.push(Rooted::new(symbol_lookup::in_parent_env(|| {
+                        arg.evaluate()
+                    })?));
+                }
+                return Ok(Place::Call {
+                    getter,
+                    setter,
+                    args,
+                });
+            }
+        }
+        Ok(Place::Reference(
+            eval_to_reference(place).try_convert_into()?,
+        ))
+    }
+    fn get(&self) -> Object {
+        match *self {
+            Place::Reference(r) => *r,
+            Place::Call {
+                getter, ref args, ..
+            } => getter.apply(args.iter().map(|arg| **arg).collect()),
+        }
+    }
+    /// Store `value` in this place, returning `value` or an error.
+    fn set(&mut self, value: Object) -> Object {
+        match *self {
+            Place::Reference(ref mut r) => {
+                let old = r.set(value);
+                watchpoints::notify(*r, old, value)
+            }
+            Place::Call {
+                setter, ref args, ..
+            } => {
+                let args: List = args.iter().map(|arg| **arg).chain(Some(value)).collect();
+                setter.apply(args)?;
+                value
+            }
+        }
+    }
+}
+
+/// Make the builtin function named `setter` the `setf-function` of
+/// `accessor`, so that `(setf (accessor args...) value)` calls
+/// `(setter args... value)`.
+fn define_setf_function(accessor: &[u8], setter: &[u8]) {
+    let setter = *symbol_lookup::make_from_global_namespace(symbol_lookup::make_symbol(setter));
+    symbol_lookup::make_symbol(accessor).put(Object::from(*SETF_FUNCTION), setter);
+}
+
+/// Split a leading docstring off the body of a `defun` or `lambda`.
+/// A string which is the only form in the body is its return value,
+/// not its documentation.
+fn split_docstring(body: List) -> (Option<Object>, List) {
+    let mut rest = body;
+    match rest.next() {
+        Some(first) if <GcRef<PhoebeString>>::is_type(first) => match rest {
+            List::Cons(_) => (Some(first), rest),
+            List::Nil => (None, body),
+        },
+        _ => (None, body),
+    }
+}
+
+/// Replaces the number in `place` with `op` applied to it and
+/// `delta`, or to it and 1 if `delta` was not supplied, as in `incf`
+/// and `decf`. Returns the new value.
+fn modify_place(
+    place: Object,
+    delta: Object,
+    op: fn(PhoebeNumber, PhoebeNumber) -> PhoebeNumber,
+) -> Object {
+    let mut place = Place::evaluate(place)?;
+    let delta = if delta.definedp() {
+        symbol_lookup::in_parent_env(|| delta.evaluate())?
+    } else {
+        Object::from(1)
+    };
+    let old = PhoebeNumber::try_convert_from(place.get()?)?;
+    let delta = PhoebeNumber::try_convert_from(delta)?;
+    place.set(Object::from(op(old, delta)))
+}
+
 fn make_builtins() {
     info!("Making builtins.");
     special_forms! {
@@ -92,7 +238,9 @@ fn make_builtins() {
             let env = {
                 let mut scope = Vec::new();
 
-                symbol_lookup::in_parent_env(|| {
+                // Each value is rooted until the new environment
+                // holds it, since evaluating the next may collect.
+                let evaluated = symbol_lookup::in_parent_env(|| {
                     for binding_pair in List::try_convert_from(*bindings)? {
                         let c: GcRef<Cons> = binding_pair.try_convert_into()?;
                         let Cons { car: symbol, cdr, .. } = *c;
@@ -101,15 +249,25 @@ fn make_builtins() {
                         if !tail.nilp() {
                             return EvaluatorError::ImproperList.into();
                         }
-                        scope.push((
-                            symbol.try_convert_into()?,
-                            value.evaluate()?
-                        ));
+                        let symbol = symbol.try_convert_into()?;
+                        let value = value.evaluate()?;
+                        roots::push_temporary_root(value);
+                        scope.push((symbol, value));
                     }
                     Object::nil()
-                })?;
+                });
+                if ops::Try::into_result(evaluated).is_err() {
+                    roots::pop_temporary_roots(scope.len());
+                    return evaluated;
+                }
 
-                Namespace::create_let_env(&scope)
+                let parent = symbol_lookup::current_env();
+                for &(sym, _) in &scope {
+                    shadowing::check("let", sym, parent);
+                }
+                let env = Namespace::create_let_env(&scope);
+                roots::pop_temporary_roots(scope.len());
+                env
             };
 
             let body = List::try_convert_from(*body)?;
@@ -122,16 +280,30 @@ fn make_builtins() {
             })
         };
         "lambda" (arglist &rest body) -> {
+            let (documentation, body) = split_docstring((*body).try_convert_into()?);
+            let function = Function::make_lambda(
+                (*arglist).try_convert_into()?,
+                body,
+                symbol_lookup::scope_for_a_new_function()
+            )?;
             Object::from(Function::allocate(
-                Function::make_lambda(
-                    (*arglist).try_convert_into()?,
-                    (*body).try_convert_into()?,
-                    symbol_lookup::scope_for_a_new_function()
-                )?
+                match documentation {
+                    Some(documentation) => function.with_documentation(documentation),
+                    None => function,
+                }
             ))
         };
-        "defvar" (name &optional value) -> {
+        "defvar" (name &optional value documentation) -> {
             let sym = <GcRef<Symbol>>::try_convert_from(*name)?;
+            if (*documentation).definedp() {
+                documentation::set_variable_documentation(sym, *documentation);
+            }
+            let global = symbol_lookup::global_env();
+            if global.update(|b| b.get(sym)).is_none() {
+                if let Some(parent) = global.parent() {
+                    shadowing::check("defvar", sym, parent);
+                }
+            }
             let mut place = symbol_lookup::make_from_global_namespace(sym);
             if place.definedp() {
                 Object::from(place)
@@ -144,32 +316,160 @@ fn make_builtins() {
                         Object::uninitialized()
                     }
                 })?;
-                *place = value;
+                place.set(value);
                 Object::from(place)
             }
         };
+        // Like `defvar`, but always binds `name` in the default global
+        // namespace, which all threads share, even within
+        // `with-namespace`. See `symbol_lookup::define_global`.
+        "defglobal" (name &optional value documentation) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*name)?;
+            if (*documentation).definedp() {
+                documentation::set_variable_documentation(sym, *documentation);
+            }
+            let env = symbol_lookup::default_global_env();
+            match env.update(|b| b.get(sym)) {
+                Some(existing) if existing.definedp() => existing,
+                _ => {
+                    let value: Object = *value;
+                    let value: Object = symbol_lookup::in_parent_env(|| {
+                        if value.definedp() {
+                            value.evaluate()
+                        } else {
+                            Object::uninitialized()
+                        }
+                    })?;
+                    symbol_lookup::define_global(sym, value)
+                }
+            }
+        };
         "boundp" (symbol) -> {
             let sym = <GcRef<Symbol>>::try_convert_from(*symbol)?;
             symbol_lookup::get_from_global_namespace(sym).is_some().into()
         };
+        "fboundp" (symbol) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            symbol_lookup::get_function_from_global_namespace(sym)
+                .map_or(false, |r| <GcRef<Function>>::is_type(*r))
+                .into()
+        };
+        "makunbound" (symbol &optional namespace) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            let env = if (*namespace).definedp() {
+                <GcRef<Namespace>>::try_convert_from(
+                    symbol_lookup::in_parent_env(|| (*namespace).evaluate())?
+                )?
+            } else {
+                symbol_lookup::global_env()
+            };
+            env.remove(sym);
+            Object::from(sym)
+        };
+        // Unlike `makunbound`, this removes a lexical binding if there
+        // is one, revealing whichever binding it shadowed.
+        "unbind" (symbol) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            symbol_lookup::in_parent_env(|| {
+                symbol_lookup::current_env().unbind(sym);
+                Object::from(sym)
+            })
+        };
+        // In Lisp-2 mode this removes `symbol`'s function cell.
+        // Otherwise, Phoebe has a single namespace for functions and
+        // variables, so this is `makunbound`, except that it leaves
+        // variables which aren't functions alone.
+        "fmakunbound" (symbol) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            let env = symbol_lookup::global_env();
+            if lisp2() {
+                env.remove_function(sym);
+            } else if env.get_sym_ref(sym).map_or(false, |r| <GcRef<Function>>::is_type(*r)) {
+                env.remove(sym);
+            }
+            Object::from(sym)
+        };
+        // `(function name)` is the function `name` names at the head
+        // of a form, which in Lisp-2 mode is its function cell.
+        // `(function (lambda ...))` is the same as the lambda.
+        "function" (name) -> {
+            let name = *name;
+            symbol_lookup::in_parent_env(|| match <GcRef<Symbol>>::maybe_from(name) {
+                Some(sym) => Object::from(symbol_lookup::lookup_function(sym)?),
+                None => name.evaluate(),
+            })
+        };
         "defun" (name arglist &rest body) -> {
             let name = (*name).try_convert_into()?;
+            let (documentation, body) = split_docstring((*body).try_convert_into()?);
             let func = Object::from(Function::allocate(
                 Function::make_lambda(
                     (*arglist).try_convert_into()?,
-                    (*body).try_convert_into()?,
+                    body,
                     symbol_lookup::scope_for_a_new_function()
                 )?.with_name(name)
             ));
-            *(symbol_lookup::make_from_global_namespace(name)) = func;
+            symbol_lookup::make_function_from_global_namespace(name).set(func);
+            if let Some(documentation) = documentation {
+                documentation::set_documentation(name, documentation);
+            }
             func
         };
         "setf" (place value) -> {
-            let mut place: Reference = eval_to_reference(*place).try_convert_into()?;
+            let mut place = Place::evaluate(*place)?;
             let value = *value;
             let value = symbol_lookup::in_parent_env(|| value.evaluate())?;
-            *place = value;
-            value
+            place.set(value)
+        };
+        "psetf" (&rest pairs) -> {
+            // Every place and value is evaluated before any place is
+            // assigned, so `(psetf a b b a)` swaps `a` and `b`.
+            let pairs = List::try_convert_from(*pairs)?;
+            let mut assignments = Vec::new();
+            let mut forms = pairs;
+            while let Some(place) = forms.next() {
+                let value = match forms.next() {
+                    Some(v) => v,
+                    None => {
+                        return EvaluatorError::OddArgCount {
+                            found: pairs.count(),
+                        }.into();
+                    }
+                };
+                let place = Place::evaluate(place)?;
+                let value = symbol_lookup::in_parent_env(|| value.evaluate())?;
+                // Later values are evaluated before this one is
+                // stored anywhere.
+                assignments.push((place, Rooted::new(value)));
+            }
+            for (mut place, value) in assignments {
+                place.set(*value)?;
+            }
+            Object::nil()
+        };
+        "incf" (place &optional delta) -> {
+            modify_place(*place, *delta, ops::Add::add)
+        };
+        "decf" (place &optional delta) -> {
+            modify_place(*place, *delta, ops::Sub::sub)
+        };
+        "push" (item place) -> {
+            let item = *item;
+            let item = symbol_lookup::in_parent_env(|| item.evaluate())?;
+            let mut place = Place::evaluate(*place)?;
+            let list = Object::from(Cons::allocate(Cons::new(item, place.get()?)));
+            place.set(list)
+        };
+        "pop" (place) -> {
+            let mut place = Place::evaluate(*place)?;
+            let list = place.get()?;
+            if list.nilp() {
+                Object::nil()
+            } else {
+                let Cons { car, cdr, .. } = *<GcRef<Cons>>::try_convert_from(list)?;
+                place.set(cdr)?;
+                car
+            }
         };
         "quote" (x) -> {
             *x
@@ -177,6 +477,13 @@ fn make_builtins() {
     };
 
     builtin_functions! {
+        // Returns `t` if `name`'s shared global binding was `eq` to
+        // `old` and has been replaced with `new`. See
+        // `symbol_lookup::compare_and_swap_global`.
+        "compare-and-swap-global" (name old new) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*name)?;
+            Object::from(symbol_lookup::compare_and_swap_global(sym, *old, *new))
+        };
         "cons" (first second) -> {
             Object::from(
                 Cons::allocate(
@@ -187,15 +494,50 @@ fn make_builtins() {
         "list" (&rest elements) -> {
             *elements
         };
-        "debug" (obj) -> {
-            println!("{:?}", *obj);
-            *obj
+        "eq" (first second) -> {
+            Object::from(*first == *second)
+        };
+        "eql" (first second) -> {
+            Object::from((*first).eql(*second))
+        };
+        "equal" (first second) -> {
+            Object::from(equality::equal_within(*first, *second, equality::budget())?)
+        };
+        "not" (obj) -> {
+            Object::from(!bool::from(*obj))
+        };
+        "null" (obj) -> {
+            Object::from((*obj).nilp())
+        };
+        "type-of" (obj) -> {
+            Object::from((*obj).type_name())
         };
     };
 
     namespacing::make_namespace_builtins();
     error_handling::make_error_builtins();
     math_builtins::make_math_builtins();
+    printing::make_printing_builtins();
+    reading::make_reading_builtins();
+    introspection::make_introspection_builtins();
+    watching::make_watchpoint_builtins();
+    symbols::make_symbol_builtins();
+    functional::make_functional_builtins();
+    vectors::make_vector_builtins();
+    hash_tables::make_hash_table_builtins();
+    characters::make_character_builtins();
+    structures::make_structure_builtins();
+    weak_refs::make_weak_ref_builtins();
+    boxes::make_box_builtins();
+    promises::make_promise_builtins();
+    generic_functions::make_generic_function_builtins();
+    classes::make_class_builtins();
+    conditions::make_condition_builtins();
+    streams::make_stream_builtins();
+    timestamps::make_timestamp_builtins();
+    regexes::make_regex_builtins();
+    pathnames::make_pathname_builtins();
+    loading::make_load_builtins();
 
     info!("Finished making builtin functions.");
 }
@@ -1,5 +1,6 @@
 //! Builtin functions and special forms related to namespacing.
 
+use crate::gensym;
 use crate::prelude::*;
 
 pub fn make_namespace_builtins() {
@@ -53,8 +54,7 @@ pub fn make_namespace_builtins() {
             let nmspc = Object::from(Namespace::allocate(nmspc));
 
             if let Some(s) = name {
-                let mut r = symbol_lookup::make_from_global_namespace(s);
-                *r = nmspc;
+                symbol_lookup::make_from_global_namespace(s).set(nmspc);
             };
 
             nmspc
@@ -81,5 +81,55 @@ pub fn make_namespace_builtins() {
                 })
             })
         };
+        "export" (&rest symbols) -> {
+            let symbols = List::try_convert_from(*symbols)?;
+            let env = symbol_lookup::global_env();
+            for sym in symbols {
+                env.export(<GcRef<Symbol>>::try_convert_from(sym)?);
+            }
+            Object::from(symbols)
+        };
+        "import" (namespace &key symbols alias) -> {
+            let namespace = <GcRef<Namespace>>::try_convert_from(
+                Evaluate::evaluate(&*namespace)?
+            )?;
+            let symbols = if (*symbols).definedp() {
+                let mut syms = Vec::new();
+                for sym in List::try_convert_from(*symbols)? {
+                    syms.push(<GcRef<Symbol>>::try_convert_from(sym)?);
+                }
+                syms
+            } else {
+                namespace.exports()
+            };
+            let alias = (*alias).definedp() && bool::from(*alias);
+            symbol_lookup::global_env().import_from(namespace, &symbols, alias)?;
+            Object::from(symbols.into_iter().map(Object::from).collect::<List>())
+        };
+    }
+    builtin_functions! {
+        "namespace-exports" (namespace) -> {
+            let namespace = <GcRef<Namespace>>::try_convert_from(*namespace)?;
+            Object::from(namespace.exports().into_iter().map(Object::from).collect::<List>())
+        };
+        "delete-namespace" (namespace) -> {
+            let namespace = <GcRef<Namespace>>::try_convert_from(*namespace)?;
+            let env = symbol_lookup::global_env();
+            env.unuse_namespace(namespace);
+            if let Some(name) = namespace.name().and_then(<GcRef<Symbol>>::maybe_from) {
+                gensym::remove_prefix(name);
+                env.update(|b| {
+                    if b.get(name) == Some(Object::from(namespace)) {
+                        b.remove(name);
+                    }
+                });
+            }
+            Object::from(true)
+        };
+        "use-namespace" (namespace) -> {
+            let namespace = <GcRef<Namespace>>::try_convert_from(*namespace)?;
+            symbol_lookup::global_env().use_namespace(namespace);
+            Object::from(namespace)
+        };
     }
 }
@@ -0,0 +1,49 @@
+//! Builtin functions which build and take apart pathnames. Wherever
+//! they take a path, a string will do in place of a pathname.
+
+use crate::prelude::*;
+use crate::types::pathname::{path_designated_by, Pathname};
+
+fn allocate_pathname(p: Pathname) -> Object {
+    Object::from(Native::allocate(Native::new(p)))
+}
+
+pub fn make_pathname_builtins() {
+    builtin_functions! {
+        "pathname" (path) -> {
+            allocate_pathname(Pathname::new(path_designated_by(*path)?))
+        };
+        // `base` followed by each of `parts` in turn, so
+        // `(path-join "a" "b" "c.phoebe")` is `a/b/c.phoebe` on Unix.
+        // An absolute part replaces everything before it.
+        "path-join" (base &rest parts) -> {
+            let mut path = Pathname::new(path_designated_by(*base)?);
+            for part in List::try_convert_from(*parts)? {
+                path = path.join(path_designated_by(part)?);
+            }
+            allocate_pathname(path)
+        };
+        // `path` without its last component, or `nil` if it has none.
+        "path-parent" (path) -> {
+            match Pathname::new(path_designated_by(*path)?).parent() {
+                Some(parent) => allocate_pathname(parent),
+                None => Object::nil(),
+            }
+        };
+        // The last component of `path` as a string, or `nil` if it is
+        // a root or ends in `..`.
+        "path-filename" (path) -> {
+            match Pathname::new(path_designated_by(*path)?).file_name() {
+                Some(name) => Object::from(name),
+                None => Object::nil(),
+            }
+        };
+        "path->string" (path) -> {
+            Object::from(Pathname::new(path_designated_by(*path)?).to_string_lossy())
+        };
+        "pathnamep" (obj) -> {
+            let native = <GcRef<Native>>::maybe_from(*obj);
+            Object::from(native.map_or(false, |n| n.downcast_ref::<Pathname>().is_some()))
+        };
+    }
+}
@@ -0,0 +1,31 @@
+//! Builtin functions which write to the standard output, or to the
+//! stream passed as their last, optional argument.
+
+use super::streams::with_output_to;
+use crate::prelude::*;
+use crate::printer;
+use std::io::Write;
+
+pub fn make_printing_builtins() {
+    builtin_functions! {
+        "print" (obj &optional stream) -> {
+            with_output_to(*stream, |o| printer::print_to(o, *obj))?;
+            *obj
+        };
+        "terpri" (&optional stream) -> {
+            with_output_to(*stream, |o| writeln!(o))?;
+            Object::nil()
+        };
+        "fresh-line" (&optional stream) -> {
+            Object::from(with_output_to(*stream, |o| o.fresh_line())?)
+        };
+        "describe" (obj &optional stream) -> {
+            with_output_to(*stream, |o| printer::describe_to(o, *obj))?;
+            Object::nil()
+        };
+        "debug" (obj &optional stream) -> {
+            with_output_to(*stream, |o| printer::debug_to(o, *obj))?;
+            *obj
+        };
+    }
+}
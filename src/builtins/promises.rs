@@ -0,0 +1,59 @@
+//! `delay` and `force`, which make and evaluate lazy promises.
+//! Promises are a built-in structure type, as if defined by
+//! `(defstruct promise forced value)`. Until a promise is forced,
+//! `value` holds a thunk which evaluates the delayed form in the
+//! environment `delay` was called in; afterwards it holds that form's
+//! value.
+
+use super::structures::{define_structure, make_structure, structure_of_type};
+use crate::prelude::*;
+
+lazy_static! {
+    static ref PROMISE: GcRef<Symbol> = { symbol_lookup::make_symbol(b"promise") };
+}
+
+pub fn make_promise_builtins() {
+    let slot_names: List = [&b"forced"[..], b"value"]
+        .iter()
+        .map(|name| Object::from(symbol_lookup::make_symbol(name)))
+        .collect();
+    define_structure(*PROMISE, slot_names).unwrap();
+
+    special_forms! {
+        // A promise which will evaluate `form` when it is first
+        // forced.
+        "delay" (form) -> {
+            let body: List = [*form].iter().cloned().collect();
+            let thunk = Function::allocate(Function::make_lambda(
+                List::nil(),
+                body,
+                symbol_lookup::scope_for_a_new_function(),
+            )?);
+            let promise = make_structure(*PROMISE, vec![Object::nil(), Object::from(thunk)])?;
+            Object::from(promise)
+        };
+    }
+
+    builtin_functions! {
+        // The value of `promise`'s delayed form, which is evaluated
+        // only the first time `promise` is forced. Any object which
+        // isn't a promise is its own value.
+        "force" (promise) -> {
+            let mut promise = match structure_of_type(*promise, *PROMISE) {
+                Ok(p) => p,
+                Err(_) => return *promise,
+            };
+            if promise.slots()[0].nilp() {
+                let thunk = <GcRef<Function>>::try_convert_from(promise.slots()[1])?;
+                let value = thunk.apply(List::nil())?;
+                // Forcing the thunk may have forced this promise
+                // recursively, in which case that value stands.
+                if promise.slots()[0].nilp() {
+                    promise.set_slot(1, value);
+                    promise.set_slot(0, Object::t());
+                }
+            }
+            promise.slots()[1]
+        };
+    }
+}
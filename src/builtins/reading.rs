@@ -0,0 +1,40 @@
+//! Builtin functions which consume the standard input, or the stream
+//! passed as their optional argument.
+//!
+//! Phoebe doesn't have strings or characters yet, so for now
+//! `read-line` returns a symbol and `read-char` returns the byte it
+//! read as an integer. Both return `nil` at the end of input.
+
+use super::streams::with_input_from;
+use crate::prelude::*;
+
+pub fn make_reading_builtins() {
+    builtin_functions! {
+        "read-line" (&optional stream) -> {
+            let line = with_input_from(*stream, |input| {
+                let mut line = Vec::new();
+                for c in input {
+                    if c == b'\n' {
+                        return Some(line);
+                    }
+                    line.push(c);
+                }
+                if line.is_empty() {
+                    None
+                } else {
+                    Some(line)
+                }
+            })?;
+            match line {
+                Some(line) => Object::from(symbol_lookup::intern(&line)),
+                None => Object::nil(),
+            }
+        };
+        "read-char" (&optional stream) -> {
+            match with_input_from(*stream, |input| input.next())? {
+                Some(c) => Object::from(i32::from(c)),
+                None => Object::nil(),
+            }
+        };
+    }
+}
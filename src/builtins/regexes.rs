@@ -0,0 +1,59 @@
+//! Builtin functions which compile and match regexes. Each of them
+//! also accepts a pattern string in place of a regex, which is
+//! compiled for that call.
+
+use crate::prelude::*;
+use crate::types::regex::Regex;
+
+lazy_static! {
+    static ref REGEX: GcRef<Symbol> = { symbol_lookup::make_symbol(b"regex") };
+}
+
+/// Call `f` on `obj`, which is a regex or a pattern string.
+fn with_regex<F>(obj: Object, f: F) -> Object
+where
+    F: FnOnce(&Regex) -> Object,
+{
+    if let Some(pattern) = <GcRef<PhoebeString>>::maybe_from(obj) {
+        return f(&Regex::new(pattern.as_str())?);
+    }
+    match <GcRef<Native>>::maybe_from(obj).and_then(|n| n.downcast_ref::<Regex>().map(f)) {
+        Some(res) => res,
+        None => Error::type_error(*REGEX).into(),
+    }
+}
+
+pub fn make_regex_builtins() {
+    builtin_functions! {
+        "regex" (pattern) -> {
+            let pattern = <GcRef<PhoebeString>>::try_convert_from(*pattern)?;
+            Object::from(Native::allocate(Native::new(Regex::new(pattern.as_str())?)))
+        };
+        // `nil` if `re` doesn't match `text`. Otherwise, a list of the
+        // leftmost match followed by the text of each capture group,
+        // or `nil` for groups which didn't participate, so
+        // `(regex-match "(a)(x)?b" "cab")` is `("ab" "a" nil)`.
+        "regex-match" (re text) -> {
+            let text = <GcRef<PhoebeString>>::try_convert_from(*text)?;
+            with_regex(*re, |re| match re.captures(text.as_str()) {
+                Some(groups) => {
+                    let groups: List = groups
+                        .into_iter()
+                        .map(|g| g.map(Object::from).unwrap_or_else(Object::nil))
+                        .collect();
+                    Object::from(groups)
+                }
+                None => Object::nil(),
+            })
+        };
+        // `text` with every match of `re` replaced by `replacement`,
+        // in which `$1` stands for the first capture group.
+        "regex-replace" (re text replacement) -> {
+            let text = <GcRef<PhoebeString>>::try_convert_from(*text)?;
+            let replacement = <GcRef<PhoebeString>>::try_convert_from(*replacement)?;
+            with_regex(*re, |re| {
+                Object::from(re.replace_all(text.as_str(), replacement.as_str()))
+            })
+        };
+    }
+}
@@ -0,0 +1,62 @@
+//! Scheme-compatible names for Phoebe's builtins, to ease porting
+//! small Scheme programs. None of these are defined until
+//! `(require 'scheme-compat)`, which also makes the reader accept
+//! `#t` and `#f`.
+//!
+//! Once Phoebe has macros, `define` and `begin` should become macros
+//! in a Phoebe source file, alongside the aliases.
+
+use crate::prelude::*;
+
+/// Evaluate the form `(head . tail)` in the caller's environment.
+fn call_in_parent(head: &[u8], tail: Object) -> Object {
+    let form = Object::from(Cons::allocate(Cons::new(
+        Object::from(symbol_lookup::make_symbol(head)),
+        tail,
+    )));
+    symbol_lookup::in_parent_env(|| form.evaluate())
+}
+
+/// Bind `alias` to the same function as `name`.
+fn alias(alias: &[u8], name: &[u8]) {
+    let function = symbol_lookup::default_global_env()
+        .get_sym_ref(symbol_lookup::make_symbol(name))
+        .expect("Aliased a builtin which doesn't exist");
+    symbol_lookup::add_to_global(symbol_lookup::make_symbol(alias), *function);
+}
+
+pub fn make_scheme_compat_builtins() {
+    special_forms! {
+        // `(define (name . args) body...)` defines a function, as
+        // `defun` does. `(define name value)` defines a global
+        // variable, and unlike `defvar` replaces any existing value.
+        "define" (target &rest body) -> {
+            if let Some(c) = <GcRef<Cons>>::maybe_from(*target) {
+                let Cons { car: name, cdr: arglist, .. } = *c;
+                let tail = Cons::new(name, Object::from(Cons::allocate(Cons::new(arglist, *body))));
+                call_in_parent(b"defun", Object::from(Cons::allocate(tail)))
+            } else {
+                let mut body = List::try_convert_from(*body)?;
+                let value = match (body.next(), body.next()) {
+                    (Some(value), None) => value,
+                    _ => return EvaluatorError::ImproperList.into(),
+                };
+                let name: List = [*target].iter().cloned().collect();
+                call_in_parent(b"defvar", Object::from(name))?;
+                let assignment: List = [*target, value].iter().cloned().collect();
+                call_in_parent(b"setf", Object::from(assignment))
+            }
+        };
+        "begin" (&rest body) -> {
+            symbol_lookup::in_parent_env(|| {
+                let mut res = Object::nil();
+                for form in List::try_convert_from(*body)? {
+                    res = form.evaluate()?;
+                }
+                res
+            })
+        };
+    }
+    alias(b"set!", b"setf");
+    alias(b"display", b"print");
+}
@@ -0,0 +1,141 @@
+//! Builtin functions which open, close, read from and write to
+//! streams, along with the variables `*standard-input*`,
+//! `*standard-output*` and `*error-output*`, which are bound to
+//! streams reading from and writing to the thread's standard streams.
+
+use crate::prelude::*;
+use crate::printer::{self, ColumnTracker};
+use crate::standard_streams::{with_standard_input, with_standard_output};
+use crate::types::pathname::path_designated_by;
+use crate::types::stream::{Direction, Stream};
+use std::io::{self, Write};
+
+lazy_static! {
+    static ref STREAM: GcRef<Symbol> = { symbol_lookup::make_symbol(b"stream") };
+    static ref DIRECTION: GcRef<Symbol> = { symbol_lookup::make_symbol(b"direction") };
+    static ref INPUT: GcRef<Symbol> = { symbol_lookup::make_symbol(b":input") };
+    static ref OUTPUT: GcRef<Symbol> = { symbol_lookup::make_symbol(b":output") };
+    static ref APPEND: GcRef<Symbol> = { symbol_lookup::make_symbol(b":append") };
+}
+
+/// Call `fun` on the `Stream` `obj`.
+fn with_stream<F, T>(obj: Object, fun: F) -> Result<T, GcRef<Error>>
+where
+    F: FnOnce(&mut Stream) -> Result<T, EvaluatorError>,
+{
+    let mut native = <GcRef<Native>>::maybe_from(obj).ok_or_else(|| Error::type_error(*STREAM))?;
+    match native.downcast_mut::<Stream>() {
+        Some(stream) => Ok(fun(stream)?),
+        None => Err(Error::type_error(*STREAM)),
+    }
+}
+
+/// Call `fun` on `stream`'s output, or on the standard output if
+/// `stream` was not supplied.
+pub(super) fn with_output_to<F, T>(stream: Object, fun: F) -> Result<T, GcRef<Error>>
+where
+    F: FnOnce(&mut ColumnTracker<Write>) -> io::Result<T>,
+{
+    if stream.definedp() {
+        with_stream(stream, |s| Ok(s.with_output(fun)??))
+    } else {
+        Ok(with_standard_output(fun)?)
+    }
+}
+
+/// Call `fun` on `stream`'s input, or on the standard input if
+/// `stream` was not supplied.
+pub(super) fn with_input_from<F, T>(stream: Object, fun: F) -> Result<T, GcRef<Error>>
+where
+    F: FnOnce(&mut Iterator<Item = u8>) -> T,
+{
+    if stream.definedp() {
+        with_stream(stream, |s| s.with_input(fun))
+    } else {
+        Ok(with_standard_input(fun))
+    }
+}
+
+fn make_stream(stream: Stream) -> Object {
+    Object::from(Native::allocate(Native::new(stream)))
+}
+
+pub fn make_stream_builtins() {
+    for &(name, stream) in &[
+        (
+            &b"*standard-input*"[..],
+            Stream::standard_input as fn() -> Stream,
+        ),
+        (b"*standard-output*", Stream::standard_output),
+        (b"*error-output*", Stream::standard_error),
+    ] {
+        symbol_lookup::add_to_global(symbol_lookup::make_symbol(name), make_stream(stream()));
+    }
+
+    builtin_functions! {
+        // Opens the file at `path`. `direction` is `:input`, the
+        // default, `:output`, which truncates the file, or `:append`.
+        "open" (path &key direction) -> {
+            let path = path_designated_by(*path)?;
+            let direction = if !(*direction).definedp() || *direction == Object::from(*INPUT) {
+                Direction::Input
+            } else if *direction == Object::from(*OUTPUT) {
+                Direction::Output
+            } else if *direction == Object::from(*APPEND) {
+                Direction::Append
+            } else {
+                return Error::type_error(*DIRECTION).into();
+            };
+            make_stream(Stream::open(&path, direction)?)
+        };
+        "close" (stream) -> {
+            with_stream(*stream, |s| Ok(s.close()?))?;
+            Object::t()
+        };
+        "streamp" (obj) -> {
+            let native = <GcRef<Native>>::maybe_from(*obj);
+            Object::from(native.map_or(false, |n| n.downcast_ref::<Stream>().is_some()))
+        };
+        "make-string-input-stream" (string) -> {
+            let string = <GcRef<PhoebeString>>::try_convert_from(*string)?;
+            make_stream(Stream::from_string(string.as_str()))
+        };
+        "make-string-output-stream" () -> {
+            make_stream(Stream::string_output())
+        };
+        // Returns everything written to the string output stream
+        // `stream` since it was made or this was last called.
+        "get-output-stream-string" (stream) -> {
+            let string = with_stream(*stream, |s| Ok(s.take_output_string()))?;
+            match string {
+                Some(string) => Object::from(string.as_str()),
+                None => Error::type_error(*STREAM).into(),
+            }
+        };
+        // Reads one form from `stream`, returning `eof_value`, or
+        // `nil`, at the end of input.
+        "read-from-stream" (stream &optional eof_value) -> {
+            match with_stream(*stream, Stream::read_form)? {
+                Some(form) => form,
+                None if (*eof_value).definedp() => *eof_value,
+                None => Object::nil(),
+            }
+        };
+        // Writes strings as their contents and byte vectors as their
+        // bytes, and any other object as `print` would. Returns
+        // `object`.
+        "write-to-stream" (stream object) -> {
+            let object = *object;
+            with_output_to(*stream, |o| {
+                if let Some(s) = <GcRef<PhoebeString>>::maybe_from(object) {
+                    o.write_all(s.as_str().as_bytes())
+                } else if let Some(b) = <GcRef<Bytes>>::maybe_from(object) {
+                    o.write_all(b.as_slice())
+                } else {
+                    printer::print_to(o, object)
+                }
+            })?;
+            object
+        };
+    }
+}
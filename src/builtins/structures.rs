@@ -0,0 +1,116 @@
+//! `defstruct`, which defines record types along with a constructor,
+//! a predicate and an accessor for each slot.
+
+use crate::prelude::*;
+use crate::types::function::KEY;
+
+lazy_static! {
+    static ref OBJECT: GcRef<Symbol> = { symbol_lookup::make_symbol(b"object") };
+    /// Each structure type's name has its slot names stored under
+    /// this property, for `make_structure`.
+    static ref SLOT_NAMES: GcRef<Symbol> = { symbol_lookup::make_symbol(b"structure-slot-names") };
+}
+
+/// Define `function` as the function named `name` in the global
+/// namespace.
+pub(super) fn define_function(name: &str, function: Function) {
+    let name = symbol_lookup::make_symbol(name.as_bytes());
+    let function = Function::allocate(function.with_name(name));
+    symbol_lookup::make_function_from_global_namespace(name).set(Object::from(function));
+}
+
+/// `obj` as a structure of the type named `name`.
+pub(super) fn structure_of_type(
+    obj: Object,
+    name: GcRef<Symbol>,
+) -> Result<GcRef<Structure>, GcRef<Error>> {
+    match <GcRef<Structure>>::maybe_from(obj) {
+        Some(s) if s.name() == name => Ok(s),
+        _ => Err(Error::type_error(name)),
+    }
+}
+
+/// A new structure of the type named `name`, which must have been
+/// defined by `define_structure`, whose slots hold `slots`.
+pub(super) fn make_structure(
+    name: GcRef<Symbol>,
+    slots: Vec<Object>,
+) -> Result<GcRef<Structure>, GcRef<Error>> {
+    match name
+        .get(Object::from(*SLOT_NAMES))
+        .and_then(List::maybe_from)
+    {
+        Some(slot_names) if slot_names.count() == slots.len() => {
+            Ok(Structure::allocate(Structure::new(name, slot_names, slots)))
+        }
+        _ => Err(Error::type_error(name)),
+    }
+}
+
+/// Define the constructor `make-NAME`, the predicate `NAME-p` and an
+/// accessor for each of `slot_names`, as `defstruct` does.
+pub(super) fn define_structure(
+    mut name: GcRef<Symbol>,
+    slot_names: List,
+) -> Result<(), GcRef<Error>> {
+    name.put(Object::from(*SLOT_NAMES), Object::from(slot_names));
+    let captured = vec![Object::from(name), Object::from(slot_names)];
+
+    let arglist = slot_names.push(Object::from(*KEY));
+    let constructor = Function::make_closure(arglist, captured.clone(), |captured| {
+        let name = <GcRef<Symbol>>::try_convert_from(captured[0])?;
+        let slot_names = List::try_convert_from(captured[1])?;
+        let mut slots = Vec::new();
+        for slot in slot_names {
+            let value = *symbol_lookup::lookup_symbol(slot.try_convert_into()?)?;
+            slots.push(if value.definedp() {
+                value
+            } else {
+                Object::nil()
+            });
+        }
+        Object::from(Structure::allocate(Structure::new(name, slot_names, slots)))
+    })?;
+    define_function(&format!("make-{}", *name), constructor);
+
+    let arglist: List = [Object::from(*OBJECT)].iter().cloned().collect();
+    let predicate = Function::make_closure(arglist, captured.clone(), |captured| {
+        let name = <GcRef<Symbol>>::try_convert_from(captured[0])?;
+        let obj = *symbol_lookup::lookup_symbol(*OBJECT)?;
+        Object::from(structure_of_type(obj, name).is_ok())
+    })?;
+    define_function(&format!("{}-p", *name), predicate);
+
+    for (index, slot) in slot_names.enumerate() {
+        let mut captured = captured.clone();
+        captured.push(Object::from(index as i32));
+        let accessor = Function::make_closure(arglist, captured, |captured| {
+            let name = <GcRef<Symbol>>::try_convert_from(captured[0])?;
+            let index = i32::try_convert_from(captured[2])?;
+            let obj = *symbol_lookup::lookup_symbol(*OBJECT)?;
+            let mut structure = structure_of_type(obj, name)?;
+            Object::from(structure.ref_slot(index as usize))
+        })?;
+        define_function(&format!("{}-{}", *name, slot), accessor);
+    }
+    Ok(())
+}
+
+pub fn make_structure_builtins() {
+    special_forms! {
+        // `(defstruct point x y)` defines `make-point`, which takes
+        // each slot as a keyword argument, defaulting to `nil`; the
+        // predicate `point-p`; and the accessors `point-x` and
+        // `point-y`, which `setf` can assign through. Returns the
+        // name of the type.
+        "defstruct" (name &rest slots) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            let slot_names = List::try_convert_from(*slots)?;
+            for slot in slot_names {
+                <GcRef<Symbol>>::try_convert_from(slot)?;
+            }
+            define_structure(name, slot_names)?;
+            Object::from(name)
+        };
+    }
+}
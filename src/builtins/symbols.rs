@@ -0,0 +1,117 @@
+//! Builtin functions which inspect and create symbols.
+//!
+//! Phoebe doesn't have strings yet, so for now a symbol's name is
+//! represented by the interned symbol with that name, and `intern`,
+//! `make-symbol`, `gensym` and `set-gensym-prefix` take their names
+//! as symbols.
+//! Likewise, `number-to-string`, `symbol-to-string` and
+//! `string-to-symbol` return and take interned symbols in place of
+//! strings.
+
+use crate::evaluator::should_eval_to_reference;
+use crate::gensym;
+use crate::prelude::*;
+
+/// The name of `namespace`, or of the current global namespace if
+/// `namespace` was not supplied, under which its gensym prefix is
+/// registered.
+fn namespace_name(namespace: Object) -> Result<GcRef<Symbol>, GcRef<Error>> {
+    let namespace = if namespace.definedp() {
+        <GcRef<Namespace>>::try_convert_from(namespace)?
+    } else {
+        symbol_lookup::global_env()
+    };
+    match namespace.name().and_then(<GcRef<Symbol>>::maybe_from) {
+        Some(name) => Ok(name),
+        None => Err(Error::type_error(symbol_lookup::make_symbol(
+            b"named-namespace",
+        ))),
+    }
+}
+
+pub fn make_symbol_builtins() {
+    builtin_functions! {
+        "symbol-name" (sym) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            Object::from(symbol_lookup::intern((*sym).as_ref()))
+        };
+        "keywordp" (obj) -> {
+            Object::from(Keyword::maybe_from(*obj).is_some())
+        };
+        "intern" (name) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            Object::from(symbol_lookup::intern((*name).as_ref()))
+        };
+        "symbol-plist" (sym) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            sym.plist()
+        };
+        "get" (sym indicator &optional default) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            match (*sym).get(*indicator) {
+                Some(value) => value,
+                None if (*default).definedp() => *default,
+                None => Object::nil(),
+            }
+        };
+        "put" (sym indicator value) -> {
+            let mut sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            (*sym).put(*indicator, *value);
+            *value
+        };
+        // Under `setf`, returns a reference to `sym`'s function in the
+        // global namespace, creating its function cell if need be, so
+        // that `(setf (symbol-function sym) function)` works.
+        "symbol-function" (sym) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            if should_eval_to_reference() {
+                Object::from(symbol_lookup::make_function_from_global_namespace(sym))
+            } else {
+                match symbol_lookup::get_function_from_global_namespace(sym) {
+                    Some(r) if (*r).definedp() => *r,
+                    _ => EvaluatorError::UndefinedFunction { name: sym }.into(),
+                }
+            }
+        };
+        "number-to-string" (num) -> {
+            let num = PhoebeNumber::try_convert_from(*num)?;
+            let text = format!("{}", Object::from(num));
+            Object::from(symbol_lookup::intern(text.as_bytes()))
+        };
+        "symbol-to-string" (sym) -> {
+            let sym = <GcRef<Symbol>>::try_convert_from(*sym)?;
+            Object::from(symbol_lookup::intern((*sym).as_ref()))
+        };
+        "string-to-symbol" (string) -> {
+            let string = <GcRef<Symbol>>::try_convert_from(*string)?;
+            Object::from(symbol_lookup::intern((*string).as_ref()))
+        };
+        "make-symbol" (name) -> {
+            let name = <GcRef<Symbol>>::try_convert_from(*name)?;
+            Object::from(symbol_lookup::make_uninterned_symbol((*name).as_ref()))
+        };
+        // A new uninterned symbol, named by `prefix`, or else by the
+        // current global namespace's gensym prefix, and the counter.
+        "gensym" (&optional prefix) -> {
+            if (*prefix).definedp() {
+                let prefix = <GcRef<Symbol>>::try_convert_from(*prefix)?;
+                Object::from(gensym::make_gensym_with_prefix((*prefix).as_ref()))
+            } else {
+                Object::from(gensym::make_gensym())
+            }
+        };
+        "gensym-counter" () -> {
+            Object::from(gensym::gensym_counter() as i32)
+        };
+        "gensym-prefix" (&optional namespace) -> {
+            let name = namespace_name(*namespace)?;
+            Object::from(symbol_lookup::intern(&gensym::prefix_for(name)))
+        };
+        "set-gensym-prefix" (prefix &optional namespace) -> {
+            let prefix = <GcRef<Symbol>>::try_convert_from(*prefix)?;
+            let name = namespace_name(*namespace)?;
+            gensym::set_prefix(name, (*prefix).as_ref());
+            Object::from(prefix)
+        };
+    }
+}
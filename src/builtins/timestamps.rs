@@ -0,0 +1,64 @@
+//! Builtin functions which make and compare timestamps. Phoebe's
+//! integers are only 32 bits, so seconds are passed and returned as
+//! floats.
+
+use crate::prelude::*;
+use crate::types::timestamp::Timestamp;
+
+lazy_static! {
+    static ref TIMESTAMP: GcRef<Symbol> = { symbol_lookup::make_symbol(b"timestamp") };
+}
+
+fn timestamp(obj: Object) -> Result<Timestamp, GcRef<Error>> {
+    <GcRef<Native>>::maybe_from(obj)
+        .and_then(|n| n.downcast_ref::<Timestamp>().cloned())
+        .ok_or_else(|| Error::type_error(*TIMESTAMP))
+}
+
+fn allocate_timestamp(t: Timestamp) -> Object {
+    Object::from(Native::allocate(Native::new(t)))
+}
+
+pub fn make_timestamp_builtins() {
+    builtin_functions! {
+        "now" () -> {
+            allocate_timestamp(Timestamp::now())
+        };
+        // The timestamp `seconds` after the Unix epoch.
+        "seconds-to-timestamp" (seconds) -> {
+            let seconds = f64::from(PhoebeNumber::try_convert_from(*seconds)?);
+            allocate_timestamp(Timestamp::from_seconds(seconds))
+        };
+        // The seconds from the Unix epoch to `time`.
+        "timestamp-seconds" (time) -> {
+            Object::from(timestamp(*time)?.seconds())
+        };
+        // The seconds from `b` to `a`, negative if `a` is earlier.
+        "time-difference" (a b) -> {
+            Object::from(timestamp(*a)?.seconds_since(timestamp(*b)?))
+        };
+        // `time` as a string in ISO 8601 format, in UTC, like
+        // "2018-07-04T12:00:00.000000000Z".
+        "format-time" (time) -> {
+            Object::from(&timestamp(*time)?.iso8601()[..])
+        };
+        // `time` in UTC, as the list
+        // `(year month day hour minute second nanosecond)`.
+        "decode-time" (time) -> {
+            let d = timestamp(*time)?.decode();
+            let fields: List = [
+                d.year as i32,
+                d.month as i32,
+                d.day as i32,
+                d.hour as i32,
+                d.minute as i32,
+                d.second as i32,
+                d.nanosecond as i32,
+            ]
+                .iter()
+                .map(|&n| Object::from(n))
+                .collect();
+            Object::from(fields)
+        };
+    }
+}
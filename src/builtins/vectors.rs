@@ -0,0 +1,113 @@
+//! Builtin functions which make and manipulate vectors and byte
+//! vectors, along with `length`, which works on any sequence.
+
+use crate::prelude::*;
+
+/// Convert `obj` into an index or size, which must be a non-negative
+/// integer.
+fn index(obj: Object) -> Result<usize, GcRef<Error>> {
+    match i32::try_convert_from(obj)? {
+        n if n >= 0 => Ok(n as usize),
+        _ => Err(Error::type_error(symbol_lookup::make_symbol(b"index"))),
+    }
+}
+
+/// Convert `obj` into a byte, which must be an integer from 0 to 255.
+fn byte(obj: Object) -> Result<u8, GcRef<Error>> {
+    match i32::try_convert_from(obj)? {
+        n if 0 <= n && n <= 255 => Ok(n as u8),
+        _ => Err(Error::type_error(symbol_lookup::make_symbol(b"byte"))),
+    }
+}
+
+pub fn make_vector_builtins() {
+    builtin_functions! {
+        "make-vector" (size &optional initial_element) -> {
+            let initial_element = if (*initial_element).definedp() {
+                *initial_element
+            } else {
+                Object::nil()
+            };
+            let elements = vec![initial_element; index(*size)?];
+            Object::from(Vector::allocate(Vector::new(elements)))
+        };
+        "vector" (&rest elements) -> {
+            let elements = List::try_convert_from(*elements)?.collect();
+            Object::from(Vector::allocate(Vector::new(elements)))
+        };
+        // Returns a reference to the element, so that
+        // `(setf (aref v i) x)` works.
+        "aref" (vector idx) -> {
+            let mut vector = <GcRef<Vector>>::try_convert_from(*vector)?;
+            Object::from(vector.ref_element(index(*idx)?)?)
+        };
+        // Appends `item` to `vector`, growing it, and returns the
+        // index `item` was stored at.
+        "vector-push" (item vector) -> {
+            let mut vector = <GcRef<Vector>>::try_convert_from(*vector)?;
+            vector.push(*item);
+            Object::from(vector.len() as i32 - 1)
+        };
+        "vector-pop" (vector) -> {
+            let mut vector = <GcRef<Vector>>::try_convert_from(*vector)?;
+            vector.pop().unwrap_or_else(Object::nil)
+        };
+        "make-bytes" (size &optional initial_byte) -> {
+            let initial_byte = if (*initial_byte).definedp() {
+                byte(*initial_byte)?
+            } else {
+                0
+            };
+            let bytes = vec![initial_byte; index(*size)?];
+            Object::from(Bytes::allocate(Bytes::new(bytes)))
+        };
+        "bytes" (&rest bytes) -> {
+            let mut contents = Vec::new();
+            for b in List::try_convert_from(*bytes)? {
+                contents.push(byte(b)?);
+            }
+            Object::from(Bytes::allocate(Bytes::new(contents)))
+        };
+        "byte-ref" (bytes idx) -> {
+            let bytes = <GcRef<Bytes>>::try_convert_from(*bytes)?;
+            Object::from(i32::from(bytes.get(index(*idx)?)?))
+        };
+        // The `setf-function` of `byte-ref`.
+        "set-byte-ref" (bytes idx value) -> {
+            let mut bytes = <GcRef<Bytes>>::try_convert_from(*bytes)?;
+            bytes.set(index(*idx)?, byte(*value)?)?;
+            *value
+        };
+        // Encodes `string` as UTF-8.
+        "string-to-bytes" (string) -> {
+            let string = <GcRef<PhoebeString>>::try_convert_from(*string)?;
+            Object::from(Bytes::allocate(Bytes::new(string.as_str().as_bytes().to_vec())))
+        };
+        // Decodes `bytes` as UTF-8, signaling an `encoding-error` if
+        // they aren't valid.
+        "bytes-to-string" (bytes) -> {
+            let bytes = <GcRef<Bytes>>::try_convert_from(*bytes)?;
+            match ::std::str::from_utf8(bytes.as_slice()) {
+                Ok(s) => Object::from(s),
+                Err(e) => EvaluatorError::InvalidUtf8 {
+                    valid_up_to: e.valid_up_to(),
+                }
+                .into(),
+            }
+        };
+        "length" (sequence) -> {
+            let sequence = *sequence;
+            let length = if let Some(v) = <GcRef<Vector>>::maybe_from(sequence) {
+                v.len()
+            } else if let Some(b) = <GcRef<Bytes>>::maybe_from(sequence) {
+                b.len()
+            } else if let Some(s) = <GcRef<PhoebeString>>::maybe_from(sequence) {
+                s.as_str().chars().count()
+            } else {
+                List::try_convert_from(sequence)?.count()
+            };
+            Object::from(length as i32)
+        };
+    }
+    super::define_setf_function(b"byte-ref", b"set-byte-ref");
+}
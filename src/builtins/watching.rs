@@ -0,0 +1,23 @@
+//! Builtin functions which watch global bindings. See the
+//! `watchpoints` module.
+
+use crate::prelude::*;
+use crate::watchpoints;
+
+pub fn make_watchpoint_builtins() {
+    builtin_functions! {
+        // Calls `function` with the symbol, old value and new value
+        // whenever `setf` stores into `symbol`'s global binding.
+        "watch" (symbol function) -> {
+            let symbol = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            let function = <GcRef<Function>>::try_convert_from(*function)?;
+            watchpoints::watch(symbol, function);
+            Object::from(symbol)
+        };
+        // Returns `t` if `symbol`'s global binding was watched.
+        "unwatch" (symbol) -> {
+            let symbol = <GcRef<Symbol>>::try_convert_from(*symbol)?;
+            Object::from(watchpoints::unwatch(symbol))
+        };
+    }
+}
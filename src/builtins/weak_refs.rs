@@ -0,0 +1,23 @@
+//! Builtin functions which make and read weak references.
+
+use crate::prelude::*;
+
+pub fn make_weak_ref_builtins() {
+    builtin_functions! {
+        // A weak reference to `object`, which doesn't keep `object`
+        // alive.
+        "weak-ref" (object) -> {
+            let mut target = *object;
+            while let Some(r) = Reference::maybe_from(target) {
+                target = *r;
+            }
+            Object::from(WeakRef::allocate(WeakRef::new(target)))
+        };
+        // The target of `weak_ref`, or `nil` if it has been
+        // collected.
+        "weak-ref-value" (weak_ref) -> {
+            let weak_ref = <GcRef<WeakRef>>::try_convert_from(*weak_ref)?;
+            weak_ref.target().unwrap_or_else(Object::nil)
+        };
+    }
+}
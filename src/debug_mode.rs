@@ -0,0 +1,31 @@
+//! In debug mode, `debug-assert` checks its assertion; otherwise it
+//! does nothing, not even evaluate it. Debug mode is off unless an
+//! `Interpreter` is configured `with_debug_mode(true)`, and is
+//! installed per-thread for the duration of that interpreter's REPL.
+
+use std::cell::Cell;
+
+thread_local! {
+    static DEBUG_MODE: Cell<bool> = { Cell::new(false) };
+}
+
+/// True if this thread is in debug mode.
+pub fn debug_mode() -> bool {
+    DEBUG_MODE.with(Cell::get)
+}
+
+/// Executes `fun` with this thread in debug mode if `debug_mode`,
+/// restoring the previous mode when finished.
+pub fn with_debug_mode<F, T>(debug_mode: bool, fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEBUG_MODE.with(|d| d.set(self.0));
+        }
+    }
+    let _restore = Restore(DEBUG_MODE.with(|d| d.replace(debug_mode)));
+    fun()
+}
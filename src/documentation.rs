@@ -0,0 +1,37 @@
+//! Documentation attached to functions and global variables. A
+//! function's documentation is stored on its `Function`, so it
+//! follows the function wherever it's passed. A variable's is stored
+//! on the variable's name.
+//!
+//! Documentation is usually a docstring, but any object will do.
+
+use crate::prelude::*;
+
+pub fn variable_documentation(sym: GcRef<Symbol>) -> Option<Object> {
+    sym.documentation()
+}
+
+pub fn set_variable_documentation(mut sym: GcRef<Symbol>, documentation: Object) {
+    sym.set_documentation(documentation);
+}
+
+/// The documentation of whatever `sym` names in the global
+/// namespace: the function's if it names a function, or else the
+/// variable's.
+pub fn documentation(sym: GcRef<Symbol>) -> Option<Object> {
+    let value = symbol_lookup::get_function_from_global_namespace(sym).map(|r| *r);
+    match value.and_then(<GcRef<Function>>::maybe_from) {
+        Some(f) => f.documentation(),
+        None => variable_documentation(sym),
+    }
+}
+
+/// Attach `documentation` to the function `sym` names, or if it
+/// doesn't name a function, to the variable `sym`.
+pub fn set_documentation(sym: GcRef<Symbol>, documentation: Object) {
+    let value = symbol_lookup::get_function_from_global_namespace(sym).map(|r| *r);
+    match value.and_then(<GcRef<Function>>::maybe_from) {
+        Some(mut f) => f.set_documentation(documentation),
+        None => set_variable_documentation(sym, documentation),
+    }
+}
@@ -9,7 +9,13 @@
 //!
 //! * the unsafe function `eval_from_stack`, which is a part of the
 //!   read-eval-print loop
+//!
+//! Every evaluation is a safepoint, see `gc::safepoint`, checks the
+//! heap limit set by `gc::set_heap_limit` and checks whether the
+//! thread has been interrupted, see the `interrupt` module.
 
+use crate::gc::{self, safepoint::Mutator};
+use crate::interrupt;
 use crate::prelude::*;
 use crate::types::ExpandedObject;
 use std::cell::Cell;
@@ -20,7 +26,7 @@ thread_local! {
     };
 }
 
-fn should_eval_to_reference() -> bool {
+pub fn should_eval_to_reference() -> bool {
     EVAL_TO_REFERENCE.with(|r| r.get())
 }
 
@@ -55,6 +61,12 @@ impl Evaluate for Object {
     fn evaluate(&self) -> Object {
         info!("Evaluating {}.", self);
 
+        let _mutator = Mutator::enter();
+        gc::check_heap_limit()?;
+        if interrupt::take_interrupt() {
+            return EvaluatorError::Interrupted.into();
+        }
+
         (*self)?;
 
         let mut o = self.expand_quiet().evaluate();
@@ -70,9 +82,10 @@ impl Evaluate for Object {
 }
 
 impl Evaluate for ExpandedObject {
-    /// Floats, `Immediate`s, `Function`s and `Namespace`s are all
-    /// self-evaluating. `Reference`s evaluate to the value they
-    /// dereference to. `HeapObject`s evaluate by dereferencing and
+    /// Floats, ratios, `Immediate`s, strings, vectors, byte vectors,
+    /// hash tables, structures, weak references, native objects,
+    /// `Function`s and `Namespace`s are all self-evaluating.
+    /// `Reference`s evaluate to the value they dereference to. `HeapObject`s evaluate by dereferencing and
     /// evaluating themselves. `Symbol`s are looked up. `Cons`es are
     /// the only `Object`s with a serious, beefy `evaluate`
     /// implementation.
@@ -82,10 +95,18 @@ impl Evaluate for ExpandedObject {
             ExpandedObject::Immediate(i) => Object::from(i),
             ExpandedObject::Reference(ref r) => **r,
             ExpandedObject::Symbol(s) => s.evaluate(),
+            ExpandedObject::String(s) => Object::from(s),
             ExpandedObject::Function(f) => Object::from(f),
             ExpandedObject::Cons(c) => c.evaluate(),
             ExpandedObject::Namespace(n) => Object::from(n),
             ExpandedObject::HeapObject(h) => (**h).evaluate(),
+            ExpandedObject::Vector(v) => Object::from(v),
+            ExpandedObject::HashTable(h) => Object::from(h),
+            ExpandedObject::Ratio(r) => Object::from(r),
+            ExpandedObject::Structure(s) => Object::from(s),
+            ExpandedObject::Bytes(b) => Object::from(b),
+            ExpandedObject::WeakRef(w) => Object::from(w),
+            ExpandedObject::Native(n) => Object::from(n),
             ExpandedObject::QuietError(e) => Object::quiet_error(e),
         }
     }
@@ -1,6 +1,7 @@
 //! Contains the trait `GarbageCollected`, which is implemented by all
 //! heap-allocated `Object` variants.
 
+use super::roots::record_allocation;
 use super::{GcMark, GcRef};
 use crate::allocate::add_to_alloced;
 use crate::types::Object;
@@ -36,6 +37,8 @@ where
     /// which handles wrapping the `NonNull` into a `GcRef`.
     fn allocate(raw: Self::ConvertFrom) -> GcRef<Self> {
         let r = Self::alloc_one_and_initialize(raw).into();
+        // Root it before the collector can see it.
+        record_allocation(Object::from(r));
         add_to_alloced(Object::from(r));
         r
     }
@@ -50,18 +53,24 @@ where
     /// This function is called by `gc_mark` and allows collections to
     /// mark their children. Atoms can write a do-nothing
     /// implementation.
-    fn gc_mark_children(&mut self, mark: usize);
+    fn gc_mark_children(&mut self, mark: bool);
 
-    /// Sets `my_marking` to `m` and runs `gc_mark_children`.
-    fn gc_mark(obj: &mut GcRef<Self>, m: usize) {
+    /// Sets `my_marking` to `m` and, if it wasn't already marked,
+    /// queues it for `gc_mark_children`.
+    fn gc_mark(obj: &mut GcRef<Self>, m: bool) {
         let old_m = obj.my_marking().swap(m, Ordering::SeqCst);
         if old_m != m {
-            obj.gc_mark_children(m);
+            super::shade(Object::from(*obj));
         }
     }
 
+    /// Sets `my_marking` to `white`, without marking its children.
+    fn whiten(obj: &GcRef<Self>, white: bool) {
+        obj.my_marking().store(white, Ordering::SeqCst);
+    }
+
     /// True iff `my_marking != current_marking`.
-    fn should_dealloc(obj: &GcRef<Self>, current_marking: usize) -> bool {
+    fn should_dealloc(obj: &GcRef<Self>, current_marking: bool) -> bool {
         obj.my_marking().load(Ordering::SeqCst) != current_marking
     }
 }
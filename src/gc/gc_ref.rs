@@ -109,12 +109,15 @@ where
     T: GarbageCollected,
     Object: convert::From<Self>,
 {
-    pub fn should_dealloc(&self, m: usize) -> bool {
+    pub fn should_dealloc(&self, m: bool) -> bool {
         T::should_dealloc(self, m)
     }
-    pub fn gc_mark(mut self, m: usize) {
+    pub fn gc_mark(mut self, m: bool) {
         T::gc_mark(&mut self, m)
     }
+    pub(crate) fn whiten(self, white: bool) {
+        T::whiten(&self, white)
+    }
 }
 
 impl<T> Evaluate for GcRef<T>
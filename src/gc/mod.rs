@@ -1,93 +1,515 @@
 //! Phoebe's parallel/concurrent mark-and-sweep garbage collector.
 //!
-//! TODO: Move away from `usize` as `GcMark` and replace it with
-//! `bool`; replace `IS_GC_RUNNING` and `THE_GC_MARK` with a
-//! `Mutex<GcInfo>`, where `GcInfo` is a struct that maps
-//! `true`/`false` to "white" and "black".
+//! Marking is tri-color. Marking an object colors it gray by pushing
+//! it onto `GRAY_OBJECTS`, and it becomes black once its children
+//! have been marked in turn by `mark_step`. The roots are marked
+//! while their owners' locks are held, but the rest of the heap is
+//! traced in steps of `MARK_STEP_SIZE` objects without holding any
+//! locks, so other threads can keep allocating, reading symbols and
+//! using their stacks. Only the final remark and the sweep hold the
+//! `ALLOCED_OBJECTS` lock, and run only once every thread which is
+//! evaluating has stopped at a safepoint; see `safepoint`. The
+//! remark marks the roots again, along
+//! with everything allocated since marking began, to catch objects
+//! which moved into a root while the heap was being traced.
+//!
+//! Objects can also move between heap objects while the heap is being
+//! traced, as when `setf` stores an object into a cons which has
+//! already been marked and then overwrites the slot it came from.
+//! The remark wouldn't find such an object, so while a pass is
+//! marking, code which overwrites or removes an object's reference to
+//! another calls `write_barrier` with the old value, which shades it.
+//! This is a snapshot-at-the-beginning barrier: everything reachable
+//! when marking began survives the pass, as does everything allocated
+//! since.
+//!
+//! Vectors, hash tables and native objects keep their children in
+//! Rust collections which a mutator may reallocate at any time, with
+//! no lock the collector could take, so while the mutators are
+//! running `mark_step` puts off marking their children until the
+//! remark. See `Object::children_may_move`.
+//!
+//! The symbol intern table is weak: after marking, the remark drops
+//! the table's entries for any unmarked symbols, so they can be swept
+//! like anything else. See `symbol_lookup::SYMBOLS_HEAP`.
+//!
+//! Embedders can tune how often the collector runs with
+//! `set_threshold`, hold it off during critical sections with
+//! `pause`, run it on demand with `collect`, and cap the number of
+//! live objects with `set_heap_limit`. Values they hold in Rust
+//! variables have to be kept alive with `Rooted`. Each pass times its
+//! mark and sweep phases; `stats` sums them, and `on_collection`
+//! registers a callback which is passed each pass's `PassSummary`.
+//!
+//! Marks are `bool`s, and which of `true` and `false` means "white"
+//! (unmarked) swaps after every pass. See `GcInfo`.
 
-use crate::allocate::deallocate;
-use crate::allocate::ALLOCED_OBJECTS;
+use crate::allocate::{deallocate, live_object_count, total_allocation_count, ALLOCED_OBJECTS};
 use crate::builtins::make_builtins_once;
-use crate::stack::gc_mark_stack;
+use crate::symbol_lookup::{self, SYMBOLS_HEAP};
+use crate::types::conversions::MaybeFrom;
+use crate::types::weak_ref::WeakRef;
 use crate::types::Object;
 use std::{
+    cell::RefCell,
     default::Default,
+    fmt, mem,
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        MutexGuard,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Condvar, Mutex, MutexGuard,
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-#[cfg(test)]
-use std::sync;
-
 /// The garbage collector's stack size, in bytes. This doesn't need to
 /// be particularly large; the 2MiB default is excessive.
 const GARBAGE_COLLECTOR_STACK_SIZE: usize = 32 * 1024;
 
-/// This is currently set to `0` for testing purposes - bugs get
-/// caught much more quickly when the gc runs immediately. A
-/// reasonable value would be based off the number of builtin
-/// functions, and is probably in the hundreds or low thousands. Emacs
-/// uses like 80000 or something, but is also a much larger
-/// interpreter with many more builtins.
+/// The number of gray objects `mark_step` blackens at a time.
+const MARK_STEP_SIZE: usize = 256;
+
+/// The threshold until `set_threshold` is called. This is currently
+/// set to `0` for testing purposes - bugs get caught much more
+/// quickly when the gc runs immediately. A reasonable value would be
+/// based off the number of builtin functions, and is probably in the
+/// hundreds or low thousands. Emacs uses like 80000 or something, but
+/// is also a much larger interpreter with many more builtins.
 const INITIAL_GC_THRESHOLD: usize = 0;
 
-#[cfg(test)]
-lazy_static! {
-    /// `GC_SIGNAL_TUPLE.0` is a `Mutex<bool>` representing the
-    /// garbage collector having run, and `.1` is a `Condvar` which
-    /// signals whenever the garbage collector runs.
-    pub static ref GC_SIGNAL_TUPLE: (sync::Mutex<bool>, sync::Condvar) = {
-        (sync::Mutex::new(false), sync::Condvar::new())
-    };
+/// How many passes have started and finished, what the last one
+/// did, how long they've spent in each phase in total, and how many
+/// `GcPause`s are alive.
+#[derive(Default)]
+struct Passes {
+    started: usize,
+    finished: usize,
+    /// The number of passes which threads waiting in `collect` need
+    /// to have started.
+    requested: usize,
+    last: PassSummary,
+    total_pause: Duration,
+    total_mark: Duration,
+    total_sweep: Duration,
+    pauses: usize,
 }
 
+/// What a pass did, as returned by `collect` and passed to the
+/// `on_collection` callback.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PassSummary {
+    /// Objects deallocated.
+    pub freed: usize,
+    /// Objects which survived.
+    pub live: usize,
+    /// The time spent marking, from marking the roots until the end
+    /// of the remark, including the time other threads kept running.
+    pub mark: Duration,
+    /// The time spent clearing weak references and sweeping.
+    pub sweep: Duration,
+    /// The time the mutators were stopped, from when the collector
+    /// began waiting for them to reach safepoints until the end of
+    /// the sweep.
+    pub pause: Duration,
+}
+
+type CollectionCallback = Box<Fn(&PassSummary) + Send>;
+
 lazy_static! {
-    pub static ref THE_GC_THREAD: JoinHandle<!> = {
+    pub(crate) static ref THE_GC_THREAD: JoinHandle<!> = {
         thread::Builder::new()
             .name("Garbage collector".to_owned())
             .stack_size(GARBAGE_COLLECTOR_STACK_SIZE)
             .spawn(gc_thread)
             .unwrap()
     };
-    static ref THE_GC_MARK: AtomicUsize = { AtomicUsize::default() };
-    /// Whenever we finish evaluating an `Object`, we check to see if
-    /// `alloced_count` is larger than `GC_THRESHOLD` and if it is,
-    /// spawn a garbage collector thread.
-    ///
-    /// Future optimization: find some way to base `GC_THRESHOLD` off
-    /// of `ALLOCED_OBJECTS`' reserved capacity, to discourage
-    /// reallocation.
-    pub static ref GC_THRESHOLD: AtomicUsize = { AtomicUsize::new(INITIAL_GC_THRESHOLD) };
+    static ref GC_INFO: Mutex<GcInfo> = { Mutex::default() };
+    /// `.1` is notified whenever a pass finishes or the last
+    /// `GcPause` is dropped.
+    static ref PASSES: (Mutex<Passes>, Condvar) = { (Mutex::default(), Condvar::new()) };
+    /// The lowest `GC_THRESHOLD` may fall to, set by `set_threshold`.
+    static ref MIN_GC_THRESHOLD: AtomicUsize = { AtomicUsize::new(INITIAL_GC_THRESHOLD) };
+    /// Whenever the allocator thread adds an object to
+    /// `ALLOCED_OBJECTS`, it checks whether the vector is longer than
+    /// `GC_THRESHOLD`, and if it is, wakes the collector. Each pass
+    /// sets it for the next; see `policy`.
+    pub(crate) static ref GC_THRESHOLD: AtomicUsize = { AtomicUsize::new(INITIAL_GC_THRESHOLD) };
+    /// Set by `set_heap_limit`; `usize::max_value()` means there's no
+    /// limit.
+    static ref HEAP_LIMIT: AtomicUsize = { AtomicUsize::new(usize::max_value()) };
+    /// Set by `on_collection`.
+    static ref COLLECTION_CALLBACK: Mutex<Option<CollectionCallback>> = { Mutex::new(None) };
+    /// True from when a pass begins marking its roots until the end of
+    /// its remark. It only changes while `BARRIER_OBJECTS` is locked.
+    static ref MARKING: AtomicBool = { AtomicBool::new(false) };
+    /// Objects shaded by `write_barrier`, which `mark_step` marks.
+    static ref BARRIER_OBJECTS: Mutex<Vec<Object>> = { Mutex::new(Vec::new()) };
+}
+
+thread_local! {
+    /// Objects which have been marked but whose children have not,
+    /// the "gray" objects of tri-color marking. Only the thread
+    /// running `gc_pass` uses this.
+    static GRAY_OBJECTS: RefCell<Vec<Object>> = { RefCell::new(Vec::new()) };
+    /// Gray objects whose children `mark_step` has left for the
+    /// remark, because the mutators were running.
+    static DEFERRED_OBJECTS: RefCell<Vec<Object>> = { RefCell::new(Vec::new()) };
 }
 
 pub mod garbage_collected;
 pub mod gc_ref;
+mod policy;
+pub(crate) mod pool;
+pub(crate) mod roots;
+pub mod safepoint;
 
 pub use self::garbage_collected::GarbageCollected;
 pub use self::gc_ref::GcRef;
+pub use self::roots::Rooted;
+
+/// There are only two states, which in gc theory are called "white"
+/// and "black", but which is `true` swaps after each pass. Ask
+/// `GcInfo` which is which.
+pub type GcMark = AtomicBool;
+
+/// Maps the two values of a `GcMark` to "white" and "black". A pass
+/// marks every object it reaches black, and deallocates the ones
+/// left white. Rather than whitening the survivors afterwards, the
+/// colors swap: the black of one pass is the white of the next.
+#[derive(Default)]
+struct GcInfo {
+    /// The mark of objects which the current pass hasn't reached, or
+    /// which the next pass won't have reached when it starts. It only
+    /// changes while `ALLOCED_OBJECTS` is locked.
+    white: bool,
+}
+
+impl GcInfo {
+    fn black(&self) -> bool {
+        !self.white
+    }
+}
+
+/// The mark of objects which haven't been reached by the current or
+/// next pass. The allocator thread gives each new object this mark
+/// as it adds the object to `ALLOCED_OBJECTS`, while holding the
+/// lock, so the colors can't swap in between.
+pub(crate) fn white() -> bool {
+    GC_INFO.lock().unwrap().white
+}
+
+/// A snapshot of the collector's counters, as returned by `stats`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct GcStats {
+    /// Objects allocated by every thread since the runtime started.
+    pub objects_allocated: usize,
+    /// Objects which have been allocated and not yet collected.
+    pub objects_live: usize,
+    /// Objects freed by the most recent pass.
+    pub freed_last_pass: usize,
+    /// Passes which have finished.
+    pub collections: usize,
+    /// The total time passes have spent stopping the mutators,
+    /// remarking and sweeping, during which no Phoebe code runs.
+    pub total_pause: Duration,
+    /// The total time passes have spent marking. See
+    /// `PassSummary::mark`.
+    pub total_mark: Duration,
+    /// The total time passes have spent sweeping.
+    pub total_sweep: Duration,
+}
+
+/// Take a snapshot of the collector's counters. Like
+/// `metrics::metrics`, the counters are read one at a time.
+pub fn stats() -> GcStats {
+    let objects_allocated = total_allocation_count();
+    let objects_live = ALLOCED_OBJECTS.lock().unwrap().len();
+    let passes = PASSES.0.lock().unwrap();
+    GcStats {
+        objects_allocated,
+        objects_live,
+        freed_last_pass: passes.last.freed,
+        collections: passes.finished,
+        total_pause: passes.total_pause,
+        total_mark: passes.total_mark,
+        total_sweep: passes.total_sweep,
+    }
+}
 
-/// This could easily be changed to `AtomicBool` - there are only two
-/// states, which in gc theory are called "white" and "black". A
-/// `bool` feels unintuitive because the two swap after each garbage
-/// collection, meaning that half of the time the mark `true` would
-/// mean "white" (in use, keep), but the other half of the time it
-/// would mean "black" (not in use, deallocate).
-pub type GcMark = AtomicUsize;
+/// Formats a `Duration` as fractional seconds.
+struct Seconds(Duration);
 
-/// Future optimization: find some way to base `GC_THRESHOLD` off of
-/// `ALLOCED_OBJECTS`' reserved capacity, to discourage
-/// reallocation.
-fn update_gc_threshold(alloced: &MutexGuard<Vec<Object>>) {
-    let new_thresh = alloced.len() * 2;
-    GC_THRESHOLD.store(new_thresh, Ordering::Relaxed);
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:06} seconds",
+            self.0.as_secs(),
+            self.0.subsec_micros()
+        )
+    }
+}
+
+/// Formats the counters as the summary printed by the `room`
+/// builtin.
+impl fmt::Display for GcStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Objects allocated: {}", self.objects_allocated)?;
+        writeln!(f, "Objects live: {}", self.objects_live)?;
+        writeln!(
+            f,
+            "Objects freed by the last collection: {}",
+            self.freed_last_pass
+        )?;
+        writeln!(f, "Collections: {}", self.collections)?;
+        writeln!(f, "Total mark time: {}", Seconds(self.total_mark))?;
+        writeln!(f, "Total sweep time: {}", Seconds(self.total_sweep))?;
+        writeln!(f, "Total pause time: {}", Seconds(self.total_pause))
+    }
+}
+
+/// Signaled as a `heap-exhausted-error` when more objects are live
+/// than `set_heap_limit` allows, even after a complete pass.
+#[derive(Fail, Debug, Copy, Clone, PartialEq, Eq)]
+#[fail(
+    display = "The heap is exhausted: {} objects are live, but the limit is {}",
+    live, limit
+)]
+pub struct HeapExhausted {
+    pub live: usize,
+    pub limit: usize,
+}
+
+/// Signal a `heap-exhausted-error`, rather than let more than `limit`
+/// objects be live, or remove the limit if `limit` is `None`. Phoebe
+/// code can catch the error, so embedders can use this to cap
+/// untrusted scripts. The limit is checked at safepoints, so a
+/// builtin which allocates many objects at once may overshoot it.
+pub fn set_heap_limit(limit: Option<usize>) {
+    HEAP_LIMIT.store(limit.unwrap_or_else(usize::max_value), Ordering::Relaxed);
+}
+
+/// The limit set by `set_heap_limit`, if any.
+pub fn heap_limit() -> Option<usize> {
+    match HEAP_LIMIT.load(Ordering::Relaxed) {
+        n if n == usize::max_value() => None,
+        n => Some(n),
+    }
+}
+
+/// Called at every safepoint. If more objects are live than the heap
+/// limit allows, run a complete pass, and if that doesn't free
+/// enough, return an error. While the collector is paused, there's
+/// no pass to wait for, so this returns the error immediately.
+pub(crate) fn check_heap_limit() -> Result<(), HeapExhausted> {
+    let limit = HEAP_LIMIT.load(Ordering::Relaxed);
+    if live_object_count() <= limit {
+        return Ok(());
+    }
+    if PASSES.0.lock().unwrap().pauses == 0 {
+        wait_for_pass();
+    }
+    let live = live_object_count();
+    if live <= limit {
+        Ok(())
+    } else {
+        Err(HeapExhausted { live, limit })
+    }
+}
+
+/// Call `callback` with the summary of every pass from now on, in
+/// place of any previous callback. It runs on the collector's thread
+/// after the pass has finished, so it must not wait for another pass
+/// with `collect` or `pause`, or replace itself.
+pub fn on_collection<F>(callback: F)
+where
+    F: Fn(&PassSummary) + Send + 'static,
+{
+    *COLLECTION_CALLBACK.lock().unwrap() = Some(Box::new(callback));
+}
+
+/// Remove the callback registered by `on_collection`, if any.
+pub fn clear_on_collection() {
+    *COLLECTION_CALLBACK.lock().unwrap() = None;
+}
+
+/// Start a pass whenever more than `n` objects are allocated. After
+/// each pass, the threshold adapts to how many objects survived it
+/// and how quickly threads are allocating, but never falls below
+/// `n`. See `policy`.
+pub fn set_threshold(n: usize) {
+    MIN_GC_THRESHOLD.store(n, Ordering::Relaxed);
+    GC_THRESHOLD.store(n, Ordering::Relaxed);
+}
+
+/// The number of allocated objects above which the next pass starts.
+pub fn threshold() -> usize {
+    GC_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// While a `GcPause` is alive, no objects are freed. Dropping the
+/// last one lets the collector resume.
+pub struct GcPause {
+    _private: (),
+}
+
+/// Stop the garbage collector until the returned `GcPause` is
+/// dropped, first waiting for any pass which is already underway to
+/// finish. This is for embedders' critical sections, like handing an
+/// object to code which doesn't know to root it. Pauses nest, and
+/// the collector stays paused while any thread holds one.
+pub fn pause() -> GcPause {
+    let (ref passes, ref changed) = *PASSES;
+    let mut passes = passes.lock().unwrap();
+    passes.pauses += 1;
+    safepoint::blocking(move || {
+        while passes.started != passes.finished {
+            passes = changed.wait(passes).unwrap();
+        }
+    });
+    GcPause { _private: () }
+}
+
+impl Drop for GcPause {
+    fn drop(&mut self) {
+        let (ref passes, ref changed) = *PASSES;
+        let mut passes = passes.lock().unwrap();
+        passes.pauses -= 1;
+        if passes.pauses == 0 {
+            THE_GC_THREAD.thread().unpark();
+            changed.notify_all();
+        }
+    }
+}
+
+/// Run a complete pass, waiting for it to finish, and return what it
+/// did, or what a later pass did if another began before this thread
+/// woke. The pass frees every object which was unreachable when it
+/// started, except that objects allocated just beforehand may not
+/// have reached the collector yet, in which case they survive until
+/// the next pass. The calling thread's most recent allocation stops
+/// being a root.
+///
+/// If the collector is paused, this waits until it isn't, so calling
+/// it while this thread holds a `GcPause` never returns.
+pub fn collect() -> PassSummary {
+    roots::forget_last_allocation();
+    wait_for_pass()
+}
+
+fn wait_for_pass() -> PassSummary {
+    let (ref passes, ref changed) = *PASSES;
+    let mut passes = passes.lock().unwrap();
+    // A pass which is already underway may have marked objects
+    // before they became unreachable, so wait for the one after it.
+    let target = passes.started + 1;
+    passes.requested = passes.requested.max(target);
+    safepoint::blocking(move || {
+        while passes.finished < target {
+            THE_GC_THREAD.thread().unpark();
+            passes = changed.wait(passes).unwrap();
+        }
+        passes.last
+    })
+}
+
+/// Clear every surviving weak reference whose target is about to be
+/// deallocated. This has to happen before sweeping, while the
+/// targets' marks can still be read.
+fn clear_weak_refs(m: bool, heap: &MutexGuard<Vec<Object>>) {
+    for &obj in heap.iter() {
+        if let Some(mut w) = <GcRef<WeakRef>>::maybe_from(obj) {
+            if obj.should_dealloc(m) {
+                continue;
+            }
+            if let Some(target) = w.target() {
+                if target.should_dealloc(m) {
+                    debug!("{} is unmarked; clearing a weak reference to it.", target);
+                    w.clear();
+                }
+            }
+        }
+    }
+}
+
+/// Called by `GarbageCollected::gc_mark` on each object it newly
+/// marks, so that `mark_step` will mark its children.
+pub(crate) fn shade(obj: Object) {
+    GRAY_OBJECTS.with(|g| g.borrow_mut().push(obj));
+}
+
+/// Called with the value of a slot in a heap object just before it is
+/// overwritten or removed, so that a pass which is marking won't miss
+/// the value if it's since been stored somewhere already marked. See
+/// the module documentation.
+pub(crate) fn write_barrier(old: Object) {
+    if !MARKING.load(Ordering::SeqCst) {
+        return;
+    }
+    let mut barrier = BARRIER_OBJECTS.lock().unwrap();
+    // The pass may have finished since `MARKING` was read, in which
+    // case `old` must not be marked by the next one before it starts.
+    if MARKING.load(Ordering::SeqCst) {
+        barrier.push(old);
+    }
+}
+
+/// Begin or end a pass's marking. Marking has to end while the
+/// mutators are stopped, once `mark_step` has emptied
+/// `BARRIER_OBJECTS`.
+fn set_marking(marking: bool) {
+    let mut barrier = BARRIER_OBJECTS.lock().unwrap();
+    barrier.clear();
+    MARKING.store(marking, Ordering::SeqCst);
+}
+
+/// Mark the children of up to `MARK_STEP_SIZE` gray objects, returning
+/// `true` once there are none left, either gray or shaded by
+/// `write_barrier`. Unless `mutators_stopped`, objects whose children
+/// may move are deferred instead.
+fn mark_step(m: bool, mutators_stopped: bool) -> bool {
+    for _ in 0..MARK_STEP_SIZE {
+        // Don't hold the borrow while marking children, which will
+        // push more gray objects.
+        match GRAY_OBJECTS.with(|g| g.borrow_mut().pop()) {
+            Some(obj) if !mutators_stopped && obj.children_may_move() => {
+                DEFERRED_OBJECTS.with(|d| d.borrow_mut().push(obj));
+            }
+            Some(obj) => obj.gc_mark_children(m),
+            None => {
+                let shaded = mem::replace(&mut *BARRIER_OBJECTS.lock().unwrap(), Vec::new());
+                if shaded.is_empty() {
+                    return true;
+                }
+                for obj in shaded {
+                    obj.gc_mark(m);
+                }
+            }
+        }
+    }
+    false
+}
+
+fn mark_until_done(m: bool, mutators_stopped: bool) {
+    while !mark_step(m, mutators_stopped) {
+        thread::yield_now();
+    }
+}
+
+/// Mark the children of the objects `mark_step` deferred, once the
+/// mutators are stopped.
+fn mark_deferred(m: bool) {
+    let deferred = DEFERRED_OBJECTS.with(|d| mem::replace(&mut *d.borrow_mut(), Vec::new()));
+    for obj in deferred {
+        obj.gc_mark_children(m);
+    }
+    mark_until_done(m, true);
 }
 
 /// Iterate through all of the allocated objects and filter out any
-/// which are not marked "white" (in use).
-fn sweep(m: usize, heap: &mut MutexGuard<Vec<Object>>) {
+/// which are not marked "white" (in use), returning how many were
+/// deallocated.
+fn sweep(m: bool, heap: &mut MutexGuard<Vec<Object>>) -> usize {
     let mut n_removed: usize = 0;
     let mut new_heap = Vec::with_capacity(heap.len());
     for obj in (*heap).drain(..) {
@@ -102,49 +524,112 @@ fn sweep(m: usize, heap: &mut MutexGuard<Vec<Object>>) {
     }
     **heap = new_heap;
     info!("Finished sweeping; deallocated {} objects.", n_removed);
-}
-
-fn mark_scope(m: usize) {
-    use crate::symbol_lookup::{gc_mark_scope, SYMBOLS_HEAP};
-    for &s in SYMBOLS_HEAP.lock().unwrap().values() {
-        s.gc_mark(m);
-    }
-    gc_mark_scope(m);
+    n_removed
 }
 
 /// This is the function which gc threads run with. It will exit
-/// immediately if another garbage collector is already running;
-/// otherwise it will mark all accessible objects and deallocate any
-/// others.
-pub fn gc_pass() {
+/// immediately if the collector is paused; otherwise it will mark all
+/// accessible objects and deallocate any others.
+fn gc_pass() {
+    {
+        let mut passes = PASSES.0.lock().unwrap();
+        if passes.pauses > 0 {
+            info!("Not garbage collecting; the collector is paused.");
+            return;
+        }
+        passes.started += 1;
+    }
     info!("Garbage collecting.");
 
-    {
+    let mark = GC_INFO.lock().unwrap().black();
+    let mark_start = Instant::now();
+    set_marking(true);
+    symbol_lookup::watch_interning();
+    // Objects allocated after this point aren't reachable from
+    // anything marked so far, so they're marked by the remark.
+    let n_old_objects = ALLOCED_OBJECTS.lock().unwrap().len();
+    roots::gc_mark_roots(mark);
+    symbol_lookup::gc_mark_symbols(mark, &SYMBOLS_HEAP.lock().unwrap());
+    mark_until_done(mark, false);
+
+    let summary = {
+        let pause_start = Instant::now();
+        let _stopped = safepoint::stop_mutators();
         let mut lock = ALLOCED_OBJECTS.lock().unwrap();
         debug!("Acquired the ALLOCED_OBJECTS lock");
-        let mark = THE_GC_MARK.fetch_add(1, Ordering::Relaxed);
-        gc_mark_stack(mark);
-        mark_scope(mark);
-        sweep(mark, &mut lock);
-        update_gc_threshold(&lock);
+        roots::gc_mark_roots(mark);
+        for &obj in &lock[n_old_objects..] {
+            obj.gc_mark(mark);
+        }
+        mark_deferred(mark);
+        {
+            // Threads reading or evaluating hold their stacks while
+            // interning symbols, so the table has to be locked after
+            // the roots are marked rather than before.
+            let mut symbols = SYMBOLS_HEAP.lock().unwrap();
+            symbol_lookup::gc_mark_symbols(mark, &symbols);
+            mark_until_done(mark, true);
+            symbol_lookup::forget_unmarked_symbols(mark, &mut symbols);
+        }
+        // The mutators are stopped, so nothing can be shaded between
+        // the last `mark_step` and here.
+        set_marking(false);
+        let sweep_start = Instant::now();
+        clear_weak_refs(mark, &lock);
+        let freed = sweep(mark, &mut lock);
+        // Every survivor is black, so black becomes white.
+        GC_INFO.lock().unwrap().white = mark;
+        policy::update_gc_threshold(&lock, pause_start.elapsed());
         debug!("Dropping the ALLOCED_OBJECTS lock");
-    }
+        PassSummary {
+            freed,
+            live: lock.len(),
+            mark: sweep_start - mark_start,
+            sweep: sweep_start.elapsed(),
+            pause: pause_start.elapsed(),
+        }
+    };
 
-    #[cfg(test)]
     {
-        let (ref mutex, ref cond_var) = *GC_SIGNAL_TUPLE;
-
-        *(mutex.lock().unwrap()) = true;
-        cond_var.notify_all();
+        let (ref passes, ref changed) = *PASSES;
+        let mut passes = passes.lock().unwrap();
+        passes.finished += 1;
+        passes.last = summary;
+        passes.total_pause += summary.pause;
+        passes.total_mark += summary.mark;
+        passes.total_sweep += summary.sweep;
+        changed.notify_all();
+    }
+    if let Some(ref callback) = *COLLECTION_CALLBACK.lock().unwrap() {
+        callback(&summary);
     }
 
     info!("Finished garbage collecting.");
 }
 
+/// The number of garbage collection passes which have finished.
+pub fn gc_count() -> usize {
+    PASSES.0.lock().unwrap().finished
+}
+
+/// Whether a thread is waiting in `collect` or `ALLOCED_OBJECTS` has
+/// outgrown `GC_THRESHOLD`. The collector may be unparked while a
+/// pass is underway, or spuriously, and this keeps it from starting
+/// another pass straight after for no reason.
+fn pass_is_due() -> bool {
+    {
+        let passes = PASSES.0.lock().unwrap();
+        if passes.requested > passes.started {
+            return true;
+        }
+    }
+    ALLOCED_OBJECTS.lock().unwrap().len() > GC_THRESHOLD.load(Ordering::Relaxed)
+}
+
 fn gc_thread() -> ! {
     make_builtins_once();
     loop {
-        {
+        if pass_is_due() {
             gc_pass();
         }
         thread::park();
@@ -162,47 +647,60 @@ mod test {
         let dead_beef = stack::with_stack(|s| {
             let dead_beef: Object =
                 HeapObject::allocate(HeapObject::around(Object::from(0xdead_beefusize))).into();
-            s.push(dead_beef);
+            s.push(dead_beef).unwrap();
 
             dead_beef
         });
 
-        {
-            let (ref al_mutex, ref al_cond_var) = *ALLOCATOR_SIGNAL_TUPLE;
-            let mut lock = al_mutex.lock().unwrap();
+        wait_until_alloced(dead_beef);
 
-            while *lock != dead_beef {
-                lock = al_cond_var.wait(lock).unwrap();
-            }
-        }
+        assert_eq!(stack::pop().unwrap(), dead_beef);
 
+        collect();
         {
             let a_o = ALLOCED_OBJECTS.lock().unwrap();
-            assert!(a_o.contains(&dead_beef));
+            assert!(!(a_o.contains(&dead_beef)));
         }
+    }
+    #[test]
+    fn long_lists_survive() {
+        // Deep enough to overflow the collector's stack if it marked
+        // recursively.
+        let list: List = (0..10_000).map(Object::from).collect();
+        let list = Object::from(list);
+        stack::push_ref(list).unwrap();
 
-        assert_eq!(stack::pop().unwrap(), dead_beef);
-
-        {
-            let (ref gc_mutex, ref gc_cond_var) = *GC_SIGNAL_TUPLE;
-            let mut lock = gc_mutex.lock().unwrap();
-
-            // We wait through two gc cycles in case one has already
-            // started - the already-in-progress one may not
-            // deallocate `dead_beef`, but the next one must.
-            for _ in 0..2 {
-                *lock = false;
+        collect();
+        let sum: i32 = List::try_convert_from(list)
+            .unwrap()
+            .map(|n| i32::try_convert_from(n).unwrap())
+            .sum();
+        assert_eq!(sum, (0..10_000).sum());
+        assert_eq!(stack::pop().unwrap(), list);
+    }
+    #[test]
+    fn unreferenced_symbols_are_forgotten() {
+        let forgotten = symbol_lookup::intern(b"a-symbol-nothing-refers-to");
+        symbol_lookup::intern(b"a-documented-symbol").set_documentation(Object::from(1));
+        symbol_lookup::make_symbol(b"a-symbol-rust-refers-to");
+        wait_until_alloced(Object::from(forgotten));
 
-                THE_GC_THREAD.thread().unpark();
+        collect();
+        let symbols = SYMBOLS_HEAP.lock().unwrap();
+        assert!(!symbols.contains_key(&b"a-symbol-nothing-refers-to"[..]));
+        assert!(symbols.contains_key(&b"a-documented-symbol"[..]));
+        assert!(symbols.contains_key(&b"a-symbol-rust-refers-to"[..]));
+    }
+    /// Wait for the allocator thread to add `obj` to `ALLOCED_OBJECTS`,
+    /// after which the next pass knows about it.
+    fn wait_until_alloced(obj: Object) {
+        let (ref al_mutex, ref al_cond_var) = *ALLOCATOR_SIGNAL_TUPLE;
+        let mut lock = al_mutex.lock().unwrap();
 
-                while !*lock {
-                    lock = gc_cond_var.wait(lock).unwrap();
-                }
-            }
-        }
-        {
-            let a_o = ALLOCED_OBJECTS.lock().unwrap();
-            assert!(!(a_o.contains(&dead_beef)));
+        // Other threads may be allocating too, so the most recently
+        // allocated object isn't necessarily `obj`.
+        while !ALLOCED_OBJECTS.lock().unwrap().contains(&obj) {
+            lock = al_cond_var.wait(lock).unwrap();
         }
     }
 }
@@ -0,0 +1,112 @@
+//! When the collector runs. A pass starts once `ALLOCED_OBJECTS`
+//! holds more than `GC_THRESHOLD` objects, or when a thread calls
+//! `collect`. Each pass sets the threshold for the next from:
+//!
+//! * the number of objects which survived it, since the heap may grow
+//!   to `GROWTH_FACTOR` times its live size between passes,
+//!
+//! * how fast threads are allocating and how long the pass stopped
+//!   them for, since the mutators should get to run for at least
+//!   `PAUSE_SPACING` times as long as they were stopped before the
+//!   next pass stops them again. Without this, a program which
+//!   allocates quickly would spend most of its time collecting,
+//!
+//! * `ALLOCED_OBJECTS`' reserved capacity, since growing the vector
+//!   for only a few objects more, which will be swept soon anyway,
+//!   is wasteful, and
+//!
+//! * the floor set by `set_threshold`.
+
+use super::MIN_GC_THRESHOLD;
+use crate::allocate::total_allocation_count;
+use crate::types::Object;
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The heap may grow to this many times the objects which survived
+/// the last pass before the next one starts.
+const GROWTH_FACTOR: usize = 2;
+
+/// The mutators get to run for at least this many times as long as a
+/// pass stopped them before the next pass starts, so they're stopped
+/// for at most a tenth of the time.
+const PAUSE_SPACING: u32 = 9;
+
+/// A threshold which is past `ALLOCED_OBJECTS`' capacity by no more
+/// than the capacity over this is lowered to the capacity, so that
+/// the vector is swept rather than reallocated.
+const CAPACITY_SLACK_DIVISOR: usize = 4;
+
+/// What the policy remembers from the last pass.
+struct LastPass {
+    finished: Instant,
+    /// `total_allocation_count` when it finished.
+    allocated: usize,
+}
+
+lazy_static! {
+    static ref LAST_PASS: Mutex<Option<LastPass>> = { Mutex::new(None) };
+}
+
+fn seconds(d: Duration) -> f64 {
+    d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000.0
+}
+
+/// The threshold for the next pass, when `live` objects survived the
+/// last one, in a vector with room for `capacity`, the mutators
+/// allocated `rate` objects per second before it, and it stopped
+/// them for `pause`.
+fn next_threshold(live: usize, capacity: usize, rate: f64, pause: Duration) -> usize {
+    let grown = live * GROWTH_FACTOR;
+    let spaced = live + (rate * seconds(pause * PAUSE_SPACING)) as usize;
+    let mut threshold = grown.max(spaced);
+    if threshold > capacity && threshold - capacity <= capacity / CAPACITY_SLACK_DIVISOR {
+        threshold = capacity;
+    }
+    threshold.max(MIN_GC_THRESHOLD.load(Ordering::Relaxed))
+}
+
+/// Called at the end of each pass, which stopped the mutators for
+/// `pause`, while it still holds `alloced`.
+pub(super) fn update_gc_threshold(alloced: &MutexGuard<Vec<Object>>, pause: Duration) {
+    let now = Instant::now();
+    let allocated = total_allocation_count();
+    let mut last_pass = LAST_PASS.lock().unwrap();
+    let rate = match *last_pass {
+        Some(LastPass {
+            finished,
+            allocated: allocated_before,
+        }) => {
+            let elapsed = seconds(now - finished);
+            if elapsed > 0.0 {
+                (allocated - allocated_before) as f64 / elapsed
+            } else {
+                0.0
+            }
+        }
+        None => 0.0,
+    };
+    *last_pass = Some(LastPass {
+        finished: now,
+        allocated,
+    });
+    let threshold = next_threshold(alloced.len(), alloced.capacity(), rate, pause);
+    super::GC_THRESHOLD.store(threshold, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn threshold_adapts() {
+        let ms = Duration::from_millis;
+        // Slow allocation: the heap may double.
+        assert_eq!(next_threshold(1000, 100_000, 10.0, ms(1)), 2000);
+        // Fast allocation and long pauses space passes further apart.
+        assert_eq!(next_threshold(1000, 100_000, 1_000_000.0, ms(10)), 91_000);
+        // Just past the capacity, so the vector needn't grow.
+        assert_eq!(next_threshold(1000, 1900, 0.0, ms(1)), 1900);
+        assert_eq!(next_threshold(1000, 1000, 0.0, ms(1)), 2000);
+    }
+}
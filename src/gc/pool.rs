@@ -0,0 +1,71 @@
+//! Free lists of memory for types which are allocated and collected
+//! in large numbers, like the `Namespace` every function call makes.
+//! Deallocating one of these puts its memory back in the type's
+//! `Pool` rather than returning it to the system allocator, and the
+//! next allocation reuses it.
+//!
+//! Objects are only ever put back in a pool by the garbage collector,
+//! so a pooled block is never reachable from anything.
+
+use std::alloc::{Alloc, Global};
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+/// The most free blocks a `Pool` keeps. Any more are returned to the
+/// system allocator, so a burst of allocations doesn't hold on to its
+/// memory forever.
+pub const POOL_CAPACITY: usize = 4096;
+
+/// `NonNull` isn't `Send`, but a free block is only ever touched by
+/// whichever thread took it from the pool.
+struct FreeBlock<T>(NonNull<T>);
+
+unsafe impl<T> Send for FreeBlock<T> {}
+
+pub struct Pool<T> {
+    free: Mutex<Vec<FreeBlock<T>>>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool {
+            free: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    /// Uninitialized memory for a `T`, reused from the pool if
+    /// possible.
+    pub fn alloc(&self) -> NonNull<T> {
+        match self.free.lock().unwrap().pop() {
+            Some(FreeBlock(block)) => block,
+            None => Global.alloc_one().unwrap(),
+        }
+    }
+    /// Put `block`, which came from `alloc` and whose `T` has already
+    /// been dropped, back in the pool.
+    pub unsafe fn free(&self, block: NonNull<T>) {
+        let mut free = self.free.lock().unwrap();
+        if free.len() < POOL_CAPACITY {
+            free.push(FreeBlock(block));
+        } else {
+            Global.dealloc_one(block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn blocks_are_reused() {
+        let pool: Pool<u64> = Pool::default();
+        let block = pool.alloc();
+        unsafe { pool.free(block) };
+        assert_eq!(pool.free.lock().unwrap().len(), 1);
+        assert_eq!(pool.alloc(), block);
+        assert_eq!(pool.free.lock().unwrap().len(), 0);
+        unsafe { Global.dealloc_one(block) };
+    }
+}
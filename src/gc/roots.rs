@@ -0,0 +1,295 @@
+//! The garbage collector's roots, the objects from which it begins
+//! marking. They are:
+//!
+//! * every thread's stack, in `stack::STACKS`,
+//! * every thread's `ThreadRoots`, in `THREAD_ROOTS`, which hold its
+//!   environment stack and any temporaries it has registered,
+//! * the names of namespaces with gensym prefixes,
+//! * the symbol under which generic functions are stored, and
+//! * the pinned symbols in `symbol_lookup::SYMBOLS_HEAP`. Those are
+//!   marked separately, by `symbol_lookup::gc_mark_symbols`, because
+//!   the collector has to hold the table until it has dropped the
+//!   unmarked ones.
+//!
+//! A thread registers its roots the first time it uses them, and
+//! unregisters them when it exits. Each is behind a lock shared with
+//! the collector, so the collector sees every thread's roots no
+//! matter which thread it runs alongside.
+//!
+//! Each thread's most recent allocation or interned symbol is also a
+//! root, from before the collector learns of it until the thread
+//! allocates again, so a new object can't be collected before its
+//! thread stores it somewhere. An object which a thread only holds in a Rust local
+//! isn't otherwise reachable, though, so a thread which must
+//! allocate again before storing such an object somewhere should
+//! first register it with `push_temporary_root`, or, outside of
+//! Phoebe itself, wrap it in a `Rooted`.
+
+use crate::builtins::generic_functions::gc_mark_plist_key;
+use crate::gensym::gc_mark_prefixes;
+use crate::prelude::*;
+use crate::stack::gc_mark_stack;
+use crate::symbol_lookup::default_global_env;
+use crate::watchpoints::gc_mark_watches;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Mutex, RwLock};
+use std::thread::{self, ThreadId};
+
+thread_local! {
+    static ROOTS_KEY: RootsKey = { RootsKey(thread::current().id()) };
+}
+
+/// The key of this thread's entry in `THREAD_ROOTS`.
+struct RootsKey(ThreadId);
+
+impl Drop for RootsKey {
+    /// Unregister the thread's roots when it exits, as
+    /// `stack::LocalStack` does its stack. The collector holds
+    /// `THREAD_ROOTS` while it marks the roots, so this waits for it
+    /// to finish with them.
+    fn drop(&mut self) {
+        if let Ok(mut roots) = THREAD_ROOTS.write() {
+            roots.remove(&self.0);
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref THREAD_ROOTS: RwLock<HashMap<ThreadId, Mutex<ThreadRoots>>> =
+        { RwLock::new(HashMap::new()) };
+}
+
+/// The roots belonging to a single thread, other than its stack.
+pub struct ThreadRoots {
+    /// A stack of `Namespace`s, each of which corresponds to either a
+    /// function's stack frame or a `let` environment. The first is
+    /// the thread's global environment.
+    pub env_stack: Vec<GcRef<Namespace>>,
+    /// Objects registered by `push_temporary_root`.
+    pub temporaries: Vec<Object>,
+    /// Set by `GarbageCollected::allocate` and `symbol_lookup::intern`.
+    pub last_allocated: Object,
+}
+
+impl ThreadRoots {
+    fn gc_mark(&self, m: bool) {
+        self.last_allocated.gc_mark(m);
+        for env in &self.env_stack {
+            env.gc_mark(m);
+        }
+        for obj in &self.temporaries {
+            obj.gc_mark(m);
+        }
+    }
+}
+
+/// Call `fun` on the current thread's roots, registering them if this
+/// is the first call on this thread. `fun` must not call
+/// `with_thread_roots` itself.
+pub fn with_thread_roots<F, R>(fun: F) -> R
+where
+    F: FnOnce(&mut ThreadRoots) -> R,
+{
+    let res = with_registered_roots(|r| {
+        if r.env_stack.is_empty() {
+            Err(fun)
+        } else {
+            Ok(fun(r))
+        }
+    });
+    match res {
+        Ok(res) => res,
+        Err(fun) => {
+            // Getting the default global environment may allocate it,
+            // so don't hold this thread's roots while doing so.
+            let global_env = default_global_env();
+            with_registered_roots(|r| {
+                if r.env_stack.is_empty() {
+                    r.env_stack.push(global_env);
+                }
+                fun(r)
+            })
+        }
+    }
+}
+
+/// Make `obj` this thread's most recent allocation. Unlike
+/// `with_thread_roots`, this never needs the default global
+/// environment, so it is safe to call while allocating it.
+pub fn record_allocation(obj: Object) {
+    with_registered_roots(|r| r.last_allocated = obj)
+}
+
+/// Stop rooting this thread's most recent allocation.
+pub fn forget_last_allocation() {
+    record_allocation(Object::nil())
+}
+
+/// Like `with_thread_roots`, except that a newly registered thread's
+/// environment stack is empty.
+fn with_registered_roots<F, R>(fun: F) -> R
+where
+    F: FnOnce(&mut ThreadRoots) -> R,
+{
+    let k = ROOTS_KEY.with(|k| k.0);
+    {
+        if let Some(m) = THREAD_ROOTS.read().unwrap().get(&k) {
+            return fun(&mut m.lock().unwrap());
+        }
+    }
+    {
+        let roots = ThreadRoots {
+            env_stack: Vec::new(),
+            temporaries: Vec::new(),
+            last_allocated: Object::nil(),
+        };
+        THREAD_ROOTS
+            .write()
+            .unwrap()
+            .entry(k)
+            .or_insert_with(|| Mutex::new(roots));
+    }
+    if let Some(m) = THREAD_ROOTS.read().unwrap().get(&k) {
+        fun(&mut m.lock().unwrap())
+    } else {
+        unreachable!()
+    }
+}
+
+/// Keep `obj` alive, even if nothing else refers to it, until a
+/// matching call to `pop_temporary_roots`.
+pub fn push_temporary_root(obj: Object) {
+    with_thread_roots(|r| r.temporaries.push(obj))
+}
+
+/// Unregister the `n` most recently pushed temporary roots.
+pub fn pop_temporary_roots(n: usize) {
+    with_thread_roots(|r| {
+        debug_assert!(r.temporaries.len() >= n);
+        let len = r.temporaries.len() - n;
+        r.temporaries.truncate(len);
+    })
+}
+
+/// Keeps a Phoebe value alive for as long as it's in scope, so that
+/// Rust code, like an embedding application's, can hold on to it
+/// across allocations and evaluations. `root!` makes one in place of
+/// a variable. Like the temporary roots it's built on, it belongs to
+/// the thread which made it, so it isn't `Send`.
+pub struct Rooted<T>
+where
+    T: Copy,
+    Object: From<T>,
+{
+    value: T,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T> Rooted<T>
+where
+    T: Copy,
+    Object: From<T>,
+{
+    pub fn new(value: T) -> Rooted<T> {
+        push_temporary_root(Object::from(value));
+        Rooted {
+            value,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for Rooted<T>
+where
+    T: Copy,
+    Object: From<T>,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Drop for Rooted<T>
+where
+    T: Copy,
+    Object: From<T>,
+{
+    /// `Rooted`s are usually dropped in the reverse of the order they
+    /// were made in, but not always, so this unregisters this one
+    /// wherever it is.
+    fn drop(&mut self) {
+        let obj = Object::from(self.value);
+        with_thread_roots(|r| {
+            if let Some(i) = r.temporaries.iter().rposition(|&o| o == obj) {
+                r.temporaries.remove(i);
+            }
+        })
+    }
+}
+
+/// Replace each of the named variables with a `Rooted` holding its
+/// value, or bind a new rooted variable:
+///
+/// ```rust
+/// # #[macro_use] extern crate phoebe;
+/// # fn main() {
+/// use phoebe::types::userdata::Userdata;
+/// use phoebe::types::Object;
+///
+/// let handle = Object::from(Userdata::new("handle", ()));
+/// root!(handle);
+/// root!(let other = Object::from(Userdata::new("handle", ())));
+/// // Neither can be collected until they go out of scope.
+/// assert!(*handle != *other);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! root {
+    (let $name:ident = $value:expr) => {
+        let $name = $crate::gc::Rooted::new($value);
+    };
+    ($($name:ident),+ $(,)*) => {
+        $(let $name = $crate::gc::Rooted::new($name);)+
+    };
+}
+
+pub fn gc_mark_roots(m: bool) {
+    gc_mark_stack(m);
+    for roots in THREAD_ROOTS.read().unwrap().values() {
+        roots.lock().unwrap().gc_mark(m);
+    }
+    gc_mark_prefixes(m);
+    gc_mark_plist_key(m);
+    gc_mark_watches(m);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn temporary_roots_nest() {
+        let a = Object::from(1);
+        let b = Object::from(2);
+        push_temporary_root(a);
+        push_temporary_root(b);
+        with_thread_roots(|r| assert_eq!(&r.temporaries[..], &[a, b]));
+        pop_temporary_roots(1);
+        with_thread_roots(|r| assert_eq!(&r.temporaries[..], &[a]));
+        pop_temporary_roots(1);
+        with_thread_roots(|r| assert!(r.temporaries.is_empty()));
+    }
+    #[test]
+    fn rooted_may_drop_out_of_order() {
+        let a = Object::from(1);
+        let b = Object::from(2);
+        root!(a, b);
+        with_thread_roots(|r| assert_eq!(&r.temporaries[..], &[*a, *b]));
+        drop(a);
+        with_thread_roots(|r| assert_eq!(&r.temporaries[..], &[*b]));
+        drop(b);
+        with_thread_roots(|r| assert!(r.temporaries.is_empty()));
+    }
+}
@@ -0,0 +1,185 @@
+//! The handshake between the collector and the threads evaluating
+//! Phoebe code, the "mutators", which makes sure that no mutator is
+//! changing conses and namespaces while the collector remarks and
+//! sweeps them.
+//!
+//! A thread is a mutator from when it starts evaluating until its
+//! outermost `Evaluate::evaluate` returns, and every nested call to
+//! `evaluate` is a safepoint. Before remarking, the collector calls
+//! `stop_mutators`, which raises `REQUESTED` and waits until no
+//! mutators are running. A mutator which reaches a safepoint while
+//! `REQUESTED` is raised stops running and sleeps until the
+//! collector has finished sweeping, and a thread which starts
+//! evaluating in the meantime sleeps before it becomes a mutator.
+//!
+//! Threads which aren't evaluating are never waited for, so Rust
+//! code which changes objects outside of `evaluate`, like an
+//! embedder's, should hold a `GcPause` while it does so. A mutator
+//! which waits for another thread, or for input, must do so inside
+//! `blocking`, or the collector may wait for it forever.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+
+/// Raised by `stop_mutators`, and polled at every safepoint. It's
+/// read without locking `HANDSHAKE`, so that safepoints are cheap.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+struct Handshake {
+    /// The number of `StoppedMutators` which are alive, or whose
+    /// `stop_mutators` is waiting.
+    requested: usize,
+    /// The number of mutators which are neither at a safepoint nor
+    /// inside `blocking`.
+    running: usize,
+}
+
+lazy_static! {
+    /// `.1` is notified whenever `running` or `requested` falls to
+    /// `0`.
+    static ref HANDSHAKE: (Mutex<Handshake>, Condvar) = { (Mutex::default(), Condvar::new()) };
+}
+
+thread_local! {
+    /// How many calls to `evaluate` this thread is inside of. It is a
+    /// mutator iff this isn't `0`.
+    static DEPTH: Cell<usize> = { Cell::new(0) };
+}
+
+fn start_running() {
+    let (ref handshake, ref changed) = *HANDSHAKE;
+    let mut handshake = handshake.lock().unwrap();
+    while handshake.requested > 0 {
+        handshake = changed.wait(handshake).unwrap();
+    }
+    handshake.running += 1;
+}
+
+fn stop_running() {
+    let (ref handshake, ref changed) = *HANDSHAKE;
+    let mut handshake = handshake.lock().unwrap();
+    handshake.running -= 1;
+    if handshake.running == 0 {
+        changed.notify_all();
+    }
+}
+
+/// Held by a thread for the duration of each call to `evaluate`.
+pub(crate) struct Mutator {
+    _private: (),
+}
+
+impl Mutator {
+    /// Called at the start of every `evaluate`. The outermost call
+    /// makes this thread a mutator, and the rest are safepoints.
+    pub(crate) fn enter() -> Mutator {
+        let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+        if depth == 0 {
+            start_running();
+        } else if REQUESTED.load(Ordering::SeqCst) {
+            stop_running();
+            start_running();
+        }
+        Mutator { _private: () }
+    }
+}
+
+impl Drop for Mutator {
+    fn drop(&mut self) {
+        let depth = DEPTH.with(|d| {
+            d.set(d.get() - 1);
+            d.get()
+        });
+        if depth == 0 {
+            stop_running();
+        }
+    }
+}
+
+/// Puts the current thread back to work once `blocking` is done.
+struct Resume {
+    depth: usize,
+}
+
+impl Drop for Resume {
+    fn drop(&mut self) {
+        start_running();
+        DEPTH.with(|d| d.set(self.depth));
+    }
+}
+
+/// Call `fun`, which may block waiting for another thread, like
+/// taking a lock which another mutator holds while it evaluates.
+/// While it does so, the collector won't wait for this thread; if
+/// the collector has stopped the mutators when `fun` returns, this
+/// waits until they resume.
+pub fn blocking<F, R>(fun: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // Anything `fun` evaluates makes this thread a mutator anew.
+    let depth = DEPTH.with(|d| d.replace(0));
+    if depth == 0 {
+        return fun();
+    }
+    stop_running();
+    let _resume = Resume { depth };
+    fun()
+}
+
+/// While this is alive, no mutators are running.
+pub(crate) struct StoppedMutators {
+    _private: (),
+}
+
+/// Wait until every mutator has reached a safepoint. They stay
+/// stopped until the returned `StoppedMutators` is dropped. The
+/// caller mustn't be a mutator itself.
+pub(crate) fn stop_mutators() -> StoppedMutators {
+    let (ref handshake, ref changed) = *HANDSHAKE;
+    let mut handshake = handshake.lock().unwrap();
+    handshake.requested += 1;
+    REQUESTED.store(true, Ordering::SeqCst);
+    while handshake.running > 0 {
+        handshake = changed.wait(handshake).unwrap();
+    }
+    StoppedMutators { _private: () }
+}
+
+impl Drop for StoppedMutators {
+    fn drop(&mut self) {
+        let (ref handshake, ref changed) = *HANDSHAKE;
+        let mut handshake = handshake.lock().unwrap();
+        handshake.requested -= 1;
+        if handshake.requested == 0 {
+            REQUESTED.store(false, Ordering::SeqCst);
+            changed.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn collector_waits_for_mutators() {
+        let (stopped_send, stopped) = mpsc::channel();
+        let mutator = Mutator::enter();
+        let collector = thread::spawn(move || {
+            let _stopped = stop_mutators();
+            stopped_send.send(()).unwrap();
+        });
+        // Still running, so the collector has to wait.
+        assert!(stopped.recv_timeout(Duration::from_millis(100)).is_err());
+        // Not running while blocked.
+        blocking(|| stopped.recv().unwrap());
+        collector.join().unwrap();
+        drop(mutator);
+    }
+}
@@ -1,9 +1,92 @@
+//! Gensyms are symbols generated by Phoebe rather than written by the
+//! programmer. Each is named by a prefix followed by the value of a
+//! global counter, like `GENSYM-12`. Gensyms are uninterned, so no
+//! symbol read or interned elsewhere is ever `eq` to one, even if it
+//! has the same name, which is what lets macros use them for
+//! variables that can't capture their callers'.
+//!
+//! The prefix depends on the global namespace at the time the gensym
+//! is made: a namespace may register its own prefix with
+//! `set_prefix`, so that symbols generated by code in that namespace
+//! can be traced back to it. Namespaces without a prefix of their own
+//! use `DEFAULT_PREFIX`.
+
 use crate::prelude::*;
-use crate::symbol_lookup::make_symbol;
+use crate::symbol_lookup::make_uninterned_symbol;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::Mutex;
+
+pub const DEFAULT_PREFIX: &[u8] = b"GENSYM";
 
 static GENSYM_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
 
+lazy_static! {
+    /// Maps the names of namespaces to their gensym prefixes. The
+    /// registry is keyed by name rather than by `GcRef<Namespace>`
+    /// so that it outlives the namespace, which may be replaced by a
+    /// new one of the same name. `gc_mark_prefixes` keeps the names
+    /// alive.
+    static ref PREFIXES: Mutex<HashMap<GcRef<Symbol>, Vec<u8>>> = { Mutex::new(HashMap::new()) };
+}
+
+/// Make a gensym using the prefix of the current global namespace.
 pub fn make_gensym() -> GcRef<Symbol> {
-    make_symbol(format!("GENSYM-{}", GENSYM_COUNT.fetch_add(1, Ordering::Relaxed)).as_bytes())
+    make_gensym_with_prefix(&current_prefix())
+}
+
+/// Make a gensym named `prefix`, a hyphen and the counter.
+pub fn make_gensym_with_prefix(prefix: &[u8]) -> GcRef<Symbol> {
+    let mut name = prefix.to_owned();
+    name.extend_from_slice(format!("-{}", GENSYM_COUNT.fetch_add(1, Ordering::Relaxed)).as_bytes());
+    make_uninterned_symbol(&name)
+}
+
+/// The number which the next gensym will end with.
+pub fn gensym_counter() -> usize {
+    GENSYM_COUNT.load(Ordering::Relaxed)
+}
+
+/// Use `prefix` for gensyms made while the namespace named
+/// `namespace` is the global namespace.
+pub fn set_prefix(namespace: GcRef<Symbol>, prefix: &[u8]) {
+    PREFIXES
+        .lock()
+        .unwrap()
+        .insert(namespace, prefix.to_owned());
+}
+
+/// Forget the prefix of the namespace named `namespace`, if it has
+/// one, so that its name can be collected.
+pub fn remove_prefix(namespace: GcRef<Symbol>) {
+    PREFIXES.lock().unwrap().remove(&namespace);
+}
+
+/// The prefix used for gensyms made while the namespace named
+/// `namespace` is the global namespace.
+pub fn prefix_for(namespace: GcRef<Symbol>) -> Vec<u8> {
+    PREFIXES
+        .lock()
+        .unwrap()
+        .get(&namespace)
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_PREFIX.to_owned())
+}
+
+/// Mark the names of every namespace with a prefix. Called by
+/// `gc::roots::gc_mark_roots`.
+pub(crate) fn gc_mark_prefixes(m: bool) {
+    for &name in PREFIXES.lock().unwrap().keys() {
+        name.gc_mark(m);
+    }
+}
+
+fn current_prefix() -> Vec<u8> {
+    match symbol_lookup::global_env()
+        .name()
+        .and_then(<GcRef<Symbol>>::maybe_from)
+    {
+        Some(name) => prefix_for(name),
+        None => DEFAULT_PREFIX.to_owned(),
+    }
 }
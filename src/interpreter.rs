@@ -0,0 +1,315 @@
+//! An `Interpreter` bundles the configuration an embedding
+//! application supplies to Phoebe. It is built by chaining `with_*`
+//! methods onto `Interpreter::new()`:
+//!
+//! ```rust
+//! # extern crate phoebe;
+//! # fn main() {
+//! use phoebe::Interpreter;
+//!
+//! let mut interpreter = Interpreter::new().with_output(Vec::new());
+//! let mut input: &[u8] = b"(debug 3)";
+//! let mut output = Vec::new();
+//! let mut error = Vec::new();
+//! interpreter.repl(&mut input, &mut output, &mut error, false).unwrap();
+//! assert_eq!(output, b"3\n");
+//! # }
+//! ```
+
+use crate::builtins::make_builtins_once;
+use crate::debug_mode::with_debug_mode;
+use crate::lisp2::with_lisp2;
+use crate::printer::ColumnTracker;
+use crate::query;
+use crate::reader::Limits;
+use crate::repl::{read_eval_print_loop, Colors, ReplError, ReplStream};
+use crate::shadowing::with_shadowing_warnings;
+use crate::standard_streams::{with_standard_input_from, with_standard_streams};
+use crate::symbol_lookup::{self, Snapshot};
+use crate::types::equality::{self, Budget};
+use crate::types::namespace::Bindings;
+use crate::version::version_info;
+use std::io::{self, Read, Write};
+
+#[derive(Default)]
+pub struct Interpreter {
+    output: Option<ColumnTracker<Box<Write + Send>>>,
+    error: Option<Box<Write + Send>>,
+    input: Option<Box<Iterator<Item = u8> + Send>>,
+    banner: bool,
+    reader_limits: Limits,
+    equal_budget: Budget,
+    debug_mode: bool,
+    lisp2: bool,
+    shadowing_warnings: bool,
+    colors: Colors,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter::default()
+    }
+
+    /// Send everything Phoebe code writes to its standard output to
+    /// `output` rather than to the REPL's output stream.
+    pub fn with_output<W>(self, output: W) -> Interpreter
+    where
+        W: Write + Send + 'static,
+    {
+        Interpreter {
+            output: Some(ColumnTracker::new(Box::new(output))),
+            ..self
+        }
+    }
+
+    /// Send everything Phoebe code writes to its standard error to
+    /// `error` rather than to the REPL's error stream.
+    pub fn with_error_output<W>(self, error: W) -> Interpreter
+    where
+        W: Write + Send + 'static,
+    {
+        Interpreter {
+            error: Some(Box::new(error)),
+            ..self
+        }
+    }
+
+    /// Have Phoebe code which reads from its standard input read from
+    /// `input` rather than sharing the REPL's input stream.
+    pub fn with_input<R>(self, input: R) -> Interpreter
+    where
+        R: Read + Send + 'static,
+    {
+        Interpreter {
+            input: Some(Box::new(input.bytes().map(Result::unwrap))),
+            ..self
+        }
+    }
+
+    /// If `banner`, the first call to `repl` will begin by printing
+    /// Phoebe's version and features.
+    pub fn with_banner(self, banner: bool) -> Interpreter {
+        Interpreter { banner, ..self }
+    }
+
+    /// Signal reader errors for input which exceeds `limits`, rather
+    /// than the default `Limits`.
+    pub fn with_reader_limits(self, reader_limits: Limits) -> Interpreter {
+        Interpreter {
+            reader_limits,
+            ..self
+        }
+    }
+
+    /// Have the `equal` builtin signal an error rather than do more
+    /// work than `budget` allows, for embedders which compare
+    /// untrusted data.
+    pub fn with_equal_budget(self, equal_budget: Budget) -> Interpreter {
+        Interpreter {
+            equal_budget,
+            ..self
+        }
+    }
+
+    /// If `debug_mode`, `debug-assert` checks its assertions. See the
+    /// `debug_mode` module.
+    pub fn with_debug_mode(self, debug_mode: bool) -> Interpreter {
+        Interpreter { debug_mode, ..self }
+    }
+
+    /// Turn debug mode on or off for later calls to `repl`.
+    pub fn set_debug_mode(&mut self, debug_mode: bool) {
+        self.debug_mode = debug_mode;
+    }
+
+    /// If `lisp2`, functions and variables of the same name don't
+    /// collide. See the `lisp2` module.
+    pub fn with_lisp2(self, lisp2: bool) -> Interpreter {
+        Interpreter { lisp2, ..self }
+    }
+
+    /// Print the REPL's prompts and errors in `colors`, which should
+    /// only be used when they're written to a terminal. By default,
+    /// nothing is colored.
+    pub fn with_colors(self, colors: Colors) -> Interpreter {
+        Interpreter { colors, ..self }
+    }
+
+    /// If `shadowing_warnings`, bindings which shadow visible ones,
+    /// including builtins, write a warning to the standard error. See
+    /// the `shadowing` module.
+    pub fn with_shadowing_warnings(self, shadowing_warnings: bool) -> Interpreter {
+        Interpreter {
+            shadowing_warnings,
+            ..self
+        }
+    }
+
+    /// Calls `fun` with the bindings of this thread's global
+    /// namespace, which are locked until `fun` returns. A batch of
+    /// changes made with `Bindings::set` therefore appears to other
+    /// threads all at once, rather than one binding at a time.
+    pub fn update<F, T>(&mut self, fun: F) -> T
+    where
+        F: FnOnce(&mut Bindings) -> T,
+    {
+        make_builtins_once();
+        symbol_lookup::global_env().update(fun)
+    }
+
+    /// A snapshot of this thread's global namespace, which can later
+    /// undo definitions made by calls to `repl`. See
+    /// `symbol_lookup::snapshot_global_env`.
+    pub fn snapshot(&mut self) -> Snapshot {
+        make_builtins_once();
+        symbol_lookup::snapshot_global_env()
+    }
+
+    /// A JSON description of what the symbol `name` is bound to,
+    /// for editors which show arglists and documentation as the user
+    /// types. See the `query` module.
+    pub fn query(&mut self, name: &str) -> String {
+        make_builtins_once();
+        query::describe(name.as_bytes()).to_json()
+    }
+
+    /// Read, evaluate and print from `input` until it is empty, as
+    /// `repl::repl` does. Results and reader errors are written to
+    /// `output` and `error`; output produced by Phoebe code goes to
+    /// this interpreter's sinks, or to `output` and `error` if none
+    /// were configured. Likewise, Phoebe code reads from this
+    /// interpreter's input, or else from whatever of `input` the REPL
+    /// hasn't yet read.
+    pub fn repl<I, O, E>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+        error: &mut E,
+        should_prompt: bool,
+    ) -> Result<(), ReplError>
+    where
+        I: Read,
+        O: Write,
+        E: Write,
+    {
+        make_builtins_once();
+
+        let mut output = ColumnTracker::new(output);
+
+        if self.banner {
+            writeln!(output, "{}", version_info())?;
+            self.banner = false;
+        }
+
+        let modes = self.modes();
+        let limits = self.reader_limits;
+        let colors = self.colors;
+
+        // When no sink is configured, the REPL's own streams double
+        // as Phoebe's standard streams, and the REPL writes to them
+        // through `standard_streams`.
+        let (standard_output, repl_output) = match self.output {
+            Some(ref mut o) => (
+                o as &mut ColumnTracker<Write + '_>,
+                ReplStream::Own(&mut output),
+            ),
+            None => (
+                &mut output as &mut ColumnTracker<Write + '_>,
+                ReplStream::Standard,
+            ),
+        };
+        let (standard_error, repl_error) = match self.error {
+            Some(ref mut e) => (&mut **e as &mut Write, ReplStream::Own(error)),
+            None => (error as &mut Write, ReplStream::Standard),
+        };
+
+        let standard_input = match self.input {
+            Some(ref mut i) => Some(&mut **i as &mut Iterator<Item = u8>),
+            None => None,
+        };
+
+        modes.install(|| {
+            with_standard_streams(standard_output, standard_error, || {
+                read_eval_print_loop(
+                    input,
+                    standard_input,
+                    repl_output,
+                    repl_error,
+                    should_prompt,
+                    limits,
+                    colors,
+                )
+            })
+        })
+    }
+
+    /// Calls `fun` with this interpreter's modes, sinks and input
+    /// installed for this thread, as they are while `repl` runs, for
+    /// evaluating outside of a REPL. Without sinks, Phoebe code
+    /// writes to the process's `stdout` and `stderr`, and without an
+    /// input, it reads from `stdin`.
+    pub(crate) fn with_configuration<F, T>(&mut self, fun: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        make_builtins_once();
+
+        let modes = self.modes();
+        let mut stdout = ColumnTracker::new(io::stdout());
+        let mut stderr = io::stderr();
+        let standard_output: &mut ColumnTracker<Write> = match self.output {
+            Some(ref mut o) => o,
+            None => &mut stdout,
+        };
+        let standard_error: &mut Write = match self.error {
+            Some(ref mut e) => &mut **e,
+            None => &mut stderr,
+        };
+        let input = &mut self.input;
+        modes.install(|| {
+            with_standard_streams(standard_output, standard_error, || match *input {
+                Some(ref mut i) => with_standard_input_from(&mut **i, fun),
+                None => fun(),
+            })
+        })
+    }
+
+    /// The reader limits configured by `with_reader_limits`.
+    pub(crate) fn reader_limits(&self) -> Limits {
+        self.reader_limits
+    }
+
+    fn modes(&self) -> Modes {
+        Modes {
+            equal_budget: self.equal_budget,
+            debug_mode: self.debug_mode,
+            lisp2: self.lisp2,
+            shadowing_warnings: self.shadowing_warnings,
+        }
+    }
+}
+
+/// The per-thread settings an `Interpreter` installs while it
+/// evaluates.
+#[derive(Copy, Clone)]
+struct Modes {
+    equal_budget: Budget,
+    debug_mode: bool,
+    lisp2: bool,
+    shadowing_warnings: bool,
+}
+
+impl Modes {
+    fn install<F, T>(self, fun: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        equality::with_budget(self.equal_budget, || {
+            with_debug_mode(self.debug_mode, || {
+                with_lisp2(self.lisp2, || {
+                    with_shadowing_warnings(self.shadowing_warnings, fun)
+                })
+            })
+        })
+    }
+}
@@ -0,0 +1,43 @@
+//! Interrupting a thread makes the next evaluation it begins signal
+//! an `interrupted-error`, which unwinds to the REPL's prompt unless
+//! Phoebe code catches it, so that a runaway loop can be stopped
+//! without killing the process. Each thread has its own flag, which
+//! any other thread, or a signal handler like the REPL binary's
+//! Ctrl-C handler, sets through the `InterruptHandle` the interrupted
+//! thread made.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    static INTERRUPTED: Arc<AtomicBool> = { Arc::new(AtomicBool::new(false)) };
+}
+
+/// Interrupts the thread which made it with `interrupt_handle`.
+#[derive(Clone, Debug)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    /// Make the thread's next evaluation signal an `interrupted-error`.
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A handle which interrupts this thread.
+pub fn interrupt_handle() -> InterruptHandle {
+    INTERRUPTED.with(|i| InterruptHandle(Arc::clone(i)))
+}
+
+/// Forget any interrupt this thread hasn't yet acted on, as the REPL
+/// does before each form, so that an interrupt which arrived while it
+/// was waiting for input doesn't stop the next evaluation.
+pub fn clear_interrupt() {
+    INTERRUPTED.with(|i| i.store(false, Ordering::SeqCst));
+}
+
+/// Called at every evaluation. True, once, if this thread has been
+/// interrupted since the last call.
+pub(crate) fn take_interrupt() -> bool {
+    INTERRUPTED.with(|i| i.load(Ordering::Relaxed) && i.swap(false, Ordering::SeqCst))
+}
@@ -11,18 +11,40 @@ extern crate failure_derive;
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate regex;
+#[cfg(feature = "line-editing")]
+extern crate rustyline;
 
 pub(crate) mod allocate;
+pub mod backtrace;
 mod builtins;
+pub mod debug_mode;
+pub(crate) mod documentation;
 pub(crate) mod evaluator;
-pub(crate) mod gc;
+pub mod gc;
 pub mod gensym;
+mod interpreter;
+pub mod interrupt;
+pub mod lisp2;
+pub mod load;
+pub mod metrics;
 pub(crate) mod prelude;
 pub(crate) mod printer;
-pub(crate) mod reader;
+pub mod query;
+mod random;
+pub mod reader;
 pub mod repl;
+pub mod session;
+pub mod shadowing;
 mod stack;
+pub(crate) mod standard_streams;
 pub mod symbol_lookup;
 pub mod types;
+pub mod version;
+pub mod watchpoints;
 
+pub use crate::interpreter::Interpreter;
+pub use crate::metrics::metrics;
 pub use crate::repl::repl;
+pub use crate::session::Session;
+pub use crate::version::version_info;
@@ -0,0 +1,41 @@
+//! In Lisp-2 mode, each binding in a heap `Namespace` has a function
+//! cell as well as its value, so that a variable and a function of
+//! the same name don't collide. A symbol at the head of a form is
+//! looked up in function cells, and `defun` and its relatives set
+//! them; everywhere else, symbols are looked up by value, as in
+//! Lisp-1 mode. `function` and `symbol-function` get at function
+//! cells from anywhere else, and `funcall` calls a function held in
+//! a variable.
+//!
+//! Builtin functions are bound in both their function and value
+//! cells, so they work the same way in either mode. Lisp-2 mode is
+//! off unless an `Interpreter` is configured `with_lisp2(true)`, and
+//! is installed per-thread for the duration of that interpreter's
+//! REPL.
+
+use std::cell::Cell;
+
+thread_local! {
+    static LISP2: Cell<bool> = { Cell::new(false) };
+}
+
+/// True if this thread is in Lisp-2 mode.
+pub fn lisp2() -> bool {
+    LISP2.with(Cell::get)
+}
+
+/// Executes `fun` with this thread in Lisp-2 mode if `lisp2`,
+/// restoring the previous mode when finished.
+pub fn with_lisp2<F, T>(lisp2: bool, fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LISP2.with(|l| l.set(self.0));
+        }
+    }
+    let _restore = Restore(LISP2.with(|l| l.replace(lisp2)));
+    fun()
+}
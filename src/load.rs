@@ -0,0 +1,251 @@
+//! Loading evaluates every top-level form of a file in turn, for its
+//! side effects. A `Loader` either stops at the first malformed or
+//! erroring form, or (by default) reports it to the standard error,
+//! skips ahead to the next top-level form and carries on, returning a
+//! summary of everything which went wrong once the whole file has
+//! been loaded.
+//!
+//! The names defined by top-level `defun`, `defvar` and `defglobal`
+//! forms are given the source location `file:line`, which
+//! `query::describe` reports.
+
+use crate::builtins::make_builtins_once;
+use crate::evaluator::eval_from_stack;
+use crate::prelude::*;
+use crate::reader::{read_with_limits, skip_whitespace, Limits, ReaderError};
+use crate::stack::StackOverflowError;
+use crate::standard_streams::with_standard_error;
+use crate::types::string::make_string;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, Read};
+use std::iter::Peekable;
+use std::ops::Try;
+use std::path::Path;
+use std::{convert, fmt};
+
+#[derive(Fail, Debug)]
+pub enum LoadError {
+    #[fail(display = "IO error {}", _0)]
+    IoError(io::Error),
+    #[fail(display = "{}", _0)]
+    StackOverflow(StackOverflowError),
+    #[fail(display = "{}", _0)]
+    Forms(FormErrors),
+}
+
+impl convert::From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::IoError(e)
+    }
+}
+
+impl convert::From<StackOverflowError> for LoadError {
+    fn from(e: StackOverflowError) -> LoadError {
+        LoadError::StackOverflow(e)
+    }
+}
+
+/// A line and column in a file, both counted from 1.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// The `Location` of the byte at `offset` in `source`.
+    fn of(source: &[u8], offset: usize) -> Location {
+        let before = &source[..offset.min(source.len())];
+        let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+        let column = match before.iter().rposition(|&c| c == b'\n') {
+            Some(newline) => offset - newline,
+            None => offset + 1,
+        };
+        Location { line, column }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Fail, Debug)]
+pub enum FormErrorKind {
+    #[fail(display = "{}", _0)]
+    Read(ReaderError),
+    /// Evaluating the form returned an error. Phoebe errors are
+    /// garbage collected, so only their message is kept.
+    #[fail(display = "{}", _0)]
+    Eval(String),
+}
+
+/// An error in the top-level form beginning at `location`.
+#[derive(Fail, Debug)]
+#[fail(display = "{}: {}", location, kind)]
+pub struct FormError {
+    pub location: Location,
+    pub kind: FormErrorKind,
+}
+
+/// Every `FormError` encountered while loading `file`.
+#[derive(Fail, Debug)]
+pub struct FormErrors {
+    pub file: String,
+    pub errors: Vec<FormError>,
+}
+
+impl fmt::Display for FormErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} error(s) while loading {}",
+            self.errors.len(),
+            self.file
+        )?;
+        for e in &self.errors {
+            write!(f, "\n{}:{}", self.file, e)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Loader {
+    fail_fast: bool,
+    reader_limits: Limits,
+}
+
+impl Loader {
+    pub fn new() -> Loader {
+        Loader::default()
+    }
+
+    /// If `fail_fast`, stop loading at the first erroring form
+    /// instead of reporting it and moving on to the next one.
+    pub fn with_fail_fast(self, fail_fast: bool) -> Loader {
+        Loader { fail_fast, ..self }
+    }
+
+    /// Treat forms which exceed `limits` as reader errors, rather
+    /// than the default `Limits`.
+    pub fn with_reader_limits(self, reader_limits: Limits) -> Loader {
+        Loader {
+            reader_limits,
+            ..self
+        }
+    }
+
+    /// Read and evaluate every form in the file at `path`.
+    pub fn load_file<P>(&self, path: P) -> Result<(), LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        self.load(&path.display().to_string(), &mut file)
+    }
+
+    /// Read and evaluate every form in `input`, using `name` as the
+    /// file name in error messages.
+    pub fn load<R>(&self, name: &str, input: &mut R) -> Result<(), LoadError>
+    where
+        R: Read,
+    {
+        make_builtins_once();
+
+        let mut source = Vec::new();
+        input.read_to_end(&mut source)?;
+
+        // Counts the bytes pulled out of `source`, which is one more
+        // than the offset of the byte the reader has peeked at.
+        let consumed = Cell::new(0usize);
+        let input = &mut source
+            .iter()
+            .cloned()
+            .inspect(|_| consumed.set(consumed.get() + 1))
+            .peekable();
+
+        let mut errors = Vec::new();
+        loop {
+            skip_whitespace(input);
+            let location = Location::of(&source, consumed.get().saturating_sub(1));
+            let kind = match read_with_limits(input, self.reader_limits) {
+                Ok(None) => break,
+                Ok(Some(obj)) => {
+                    let defined = defined_name(obj);
+                    match eval_top_level(obj)? {
+                        Ok(_) => {
+                            if let Some(mut sym) = defined {
+                                let here = make_string(&format!("{}:{}", name, location.line));
+                                sym.set_source_location(Object::from(here));
+                            }
+                            continue;
+                        }
+                        Err(e) => FormErrorKind::Eval(e),
+                    }
+                }
+                Err(e) => {
+                    skip_to_next_top_level_form(input);
+                    FormErrorKind::Read(e)
+                }
+            };
+            let error = FormError { location, kind };
+            if self.fail_fast {
+                errors.push(error);
+                break;
+            }
+            with_standard_error(|e| writeln!(e, "{}:{}", name, error))?;
+            errors.push(error);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(LoadError::Forms(FormErrors {
+                file: name.to_owned(),
+                errors,
+            }))
+        }
+    }
+}
+
+/// The heads of the top-level forms whose names `Loader` records the
+/// locations of.
+const DEFINING_FORMS: &[&[u8]] = &[b"defun", b"defvar", b"defglobal"];
+
+/// The symbol `form` defines, if it's one of `DEFINING_FORMS`.
+fn defined_name(form: Object) -> Option<GcRef<Symbol>> {
+    let mut form = List::maybe_from(form)?;
+    let head = <GcRef<Symbol>>::maybe_from(form.next()?)?;
+    let head: &[u8] = (*head).as_ref();
+    if !DEFINING_FORMS.contains(&head) {
+        return None;
+    }
+    <GcRef<Symbol>>::maybe_from(form.next()?)
+}
+
+/// Evaluate `obj` the way the REPL does, keeping it on the stack so
+/// that it isn't collected out from under the evaluator.
+pub(crate) fn eval_top_level(obj: Object) -> Result<Result<Object, String>, StackOverflowError> {
+    stack::with_stack(|s| stack::make_stack_frame(s, &[obj]))?;
+    unsafe { eval_from_stack() };
+    let result = stack::with_stack(|s| s.pop().unwrap());
+    Ok(result.into_result().map_err(|e| format!("{}", e)))
+}
+
+/// After a reader error, there's no telling where the malformed form
+/// was meant to end, so skip ahead to the next line which starts with
+/// an open paren and assume that a new top-level form begins there.
+fn skip_to_next_top_level_form<I>(input: &mut Peekable<I>)
+where
+    I: Iterator<Item = u8>,
+{
+    while let Some(c) = input.next() {
+        if c == b'\n' && input.peek() == Some(&b'(') {
+            return;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Counters describing the state of the runtime, for applications
+//! which embed Phoebe in a long-running process and want to monitor
+//! it.
+
+use crate::allocate::ALLOCED_OBJECTS;
+use crate::gc;
+use crate::gc::roots::THREAD_ROOTS;
+use crate::stack::{MAX_STACK_SIZE, STACKS};
+use crate::symbol_lookup::SYMBOLS_HEAP;
+use std::collections::HashSet;
+use std::fmt;
+use std::thread::ThreadId;
+
+/// A snapshot of the runtime's counters, as returned by `metrics`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Metrics {
+    /// Objects which have been allocated and not yet collected.
+    pub objects_live: usize,
+    /// Garbage collection passes which have finished.
+    pub gc_count: usize,
+    /// Symbols in the intern table.
+    pub symbols_interned: usize,
+    /// Living threads which have created a stack.
+    pub threads_with_stacks: usize,
+    /// The most objects any thread's stack has held at once. See
+    /// `stack_usage`.
+    pub stack_peak: usize,
+    /// The most objects a thread's stack may hold.
+    pub stack_capacity: usize,
+    /// Namespaces kept alive because some thread's environment
+    /// stack refers to them.
+    pub env_roots: usize,
+    /// The total number of references from environment stacks to
+    /// those namespaces.
+    pub env_refs: usize,
+}
+
+/// Take a snapshot of the runtime's counters. The counters are read
+/// one at a time, so a snapshot taken while other threads are running
+/// may not be entirely consistent.
+pub fn metrics() -> Metrics {
+    let (env_roots, env_refs) = {
+        let mut envs = HashSet::new();
+        let mut env_refs = 0;
+        for roots in THREAD_ROOTS.read().unwrap().values() {
+            let roots = roots.lock().unwrap();
+            envs.extend(roots.env_stack.iter().cloned());
+            env_refs += roots.env_stack.len();
+        }
+        (envs.len(), env_refs)
+    };
+    let stack_peak = stack_usage().iter().map(|u| u.peak).max().unwrap_or(0);
+    Metrics {
+        objects_live: ALLOCED_OBJECTS.lock().unwrap().len(),
+        gc_count: gc::gc_count(),
+        symbols_interned: SYMBOLS_HEAP.lock().unwrap().len(),
+        threads_with_stacks: STACKS.read().unwrap().len(),
+        stack_peak,
+        stack_capacity: MAX_STACK_SIZE,
+        env_roots,
+        env_refs,
+    }
+}
+
+/// How much of one thread's stack is in use, as returned by
+/// `stack_usage`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StackUsage {
+    /// The name of the thread, if it has one.
+    pub thread: Option<String>,
+    /// Objects on the stack now.
+    pub depth: usize,
+    /// The most objects which have been on the stack at once. A peak
+    /// close to `capacity` means that a program recursed nearly deep
+    /// enough to signal a `stack-overflow-error`.
+    pub peak: usize,
+    /// The most objects the stack may hold.
+    pub capacity: usize,
+}
+
+/// The usage of every living thread's stack, in the order the
+/// threads created them. Like `metrics`, this reads each stack in
+/// turn.
+pub fn stack_usage() -> Vec<StackUsage> {
+    STACKS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|stack| {
+            let stack = stack.lock().unwrap();
+            StackUsage {
+                thread: stack.thread().map(String::from),
+                depth: stack.len(),
+                peak: stack.peak(),
+                capacity: MAX_STACK_SIZE,
+            }
+        })
+        .collect()
+}
+
+/// The living threads which have registered garbage collector roots,
+/// in no particular order.
+pub fn threads_with_roots() -> Vec<ThreadId> {
+    THREAD_ROOTS.read().unwrap().keys().cloned().collect()
+}
+
+/// Returns `false` if a thread panicked while holding one of the
+/// runtime's global locks, after which the runtime can no longer be
+/// used.
+pub fn healthy() -> bool {
+    !(ALLOCED_OBJECTS.is_poisoned()
+        || SYMBOLS_HEAP.is_poisoned()
+        || STACKS.is_poisoned()
+        || THREAD_ROOTS.is_poisoned())
+}
+
+impl Metrics {
+    /// Each counter as a `(name, value)` pair, with names as used by
+    /// the `runtime-stats` builtin.
+    pub fn pairs(&self) -> [(&'static str, usize); 8] {
+        [
+            ("objects-live", self.objects_live),
+            ("gc-count", self.gc_count),
+            ("symbols-interned", self.symbols_interned),
+            ("threads-with-stacks", self.threads_with_stacks),
+            ("stack-peak", self.stack_peak),
+            ("stack-capacity", self.stack_capacity),
+            ("env-roots", self.env_roots),
+            ("env-refs", self.env_refs),
+        ]
+    }
+}
+
+/// Formats the counters in the Prometheus text exposition format, one
+/// `phoebe_`-prefixed gauge per line.
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(name, value) in self.pairs().iter() {
+            writeln!(f, "phoebe_{} {}", name.replace('-', "_"), value)?;
+        }
+        Ok(())
+    }
+}
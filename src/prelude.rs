@@ -1,17 +1,25 @@
 pub(crate) use crate::evaluator::Evaluate;
 pub(crate) use crate::stack;
 pub(crate) use crate::symbol_lookup;
+pub use crate::types::bytes::Bytes;
 pub use crate::types::cons::Cons;
 pub use crate::types::conversions::*;
 pub use crate::types::error::{Error, EvaluatorError};
 pub use crate::types::function::Function;
+pub use crate::types::hash_table::HashTable;
 pub use crate::types::heap_object::HeapObject;
 pub use crate::types::immediate::Immediate;
 pub use crate::types::list::List;
 pub use crate::types::namespace::Namespace;
+pub use crate::types::native::Native;
 pub use crate::types::number::PhoebeNumber;
+pub use crate::types::ratio::Ratio;
 pub use crate::types::reference::Reference;
-pub use crate::types::symbol::Symbol;
+pub use crate::types::string::PhoebeString;
+pub use crate::types::structure::Structure;
+pub use crate::types::symbol::{Keyword, Symbol};
+pub use crate::types::vector::Vector;
+pub use crate::types::weak_ref::WeakRef;
 pub use crate::types::Object;
 
 pub(crate) use crate::gc::{GarbageCollected, GcMark, GcRef};
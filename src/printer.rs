@@ -1,8 +1,71 @@
-use crate::stack;
 /// Phoebe's printing facitlities are currently very bare-bones. In
 /// the future, they may be expanded to interact with runtime config
 /// like `print-readably` vs `pretty-print`, etc.
-use crate::types::Object;
+use crate::documentation;
+use crate::prelude::*;
+use crate::stack;
+use crate::types::visit::ObjectVisitor;
+use std::io::{self, Write};
+
+/// Wraps a stream and keeps track of which column (in bytes) its
+/// cursor is in, so that `fresh_line` can avoid printing blank
+/// lines. The REPL wraps its output in a `ColumnTracker` and installs
+/// that same `ColumnTracker` as Phoebe's standard output, so that
+/// prompts and results are never glued onto the end of a line printed
+/// by Phoebe code.
+pub struct ColumnTracker<W: ?Sized> {
+    column: usize,
+    inner: W,
+}
+
+impl<W> ColumnTracker<W> {
+    pub fn new(inner: W) -> ColumnTracker<W> {
+        ColumnTracker { column: 0, inner }
+    }
+}
+
+impl<W: ?Sized> ColumnTracker<W> {
+    pub fn column(&self) -> usize {
+        self.column
+    }
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+    /// Note that the cursor is at the start of a line, having been
+    /// moved there by something other than this writer, like a
+    /// terminal echoing a line of input.
+    pub fn reset_column(&mut self) {
+        self.column = 0;
+    }
+}
+
+impl<W: Write + ?Sized> ColumnTracker<W> {
+    /// Write a newline unless the cursor is already at the start of a
+    /// line. Returns `true` iff a newline was written.
+    pub fn fresh_line(&mut self) -> io::Result<bool> {
+        if self.column == 0 {
+            Ok(false)
+        } else {
+            writeln!(self)?;
+            Ok(true)
+        }
+    }
+}
+
+impl<W: Write + ?Sized> Write for ColumnTracker<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        let written = &buf[..n];
+        self.column = match written.iter().rposition(|&b| b == b'\n') {
+            Some(newline) => n - newline - 1,
+            None => self.column + n,
+        };
+        Ok(n)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
 
 pub fn print(obj: Object) -> Result<String, String> {
     use std::ops::Try;
@@ -18,3 +81,92 @@ pub unsafe fn print_from_stack() -> Result<String, String> {
         print(to_print)
     })
 }
+
+/// Write `obj` to `o`, as the REPL would print it but without a
+/// trailing newline.
+pub fn print_to(o: &mut ColumnTracker<Write>, obj: Object) -> io::Result<()> {
+    write!(o, "{}", obj)
+}
+
+/// Write `obj`'s debugging representation, followed by a newline, to
+/// `o`.
+pub fn debug_to(o: &mut ColumnTracker<Write>, obj: Object) -> io::Result<()> {
+    writeln!(o, "{:?}", obj)
+}
+
+/// Lines describing the object visited, after the first line naming
+/// its type. See `describe_to_standard_output`.
+struct Describe;
+
+impl ObjectVisitor for Describe {
+    type Output = Vec<String>;
+    fn visit_other(&mut self, _: Object) -> Vec<String> {
+        Vec::new()
+    }
+    fn visit_namespace(&mut self, namespace: &Namespace) -> Vec<String> {
+        let mut names: Vec<String> = namespace
+            .bindings()
+            .into_iter()
+            .map(|(sym, _)| sym.to_string())
+            .collect();
+        names.sort();
+        let mut lines = Vec::new();
+        if let Some(name) = namespace.name() {
+            lines.push(format!("Name: {}", name));
+        }
+        lines.push(format!("Bindings: {}", names.join(" ")));
+        lines
+    }
+    fn visit_function(&mut self, function: &Function) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(name) = function.name() {
+            lines.push(format!("Name: {}", name));
+        }
+        lines.push(format!("Arglist: {}", function.arglist()));
+        let documentation = function
+            .documentation()
+            .or_else(|| function.name().and_then(documentation::documentation));
+        if let Some(documentation) = documentation {
+            lines.push(format!("Documentation: {}", documentation));
+        }
+        lines
+    }
+    fn visit_error(&mut self, error: &Error) -> Vec<String> {
+        let mut lines = vec![format!("Kind: {}", error.name())];
+        match error.payload() {
+            Some(payload) => lines.push(format!("Payload: {}", payload)),
+            None => lines.push(format!("Message: {}", error)),
+        }
+        lines
+    }
+}
+
+/// Write a description of `obj` to `o`: its printed representation
+/// and type, followed by details which depend on its type, such as a
+/// function's arglist or a namespace's bindings.
+pub fn describe_to(o: &mut ColumnTracker<Write>, obj: Object) -> io::Result<()> {
+    let details = obj.visit(&mut Describe);
+    o.fresh_line()?;
+    writeln!(o, "{} is of type {}.", obj, obj.type_name())?;
+    for line in details {
+        writeln!(o, "  {}", line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn track_columns() {
+        let mut tracker = ColumnTracker::new(Vec::new());
+        assert_eq!(tracker.column(), 0);
+        write!(tracker, "foo").unwrap();
+        assert_eq!(tracker.column(), 3);
+        write!(tracker, "bar\nba").unwrap();
+        assert_eq!(tracker.column(), 2);
+        assert!(tracker.fresh_line().unwrap());
+        assert!(!tracker.fresh_line().unwrap());
+        assert_eq!(tracker.inner, b"foobar\nba\n");
+    }
+}
@@ -0,0 +1,167 @@
+//! A machine-readable description of what a symbol names, for editor
+//! plugins which show a function's arglist and documentation while
+//! the user types. `describe` looks a symbol up in the global
+//! namespace, and `SymbolInfo::to_json` renders the result as a JSON
+//! object like:
+//!
+//! ```json
+//! {"name":"cons","type":"builtin","arglist":"(first second)","documentation":null,"location":null}
+//! ```
+//!
+//! `location` is where the symbol was defined, as `file:line`, if it
+//! was defined by a file `Loader` loaded, and `null` otherwise.
+
+use crate::builtins::make_builtins_once;
+use crate::documentation;
+use crate::gc::Rooted;
+use crate::prelude::*;
+use std::fmt::Write;
+
+/// What a symbol names in the global namespace.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    /// A function defined in Phoebe, with `defun` or `lambda`.
+    Function,
+    /// A function defined in Rust.
+    Builtin,
+    /// A builtin which receives its arguments unevaluated.
+    SpecialForm,
+    /// A variable whose value isn't a function.
+    Variable,
+    /// Nothing at all.
+    Unbound,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Function => "function",
+            Kind::Builtin => "builtin",
+            Kind::SpecialForm => "special-form",
+            Kind::Variable => "variable",
+            Kind::Unbound => "unbound",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub kind: Kind,
+    /// The printed arglist of a function, like `(x &optional y)`.
+    pub arglist: Option<String>,
+    pub documentation: Option<String>,
+    /// Where the definition was read from, as `file:line`.
+    pub location: Option<String>,
+}
+
+/// Describe whatever the symbol named `name` is bound to in this
+/// thread's global namespace.
+pub fn describe(name: &[u8]) -> SymbolInfo {
+    make_builtins_once();
+    // Describing a name shouldn't keep its symbol around forever, so
+    // it's rooted only while it's in use.
+    let sym = Rooted::new(symbol_lookup::intern(name));
+    let value = symbol_lookup::get_from_global_namespace(*sym)
+        .map(|r| *r)
+        .filter(|v| v.definedp());
+    let (kind, arglist) = match value.and_then(<GcRef<Function>>::maybe_from) {
+        Some(f) => {
+            let kind = if f.is_special_form() {
+                Kind::SpecialForm
+            } else if f.is_builtin() {
+                Kind::Builtin
+            } else {
+                Kind::Function
+            };
+            (kind, Some(f.arglist().to_string()))
+        }
+        None if value.is_some() => (Kind::Variable, None),
+        None => (Kind::Unbound, None),
+    };
+    SymbolInfo {
+        name: String::from_utf8_lossy(name).into_owned(),
+        kind,
+        arglist,
+        documentation: documentation::documentation(*sym).map(text),
+        location: sym.source_location().map(text),
+    }
+}
+
+/// The contents of `obj` if it's a string, or else its printed
+/// representation.
+fn text(obj: Object) -> String {
+    match <GcRef<PhoebeString>>::maybe_from(obj) {
+        Some(s) => s.as_str().to_owned(),
+        None => obj.to_string(),
+    }
+}
+
+impl SymbolInfo {
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        write_field(&mut json, "name", Some(&self.name));
+        json.push(',');
+        write_field(&mut json, "type", Some(self.kind.name()));
+        json.push(',');
+        write_field(
+            &mut json,
+            "arglist",
+            self.arglist.as_ref().map(String::as_str),
+        );
+        json.push(',');
+        write_field(
+            &mut json,
+            "documentation",
+            self.documentation.as_ref().map(String::as_str),
+        );
+        json.push(',');
+        write_field(
+            &mut json,
+            "location",
+            self.location.as_ref().map(String::as_str),
+        );
+        json.push('}');
+        json
+    }
+}
+
+/// Append `"key":value` to `json`, where a `None` value is `null`.
+fn write_field(json: &mut String, key: &str, value: Option<&str>) {
+    write_string(json, key);
+    json.push(':');
+    match value {
+        Some(v) => write_string(json, v),
+        None => json.push_str("null"),
+    }
+}
+
+fn write_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            '\r' => json.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                write!(json, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escape_json_strings() {
+        let mut json = String::new();
+        write_string(&mut json, "a \"b\"\n\\c\u{1}");
+        assert_eq!(json, r#""a \"b\"\n\\c\u0001""#);
+    }
+}
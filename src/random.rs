@@ -0,0 +1,59 @@
+//! Each thread has its own pseudo-random number generator, an
+//! xorshift64* seeded from the clock and the number of generators
+//! created before it. It is fast and good enough for sample programs
+//! and tests, but it is not suitable for cryptography.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static GENERATOR_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+thread_local! {
+    static STATE: Cell<u64> = { Cell::new(initial_seed()) };
+}
+
+fn initial_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs().wrapping_mul(1_000_000_000) + u64::from(d.subsec_nanos()))
+        .unwrap_or(0);
+    let count = GENERATOR_COUNT.fetch_add(1, Ordering::Relaxed) as u64;
+    // xorshift's state must never be zero.
+    (nanos ^ count.wrapping_mul(0x9E37_79B9_7F4A_7C15)) | 1
+}
+
+fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    })
+}
+
+/// A uniformly distributed integer in `[0, n)`. `n` must be nonzero.
+pub fn below(n: u64) -> u64 {
+    debug_assert!(n > 0);
+    ((u128::from(next_u64()) * u128::from(n)) >> 64) as u64
+}
+
+/// A uniformly distributed float in `[0, 1)`.
+pub fn unit_float() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn stay_in_range() {
+        for _ in 0..1000 {
+            assert!(below(7) < 7);
+            let f = unit_float();
+            assert!(f >= 0.0 && f < 1.0);
+        }
+    }
+}
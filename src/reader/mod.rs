@@ -1,8 +1,17 @@
-use crate::types::{list, Object};
+//! The reader turns bytes of source text into `Object`s. `read`
+//! reads a single form from a `Peekable` iterator, while `forms`
+//! wraps an input in an iterator over all of its top-level forms.
+
+use crate::gc::{roots, GarbageCollected, GcRef};
+use crate::symbol_lookup::{self, make_symbol};
+use crate::types::conversions::MaybeFrom;
+use crate::types::immediate;
+use crate::types::{list::List, string::make_string, symbol::Symbol, vector::Vector, Object};
 use std::iter::{Iterator, Peekable};
 
 mod read_num;
 use self::read_num::parse_to_object;
+pub use self::read_num::{parse_float_prefix, parse_integer_prefix};
 
 const WHITESPACE: &[u8] = &[b' ', b'\n', b'\t'];
 const COMMENT_DESIGNATOR: u8 = b';';
@@ -13,6 +22,36 @@ pub enum ReaderError {
     UnclosedList,
     #[fail(display = "A spurious close-delimiter")]
     ExtraClose,
+    #[fail(display = "A read-time conditional was missing its feature or form")]
+    IncompleteConditional,
+    #[fail(display = "A quote was missing its form")]
+    IncompleteQuote,
+    #[fail(display = "A malformed feature expression")]
+    InvalidFeature,
+    #[fail(display = "Lists were nested too deeply")]
+    TooDeep,
+    #[fail(display = "A symbol, number or string was too long")]
+    TokenTooLong,
+    #[fail(display = "A string went unclosed")]
+    UnclosedString,
+    #[fail(display = "A string was not valid UTF-8")]
+    InvalidUtf8,
+    #[fail(display = "A character literal was malformed or unknown")]
+    InvalidCharacter,
+}
+
+impl ReaderError {
+    /// True for errors which mean that the input ended partway
+    /// through a form, which more input might finish.
+    pub fn is_incomplete(&self) -> bool {
+        match *self {
+            ReaderError::UnclosedList
+            | ReaderError::UnclosedString
+            | ReaderError::IncompleteConditional
+            | ReaderError::IncompleteQuote => true,
+            _ => false,
+        }
+    }
 }
 
 /// This method is analogous to `iter.next`, but it skips past
@@ -75,116 +114,457 @@ where
     }
 }
 
-pub fn read<I>(input: &mut Peekable<I>) -> Result<Option<Object>, ReaderError>
+/// Consume any whitespace and comments at the front of `input`, so
+/// that the next byte it yields begins a form. Whitespace is skipped
+/// here; comments are skipped by `peek`, which consumes each one it
+/// finds before looking at the byte after it.
+pub fn skip_whitespace<I>(input: &mut Peekable<I>)
 where
     I: Iterator<Item = u8>,
 {
-    debug!("Call to `read`.");
-    match peek(input) {
-        Some(w) if WHITESPACE.contains(&w) => {
-            debug!("Whitespace; skipping.");
-            next(input);
-            read(input)
-        }
-        Some(b'"') => {
-            debug!("A \"; reading a string.");
-            next(input);
-            Ok(Some(read_string(input)?))
-        }
-        Some(b')') => {
-            debug!("A ); erroring.");
-            Err(ReaderError::ExtraClose)
+    while let Some(w) = peek(input) {
+        if !WHITESPACE.contains(&w) {
+            return;
         }
-        Some(b'(') => {
-            debug!("A (; reading a list.");
-            next(input);
-            Ok(Some(Object::from(read_list(input)?)))
-        }
-        Some(_) => {
-            debug!("Reading a symbol or number.");
-            Ok(Some(read_sym_or_num(input)))
-        }
-        None => {
-            debug!("End of input; returning `None`.");
-            Ok(None)
+        next(input);
+    }
+}
+
+/// Bounds on the input `read_with_limits` will accept, so that
+/// pathological input signals a `ReaderError` instead of exhausting
+/// memory.
+#[derive(Copy, Clone, Debug)]
+pub struct Limits {
+    /// The deepest that lists (and read-time conditionals) may nest.
+    pub max_depth: usize,
+    /// The longest a symbol, number or string may be, in bytes.
+    pub max_token_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_depth: 1024,
+            max_token_length: 1 << 16,
         }
     }
 }
 
-fn read_string<I>(_input: &mut Peekable<I>) -> Result<Object, ReaderError>
+/// A form which `read_with_limits` has started but not yet finished.
+enum Frame {
+    /// The elements read so far of a list whose open paren has been
+    /// consumed.
+    List(Vec<Object>),
+    /// The elements read so far of a vector, whose `#(` has been
+    /// consumed.
+    Vector(Vec<Object>),
+    /// A `#+` (if `wanted`) or `#-` conditional, and its feature
+    /// expression once that has been read.
+    Conditional {
+        wanted: bool,
+        feature: Option<Object>,
+    },
+    /// A `'`, which wraps the next form in `(quote ...)`.
+    Quote,
+}
+
+/// `read_with_limits` using the default `Limits`.
+pub fn read<I>(input: &mut Peekable<I>) -> Result<Option<Object>, ReaderError>
 where
     I: Iterator<Item = u8>,
 {
-    unimplemented!()
+    read_with_limits(input, Limits::default())
 }
 
-/// This method reads bytes into a buffer until it hits whitespace or
-/// a close-paren and then uses `read_num::parse_to_object` to convert
-/// the buffer into an `Object`. If we parsed slices instead of an
-/// iterator, we could skip the buffer and pass a slice of the input
-/// to `parse_to_object`, but parsing slices would cause other
-/// problems.
-fn read_sym_or_num<I>(input: &mut Peekable<I>) -> Object
+/// Read one top-level form from `input`, returning `None` at the end
+/// of input. Rather than recursing for each nested list, this method
+/// keeps the forms it has begun on an explicit stack of `Frame`s, so
+/// deeply nested input can't overflow the Rust stack.
+pub fn read_with_limits<I>(
+    input: &mut Peekable<I>,
+    limits: Limits,
+) -> Result<Option<Object>, ReaderError>
 where
     I: Iterator<Item = u8>,
 {
-    let mut buf = Vec::new();
+    let mut n_rooted = 0;
+    let res = read_rooted(input, limits, &mut n_rooted);
+    roots::pop_temporary_roots(n_rooted);
+    res
+}
+
+/// Does the work of `read_with_limits`. The `Frame`s are only Rust
+/// values, and the reader may not be a mutator the collector waits
+/// for, so each object read is registered as a temporary root as
+/// soon as it's finished, counting them in `n_rooted` for the caller
+/// to pop once the whole form has been read.
+fn read_rooted<I>(
+    input: &mut Peekable<I>,
+    limits: Limits,
+    n_rooted: &mut usize,
+) -> Result<Option<Object>, ReaderError>
+where
+    I: Iterator<Item = u8>,
+{
+    debug!("Call to `read`.");
+    let mut frames = Vec::new();
     loop {
-        match peek(input) {
+        let mut obj = match peek(input) {
             Some(w) if WHITESPACE.contains(&w) => {
+                debug!("Whitespace; skipping.");
+                next(input);
+                continue;
+            }
+            Some(b'"') => {
+                debug!("A \"; reading a string.");
                 next(input);
-                debug_assert!(!buf.is_empty());
-                return parse_to_object(&buf);
+                read_string(input, limits)?
             }
             Some(b')') => {
-                return parse_to_object(&buf);
+                debug!("A ); closing a list.");
+                next(input);
+                match frames.pop() {
+                    Some(Frame::List(objs)) => Object::from(objs.iter().cloned().collect::<List>()),
+                    Some(Frame::Vector(objs)) => Object::from(Vector::allocate(Vector::new(objs))),
+                    Some(Frame::Conditional { .. }) => {
+                        return Err(ReaderError::IncompleteConditional);
+                    }
+                    Some(Frame::Quote) => return Err(ReaderError::IncompleteQuote),
+                    None => return Err(ReaderError::ExtraClose),
+                }
             }
-            Some(c) => {
-                buf.push(c);
+            Some(b'(') => {
+                debug!("A (; reading a list.");
+                next(input);
+                push_frame(&mut frames, Frame::List(Vec::new()), limits)?;
+                continue;
+            }
+            Some(b'\'') => {
+                debug!("A '; quoting the next form.");
+                next(input);
+                push_frame(&mut frames, Frame::Quote, limits)?;
+                continue;
+            }
+            Some(b'#') => {
+                debug!("A #; reading a dispatch macro.");
                 next(input);
+                match peek(input) {
+                    Some(b'(') => {
+                        next(input);
+                        push_frame(&mut frames, Frame::Vector(Vec::new()), limits)?;
+                        continue;
+                    }
+                    Some(b'\\') => {
+                        next(input);
+                        read_character(input, limits)?
+                    }
+                    Some(c) if c == b'+' || c == b'-' => {
+                        next(input);
+                        let frame = Frame::Conditional {
+                            wanted: c == b'+',
+                            feature: None,
+                        };
+                        push_frame(&mut frames, frame, limits)?;
+                        continue;
+                    }
+                    _ => scheme_boolean(read_token(input, vec![b'#'], limits)?)?,
+                }
+            }
+            Some(_) => {
+                debug!("Reading a symbol or number.");
+                read_sym_or_num(input, limits)?
             }
             None => {
-                debug_assert!(!buf.is_empty());
-                return parse_to_object(&buf);
+                debug!("End of input.");
+                return match frames.last() {
+                    None => Ok(None),
+                    Some(Frame::List(_)) | Some(Frame::Vector(_)) => Err(ReaderError::UnclosedList),
+                    Some(Frame::Conditional { .. }) => Err(ReaderError::IncompleteConditional),
+                    Some(Frame::Quote) => Err(ReaderError::IncompleteQuote),
+                };
+            }
+        };
+
+        // Hand the finished `obj` to the innermost unfinished form,
+        // which may in turn finish a conditional or a quote.
+        loop {
+            roots::push_temporary_root(obj);
+            *n_rooted += 1;
+            let quoted = match frames.last() {
+                Some(Frame::Quote) => true,
+                _ => false,
+            };
+            if quoted {
+                frames.pop();
+                obj = Object::from(
+                    [Object::from(make_symbol(b"quote")), obj]
+                        .iter()
+                        .cloned()
+                        .collect::<List>(),
+                );
+                continue;
+            }
+            let keep = match frames.last_mut() {
+                None => return Ok(Some(obj)),
+                Some(Frame::List(objs)) | Some(Frame::Vector(objs)) => {
+                    objs.push(obj);
+                    break;
+                }
+                Some(Frame::Conditional {
+                    feature: ref mut feature @ None,
+                    ..
+                }) => {
+                    *feature = Some(obj);
+                    break;
+                }
+                Some(Frame::Conditional {
+                    wanted,
+                    feature: Some(feature),
+                }) => feature_present(*feature)? == *wanted,
+                Some(Frame::Quote) => unreachable!(),
+            };
+            frames.pop();
+            if keep {
+                debug!("Keeping {} after a read-time conditional.", obj);
+            } else {
+                debug!("Discarding {} after a read-time conditional.", obj);
+                break;
             }
         }
     }
 }
 
-// Notable behavior of this function: it expects that the opening
-// paren will be consumed by `read`, and it itself consumes the
-// closing paren.
-/// This method recursively calls `read`, collects the resulting
-/// objects into a vector, and then converts that vector into a
-/// list. It would be more efficent to skip the vector and build the
-/// list from the start.
-fn read_list<I>(input: &mut Peekable<I>) -> Result<list::List, ReaderError>
+/// An iterator over the top-level forms of some input. See `forms`.
+pub struct Forms<I>
 where
     I: Iterator<Item = u8>,
 {
-    let mut objs = Vec::new();
+    input: Peekable<I>,
+    limits: Limits,
+}
+
+/// Returns an iterator which reads each top-level form of `input` in
+/// turn, using the default `Limits`. A reader error doesn't end the
+/// iteration; the forms after it are read as usual.
+pub fn forms<I>(input: I) -> Forms<I::IntoIter>
+where
+    I: IntoIterator<Item = u8>,
+{
+    Forms {
+        input: input.into_iter().peekable(),
+        limits: Limits::default(),
+    }
+}
+
+impl<I> Forms<I>
+where
+    I: Iterator<Item = u8>,
+{
+    pub fn with_limits(self, limits: Limits) -> Forms<I> {
+        Forms { limits, ..self }
+    }
+}
+
+impl<I> Iterator for Forms<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = Result<Object, ReaderError>;
+    fn next(&mut self) -> Option<Result<Object, ReaderError>> {
+        match read_with_limits(&mut self.input, self.limits) {
+            Ok(Some(obj)) => Some(Ok(obj)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+fn push_frame(frames: &mut Vec<Frame>, frame: Frame, limits: Limits) -> Result<(), ReaderError> {
+    if frames.len() >= limits.max_depth {
+        return Err(ReaderError::TooDeep);
+    }
+    frames.push(frame);
+    Ok(())
+}
+
+/// Reads the text of a string whose opening `"` has been consumed,
+/// up to and including its closing `"`. Within a string, `\n` and
+/// `\t` are a newline and a tab, and a backslash before any other
+/// character, such as `"` or `\`, includes that character literally.
+/// Comment designators have no special meaning, so this reads from
+/// `input` directly rather than with `next`.
+fn read_string<I>(input: &mut Peekable<I>, limits: Limits) -> Result<Object, ReaderError>
+where
+    I: Iterator<Item = u8>,
+{
+    let mut buf = Vec::new();
+    let mut too_long = false;
     loop {
-        match peek(input) {
-            Some(w) if WHITESPACE.contains(&w) => {
-                // We have already called `peek(input)`
-                // so we don't have to worry about comments
-                input.next();
+        let c = match input.next() {
+            None => return Err(ReaderError::UnclosedString),
+            Some(b'"') => break,
+            Some(b'\\') => match input.next() {
+                None => return Err(ReaderError::UnclosedString),
+                Some(b'n') => b'\n',
+                Some(b't') => b'\t',
+                Some(c) => c,
+            },
+            Some(c) => c,
+        };
+        if buf.len() >= limits.max_token_length {
+            too_long = true;
+        } else {
+            buf.push(c);
+        }
+    }
+    if too_long {
+        return Err(ReaderError::TokenTooLong);
+    }
+    let text = String::from_utf8(buf).map_err(|_| ReaderError::InvalidUtf8)?;
+    Ok(Object::from(make_string(&text)))
+}
 
-                continue;
+/// Evaluates a feature expression against `*features*`. A feature
+/// expression is either a symbol, which is present if it is a member
+/// of `*features*`, or a list `(and ...)`, `(or ...)` or `(not x)`
+/// of feature expressions.
+fn feature_present(feature: Object) -> Result<bool, ReaderError> {
+    if let Some(sym) = <GcRef<Symbol>>::maybe_from(feature) {
+        let mut features = symbol_lookup::get_from_global_namespace(make_symbol(b"*features*"))
+            .and_then(|r| List::maybe_from(*r))
+            .unwrap_or_else(List::nil);
+        return Ok(features.any(|f| f == Object::from(sym)));
+    }
+
+    let mut clauses = List::maybe_from(feature).ok_or(ReaderError::InvalidFeature)?;
+    let operator = clauses.next().ok_or(ReaderError::InvalidFeature)?;
+    if operator == Object::from(make_symbol(b"and")) {
+        for clause in clauses {
+            if !feature_present(clause)? {
+                return Ok(false);
             }
-            Some(b')') => {
-                // We have alread called `peek(input)` so we don't
-                // have to worry about comments
-                input.next();
+        }
+        Ok(true)
+    } else if operator == Object::from(make_symbol(b"or")) {
+        for clause in clauses {
+            if feature_present(clause)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    } else if operator == Object::from(make_symbol(b"not")) {
+        match (clauses.next(), clauses.next()) {
+            (Some(clause), None) => Ok(!feature_present(clause)?),
+            _ => Err(ReaderError::InvalidFeature),
+        }
+    } else {
+        Err(ReaderError::InvalidFeature)
+    }
+}
 
-                return Ok(objs.iter().cloned().collect());
+/// Read the character literal after a `#\`, which is either a
+/// single character, like `#\a` or `#\(`, or the name of one, like
+/// `#\Space`.
+fn read_character<I>(input: &mut Peekable<I>, limits: Limits) -> Result<Object, ReaderError>
+where
+    I: Iterator<Item = u8>,
+{
+    // The first byte is taken as-is, even if it's whitespace or a
+    // delimiter, so `#\ ` and `#\)` are characters.
+    let first = input.next().ok_or(ReaderError::InvalidCharacter)?;
+    let mut buf = vec![first];
+    let more = |c: u8| {
+        if first.is_ascii_alphabetic() {
+            c.is_ascii_alphanumeric()
+        } else {
+            // The continuation bytes of a multi-byte character.
+            c & 0b1100_0000 == 0b1000_0000
+        }
+    };
+    while let Some(&c) = input.peek() {
+        if !more(c) {
+            break;
+        }
+        if buf.len() >= limits.max_token_length {
+            return Err(ReaderError::TokenTooLong);
+        }
+        buf.push(c);
+        input.next();
+    }
+    let text = String::from_utf8(buf).map_err(|_| ReaderError::InvalidUtf8)?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Object::from(c)),
+        _ => immediate::character_from_name(&text)
+            .map(Object::from)
+            .ok_or(ReaderError::InvalidCharacter),
+    }
+}
+
+/// Once the `scheme-compat` module has been `require`d, `#t` and `#f`
+/// read as `t` and `nil`. Otherwise, like any other `#foo`, they are
+/// symbols.
+fn scheme_boolean(token: Object) -> Result<Object, ReaderError> {
+    let scheme = || feature_present(Object::from(make_symbol(b"scheme-compat")));
+    if token == Object::from(make_symbol(b"#t")) && scheme()? {
+        Ok(Object::t())
+    } else if token == Object::from(make_symbol(b"#f")) && scheme()? {
+        Ok(Object::nil())
+    } else {
+        Ok(token)
+    }
+}
+
+/// This method reads bytes into a buffer until it hits whitespace or
+/// a close-paren and then uses `read_num::parse_to_object` to convert
+/// the buffer into an `Object`. If we parsed slices instead of an
+/// iterator, we could skip the buffer and pass a slice of the input
+/// to `parse_to_object`, but parsing slices would cause other
+/// problems.
+fn read_sym_or_num<I>(input: &mut Peekable<I>, limits: Limits) -> Result<Object, ReaderError>
+where
+    I: Iterator<Item = u8>,
+{
+    read_token(input, Vec::new(), limits)
+}
+
+/// The body of `read_sym_or_num`, which starts from the bytes already
+/// consumed into `buf`. A token longer than `limits.max_token_length`
+/// is skipped in its entirety rather than buffered.
+fn read_token<I>(
+    input: &mut Peekable<I>,
+    mut buf: Vec<u8>,
+    limits: Limits,
+) -> Result<Object, ReaderError>
+where
+    I: Iterator<Item = u8>,
+{
+    let mut too_long = false;
+    loop {
+        match peek(input) {
+            Some(w) if WHITESPACE.contains(&w) => {
+                next(input);
+                break;
             }
-            Some(_) => objs.push(read(input)?.unwrap()),
-            None => {
-                return Err(ReaderError::UnclosedList);
+            Some(b')') | None => {
+                break;
+            }
+            Some(c) => {
+                next(input);
+                if buf.len() >= limits.max_token_length {
+                    too_long = true;
+                } else {
+                    buf.push(c);
+                }
             }
         }
     }
+    if too_long {
+        Err(ReaderError::TokenTooLong)
+    } else {
+        debug_assert!(!buf.is_empty());
+        Ok(parse_to_object(&buf))
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +583,42 @@ mod test {
         assert_eq!(peek(iter), Some(b'w'));
     }
     #[test]
+    fn skip_whitespace_and_comments() {
+        let input = b"  ; one\n\t;; two\n\n  (w)";
+        let iter = &mut input.iter().cloned().peekable();
+        skip_whitespace(iter);
+        assert_eq!(iter.next(), Some(b'('));
+    }
+    #[test]
+    fn read_strings() {
+        let input = b"\"foo ; bar\" \"a \\\"quoted\\\" \\\\ word\\n\" \"unclosed";
+        let iter = &mut input.iter().cloned().peekable();
+        assert_eq!(format!("{}", read(iter).unwrap().unwrap()), "\"foo ; bar\"");
+        let s = read(iter).unwrap().unwrap();
+        let s = <GcRef<crate::types::string::PhoebeString>>::maybe_from(s).unwrap();
+        assert_eq!(s.as_str(), "a \"quoted\" \\ word\n");
+        match read(iter) {
+            Err(ReaderError::UnclosedString) => (),
+            _ => panic!("An unclosed string was read"),
+        }
+    }
+    #[test]
+    fn read_characters() {
+        let input = b"#\\; #\\tab #\\x) #\\bogus";
+        let iter = &mut input.iter().cloned().peekable();
+        assert_eq!(read(iter).unwrap(), Some(Object::from(';')));
+        assert_eq!(read(iter).unwrap(), Some(Object::from('\t')));
+        assert_eq!(read(iter).unwrap(), Some(Object::from('x')));
+        match read(iter) {
+            Err(ReaderError::ExtraClose) => (),
+            _ => panic!("The close-paren after #\\x was not read"),
+        }
+        match read(iter) {
+            Err(ReaderError::InvalidCharacter) => (),
+            _ => panic!("An unknown character name was read"),
+        }
+    }
+    #[test]
     fn read_atoms() {
         let input = b"1234 0.5 foo";
         let iter = &mut input.iter().cloned().peekable();
@@ -215,10 +631,42 @@ mod test {
         assert!(iter.next().is_none());
     }
     #[test]
+    fn read_hash_symbols() {
+        let input = b"#foo";
+        let iter = &mut input.iter().cloned().peekable();
+        assert_eq!(
+            read(iter).unwrap().unwrap(),
+            Object::from(crate::symbol_lookup::make_symbol(b"#foo"))
+        );
+    }
+    #[test]
+    fn read_quotes() {
+        let input = b"'foo '(1 'bar)";
+        let iter = &mut input.iter().cloned().peekable();
+        let quote = |obj| -> Object {
+            let list: List = [Object::from(make_symbol(b"quote")), obj]
+                .iter()
+                .cloned()
+                .collect();
+            Object::from(list)
+        };
+        let foo = Object::from(make_symbol(b"foo"));
+        assert!(read(iter).unwrap().unwrap().equal(quote(foo)));
+        let list: List = [Object::from(1i32), quote(Object::from(make_symbol(b"bar")))]
+            .iter()
+            .cloned()
+            .collect();
+        assert!(read(iter)
+            .unwrap()
+            .unwrap()
+            .equal(quote(Object::from(list))));
+        assert!(read(&mut b"(')".iter().cloned().peekable()).is_err());
+    }
+    #[test]
     fn read_list() {
         let input = b"(1 2 3 4 5)";
         let iter = &mut input.iter().cloned().peekable();
-        let list: crate::types::list::List = [
+        let list: List = [
             Object::from(1i32),
             Object::from(2i32),
             Object::from(3i32),
@@ -235,4 +683,66 @@ mod test {
 
         assert!(res.equal(Object::from(list)));
     }
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let input = vec![b'('; 100_000];
+        let iter = &mut input.into_iter().peekable();
+        match read(iter) {
+            Err(ReaderError::TooDeep) => (),
+            other => panic!("Expected TooDeep but found {:?}", other),
+        }
+    }
+    #[test]
+    fn custom_limits() {
+        let limits = Limits {
+            max_depth: 2,
+            max_token_length: 3,
+        };
+        let input = b"((1)) (((1))) abcd efg";
+        let iter = &mut input.iter().cloned().peekable();
+        assert!(read_with_limits(iter, limits).unwrap().is_some());
+        match read_with_limits(iter, limits) {
+            Err(ReaderError::TooDeep) => (),
+            other => panic!("Expected TooDeep but found {:?}", other),
+        }
+        let iter = &mut input[14..].iter().cloned().peekable();
+        match read_with_limits(iter, limits) {
+            Err(ReaderError::TokenTooLong) => (),
+            other => panic!("Expected TokenTooLong but found {:?}", other),
+        }
+        assert_eq!(
+            read_with_limits(iter, limits).unwrap().unwrap(),
+            Object::from(crate::symbol_lookup::make_symbol(b"efg"))
+        );
+    }
+    #[test]
+    fn extra_close_is_consumed() {
+        let input = b") 1";
+        let iter = &mut input.iter().cloned().peekable();
+        match read(iter) {
+            Err(ReaderError::ExtraClose) => (),
+            other => panic!("Expected ExtraClose but found {:?}", other),
+        }
+        assert_eq!(read(iter).unwrap().unwrap(), Object::from(1i32));
+    }
+    #[test]
+    fn iterate_over_forms() {
+        let forms = forms(b"1 (2 3) four".iter().cloned())
+            .collect::<Result<Vec<Object>, ReaderError>>()
+            .unwrap();
+        assert_eq!(forms.len(), 3);
+        assert_eq!(forms[0], Object::from(1i32));
+        assert_eq!(
+            forms[2],
+            Object::from(crate::symbol_lookup::make_symbol(b"four"))
+        );
+    }
+    #[test]
+    fn forms_continue_past_errors() {
+        let mut forms = forms(b") 1 (2".iter().cloned());
+        assert!(forms.next().unwrap().is_err());
+        assert_eq!(forms.next().unwrap().unwrap(), Object::from(1i32));
+        assert!(forms.next().unwrap().is_err());
+        assert!(forms.next().is_none());
+    }
 }
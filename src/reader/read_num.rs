@@ -1,4 +1,5 @@
-use crate::symbol_lookup::make_symbol;
+use crate::symbol_lookup::intern;
+use crate::types::number::PhoebeNumber;
 use crate::types::Object;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -22,12 +23,64 @@ pub fn parse_to_object(s: &[u8]) -> Object {
         b"nil" => Object::nil(),
         _ => match parse_decimal(s) {
             ParseDecimalResult::Integer(i) => Object::from(i),
-            ParseDecimalResult::Symbol(s) => Object::from(make_symbol(s)),
+            ParseDecimalResult::Symbol(s) => Object::from(intern(s)),
             ParseDecimalResult::Float(dec) => Object::from(dec.make_float()),
+            ParseDecimalResult::Ratio(n, d) => Object::from(PhoebeNumber::ratio(n, d)),
         },
     }
 }
 
+/// Parse an integer in `radix`, with an optional leading sign, from
+/// the start of `s`. Returns the integer and the number of bytes it
+/// took up, or `None` if `s` doesn't begin with an integer or the
+/// integer doesn't fit in an `i32`.
+pub fn parse_integer_prefix(s: &[u8], radix: u32) -> Option<(i32, usize)> {
+    debug_assert!(2 <= radix && radix <= 36);
+    let (sign, digits) = extract_sign(s);
+    let sign_len = s.len() - digits.len();
+    let mut result: i64 = 0;
+    let mut len = 0;
+    for &c in digits {
+        let digit = match (c as char).to_digit(radix) {
+            Some(d) => i64::from(d),
+            None => break,
+        };
+        result = result * i64::from(radix) + digit;
+        if result > i64::from(i32::max_value()) + 1 {
+            return None;
+        }
+        len += 1;
+    }
+    if len == 0 {
+        return None;
+    }
+    let result = match sign {
+        Sign::Positive => result,
+        Sign::Negative => -result,
+    };
+    if result > i64::from(i32::max_value()) {
+        return None;
+    }
+    Some((result as i32, sign_len + len))
+}
+
+/// Parse the longest prefix of `s` which the reader would read as a
+/// number, returning it as a float along with its length in bytes.
+pub fn parse_float_prefix(s: &[u8]) -> Option<(f64, usize)> {
+    let candidate = s
+        .iter()
+        .take_while(|&&c| c.is_ascii_digit() || b"+-.eE".contains(&c))
+        .count();
+    (1..=candidate)
+        .rev()
+        .filter_map(|len| match parse_decimal(&s[..len]) {
+            ParseDecimalResult::Integer(i) => Some((f64::from(i), len)),
+            ParseDecimalResult::Float(dec) => Some((dec.make_float(), len)),
+            ParseDecimalResult::Ratio(..) | ParseDecimalResult::Symbol(_) => None,
+        })
+        .next()
+}
+
 #[derive(PartialEq, Eq, Debug)]
 /// A sort of intermediate step between a `&[u8]` and an `f64`. The
 /// various parts of a number have been seperated, but not yet
@@ -67,6 +120,8 @@ impl<'a> DecimalFp<'a> {
 enum ParseDecimalResult<'a> {
     Integer(i32),
     Float(DecimalFp<'a>),
+    /// A numerator and a non-zero denominator, not yet normalized.
+    Ratio(i128, i128),
     Symbol(&'a [u8]),
 }
 
@@ -76,7 +131,8 @@ enum ParseDecimalResult<'a> {
 /// integral with an optional leading sign; a float for an optional
 /// leading sign, an integral, an optional `.` followed by a
 /// fractional part, and an optional `e` or `E` followed by a legal
-/// integer; or a symbol for any other string.
+/// integer; a ratio for an optional leading sign, an integral, a
+/// `/` and a non-zero integral; or a symbol for any other string.
 fn parse_decimal(input: &[u8]) -> ParseDecimalResult {
     debug_assert!(!input.is_empty());
     let (sign, s) = extract_sign(input);
@@ -138,6 +194,22 @@ fn parse_decimal(input: &[u8]) -> ParseDecimalResult {
                 }
             }
         }
+        Some(&b'/') if !integral.is_empty() => {
+            let (denominator, s) = eat_digits(&s[1..]);
+            if denominator.is_empty() || !s.is_empty() {
+                return ParseDecimalResult::Symbol(input);
+            }
+            let numerator = i128::from(parse_num_from_bytes_unchecked(integral));
+            let denominator = i128::from(parse_num_from_bytes_unchecked(denominator));
+            if denominator == 0 {
+                ParseDecimalResult::Symbol(input)
+            } else {
+                match sign {
+                    Sign::Positive => ParseDecimalResult::Ratio(numerator, denominator),
+                    Sign::Negative => ParseDecimalResult::Ratio(-numerator, denominator),
+                }
+            }
+        }
         Some(_) => ParseDecimalResult::Symbol(input),
     }
 }
@@ -248,6 +320,20 @@ mod test {
         (lhs - rhs).abs() < (lhs * ::std::f64::EPSILON)
     }
     #[test]
+    fn parse_prefixes() {
+        assert_eq!(parse_integer_prefix(b"-42abc", 10), Some((-42, 3)));
+        assert_eq!(parse_integer_prefix(b"ff", 16), Some((255, 2)));
+        assert_eq!(
+            parse_integer_prefix(b"-2147483648", 10),
+            Some((i32::min_value(), 11))
+        );
+        assert_eq!(parse_integer_prefix(b"2147483648", 10), None);
+        assert_eq!(parse_integer_prefix(b"+", 10), None);
+        assert_eq!(parse_float_prefix(b"1.5e2x"), Some((150.0, 5)));
+        assert_eq!(parse_float_prefix(b"3.x"), Some((3.0, 2)));
+        assert_eq!(parse_float_prefix(b"e5"), None);
+    }
+    #[test]
     fn parse_decimals() {
         let res = parse_decimal(b"1.23");
         assert_eq!(
@@ -306,6 +392,15 @@ mod test {
                 exp: 0,
             },)
         );
+
+        let res = parse_decimal(b"-2/4");
+        assert_eq!(res, ParseDecimalResult::Ratio(-2, 4));
+
+        let res = parse_decimal(b"1/0");
+        assert_eq!(res, ParseDecimalResult::Symbol(b"1/0"));
+
+        let res = parse_decimal(b"1/2/3");
+        assert_eq!(res, ParseDecimalResult::Symbol(b"1/2/3"));
     }
     #[test]
     fn parse_one() {
@@ -1,12 +1,116 @@
-use crate::builtins::make_builtins_once;
 use crate::evaluator::eval_from_stack;
-use crate::printer::print_from_stack;
-use crate::reader::{read, ReaderError};
+use crate::gc::{GcRef, Rooted};
+use crate::interpreter::Interpreter;
+use crate::interrupt;
+use crate::lisp2::lisp2;
+use crate::printer::{print_from_stack, ColumnTracker};
+use crate::reader::{read_with_limits, Limits, ReaderError};
 use crate::stack::{self, StackOverflowError};
+use crate::standard_streams::{
+    with_standard_error, with_standard_input_from, with_standard_output,
+};
+use crate::symbol_lookup::{self, make_symbol};
+use crate::types::{symbol::Symbol, Object};
+#[cfg(feature = "line-editing")]
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io::prelude::*;
-use std::{convert, io};
+use std::ops::Try;
+use std::{convert, fmt, io};
 
 const PROMPT: &[u8] = b"phoebe> ";
+const CONTINUATION_PROMPT: &[u8] = b"   ...> ";
+
+lazy_static! {
+    static ref LAST_RESULT: GcRef<Symbol> = { make_symbol(b"*") };
+    static ref SECOND_RESULT: GcRef<Symbol> = { make_symbol(b"**") };
+    static ref THIRD_RESULT: GcRef<Symbol> = { make_symbol(b"***") };
+}
+
+#[cfg(feature = "line-editing")]
+thread_local! {
+    /// True while the REPL is reading the rest of an unfinished form,
+    /// so that `LineEditor` knows which prompt to print.
+    static CONTINUING: Cell<bool> = { Cell::new(false) };
+}
+
+/// One of the eight standard terminal colors.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    /// The color called `name`, like `"blue"`, ignoring case.
+    pub fn named(name: &str) -> Option<Color> {
+        match name.to_lowercase().as_str() {
+            "black" => Some(Color::Black),
+            "red" => Some(Color::Red),
+            "green" => Some(Color::Green),
+            "yellow" => Some(Color::Yellow),
+            "blue" => Some(Color::Blue),
+            "magenta" => Some(Color::Magenta),
+            "cyan" => Some(Color::Cyan),
+            "white" => Some(Color::White),
+            _ => None,
+        }
+    }
+    /// The ANSI escape sequence which switches to this color.
+    fn escape(self) -> &'static str {
+        match self {
+            Color::Black => "\x1b[30m",
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Blue => "\x1b[34m",
+            Color::Magenta => "\x1b[35m",
+            Color::Cyan => "\x1b[36m",
+            Color::White => "\x1b[37m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// The colors the REPL prints its prompts and errors in, or `None`
+/// for the terminal's usual color. Results are never colored. Only
+/// use colors when writing to a terminal, since the escape sequences
+/// would end up in a file or pipe.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Colors {
+    pub prompt: Option<Color>,
+    pub error: Option<Color>,
+}
+
+impl Colors {
+    /// Errors in red, and the prompt in `prompt`.
+    pub fn with_prompt(prompt: Color) -> Colors {
+        Colors {
+            prompt: Some(prompt),
+            error: Some(Color::Red),
+        }
+    }
+}
+
+/// Write `text`, in `color` if it's `Some`.
+fn write_in<W, T>(w: &mut W, color: Option<Color>, text: T) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    T: fmt::Display,
+{
+    match color {
+        Some(c) => write!(w, "{}{}{}", c.escape(), text, RESET),
+        None => write!(w, "{}", text),
+    }
+}
 
 #[derive(Fail, Debug)]
 pub enum ReplError {
@@ -28,12 +132,14 @@ impl convert::From<StackOverflowError> for ReplError {
     }
 }
 
-/// This is a public-facing method and is usually what you want - it
-/// initializes, evaluates the input, and prints it. The only caveat
-/// is that successive calls to this will result in repeated calls to
-/// `initialize`, which is wasteful and potentially will squash
-/// things. For repeated calls, instead use `initialize` once and
-/// `read_eval_print_loop` as many times as required.
+/// Read, evaluate and print from `input` until it is empty, with a
+/// default `Interpreter`. The builtins are only sourced once, however
+/// many times this is called, and every call shares the thread's
+/// global namespace.
+///
+/// Output produced by Phoebe code goes to `output` and `error`; to
+/// redirect it elsewhere, or to evaluate forms and strings between
+/// REPLs, use a `Session` instead.
 pub fn repl<I, O, E>(
     input: &mut I,
     output: &mut O,
@@ -45,74 +151,342 @@ where
     O: Write,
     E: Write,
 {
-    make_builtins_once();
-    read_eval_print_loop(input, output, error, should_prompt)
+    Interpreter::new().repl(input, output, error, should_prompt)
+}
+
+/// The REPL's input, read a line at a time so that the REPL can tell
+/// when a form is unfinished: the unread rest of the lines read so
+/// far, followed by the rest of the underlying input. Phoebe code
+/// evaluated by the REPL reads its standard input from here, unless
+/// the `Interpreter` was configured with an input of its own.
+struct ReplInput<I> {
+    pending: VecDeque<u8>,
+    bytes: io::Bytes<I>,
+}
+
+impl<I: Read> ReplInput<I> {
+    /// Move the next line of the underlying input, including its
+    /// newline, onto the end of `pending`, returning `false` at the
+    /// end of input.
+    fn read_line(&mut self) -> io::Result<bool> {
+        let mut read_any = false;
+        for byte in &mut self.bytes {
+            let byte = byte?;
+            read_any = true;
+            self.pending.push_back(byte);
+            if byte == b'\n' {
+                break;
+            }
+        }
+        Ok(read_any)
+    }
+}
+
+impl<I: Read> Iterator for ReplInput<I> {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        match self.pending.pop_front() {
+            Some(byte) => Some(byte),
+            None => self.bytes.next().map(Result::unwrap),
+        }
+    }
 }
 
-enum ReadResult {
-    NoneRead,
-    Ok,
-    StackError(StackOverflowError),
-    ReadError(ReaderError),
+/// Phoebe code's standard input when it shares the REPL's input. The
+/// REPL's reader and Phoebe code take turns reading, so each borrows
+/// the `ReplInput` only while it reads.
+struct SharedInput<'a, I: 'a>(&'a RefCell<ReplInput<I>>);
+
+impl<'a, I: Read> Iterator for SharedInput<'a, I> {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        self.0.borrow_mut().next()
+    }
+}
+
+/// Where the REPL writes its results or its errors. When the REPL's
+/// own stream is also Phoebe's standard output or error, it belongs
+/// to `standard_streams` while the REPL runs, and the REPL writes to
+/// it through `with_standard_output` or `with_standard_error` rather
+/// than holding a second `&mut` to it.
+pub(crate) enum ReplStream<'a, W: 'a> {
+    Own(&'a mut W),
+    Standard,
+}
+
+impl<'a, O: Write> ReplStream<'a, ColumnTracker<O>> {
+    fn with_output<F, T>(&mut self, fun: F) -> T
+    where
+        F: FnOnce(&mut ColumnTracker<Write + '_>) -> T,
+    {
+        match *self {
+            ReplStream::Own(ref mut output) => fun(&mut **output),
+            ReplStream::Standard => with_standard_output(|output| fun(output)),
+        }
+    }
+}
+
+impl<'a, E: Write> ReplStream<'a, E> {
+    fn with_error<F, T>(&mut self, fun: F) -> T
+    where
+        F: FnOnce(&mut Write) -> T,
+    {
+        match *self {
+            ReplStream::Own(ref mut error) => fun(&mut **error),
+            ReplStream::Standard => with_standard_error(fun),
+        }
+    }
+}
+
+/// The values of the last three forms a REPL evaluated without
+/// error, which it binds in the global namespace to `*`, `**` and
+/// `***`, most recent first. In Lisp-1 mode, binding `*` would hide
+/// multiplication, so only `**` and `***` are bound, and the most
+/// recent result is kept here instead.
+struct ResultHistory {
+    last: Rooted<Object>,
+}
+
+impl ResultHistory {
+    fn new() -> ResultHistory {
+        ResultHistory {
+            last: Rooted::new(Object::nil()),
+        }
+    }
+    fn record(&mut self, value: Object) {
+        let previous = *self.last;
+        symbol_lookup::global_env().update(|b| {
+            let older = b
+                .get(*SECOND_RESULT)
+                .filter(|o| o.definedp())
+                .unwrap_or_else(Object::nil);
+            b.set(*THIRD_RESULT, older);
+            b.set(*SECOND_RESULT, previous);
+            if lisp2() {
+                b.set(*LAST_RESULT, value);
+            }
+        });
+        self.last = Rooted::new(value);
+    }
+}
+
+/// Read the next form from `input`. While what has been read so far
+/// is blank, or ends partway through a form, another line is read,
+/// after prompting for it if `should_prompt`: with `PROMPT` for a new
+/// form, or with `CONTINUATION_PROMPT` to finish one. An unfinished
+/// form at the end of input is a reader error, as usual.
+fn read_form<I, O>(
+    input: &RefCell<ReplInput<I>>,
+    output: &mut ReplStream<ColumnTracker<O>>,
+    should_prompt: bool,
+    limits: Limits,
+    colors: Colors,
+) -> Result<Result<Option<Object>, ReaderError>, ReplError>
+where
+    I: Read,
+    O: Write,
+{
+    let mut input = input.borrow_mut();
+    loop {
+        let (result, unread) = {
+            let mut pending = input.pending.iter().cloned().peekable();
+            let result = read_with_limits(&mut pending, limits);
+            (result, pending.len())
+        };
+        let needs_more = unread == 0
+            && match result {
+                Ok(None) => true,
+                Err(ref e) => e.is_incomplete(),
+                Ok(Some(_)) => false,
+            };
+        if needs_more {
+            let continuing = result.is_err();
+            #[cfg(feature = "line-editing")]
+            CONTINUING.with(|c| c.set(continuing));
+            if should_prompt {
+                output.with_output(|o| prompt(o, continuing, colors.prompt))?;
+            }
+            if input.read_line()? {
+                if should_prompt && input.pending.back() == Some(&b'\n') {
+                    // The terminal echoed the newline, so the cursor
+                    // is back at the start of a line.
+                    output.with_output(|o| o.reset_column());
+                }
+                continue;
+            }
+        }
+        let consumed = input.pending.len() - unread;
+        input.pending.drain(..consumed);
+        return Ok(result);
+    }
 }
 
 /// Repeatedly read, evaluate, and print from `input` into `output`,
 /// signaling any errors into `error`, until `input` is empty. If
-/// `should_prompt`, will print `phoebe> ` before each `read`. This is
-/// called internally by `repl` and is exposed mostly for testing.
-fn read_eval_print_loop<I, O, E>(
+/// `should_prompt`, will print `phoebe> ` before reading each new
+/// form, and `   ...> ` before each further line of a form which
+/// spans several. This is called internally by `repl` and is exposed
+/// mostly for testing.
+///
+/// Prompts and results always start on a fresh line, even if Phoebe
+/// code has printed to `output` without a trailing newline. Prompts
+/// and errors are printed in `colors`. Each result is recorded in a
+/// `ResultHistory`.
+///
+/// `output` and `error` are `ReplStream::Standard` when they're also
+/// Phoebe's standard output and error. Phoebe code's standard input
+/// is `standard_input` or, if that is `None`, the rest of `input`
+/// following the form being evaluated.
+pub(crate) fn read_eval_print_loop<I, O, E>(
     input: &mut I,
-    output: &mut O,
-    error: &mut E,
+    standard_input: Option<&mut Iterator<Item = u8>>,
+    mut output: ReplStream<ColumnTracker<O>>,
+    mut error: ReplStream<E>,
     should_prompt: bool,
+    limits: Limits,
+    colors: Colors,
 ) -> Result<(), ReplError>
 where
     I: Read,
     O: Write,
     E: Write,
 {
-    let input_iter = &mut input.bytes().map(Result::unwrap).peekable();
-    loop {
-        if should_prompt {
-            prompt(output)?;
-        }
-        match stack::with_stack(|s| match read(input_iter) {
-            Err(e) => ReadResult::ReadError(e),
-            Ok(None) => ReadResult::NoneRead,
-            Ok(Some(obj)) => {
-                if let Err(e) = stack::make_stack_frame(s, &[obj]) {
-                    ReadResult::StackError(e)
-                } else {
-                    ReadResult::Ok
-                }
-            }
-        }) {
-            ReadResult::NoneRead => {
+    let repl_input = &RefCell::new(ReplInput {
+        pending: VecDeque::new(),
+        bytes: input.bytes(),
+    });
+    let mut shared_input = SharedInput(repl_input);
+    let standard_input = match standard_input {
+        Some(i) => i,
+        None => &mut shared_input as &mut Iterator<Item = u8>,
+    };
+    let mut history = ResultHistory::new();
+    with_standard_input_from(standard_input, || loop {
+        match read_form(repl_input, &mut output, should_prompt, limits, colors)? {
+            Ok(None) => {
                 return Ok(());
             }
-            ReadResult::Ok => {
+            Ok(Some(obj)) => {
+                stack::with_stack(|s| stack::make_stack_frame(s, &[obj]))?;
+                interrupt::clear_interrupt();
                 unsafe { eval_from_stack() }
+                let value = stack::with_stack(|s| *s.last().unwrap());
+                if let Ok(value) = value.into_result() {
+                    history.record(value);
+                }
                 // eval_from_stack pushes its return value to the
                 // stack, but without a frame_length. Adding that
                 // frame_length turns it into the stack frame for
                 // `print_from_stack`.
-                match unsafe { print_from_stack() } {
-                    Ok(o) => writeln!(output, "{}", o)?,
-                    Err(e) => writeln!(error, "{}", e)?,
+                let printed = unsafe { print_from_stack() };
+                output.with_output(|o| o.fresh_line())?;
+                match printed {
+                    Ok(o) => output.with_output(|out| writeln!(out, "{}", o))?,
+                    Err(e) => error.with_error(|err| {
+                        write_in(err, colors.error, e)?;
+                        writeln!(err)
+                    })?,
                 }
             }
-            ReadResult::ReadError(e) => {
-                writeln!(error, "{}", e)?;
+            Err(e) => {
+                output.with_output(|o| o.fresh_line())?;
+                error.with_error(|err| {
+                    write_in(err, colors.error, e)?;
+                    writeln!(err)
+                })?;
             }
-            ReadResult::StackError(e) => {
-                return Err(e.into());
+        }
+    })
+}
+
+/// A `Read` which reads from the terminal a line at a time, printing
+/// the REPL's prompts and letting the user edit the line with the
+/// usual Emacs-style keys: the arrow keys move the cursor and step
+/// through history, `C-k` and `C-y` kill and yank, and `C-l` clears
+/// the screen. `C-c` abandons the line being edited and `C-d` on an
+/// empty line ends the input. Since it prompts for itself, pass it to
+/// `repl` with `should_prompt` false. When the standard input isn't a
+/// terminal, lines are read without editing.
+#[cfg(feature = "line-editing")]
+pub struct LineEditor {
+    editor: ::rustyline::Editor<()>,
+    line: Vec<u8>,
+    position: usize,
+    prompt_color: Option<Color>,
+}
+
+#[cfg(feature = "line-editing")]
+impl LineEditor {
+    pub fn new() -> LineEditor {
+        LineEditor {
+            editor: ::rustyline::Editor::new(),
+            line: Vec::new(),
+            position: 0,
+            prompt_color: None,
+        }
+    }
+
+    /// Print the prompts in `color`, rather than in the terminal's
+    /// usual color.
+    pub fn with_prompt_color(self, prompt_color: Option<Color>) -> LineEditor {
+        LineEditor {
+            prompt_color,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "line-editing")]
+impl Default for LineEditor {
+    fn default() -> LineEditor {
+        LineEditor::new()
+    }
+}
+
+#[cfg(feature = "line-editing")]
+impl Read for LineEditor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use rustyline::error::ReadlineError;
+        use std::str;
+
+        while self.position == self.line.len() {
+            let prompt = if CONTINUING.with(Cell::get) {
+                CONTINUATION_PROMPT
+            } else {
+                PROMPT
+            };
+            let mut styled = Vec::new();
+            write_in(
+                &mut styled,
+                self.prompt_color,
+                str::from_utf8(prompt).unwrap(),
+            )?;
+            match self.editor.readline(str::from_utf8(&styled).unwrap()) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        self.editor.add_history_entry(line.as_str());
+                    }
+                    self.line = line.into_bytes();
+                    self.line.push(b'\n');
+                    self.position = 0;
+                }
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(0),
+                Err(ReadlineError::Io(e)) => return Err(e),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
             }
         }
+        let remaining = &self.line[self.position..];
+        let n = remaining.len().min(buf.len());
+        buf[..n].copy_from_slice(&remaining[..n]);
+        self.position += n;
+        Ok(n)
     }
 }
 
 pub mod test_utilities {
     use super::*;
+    use std::sync::{Arc, Mutex};
     use std::{convert, string};
 
     #[derive(Fail, Debug)]
@@ -163,6 +537,42 @@ pub mod test_utilities {
         Ok(())
     }
 
+    /// Run `input` through `interpreter`'s REPL, without prompting,
+    /// and return what it wrote to its output and to its error
+    /// output.
+    pub fn run_repl(interpreter: &mut Interpreter, mut input: &[u8]) -> (String, String) {
+        let mut output = Vec::new();
+        let mut error = Vec::new();
+        interpreter
+            .repl(&mut input, &mut output, &mut error, false)
+            .unwrap();
+        (
+            String::from_utf8(output).unwrap(),
+            String::from_utf8(error).unwrap(),
+        )
+    }
+
+    /// A buffer which can be handed to `Interpreter::with_output` or
+    /// `with_error_output` and still be read by the test afterwards.
+    #[derive(Clone, Default)]
+    pub struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuf {
+        /// Everything written to the buffer so far.
+        pub fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
     #[macro_export]
     /// This macro is used to test that inputs result in expected
     /// outputs. Usage:
@@ -194,11 +604,21 @@ pub mod test_utilities {
     }
 }
 
-fn prompt<O>(output: &mut O) -> Result<(), ReplError>
+fn prompt<O>(
+    output: &mut ColumnTracker<O>,
+    continuing: bool,
+    color: Option<Color>,
+) -> Result<(), ReplError>
 where
-    O: Write,
+    O: Write + ?Sized,
 {
-    output.write_all(PROMPT)?;
+    output.fresh_line()?;
+    let prompt = if continuing {
+        CONTINUATION_PROMPT
+    } else {
+        PROMPT
+    };
+    write_in(output, color, String::from_utf8_lossy(prompt))?;
     output.flush()?;
     Ok(())
 }
@@ -219,4 +639,37 @@ mod test {
         }
         assert_eq!(str::from_utf8(&output).unwrap(), "(1 2 3 4)\n");
     }
+    #[test]
+    fn continue_unfinished_forms() {
+        let mut input: &[u8] = b"(list 1\n2) (list\n3)\n(+ 1\n";
+        let mut output: Vec<u8> = Vec::new();
+        let mut error: Vec<u8> = Vec::new();
+
+        repl(&mut input, &mut output, &mut error, true).unwrap();
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "phoebe>    ...> (1 2)\n   ...> (3)\nphoebe>    ...> \nphoebe> "
+        );
+        assert_eq!(str::from_utf8(&error).unwrap(), "A list went unclosed\n");
+    }
+    #[test]
+    fn colored_prompts_and_errors() {
+        let mut input: &[u8] = b")\n(list 1)\n";
+        let mut output: Vec<u8> = Vec::new();
+        let mut error: Vec<u8> = Vec::new();
+
+        Interpreter::new()
+            .with_colors(Colors::with_prompt(Color::Blue))
+            .repl(&mut input, &mut output, &mut error, true)
+            .unwrap();
+        let prompt = "\x1b[34mphoebe> \x1b[0m";
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            format!("{0}{0}(1)\n{0}", prompt)
+        );
+        assert_eq!(
+            str::from_utf8(&error).unwrap(),
+            "\x1b[31mA spurious close-delimiter\x1b[0m\n"
+        );
+    }
 }
@@ -0,0 +1,114 @@
+//! A `Session` is a long-lived Phoebe environment for an embedding
+//! application, which evaluates forms and strings and runs REPLs
+//! against whatever streams it's given, sourcing the builtins only
+//! once. Its behavior is configured by the `Interpreter` it's made
+//! from:
+//!
+//! ```rust
+//! # extern crate phoebe;
+//! # fn main() {
+//! use phoebe::{Interpreter, Session};
+//!
+//! let mut session = Session::from(Interpreter::new().with_debug_mode(true));
+//! session.eval_str("(defun add-three (n) (+ n 3))").unwrap();
+//! let result = session.eval_str("(add-three 4)").unwrap();
+//! assert_eq!(format!("{}", *result), "7");
+//! # }
+//! ```
+//!
+//! `eval_form` and `eval_str` return their values as `Rooted`s,
+//! which keep them alive across further evaluation until they're
+//! dropped.
+
+use crate::gc::Rooted;
+use crate::interpreter::Interpreter;
+use crate::load::eval_top_level;
+use crate::reader::{forms, ReaderError};
+use crate::repl::ReplError;
+use crate::stack::StackOverflowError;
+use crate::types::Object;
+use std::convert;
+use std::io::{Read, Write};
+
+#[derive(Fail, Debug)]
+pub enum SessionError {
+    #[fail(display = "{}", _0)]
+    Read(ReaderError),
+    /// Evaluating a form returned an error. Phoebe errors are garbage
+    /// collected, so only their message is kept.
+    #[fail(display = "{}", _0)]
+    Eval(String),
+    #[fail(display = "{}", _0)]
+    StackOverflow(StackOverflowError),
+}
+
+impl convert::From<ReaderError> for SessionError {
+    fn from(e: ReaderError) -> SessionError {
+        SessionError::Read(e)
+    }
+}
+
+impl convert::From<StackOverflowError> for SessionError {
+    fn from(e: StackOverflowError) -> SessionError {
+        SessionError::StackOverflow(e)
+    }
+}
+
+#[derive(Default)]
+pub struct Session {
+    interpreter: Interpreter,
+}
+
+impl Session {
+    /// A session configured by `Interpreter::new()`.
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Evaluate `form` in the global namespace, returning its value.
+    pub fn eval_form(&mut self, form: Object) -> Result<Rooted<Object>, SessionError> {
+        // The value has to be rooted before this thread stops being
+        // a mutator, after which a pass could free it at any time.
+        let result = self
+            .interpreter
+            .with_configuration(|| eval_top_level(form).map(|r| r.map(Rooted::new)))?;
+        result.map_err(SessionError::Eval)
+    }
+
+    /// Read and evaluate each form in `source` in turn, returning the
+    /// value of the last one, or `nil` if there are none. Stops at the
+    /// first form which can't be read or signals an error.
+    pub fn eval_str(&mut self, source: &str) -> Result<Rooted<Object>, SessionError> {
+        let limits = self.interpreter.reader_limits();
+        self.interpreter.with_configuration(|| {
+            let mut value = Rooted::new(Object::nil());
+            for form in forms(source.bytes()).with_limits(limits) {
+                value = Rooted::new(eval_top_level(form?)?.map_err(SessionError::Eval)?);
+            }
+            Ok(value)
+        })
+    }
+
+    /// Read, evaluate and print from `input` until it is empty. See
+    /// `Interpreter::repl`.
+    pub fn run_repl<I, O, E>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+        error: &mut E,
+        should_prompt: bool,
+    ) -> Result<(), ReplError>
+    where
+        I: Read,
+        O: Write,
+        E: Write,
+    {
+        self.interpreter.repl(input, output, error, should_prompt)
+    }
+}
+
+impl convert::From<Interpreter> for Session {
+    fn from(interpreter: Interpreter) -> Session {
+        Session { interpreter }
+    }
+}
@@ -0,0 +1,64 @@
+//! With shadowing warnings on, a `let`, a function's parameter or a
+//! `defvar` which binds a symbol that already has a visible binding
+//! writes a warning to the standard error and to the log. Shadowing a
+//! builtin, as by naming a parameter `list`, is a common source of
+//! confusing bugs. Evaluation carries on as usual after the warning.
+//!
+//! Shadowing warnings are off unless an `Interpreter` is configured
+//! `with_shadowing_warnings(true)`, and are installed per-thread for
+//! the duration of that interpreter's REPL.
+
+use crate::prelude::*;
+use crate::standard_streams::with_standard_error;
+use std::cell::Cell;
+use std::io::Write;
+
+thread_local! {
+    static SHADOWING_WARNINGS: Cell<bool> = { Cell::new(false) };
+}
+
+/// True if this thread warns about shadowed bindings.
+pub fn shadowing_warnings() -> bool {
+    SHADOWING_WARNINGS.with(Cell::get)
+}
+
+/// Executes `fun` with shadowing warnings on if `warn`, restoring the
+/// previous setting when finished.
+pub fn with_shadowing_warnings<F, T>(warn: bool, fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SHADOWING_WARNINGS.with(|s| s.set(self.0));
+        }
+    }
+    let _restore = Restore(SHADOWING_WARNINGS.with(|s| s.replace(warn)));
+    fun()
+}
+
+/// Called before a `kind` binding of `sym` is made in a new scope
+/// whose parent is `env`, to warn if `sym` is already bound there.
+pub(crate) fn check(kind: &str, sym: GcRef<Symbol>, env: GcRef<Namespace>) {
+    if !shadowing_warnings() {
+        return;
+    }
+    let shadowed = match env.get_sym_ref(sym) {
+        Some(r) if (*r).definedp() => *r,
+        _ => return,
+    };
+    let what = match <GcRef<Function>>::maybe_from(shadowed) {
+        Some(f) if f.is_builtin() => "the builtin",
+        _ => "an existing binding of",
+    };
+    warn!("{} binding of {} shadows {} {}", kind, sym, what, sym);
+    // A warning which can't be written shouldn't stop evaluation.
+    let _ = with_standard_error(|e| {
+        writeln!(
+            e,
+            "WARNING: {} binding of {} shadows {} {}",
+            kind, sym, what, sym
+        )
+    });
+}
@@ -1,26 +1,152 @@
+//! Each thread's value stack, which holds function arguments and
+//! local bindings. `Reference`s point directly at its slots, so a
+//! stack is made of fixed-size segments which never move once
+//! they're allocated, and it grows by adding segments rather than by
+//! reallocating.
+
 use crate::prelude::*;
 use std::{
-    borrow::BorrowMut,
-    collections::HashMap,
-    ops::IndexMut,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Mutex, RwLock,
-    },
+    ops::{Index, IndexMut},
+    sync::{Arc, Mutex, RwLock},
+    thread,
 };
 
-const STACK_CAPACITY: usize = 128;
+/// The number of `Object`s in each segment of a stack.
+const SEGMENT_SIZE: usize = 128;
+
+/// The most `Object`s a stack may hold. Evaluation recurses on the
+/// Rust stack too, so this has to be low enough that runaway
+/// recursion signals a `StackOverflowError` before it overflows a
+/// thread's Rust stack, even a 2MiB one in a debug build.
+pub const MAX_STACK_SIZE: usize = 8 * SEGMENT_SIZE;
+
+type Segment = Box<[Object; SEGMENT_SIZE]>;
+
+/// A thread's value stack.
+pub struct Stack {
+    /// Segments are allocated as the stack first grows into them, and
+    /// kept once it shrinks, so that a thread which recurses
+    /// repeatedly doesn't allocate each time.
+    segments: Vec<Segment>,
+    len: usize,
+    /// The greatest `len` has ever been.
+    peak: usize,
+    /// The name of the thread the stack belongs to, if it has one.
+    thread: Option<String>,
+}
+
+impl Stack {
+    fn new(thread: Option<String>) -> Stack {
+        Stack {
+            segments: Vec::new(),
+            len: 0,
+            peak: 0,
+            thread,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_ref().map(String::as_str)
+    }
+    /// Push `obj`, returning its index, unless the stack already
+    /// holds `MAX_STACK_SIZE` objects.
+    pub fn push(&mut self, obj: Object) -> Result<usize, StackOverflowError> {
+        let idx = self.len;
+        if idx == MAX_STACK_SIZE {
+            return Err(StackOverflowError {
+                stack_size: idx,
+                stack_capacity: MAX_STACK_SIZE,
+            });
+        }
+        if idx == self.segments.len() * SEGMENT_SIZE {
+            self.segments
+                .push(Box::new([Object::uninitialized(); SEGMENT_SIZE]));
+        }
+        self.len += 1;
+        self.peak = self.peak.max(self.len);
+        self[idx] = obj;
+        Ok(idx)
+    }
+    pub fn pop(&mut self) -> Option<Object> {
+        if self.is_empty() {
+            None
+        } else {
+            let obj = self[self.len - 1];
+            self.len -= 1;
+            Some(obj)
+        }
+    }
+    pub fn last(&self) -> Option<&Object> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(&self[self.len - 1])
+        }
+    }
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Object> + 'a {
+        (0..self.len).map(move |i| &self[i])
+    }
+}
+
+impl Index<usize> for Stack {
+    type Output = Object;
+    fn index(&self, idx: usize) -> &Object {
+        assert!(idx < self.len);
+        &self.segments[idx / SEGMENT_SIZE][idx % SEGMENT_SIZE]
+    }
+}
+
+impl IndexMut<usize> for Stack {
+    fn index_mut(&mut self, idx: usize) -> &mut Object {
+        assert!(idx < self.len);
+        &mut self.segments[idx / SEGMENT_SIZE][idx % SEGMENT_SIZE]
+    }
+}
 
 thread_local! {
-    static STACK_KEY: usize = {
-        STACK_NUMBER.fetch_add(1, Ordering::Relaxed)
-    };
+    /// This thread's stack. Only this thread and the collector ever
+    /// lock it, so outside of a collection the lock is never
+    /// contended.
+    static STACK: LocalStack = { LocalStack::register() };
 }
 
 lazy_static! {
-    pub static ref STACK_NUMBER: AtomicUsize = { AtomicUsize::new(0) };
-    pub static ref STACKS: RwLock<HashMap<usize, Mutex<Vec<Object>>>> =
-        { RwLock::new(HashMap::new()) };
+    /// The stack of every living thread, for the collector to mark.
+    /// A thread adds its own the first time it uses it, and removes
+    /// it when it exits, which are the only times it touches this.
+    pub static ref STACKS: RwLock<Vec<Arc<Mutex<Stack>>>> = { RwLock::new(Vec::new()) };
+}
+
+/// A thread's handle on its own stack.
+struct LocalStack(Arc<Mutex<Stack>>);
+
+impl LocalStack {
+    fn register() -> LocalStack {
+        let name = thread::current().name().map(String::from);
+        let stack = Arc::new(Mutex::new(Stack::new(name)));
+        STACKS.write().unwrap().push(stack.clone());
+        LocalStack(stack)
+    }
+}
+
+impl Drop for LocalStack {
+    /// Unregister the stack when its thread exits, so that whatever
+    /// was left on it can be collected. The collector holds `STACKS`
+    /// while it marks the stacks, so this waits for it to finish
+    /// with this one.
+    fn drop(&mut self) {
+        if let Ok(mut stacks) = STACKS.write() {
+            stacks.retain(|stack| !Arc::ptr_eq(stack, &self.0));
+        }
+    }
 }
 
 #[derive(Fail, Debug)]
@@ -33,11 +159,11 @@ pub struct ArgIndexError {
     pub stack_frame_length: usize,
 }
 
-pub fn make_stack_frame(s: &mut Vec<Object>, objs: &[Object]) -> Result<(), StackOverflowError> {
+pub fn make_stack_frame(s: &mut Stack, objs: &[Object]) -> Result<(), StackOverflowError> {
     for &obj in objs {
-        push_to_vec_checked(s, obj)?;
+        s.push(obj)?;
     }
-    push_to_vec_checked(s, objs.len().into())?;
+    s.push(objs.len().into())?;
     Ok(())
 }
 
@@ -64,54 +190,21 @@ pub fn close_stack_frame_and_return(ret_val: Object) {
         for _ in 0..n_args {
             s.pop().unwrap();
         }
-        s.push(ret_val);
+        // Popping the frame made room for this.
+        s.push(ret_val).unwrap();
     })
 }
 
 pub fn with_stack<F, R>(fun: F) -> R
 where
-    F: FnOnce(&mut Vec<Object>) -> R,
+    F: FnOnce(&mut Stack) -> R,
 {
-    let k = STACK_KEY.with(|k| *k);
-    {
-        if let Some(m) = STACKS.read().unwrap().get(&k) {
-            return fun(m.lock().unwrap().borrow_mut());
-        }
-    }
-    {
-        STACKS
-            .write()
-            .unwrap()
-            .insert(k, Mutex::new(Vec::with_capacity(STACK_CAPACITY)));
-    }
-    if let Some(m) = STACKS.read().unwrap().get(&k) {
-        fun(m.lock().unwrap().borrow_mut())
-    } else {
-        unreachable!()
-    }
+    STACK.with(|stack| fun(&mut stack.0.lock().unwrap()))
 }
 
-/// Returns a `Reference` pointing to the current top element of the
-/// `STACK`. This is useful when creating local bindings - push a
-/// value and immediately reference it.
-///
-/// Future improvement: A single method which combines `push` and
-/// `ref_top` with only one call to `with_stack`
-pub fn ref_top() -> Reference {
-    with_stack(|stack| {
-        if stack.is_empty() {
-            panic!("Attempt to reference an empty stack");
-        }
-        let i = stack.len() - 1;
-        (&mut stack[i]).into()
-    })
-}
-
-/// BUG: The `STACK` is thread local, but garbage collection is done
-/// globally. This means that the garbage collector cannot mark other
-/// threads' stacks and may deallocate them prematurely.
-pub fn gc_mark_stack(m: usize) {
-    for stack in STACKS.read().unwrap().values() {
+/// Mark every thread's stack. Called by `gc::roots::gc_mark_roots`.
+pub fn gc_mark_stack(m: bool) {
+    for stack in STACKS.read().unwrap().iter() {
         for obj in stack.lock().unwrap().iter() {
             obj.gc_mark(m)
         }
@@ -132,35 +225,38 @@ pub struct StackOverflowError {
 #[fail(display = "Attempt to pop off an empty stack.")]
 pub struct StackUnderflowError {}
 
-/// Attempts to push to a vector, returning the index of the newly
-/// pushed element if successful
-pub fn push_to_vec_checked<T>(v: &mut Vec<T>, i: T) -> Result<usize, StackOverflowError> {
-    let len = v.len();
-    let cap = v.capacity();
-    if len == cap {
-        Err(StackOverflowError {
-            stack_size: len,
-            stack_capacity: cap,
-        })
-    } else {
-        v.push(i);
-        Ok(len)
-    }
-}
-
-/// It's bad if the stack gets realloc'd - all our outstanding
-/// `Reference`s to the stack are suddenly invalid - so this method
-/// checks that a `push` will not realloc and returns an error if it
-/// will.
-pub fn push(obj: Object) -> Result<Reference, StackOverflowError> {
+/// Push `obj` and return a `Reference` to its slot, which stays
+/// valid until it's popped. This is how local bindings are made.
+pub fn push_ref(obj: Object) -> Result<Reference, StackOverflowError> {
     with_stack(|stack| {
-        let idx = push_to_vec_checked(stack, obj)?;
+        let idx = stack.push(obj)?;
         Ok(Reference::from(stack.index_mut(idx)))
     })
 }
 
-/// This method maps the `None` case of `Vec.pop`, which represents an
-/// empty `Vec`, to an error - trying to `pop` off an empty stack is a
+/// Push each of `objs`, like `push_ref`, returning `Reference`s to
+/// their slots in the same order. If there isn't room for all of
+/// them, none are pushed.
+pub fn push_frame(objs: &[Object]) -> Result<Vec<Reference>, StackOverflowError> {
+    with_stack(|stack| {
+        if stack.len() + objs.len() > MAX_STACK_SIZE {
+            return Err(StackOverflowError {
+                stack_size: stack.len(),
+                stack_capacity: MAX_STACK_SIZE,
+            });
+        }
+        Ok(objs
+            .iter()
+            .map(|&obj| {
+                let idx = stack.push(obj).unwrap();
+                Reference::from(stack.index_mut(idx))
+            })
+            .collect())
+    })
+}
+
+/// This method maps the `None` case of `Stack::pop`, which represents
+/// an empty stack, to an error - trying to `pop` off an empty stack is a
 /// serious problem.
 pub fn pop() -> Result<Object, StackUnderflowError> {
     with_stack(|s| s.pop().ok_or(StackUnderflowError {}))
@@ -176,3 +272,20 @@ pub fn end_stack_frame(length: usize) -> Result<(), StackUnderflowError> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn push_frame_is_all_or_nothing() {
+        let depth = with_stack(|s| s.len());
+        let objs = [Object::from(1), Object::from(2), Object::from(3)];
+        let refs = push_frame(&objs).unwrap();
+        assert_eq!(refs.iter().map(|&r| *r).collect::<Vec<_>>(), objs);
+        end_stack_frame(objs.len()).unwrap();
+
+        let too_many = vec![Object::nil(); MAX_STACK_SIZE];
+        assert!(push_frame(&too_many).is_err());
+        assert_eq!(with_stack(|s| s.len()), depth);
+    }
+}
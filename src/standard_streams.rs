@@ -0,0 +1,131 @@
+//! Phoebe code never writes directly to the process's `stdout` or
+//! `stderr`. Instead, each thread has a *standard output* and a
+//! *standard error*, which are installed by `with_standard_streams`
+//! for the duration of a read-eval-print loop. Builtins which produce
+//! output write to them through `with_standard_output` and
+//! `with_standard_error`.
+//!
+//! Likewise, builtins which consume input read from the *standard
+//! input* through `with_standard_input`. The read-eval-print loop
+//! installs either its own input or the `Interpreter`'s configured
+//! input as the standard input with `with_standard_input_from`.
+//!
+//! Outside of `with_standard_streams` (for example, when a builtin is
+//! called directly from Rust), the standard streams fall back to the
+//! process's `stdin`, `stdout` and `stderr`.
+
+use crate::gc::safepoint;
+use crate::printer::ColumnTracker;
+use std::cell::{Cell, RefCell};
+use std::io::{self, Read, Write};
+use std::mem;
+
+type OutputSink = *mut ColumnTracker<Write + 'static>;
+type ErrorSink = *mut (Write + 'static);
+type InputSource = *mut (Iterator<Item = u8> + 'static);
+
+thread_local! {
+    static STANDARD_OUTPUT: Cell<Option<OutputSink>> = { Cell::new(None) };
+    static STANDARD_ERROR: Cell<Option<ErrorSink>> = { Cell::new(None) };
+    static STANDARD_INPUT: Cell<Option<InputSource>> = { Cell::new(None) };
+    static FALLBACK_OUTPUT: RefCell<ColumnTracker<io::Stdout>> = {
+        RefCell::new(ColumnTracker::new(io::stdout()))
+    };
+    static FALLBACK_INPUT: RefCell<Box<Iterator<Item = u8>>> = {
+        RefCell::new(Box::new(io::stdin().bytes().map(Result::unwrap)))
+    };
+}
+
+/// Restores the previously installed streams when dropped, so that
+/// the streams are reset even if evaluation panics.
+struct RestoreStreams {
+    output: Option<OutputSink>,
+    error: Option<ErrorSink>,
+}
+
+impl Drop for RestoreStreams {
+    fn drop(&mut self) {
+        STANDARD_OUTPUT.with(|o| o.set(self.output));
+        STANDARD_ERROR.with(|e| e.set(self.error));
+    }
+}
+
+/// Restores the previously installed standard input when dropped.
+struct RestoreInput(Option<InputSource>);
+
+impl Drop for RestoreInput {
+    fn drop(&mut self) {
+        STANDARD_INPUT.with(|i| i.set(self.0));
+    }
+}
+
+/// Executes `fun` with `output` and `error` installed as this
+/// thread's standard output and standard error, restoring the
+/// previous streams when finished.
+pub fn with_standard_streams<'a, F, T>(
+    output: &'a mut ColumnTracker<Write + 'a>,
+    error: &'a mut (Write + 'a),
+    fun: F,
+) -> T
+where
+    F: FnOnce() -> T,
+{
+    // These pointers must not outlive `output` and `error`, which is
+    // garunteed by `_restore` uninstalling them before we return.
+    let output = unsafe { mem::transmute::<&'a mut ColumnTracker<Write + 'a>, OutputSink>(output) };
+    let error = unsafe { mem::transmute::<&'a mut (Write + 'a), ErrorSink>(error) };
+    let _restore = RestoreStreams {
+        output: STANDARD_OUTPUT.with(|o| o.replace(Some(output))),
+        error: STANDARD_ERROR.with(|e| e.replace(Some(error))),
+    };
+    fun()
+}
+
+/// Calls `fun` on this thread's standard output.
+pub fn with_standard_output<F, T>(fun: F) -> T
+where
+    F: FnOnce(&mut ColumnTracker<Write>) -> T,
+{
+    match STANDARD_OUTPUT.with(Cell::get) {
+        Some(sink) => fun(unsafe { &mut *sink }),
+        None => FALLBACK_OUTPUT.with(|o| fun(&mut *o.borrow_mut())),
+    }
+}
+
+/// Calls `fun` on this thread's standard error.
+pub fn with_standard_error<F, T>(fun: F) -> T
+where
+    F: FnOnce(&mut Write) -> T,
+{
+    match STANDARD_ERROR.with(Cell::get) {
+        Some(sink) => fun(unsafe { &mut *sink }),
+        None => fun(&mut io::stderr()),
+    }
+}
+
+/// Executes `fun` with `input` installed as this thread's standard
+/// input, restoring the previous standard input when finished.
+pub fn with_standard_input_from<'a, F, T>(input: &'a mut (Iterator<Item = u8> + 'a), fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    // As in `with_standard_streams`, `_restore` uninstalls this
+    // pointer before `input`'s borrow ends.
+    let input = unsafe { mem::transmute::<&'a mut (Iterator<Item = u8> + 'a), InputSource>(input) };
+    let _restore = RestoreInput(STANDARD_INPUT.with(|i| i.replace(Some(input))));
+    fun()
+}
+
+/// Calls `fun` on this thread's standard input. Reading from it may
+/// wait indefinitely for the user to type something, so `fun` is
+/// called inside `safepoint::blocking`, lest the collector wait for
+/// this thread in the meantime.
+pub fn with_standard_input<F, T>(fun: F) -> T
+where
+    F: FnOnce(&mut Iterator<Item = u8>) -> T,
+{
+    safepoint::blocking(|| match STANDARD_INPUT.with(Cell::get) {
+        Some(source) => fun(unsafe { &mut *source }),
+        None => FALLBACK_INPUT.with(|i| fun(&mut *i.borrow_mut())),
+    })
+}
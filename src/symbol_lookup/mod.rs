@@ -2,31 +2,33 @@
 //! `Symbol`s and converting those references into `Reference`s to
 //! `Object`s.
 
+use crate::gc::roots::{self, with_thread_roots};
+use crate::gc::Rooted;
+use crate::lisp2::lisp2;
 use crate::prelude::*;
+use crate::stack::StackUnderflowError;
 
 use std::collections::HashMap;
-use std::{cell, sync};
+use std::sync;
 
 static GLOBAL_NAMESPACE_NAME: &[u8] = b"global-namespace";
 
 lazy_static! {
-    /// Because `Namespace`s can be used by several threads at once,
-    /// and the garbage collector cannot see the contents of any
-    /// threads' `ENV_STACK`, we count references to `Namespace`s in
-    /// use. Each time a new stack frame is added, a call to
-    /// `add_ref_to` accompanies it, and when the stack frame ends,
-    /// there is a call to `remove_ref_to`. The garbage collector
-    /// iterates through `ENV_REF_COUNTS`' `keys` to mark all used
-    /// `Namespace`s.
-    pub static ref ENV_REF_COUNTS: sync::Mutex<HashMap<GcRef<Namespace>, usize>> =
-    { sync::Mutex::new(HashMap::new()) };
-
     /// The `SYMBOLS_HEAP` holds references to `Symbol`s in
     /// memory. Instead of directly calling
-    /// `GarbageCollected::allocate`, `Symbol`s are constructed in the
-    /// reader by `make_symbol`.
+    /// `GarbageCollected::allocate`, `Symbol`s are constructed by
+    /// `intern` or `make_symbol`.
+    ///
+    /// The table doesn't keep its symbols alive, apart from pinned
+    /// ones; each garbage collection drops the entries of any others
+    /// which nothing else refers to.
     pub static ref SYMBOLS_HEAP: sync::Mutex<HashMap<Vec<u8>, GcRef<Symbol>>> =
         { sync::Mutex::new(HashMap::new()) };
+    /// While the garbage collector is marking, `Some` of every symbol
+    /// which `intern` has returned, since those may not be reachable
+    /// from anything the collector has already marked.
+    static ref INTERNED_WHILE_MARKING: sync::Mutex<Option<Vec<GcRef<Symbol>>>> =
+        { sync::Mutex::new(None) };
     static ref DEFAULT_GLOBAL_ENV: GcRef<Namespace> = {
         Namespace::allocate(
             Namespace::default().with_name(Object::from(make_symbol(GLOBAL_NAMESPACE_NAME))),
@@ -34,81 +36,85 @@ lazy_static! {
     };
 }
 
-thread_local! {
-    /// Each thread has an `ENV_STACK`, a stack of `Namespace`s. Each
-    /// `Namespace` corresponds to either a function's stack frame or
-    /// a `let` environment.
-    static ENV_STACK: cell::RefCell<Vec<GcRef<Namespace>>> = {
-        let g_e = default_global_env();
-        add_ref_to(g_e);
-        cell::RefCell::new(vec![g_e])
-    };
-}
-
 #[derive(Fail, Debug)]
 #[fail(display = "The symbol {} is unbound.", sym)]
 pub struct UnboundSymbolError {
     pub sym: GcRef<Symbol>,
 }
 
-/// See `ENV_REF_COUNTS` for documentation.
-fn add_ref_to(n: GcRef<Namespace>) {
-    ENV_REF_COUNTS
-        .lock()
-        .unwrap()
-        .entry(n)
-        .and_modify(|n| *n += 1)
-        .or_insert(1);
-}
-
-/// See `ENV_REF_COUNTS` for documentation.
-fn remove_ref_to(n: GcRef<Namespace>) {
-    let mut ref_counts = ENV_REF_COUNTS.lock().unwrap();
-    let should_remove = {
-        let n_refs = ref_counts.get_mut(&n).unwrap();
-        debug_assert!(*n_refs > 0);
-        *n_refs -= 1;
-        *n_refs == 0
-    };
-    if should_remove {
-        let _remove_res = ref_counts.remove(&n);
-        debug_assert!(_remove_res == Some(0));
-    }
-}
-
 pub fn default_global_env() -> GcRef<Namespace> {
     *DEFAULT_GLOBAL_ENV
 }
 
 pub fn set_global_env(env: GcRef<Namespace>) {
-    ENV_STACK.with(|s| {
-        let stack: &mut Vec<GcRef<Namespace>> = &mut s.borrow_mut();
-        stack[0] = env;
-    })
+    with_thread_roots(|r| r.env_stack[0] = env)
 }
 
 pub fn current_env() -> GcRef<Namespace> {
-    ENV_STACK.with(|s| {
-        let stack: &Vec<GcRef<Namespace>> = &s.borrow();
-        stack[stack.len() - 1]
-    })
+    with_thread_roots(|r| r.env_stack[r.env_stack.len() - 1])
 }
 
 pub fn global_env() -> GcRef<Namespace> {
-    ENV_STACK.with(|s| {
-        let stack: &Vec<GcRef<Namespace>> = &s.borrow();
-        stack[0]
-    })
+    with_thread_roots(|r| r.env_stack[0])
 }
 
-/// Adds a `(SYMBOL VALUE)` pair to the global env.
-pub fn add_to_global(sym: GcRef<Symbol>, obj: Object) {
-    *(make_from_default_global_namespace(sym)) = obj;
+/// A copy of a global namespace's bindings, made by
+/// `snapshot_global_env`, which can later undo changes to them. Like
+/// a `Rooted`, it belongs to the thread which made it.
+pub struct Snapshot {
+    env: Rooted<GcRef<Namespace>>,
+    copy: Rooted<GcRef<Namespace>>,
+}
+
+impl Snapshot {
+    /// Return the namespace this snapshot was taken of to the state it
+    /// was in then. See `Namespace::restore_bindings`.
+    pub fn restore(&self) {
+        self.env.restore_bindings(&self.copy);
+    }
 }
 
-pub fn gc_mark_scope(m: usize) {
-    for env in ENV_REF_COUNTS.lock().unwrap().keys() {
-        env.gc_mark(m);
+/// Capture the bindings of `global_env()`, so that definitions and
+/// assignments made since can later be undone with
+/// `Snapshot::restore`. Only the bindings are copied: changes made
+/// inside their values, like to a hash table's entries, are not
+/// undone.
+pub fn snapshot_global_env() -> Snapshot {
+    let env = Rooted::new(global_env());
+    let copy = Rooted::new(Namespace::allocate(env.copy_bindings()));
+    Snapshot { env, copy }
+}
+
+/// Executes `fun` with a copy of `global_env()`'s bindings as this
+/// thread's global namespace, restoring the previous one when
+/// finished, so that untrusted code run by `fun` can't define or
+/// assign to globals which outlive it. As with `snapshot_global_env`,
+/// values themselves are shared with the previous global namespace,
+/// as are its parents, and globals bound by `defglobal` in
+/// `default_global_env` are still shared with other threads.
+pub fn with_fresh_global_env<F, T>(fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Restore(Rooted<GcRef<Namespace>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_global_env(*self.0);
+        }
+    }
+    let _restore = Restore(Rooted::new(global_env()));
+    let fresh = Namespace::allocate(global_env().copy_bindings());
+    set_global_env(fresh);
+    fun()
+}
+
+/// Adds a `(SYMBOL VALUE)` pair to the global env. If `obj` is a
+/// function, it's also put in `sym`'s function cell, so that it can
+/// be called in Lisp-2 mode.
+pub fn add_to_global(sym: GcRef<Symbol>, obj: Object) {
+    make_from_default_global_namespace(sym).set(obj);
+    if <GcRef<Function>>::is_type(obj) {
+        default_global_env().make_function_ref(sym).set(obj);
     }
 }
 
@@ -116,7 +122,7 @@ pub fn with_global_env<F>(env: GcRef<Namespace>, fun: F) -> Object
 where
     F: FnOnce() -> Object,
 {
-    stack::push(Object::from(global_env()))?;
+    stack::push_ref(Object::from(global_env()))?;
     set_global_env(env);
     let res = fun();
     set_global_env(unsafe { stack::pop()?.into_unchecked() });
@@ -130,23 +136,14 @@ where
     F: FnOnce() -> T,
     T: Sized,
 {
-    {
-        add_ref_to(env);
-        ENV_STACK.with(|s| {
-            s.borrow_mut().push(env);
-        })
-    }
+    with_thread_roots(|r| r.env_stack.push(env));
     let res = fun();
-    {
-        ENV_STACK.with(|s| {
-            let mut stack = s.borrow_mut();
-            let _pop = stack.pop();
-            debug_assert!(_pop.is_some());
-            debug_assert!(_pop.unwrap() == env);
-            debug_assert!(!stack.is_empty());
-        });
-        remove_ref_to(env);
-    }
+    with_thread_roots(|r| {
+        let _pop = r.env_stack.pop();
+        debug_assert!(_pop.is_some());
+        debug_assert!(_pop.unwrap() == env);
+        debug_assert!(!r.env_stack.is_empty());
+    });
     res
 }
 
@@ -168,19 +165,25 @@ pub fn in_parent_env<F>(fun: F) -> Object
 where
     F: FnOnce() -> Object,
 {
-    stack::push(Object::from({
-        ENV_STACK.with(|s| {
-            let mut stack = s.borrow_mut();
-            debug_assert!(stack.len() > 1);
-            stack.pop().unwrap()
-        })
-    }))?;
+    // Move the env straight from the env stack to the temporaries,
+    // so that it's always rooted.
+    let env = with_thread_roots(|r| {
+        debug_assert!(r.env_stack.len() > 1);
+        let env = r.env_stack.pop().unwrap();
+        r.temporaries.push(Object::from(env));
+        env
+    });
+    let pushed = stack::push_ref(Object::from(env));
+    roots::pop_temporary_roots(1);
+    pushed?;
     let res = fun();
-    let second_res = ENV_STACK.with(|s| -> Result<(), EvaluatorError> {
-        s.borrow_mut()
-            .push(unsafe { stack::pop()?.into_unchecked() });
+    let second_res = (|| -> Result<(), EvaluatorError> {
+        // Likewise, put the env back before popping it off the stack.
+        let env = stack::with_stack(|s| s.last().cloned()).ok_or(StackUnderflowError {})?;
+        with_thread_roots(|r| r.env_stack.push(unsafe { env.into_unchecked() }));
+        stack::pop()?;
         Ok(())
-    });
+    })();
 
     res?;
     second_res?;
@@ -188,19 +191,77 @@ where
     res
 }
 
-/// Create a symbol by returning a pointer to an existing one with the
-/// same name or by allocating a new one if no such exists. This is
-/// the *only legal way* to create a `Symbol` or a `GcRef<Symbol>` and
-/// it garuntees that `Symbol`s with the same name will be `eq`
-/// (pointer equal).
+/// The interned symbol named `s`, which is never collected. This is
+/// for Rust code, which may hold the symbol anywhere, like in a
+/// `lazy_static`; Phoebe code should get its symbols from `intern`.
 pub fn make_symbol(s: &[u8]) -> GcRef<Symbol> {
+    intern_symbol(s, true)
+}
+
+/// The interned symbol named `s`, which is collected once nothing
+/// refers to it. Like a newly allocated object, it's a root of this
+/// thread's until the thread allocates or interns something else.
+pub fn intern(s: &[u8]) -> GcRef<Symbol> {
+    intern_symbol(s, false)
+}
+
+fn intern_symbol(s: &[u8], permanent: bool) -> GcRef<Symbol> {
     let mut sym_heap = SYMBOLS_HEAP.lock().unwrap();
     if !sym_heap.contains_key(s) {
-        let sym = Symbol::allocate(s);
+        let mut sym = Symbol::allocate(s);
+        if Symbol::names_keyword(s) {
+            sym.make_keyword();
+        }
         let _insert_ref = sym_heap.insert(s.to_owned(), sym);
         debug_assert!(_insert_ref.is_none());
     }
-    *(sym_heap.get(s).unwrap())
+    let mut sym = *(sym_heap.get(s).unwrap());
+    if permanent {
+        sym.make_permanent();
+    }
+    roots::record_allocation(Object::from(sym));
+    if let Some(ref mut interned) = *INTERNED_WHILE_MARKING.lock().unwrap() {
+        interned.push(sym);
+    }
+    sym
+}
+
+/// Called by the garbage collector as it begins marking, so that
+/// `gc_mark_symbols` will mark every symbol interned from then on.
+pub(crate) fn watch_interning() {
+    *INTERNED_WHILE_MARKING.lock().unwrap() = Some(Vec::new());
+}
+
+/// Mark the symbols which `symbols`, the locked `SYMBOLS_HEAP`, keeps
+/// alive: the pinned ones, and those interned since
+/// `watch_interning`.
+pub(crate) fn gc_mark_symbols(m: bool, symbols: &HashMap<Vec<u8>, GcRef<Symbol>>) {
+    for &s in symbols.values() {
+        if s.is_pinned() {
+            s.gc_mark(m);
+        }
+    }
+    if let Some(ref interned) = *INTERNED_WHILE_MARKING.lock().unwrap() {
+        for &s in interned {
+            s.gc_mark(m);
+        }
+    }
+}
+
+/// Drop the entries of every symbol in `symbols`, the locked
+/// `SYMBOLS_HEAP`, which is about to be deallocated, and stop
+/// watching for newly interned symbols. No symbol can be interned
+/// while `symbols` is locked, so any unmarked ones are unreachable.
+pub(crate) fn forget_unmarked_symbols(m: bool, symbols: &mut HashMap<Vec<u8>, GcRef<Symbol>>) {
+    *INTERNED_WHILE_MARKING.lock().unwrap() = None;
+    symbols.retain(|_, s| !s.should_dealloc(m));
+}
+
+/// Allocates a fresh symbol named `s` without entering it into
+/// `SYMBOLS_HEAP`, so it is distinct from every other symbol,
+/// including the one `make_symbol(s)` would return.
+pub fn make_uninterned_symbol(s: &[u8]) -> GcRef<Symbol> {
+    Symbol::allocate(s)
 }
 
 /// This method is called by `Symbol::evaluate`. It searches the
@@ -232,14 +293,80 @@ pub fn make_from_default_global_namespace(sym: GcRef<Symbol>) -> Reference {
     default_global_env().make_sym_ref(sym)
 }
 
+/// The function `sym` names at the head of a form in the current
+/// lexical environment: its function cell in Lisp-2 mode, or else its
+/// value. See the `lisp2` module.
+pub fn lookup_function(sym: GcRef<Symbol>) -> Result<Reference, EvaluatorError> {
+    if lisp2() {
+        current_env()
+            .get_function_ref(sym)
+            .ok_or(EvaluatorError::UndefinedFunction { name: sym })
+    } else {
+        Ok(lookup_symbol(sym)?)
+    }
+}
+
+/// Like `get_from_global_namespace`, but for the function `sym`
+/// names, as `lookup_function`.
+pub fn get_function_from_global_namespace(sym: GcRef<Symbol>) -> Option<Reference> {
+    if lisp2() {
+        global_env().get_function_ref(sym)
+    } else {
+        get_from_global_namespace(sym)
+    }
+}
+
+/// Like `make_from_global_namespace`, but for the function `sym`
+/// names, as `lookup_function`. `defun` and its relatives define
+/// functions through this.
+pub fn make_function_from_global_namespace(sym: GcRef<Symbol>) -> Reference {
+    if lisp2() {
+        global_env().make_function_ref(sym)
+    } else {
+        make_from_global_namespace(sym)
+    }
+}
+
+/// Bind `sym` to `value` in `default_global_env`, which every thread
+/// shares whichever namespace is its global one, unless it's already
+/// bound there, returning the global's value. The check and the
+/// binding are made under the namespace's lock, so if several threads
+/// define the same global at once, exactly one of their values is
+/// used, and each of them returns it.
+pub fn define_global(sym: GcRef<Symbol>, value: Object) -> Object {
+    default_global_env().update(|b| match b.get(sym) {
+        Some(existing) if existing.definedp() => existing,
+        _ => {
+            b.set(sym, value);
+            value
+        }
+    })
+}
+
+/// If `sym`'s binding in `default_global_env` is `eq` to `old`,
+/// replace it with `new` and return `true`; otherwise return `false`.
+/// The comparison and the store are made under the namespace's lock,
+/// so of several threads swapping the same value at once, only one
+/// succeeds. Plain `setf`s don't take the lock, so globals which
+/// threads share should only be changed this way.
+pub fn compare_and_swap_global(sym: GcRef<Symbol>, old: Object, new: Object) -> bool {
+    default_global_env().update(|b| {
+        if b.get(sym) == Some(old) {
+            b.set(sym, new);
+            true
+        } else {
+            false
+        }
+    })
+}
+
 /// The correct scope for a newly defined function is one step behind
 /// the current scope - the current scope is either `lambda` or
 /// `defun`'s scope.
 pub fn scope_for_a_new_function() -> GcRef<Namespace> {
-    ENV_STACK.with(|s| {
-        let scope = s.borrow();
-        debug_assert!(scope.len() > 1);
-        scope[scope.len() - 2]
+    with_thread_roots(|r| {
+        debug_assert!(r.env_stack.len() > 1);
+        r.env_stack[r.env_stack.len() - 2]
     })
 }
 
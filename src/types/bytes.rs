@@ -0,0 +1,124 @@
+//! Byte vectors are growable arrays of `u8`s, stored unboxed, for
+//! binary data like the contents of files. They print as
+//! `[bytes 01 ff]`, in hexadecimal.
+
+use super::pointer_tagging::{self, ExtendedHeader, ExtendedTag, ObjectTag, PointerTag};
+use crate::prelude::*;
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref BYTES_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"bytes") };
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Bytes {
+    header: ExtendedHeader,
+    gc_marking: GcMark,
+    bytes: Vec<u8>,
+}
+
+impl Bytes {
+    pub fn new(bytes: Vec<u8>) -> Bytes {
+        Bytes {
+            header: ExtendedHeader::new(ExtendedTag::Bytes),
+            gc_marking: GcMark::default(),
+            bytes,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+    pub fn get(&self, index: usize) -> Result<u8, EvaluatorError> {
+        let length = self.len();
+        self.bytes
+            .get(index)
+            .cloned()
+            .ok_or(EvaluatorError::IndexOutOfBounds { index, length })
+    }
+    pub fn set(&mut self, index: usize, byte: u8) -> Result<(), EvaluatorError> {
+        let length = self.len();
+        let place = self
+            .bytes
+            .get_mut(index)
+            .ok_or(EvaluatorError::IndexOutOfBounds { index, length })?;
+        *place = byte;
+        Ok(())
+    }
+}
+
+impl GarbageCollected for Bytes {
+    type ConvertFrom = Bytes;
+    fn alloc_one_and_initialize(b: Bytes) -> ::std::ptr::NonNull<Bytes> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, b) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, _: bool) {}
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[bytes")?;
+        for byte in &self.bytes {
+            write!(f, " {:02x}", byte)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl convert::From<GcRef<Bytes>> for Object {
+    fn from(b: GcRef<Bytes>) -> Object {
+        Object::from_raw(ObjectTag::Extended.tag(b.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<Bytes> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Extended
+    }
+    fn is_type(obj: Object) -> bool {
+        unsafe { pointer_tagging::extended_tag_of(obj.0) == Some(ExtendedTag::Bytes) }
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *BYTES_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<Bytes> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<Bytes> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut Bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let mut x = Bytes::new(vec![1, 2]);
+            let nonnull = &mut x as *mut Bytes;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert!(<GcRef<Bytes>>::is_type(obj));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
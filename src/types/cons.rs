@@ -1,6 +1,8 @@
+use crate::gc::write_barrier;
+use crate::lisp2::lisp2;
 use crate::prelude::*;
 use crate::types::pointer_tagging::{ObjectTag, PointerTag};
-use std::{cmp, convert, fmt};
+use std::{cmp, convert, fmt, mem};
 
 lazy_static! {
     static ref CONS_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"cons") };
@@ -44,6 +46,21 @@ impl Cons {
     pub fn ref_cdr(&mut self) -> Reference {
         Reference::from(&mut self.cdr)
     }
+    /// Replace the car, returning the old one. A cons which may
+    /// already be reachable should be changed through `set_car` and
+    /// `set_cdr`, rather than by assigning to its fields, so the
+    /// collector can't miss the old values; see `gc::write_barrier`.
+    pub fn set_car(&mut self, car: Object) -> Object {
+        let old = mem::replace(&mut self.car, car);
+        write_barrier(old);
+        old
+    }
+    /// Replace the cdr, returning the old one.
+    pub fn set_cdr(&mut self, cdr: Object) -> Object {
+        let old = mem::replace(&mut self.cdr, cdr);
+        write_barrier(old);
+        old
+    }
 }
 
 impl Evaluate for Cons {
@@ -51,7 +68,13 @@ impl Evaluate for Cons {
         let mut l =
             List::try_convert_from(unsafe { GcRef::from_ptr(self as *const Cons as *mut Cons) })?;
         let f = l.next().unwrap();
-        let func = <GcRef<Function>>::try_convert_from(f.evaluate()?)?;
+        // In Lisp-2 mode, a symbol at the head of a form names a
+        // function cell rather than a variable.
+        let f = match <GcRef<Symbol>>::maybe_from(f) {
+            Some(sym) if lisp2() => *symbol_lookup::lookup_function(sym)?,
+            None => f.evaluate()?,
+        };
+        let func = <GcRef<Function>>::try_convert_from(f)?;
         func.call(l)
     }
 }
@@ -114,7 +137,7 @@ impl GarbageCollected for Cons {
     fn my_marking(&self) -> &GcMark {
         &self.gc_marking
     }
-    fn gc_mark_children(&mut self, mark: usize) {
+    fn gc_mark_children(&mut self, mark: bool) {
         self.car.gc_mark(mark);
         self.cdr.gc_mark(mark);
     }
@@ -18,6 +18,9 @@ lazy_static! {
     static ref UNSIGNED_INTEGER_TYPE_NAME: GcRef<Symbol> = {
         symbol_lookup::make_symbol(b"unsigned-integer")
     };
+    static ref CHARACTER_TYPE_NAME: GcRef<Symbol> = {
+        symbol_lookup::make_symbol(b"character")
+    };
 }
 
 #[derive(Fail, Debug)]
@@ -272,3 +275,21 @@ impl FromObject for i32 {
         *INTEGER_TYPE_NAME
     }
 }
+
+impl FromUnchecked<Object> for char {
+    unsafe fn from_unchecked(obj: Object) -> char {
+        use crate::types::immediate::ImmediateTag;
+
+        ::std::char::from_u32_unchecked(ImmediateTag::Character.untag(obj.0) as u32)
+    }
+}
+
+impl FromObject for char {
+    type Tag = super::immediate::ImmediateTag;
+    fn associated_tag() -> super::immediate::ImmediateTag {
+        super::immediate::ImmediateTag::Character
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *CHARACTER_TYPE_NAME
+    }
+}
@@ -0,0 +1,221 @@
+//! Deep copies of `Object`s, for handing a structure to another
+//! thread without either side observing the other's mutations of it.
+//!
+//! Conses, vectors, byte vectors, hash tables, structures, heap
+//! objects and namespace bindings are copied. Strings, ratios and
+//! errors are immutable, and are shared. Symbols are shared too, so
+//! that a copied symbol is still `eq` to the original, but that means
+//! their plists, documentation and source locations are shared, and
+//! those are mutable. Functions are also shared, since copying a
+//! closure would mean copying its environment, and that
+//! environment's parents, up to the global namespace; a closure and
+//! its copy therefore see each other's assignments to the variables
+//! they close over. Likewise a copied namespace shares its parent.
+//! Weak references are shared, since a copy of their target would be
+//! kept alive by nothing, as are native objects like streams, whose
+//! Rust state can't be copied in general.
+//!
+//! A copy thus shares no mutable state with the original unless it
+//! reaches a symbol's properties, a function, a namespace's parent
+//! or a native object.
+
+use crate::gc::roots;
+use crate::interpreter::Interpreter;
+use crate::prelude::*;
+use crate::types::ExpandedObject;
+use std::collections::HashMap;
+
+/// A copied object whose contents still refer to the original's.
+enum Fixup {
+    Cons(GcRef<Cons>),
+    HeapObject(GcRef<HeapObject>),
+    Vector(GcRef<Vector>),
+    HashTable(GcRef<HashTable>),
+    Structure(GcRef<Structure>),
+    Namespace(GcRef<Namespace>),
+}
+
+#[derive(Default)]
+struct Copier {
+    /// Maps each object already copied to its copy, so that shared
+    /// and circular structure stays shared and circular.
+    copies: HashMap<Object, Object>,
+    fixups: Vec<Fixup>,
+    /// Until the copy is finished, most of the copies are held only
+    /// by `copies`, so each is registered as a temporary root. This
+    /// counts them.
+    n_rooted: usize,
+}
+
+impl Copier {
+    /// Copy `obj` without copying its contents, and remember to fix
+    /// them up later. This keeps `deep_copy` from recursing.
+    fn copy_shallow(&mut self, obj: Object) -> Object {
+        if let Some(&copy) = self.copies.get(&obj) {
+            return copy;
+        }
+        let (copy, fixup) = match obj.expand_quiet() {
+            // A reference may point into another thread's stack, so
+            // copy the value it points to instead.
+            ExpandedObject::Reference(r) => return self.copy_shallow(*r),
+            ExpandedObject::Cons(c) => {
+                let copy = Cons::allocate(Cons::new(c.car, c.cdr));
+                (Object::from(copy), Fixup::Cons(copy))
+            }
+            ExpandedObject::HeapObject(h) => {
+                let copy = HeapObject::allocate(HeapObject::around(**h));
+                (Object::from(copy), Fixup::HeapObject(copy))
+            }
+            ExpandedObject::Vector(v) => {
+                let copy = Vector::allocate(Vector::new(v.elements().to_vec()));
+                (Object::from(copy), Fixup::Vector(copy))
+            }
+            ExpandedObject::HashTable(h) => {
+                let mut copy = HashTable::new(h.test());
+                for (key, value) in h.pairs() {
+                    copy.insert(key, value);
+                }
+                let copy = HashTable::allocate(copy);
+                (Object::from(copy), Fixup::HashTable(copy))
+            }
+            ExpandedObject::Bytes(b) => {
+                // Bytes refer to no other objects, so need no fixup.
+                let copy = Object::from(Bytes::allocate(Bytes::new(b.as_slice().to_vec())));
+                self.remember(obj, copy);
+                return copy;
+            }
+            ExpandedObject::Structure(s) => {
+                let copy = Structure::new(s.name(), s.slot_names(), s.slots().to_vec());
+                let copy = Structure::allocate(copy);
+                (Object::from(copy), Fixup::Structure(copy))
+            }
+            ExpandedObject::Namespace(n) => {
+                let mut copy: Namespace = n.bindings().into_iter().collect();
+                if let Some(parent) = n.parent() {
+                    copy = copy.with_parent(parent);
+                }
+                let copy = Namespace::allocate(copy.with_maybe_name(n.name()));
+                (Object::from(copy), Fixup::Namespace(copy))
+            }
+            _ => return obj,
+        };
+        self.remember(obj, copy);
+        self.fixups.push(fixup);
+        copy
+    }
+
+    fn remember(&mut self, obj: Object, copy: Object) {
+        roots::push_temporary_root(copy);
+        self.n_rooted += 1;
+        self.copies.insert(obj, copy);
+    }
+
+    fn copy(mut self, obj: Object) -> Object {
+        let copy = self.copy_shallow(obj);
+        while let Some(fixup) = self.fixups.pop() {
+            match fixup {
+                Fixup::Cons(mut c) => {
+                    c.car = self.copy_shallow(c.car);
+                    c.cdr = self.copy_shallow(c.cdr);
+                }
+                Fixup::HeapObject(mut h) => {
+                    **h = self.copy_shallow(**h);
+                }
+                Fixup::Vector(mut v) => {
+                    for element in v.elements_mut() {
+                        *element = self.copy_shallow(*element);
+                    }
+                }
+                Fixup::HashTable(mut h) => {
+                    // Copied keys may hash differently under `eql`,
+                    // so the table is rebuilt rather than updated in
+                    // place.
+                    let pairs = h.pairs();
+                    h.clear();
+                    for (key, value) in pairs {
+                        let key = self.copy_shallow(key);
+                        let value = self.copy_shallow(value);
+                        h.insert(key, value);
+                    }
+                }
+                Fixup::Structure(mut s) => {
+                    for slot in s.slots_mut() {
+                        *slot = self.copy_shallow(*slot);
+                    }
+                }
+                Fixup::Namespace(n) => {
+                    let bindings: Vec<_> = n
+                        .bindings()
+                        .into_iter()
+                        .map(|(sym, value)| (sym, self.copy_shallow(value)))
+                        .collect();
+                    n.update(|b| {
+                        for (sym, value) in bindings {
+                            b.set(sym, value);
+                        }
+                    });
+                }
+            }
+        }
+        roots::pop_temporary_roots(self.n_rooted);
+        copy
+    }
+}
+
+impl Object {
+    /// A copy of `self` and everything reachable from it, except for
+    /// the immutable and shared objects listed in the module
+    /// documentation. Structure which is shared or circular in
+    /// `self` is shared or circular in the copy.
+    pub fn deep_copy(self) -> Object {
+        Copier::default().copy(self)
+    }
+
+    /// An alias for `deep_copy`, which ignores `interpreter`. Every
+    /// `Interpreter` allocates from the same process-wide heap, so
+    /// there is nothing to copy into; this exists so that code which
+    /// passes objects between interpreters will keep working if they
+    /// ever get heaps of their own.
+    pub fn deep_copy_into(self, interpreter: &mut Interpreter) -> Object {
+        let _ = interpreter;
+        self.deep_copy()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gc::GarbageCollected;
+
+    #[test]
+    fn copies_share_nothing_mutable() {
+        let mut inner = Cons::allocate(Cons::new(Object::from(1i32), Object::nil()));
+        let outer = Object::from(Cons::allocate(Cons::new(
+            Object::from(inner),
+            Object::from(inner),
+        )));
+
+        let copy = outer.deep_copy();
+        assert!(copy.equal(outer));
+        assert!(copy != outer);
+
+        let copy = <GcRef<Cons>>::try_convert_from(copy).unwrap();
+        assert!(copy.car != Object::from(inner));
+        assert!(copy.car == copy.cdr);
+
+        inner.car = Object::from(2i32);
+        assert!(!Object::from(copy).equal(outer));
+    }
+
+    #[test]
+    fn circular_lists() {
+        let mut last = Cons::allocate(Cons::new(Object::from(2i32), Object::nil()));
+        let list = Cons::allocate(Cons::new(Object::from(1i32), Object::from(last)));
+        last.cdr = Object::from(list);
+
+        let copy = <GcRef<Cons>>::try_convert_from(Object::from(list).deep_copy()).unwrap();
+        let second = <GcRef<Cons>>::try_convert_from(copy.cdr).unwrap();
+        assert!(second.cdr == Object::from(copy));
+        assert!(Object::from(copy) != Object::from(list));
+    }
+}
@@ -0,0 +1,130 @@
+//! Structural equality, as in `Object::equal`, optionally within a
+//! `Budget`. Comparing attacker-supplied structures without one can
+//! take as long as the attacker likes, so embedders which evaluate
+//! untrusted code or data can limit the `equal` builtin with
+//! `Interpreter::with_equal_budget`.
+
+use crate::prelude::*;
+use crate::types::ExpandedObject;
+use std::cell::Cell;
+
+thread_local! {
+    static BUDGET: Cell<Budget> = { Cell::new(Budget::default()) };
+}
+
+/// Limits on how much work one comparison may do. The default
+/// `Budget` is unlimited.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Budget {
+    /// The most pairs of objects which may be compared.
+    pub max_comparisons: Option<usize>,
+    /// How deeply the comparison may descend into the cars of
+    /// conses. Following a cdr does not count, so long flat lists
+    /// are limited only by `max_comparisons`.
+    pub max_depth: Option<usize>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Budget {
+        Budget::default()
+    }
+    pub fn with_max_comparisons(self, max_comparisons: usize) -> Budget {
+        Budget {
+            max_comparisons: Some(max_comparisons),
+            ..self
+        }
+    }
+    pub fn with_max_depth(self, max_depth: usize) -> Budget {
+        Budget {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+}
+
+#[derive(Fail, Debug, Copy, Clone, PartialEq, Eq)]
+#[fail(display = "Comparison exceeded its budget of {:?}", budget)]
+pub struct BudgetExceeded {
+    pub budget: Budget,
+}
+
+/// Compare `lhs` and `rhs` as `Object::equal` does, giving up if the
+/// comparison would exceed `budget`.
+pub fn equal_within(lhs: Object, rhs: Object, budget: Budget) -> Result<bool, BudgetExceeded> {
+    let exceeded = || BudgetExceeded { budget };
+    let mut comparisons = 0;
+    let mut to_compare = vec![(lhs, rhs, 0)];
+    while let Some((lhs, rhs, depth)) = to_compare.pop() {
+        comparisons += 1;
+        if budget
+            .max_comparisons
+            .map_or(false, |max| comparisons > max)
+            || budget.max_depth.map_or(false, |max| depth > max)
+        {
+            return Err(exceeded());
+        }
+        match (lhs.expand_quiet(), rhs.expand_quiet()) {
+            (ExpandedObject::Reference(r), _) => to_compare.push((*r, rhs, depth)),
+            (_, ExpandedObject::Reference(r)) => to_compare.push((lhs, *r, depth)),
+            (ExpandedObject::Cons(a), ExpandedObject::Cons(b)) => {
+                // The car is compared first, so it's pushed last.
+                to_compare.push((a.cdr, b.cdr, depth));
+                to_compare.push((a.car, b.car, depth + 1));
+            }
+            (ExpandedObject::String(a), ExpandedObject::String(b)) => {
+                if *a != *b {
+                    return Ok(false);
+                }
+            }
+            (ExpandedObject::HeapObject(h), _) => to_compare.push((**h, rhs, depth)),
+            (_, ExpandedObject::HeapObject(h)) => to_compare.push((lhs, **h, depth)),
+            _ => {
+                if !lhs.eql(rhs) {
+                    return Ok(false);
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Executes `fun` with `budget` as this thread's `budget()`,
+/// restoring the previous budget when finished.
+pub fn with_budget<F, T>(budget: Budget, fun: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Restore(Budget);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BUDGET.with(|b| b.set(self.0));
+        }
+    }
+    let _restore = Restore(BUDGET.with(|b| b.replace(budget)));
+    fun()
+}
+
+/// The `Budget` which the `equal` builtin compares within on this
+/// thread.
+pub fn budget() -> Budget {
+    BUDGET.with(Cell::get)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn budgets_are_enforced() {
+        let list: List = (0..10i32).map(Object::from).collect();
+        let list = Object::from(list);
+        let budget = Budget::unlimited();
+        assert_eq!(equal_within(list, list, budget), Ok(true));
+        assert!(equal_within(list, list, budget.with_max_comparisons(5)).is_err());
+        assert_eq!(equal_within(list, list, budget.with_max_depth(1)), Ok(true));
+        assert_eq!(
+            equal_within(list, Object::from(1i32), budget.with_max_comparisons(1)),
+            Ok(false)
+        );
+    }
+}
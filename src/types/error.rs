@@ -1,9 +1,13 @@
+use crate::gc::HeapExhausted;
+use crate::load::LoadError;
 use crate::prelude::*;
+use crate::reader::ReaderError;
 use crate::stack::{ArgIndexError, StackOverflowError, StackUnderflowError};
 use crate::symbol_lookup::UnboundSymbolError;
 use crate::types::conversions::ConversionError;
+use crate::types::equality::BudgetExceeded;
 use crate::types::pointer_tagging::{ObjectTag, PointerTag};
-use std::convert;
+use std::{convert, io};
 
 lazy_static! {
     static ref ERROR_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"error") };
@@ -62,20 +66,56 @@ impl Error {
     pub fn user(name: GcRef<Symbol>, body: Object) -> GcRef<Error> {
         EvaluatorError::user(name, body).into()
     }
+    pub fn condition(condition: GcRef<Structure>) -> GcRef<Error> {
+        EvaluatorError::Condition(condition).into()
+    }
+    /// The object a user error was created with, as in
+    /// `(error name payload)`, or a condition's slots. Builtin errors
+    /// have none.
+    pub fn payload(&self) -> Option<Object> {
+        match self.error {
+            EvaluatorError::User { body, .. } => Some(body),
+            EvaluatorError::Condition(condition) => Some(Object::from(condition)),
+            _ => None,
+        }
+    }
     pub fn name(&self) -> GcRef<Symbol> {
         symbol_lookup::make_symbol(match self.error {
             EvaluatorError::StackOverflow(_) => b"stack-overflow-error",
             EvaluatorError::StackUnderflow(_) => b"stack-underflow-error",
             EvaluatorError::BadArgCount { .. } => b"arg-count-error",
+            EvaluatorError::OddArgCount { .. } => b"odd-arg-count-error",
             EvaluatorError::TypeError(_) => b"type-error",
             EvaluatorError::ImproperList => b"improper-list-error",
             EvaluatorError::CannotBeReferenced => b"not-a-reference-error",
             EvaluatorError::UnboundSymbol(_) => b"unbound-symbol-error",
             EvaluatorError::UnaccompaniedKey { .. } => b"unaccompanied-key-error",
             EvaluatorError::ArgIndex(_) => b"arg-out-of-bounds-error",
+            EvaluatorError::Io(_) => b"io-error",
+            EvaluatorError::Load(_) => b"load-error",
+            EvaluatorError::Read(_) => b"reader-error",
+            EvaluatorError::ClosedStream => b"stream-error",
+            EvaluatorError::Regex(_) => b"regex-error",
+            EvaluatorError::NoApplicableMethod { .. } => b"no-applicable-method-error",
+            EvaluatorError::NoSuchSlot { .. } => b"slot-missing-error",
+            EvaluatorError::MultipleSuperclasses { .. } => b"class-definition-error",
+            EvaluatorError::UndefinedFunction { .. } => b"undefined-function-error",
+            EvaluatorError::Interrupted => b"interrupted-error",
+            EvaluatorError::NotExported { .. } => b"not-exported-error",
+            EvaluatorError::ImportConflict { .. } => b"import-conflict-error",
+            EvaluatorError::EqualBudget(_) => b"equal-budget-error",
+            EvaluatorError::HeapExhausted(_) => b"heap-exhausted-error",
+            EvaluatorError::UnknownModule { .. } => b"unknown-module-error",
+            EvaluatorError::AssertionFailed { .. } => b"assertion-error",
+            EvaluatorError::Parse { .. } => b"parse-error",
+            EvaluatorError::IndexOutOfBounds { .. } => b"index-out-of-bounds-error",
+            EvaluatorError::InvalidUtf8 { .. } => b"encoding-error",
             EvaluatorError::User { name, .. } => {
                 return name;
             }
+            EvaluatorError::Condition(condition) => {
+                return condition.name();
+            }
         })
     }
 }
@@ -100,6 +140,11 @@ pub enum EvaluatorError {
     /// signal this error.
     BadArgCount { arglist: List, found: usize },
 
+    #[fail(display = "Expected an even number of arguments but found {}", found)]
+    /// Functions like `psetf`, which take alternating places and
+    /// values, signal this error when passed an unpaired argument.
+    OddArgCount { found: usize },
+
     #[fail(display = "{}", _0)]
     TypeError(ConversionError),
 
@@ -127,6 +172,114 @@ pub enum EvaluatorError {
 
     #[fail(display = "{}: {}", name, body)]
     User { name: GcRef<Symbol>, body: Object },
+
+    #[fail(display = "{}", _0)]
+    /// A condition of a type `define-condition` defined, which is a
+    /// structure holding the condition's slots.
+    Condition(GcRef<Structure>),
+
+    #[fail(display = "IO error {}", _0)]
+    /// Reading from or writing to a stream failed.
+    Io(io::Error),
+
+    #[fail(display = "{}", _0)]
+    /// Loading a file failed, either to open it or in one or more of
+    /// its forms.
+    Load(LoadError),
+
+    #[fail(display = "{}", _0)]
+    /// A comparison by the `equal` builtin exceeded the thread's
+    /// `equality::Budget`.
+    EqualBudget(BudgetExceeded),
+
+    #[fail(display = "{}", _0)]
+    /// More objects were live than `gc::set_heap_limit` allows, even
+    /// after a complete garbage collection.
+    HeapExhausted(HeapExhausted),
+
+    #[fail(display = "There is no module named {}", name)]
+    /// `require` was asked for a module which Phoebe can't provide.
+    UnknownModule { name: GcRef<Symbol> },
+
+    #[fail(display = "The assertion {} failed: {}", form, message)]
+    /// The unevaluated `form` of an `assert` evaluated to `nil`.
+    AssertionFailed { form: Object, message: Object },
+
+    #[fail(display = "Could not parse {} as a {}", text, wanted)]
+    /// `parse-integer` or `parse-float` was passed `text` which
+    /// isn't a number.
+    Parse { text: Object, wanted: GcRef<Symbol> },
+
+    #[fail(
+        display = "The index {} is out of bounds for a vector of length {}",
+        index, length
+    )]
+    /// `aref` or `byte-ref` was passed an index past the end of its
+    /// vector.
+    IndexOutOfBounds { index: usize, length: usize },
+
+    #[fail(display = "The bytes are not valid UTF-8 past byte {}", valid_up_to)]
+    /// `bytes-to-string` was passed bytes which don't encode a
+    /// string.
+    InvalidUtf8 { valid_up_to: usize },
+
+    #[fail(display = "{}", _0)]
+    /// `read-from-stream` found malformed input.
+    Read(ReaderError),
+
+    #[fail(display = "{}", _0)]
+    /// `regex` was passed a pattern which doesn't compile.
+    Regex(::regex::Error),
+
+    #[fail(display = "The stream is closed")]
+    /// A stream was read from or written to after `close`.
+    ClosedStream,
+
+    #[fail(
+        display = "No method of {} is applicable to the arguments {}",
+        name, args
+    )]
+    /// A generic function has no method whose specializers match the
+    /// types of its arguments.
+    NoApplicableMethod { name: GcRef<Symbol>, args: List },
+
+    #[fail(display = "{} has no slot named {}", object, slot)]
+    /// `slot-value` or `make-instance` named a slot which its object
+    /// or class doesn't have.
+    NoSuchSlot { object: Object, slot: GcRef<Symbol> },
+
+    #[fail(
+        display = "The class {} lists more than one superclass, but classes may have only one",
+        class
+    )]
+    MultipleSuperclasses { class: GcRef<Symbol> },
+
+    #[fail(display = "The function {} is undefined.", name)]
+    /// In Lisp-2 mode, the head of a form named a symbol with no
+    /// function cell.
+    UndefinedFunction { name: GcRef<Symbol> },
+
+    #[fail(display = "Evaluation was interrupted")]
+    /// The thread was interrupted, as by Ctrl-C in the REPL. See the
+    /// `interrupt` module.
+    Interrupted,
+
+    #[fail(display = "{} does not export {}", namespace, symbol)]
+    /// `import` named a symbol which its namespace hasn't `export`ed.
+    NotExported {
+        symbol: GcRef<Symbol>,
+        namespace: GcRef<Namespace>,
+    },
+
+    #[fail(
+        display = "Importing {} from {} conflicts with an existing binding",
+        symbol, namespace
+    )]
+    /// `import` would have replaced a binding of the same symbol.
+    ImportConflict {
+        symbol: GcRef<Symbol>,
+        namespace: GcRef<Namespace>,
+    },
 }
 
 impl convert::From<EvaluatorError> for Error {
@@ -242,6 +395,120 @@ impl convert::From<UnboundSymbolError> for GcRef<Error> {
     }
 }
 
+impl convert::From<io::Error> for EvaluatorError {
+    fn from(e: io::Error) -> Self {
+        EvaluatorError::Io(e)
+    }
+}
+
+impl convert::From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<io::Error> for GcRef<Error> {
+    fn from(e: io::Error) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
+impl convert::From<ReaderError> for EvaluatorError {
+    fn from(e: ReaderError) -> Self {
+        EvaluatorError::Read(e)
+    }
+}
+
+impl convert::From<ReaderError> for Error {
+    fn from(e: ReaderError) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<ReaderError> for GcRef<Error> {
+    fn from(e: ReaderError) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
+impl convert::From<::regex::Error> for EvaluatorError {
+    fn from(e: ::regex::Error) -> Self {
+        EvaluatorError::Regex(e)
+    }
+}
+
+impl convert::From<::regex::Error> for Error {
+    fn from(e: ::regex::Error) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<::regex::Error> for GcRef<Error> {
+    fn from(e: ::regex::Error) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
+impl convert::From<LoadError> for EvaluatorError {
+    fn from(e: LoadError) -> Self {
+        EvaluatorError::Load(e)
+    }
+}
+
+impl convert::From<LoadError> for Error {
+    fn from(e: LoadError) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<LoadError> for GcRef<Error> {
+    fn from(e: LoadError) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
+impl convert::From<BudgetExceeded> for EvaluatorError {
+    fn from(e: BudgetExceeded) -> Self {
+        EvaluatorError::EqualBudget(e)
+    }
+}
+
+impl convert::From<BudgetExceeded> for Error {
+    fn from(e: BudgetExceeded) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<BudgetExceeded> for GcRef<Error> {
+    fn from(e: BudgetExceeded) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
+impl convert::From<HeapExhausted> for EvaluatorError {
+    fn from(e: HeapExhausted) -> Self {
+        EvaluatorError::HeapExhausted(e)
+    }
+}
+
+impl convert::From<HeapExhausted> for Error {
+    fn from(e: HeapExhausted) -> Self {
+        let e = EvaluatorError::from(e);
+        e.into()
+    }
+}
+
+impl convert::From<HeapExhausted> for GcRef<Error> {
+    fn from(e: HeapExhausted) -> Self {
+        Error::allocate(e.into())
+    }
+}
+
 impl convert::From<EvaluatorError> for GcRef<Error> {
     fn from(e: EvaluatorError) -> Self {
         Error::allocate(e)
@@ -299,7 +566,7 @@ impl GarbageCollected for Error {
     fn my_marking(&self) -> &GcMark {
         &self.gc_marking
     }
-    fn gc_mark_children(&mut self, mark: usize) {
+    fn gc_mark_children(&mut self, mark: bool) {
         match self.error {
             EvaluatorError::BadArgCount { arglist, .. } => {
                 if let Some(c) = <GcRef<Cons>>::maybe_from(arglist) {
@@ -315,7 +582,52 @@ impl GarbageCollected for Error {
                 name.gc_mark(mark);
                 body.gc_mark(mark);
             }
-            _ => (),
+            EvaluatorError::Condition(condition) => condition.gc_mark(mark),
+            EvaluatorError::AssertionFailed { form, message } => {
+                form.gc_mark(mark);
+                message.gc_mark(mark);
+            }
+            EvaluatorError::Parse { text, wanted } => {
+                text.gc_mark(mark);
+                wanted.gc_mark(mark);
+            }
+            EvaluatorError::NoApplicableMethod { name, args } => {
+                name.gc_mark(mark);
+                if let Some(c) = <GcRef<Cons>>::maybe_from(args) {
+                    c.gc_mark(mark);
+                }
+            }
+            EvaluatorError::NoSuchSlot { object, slot } => {
+                object.gc_mark(mark);
+                slot.gc_mark(mark);
+            }
+            EvaluatorError::MultipleSuperclasses { class } => class.gc_mark(mark),
+            EvaluatorError::UndefinedFunction { name } => name.gc_mark(mark),
+            EvaluatorError::UnknownModule { name } => name.gc_mark(mark),
+            EvaluatorError::NotExported { symbol, namespace }
+            | EvaluatorError::ImportConflict { symbol, namespace } => {
+                symbol.gc_mark(mark);
+                namespace.gc_mark(mark);
+            }
+            // These refer to no objects. They're listed, rather than
+            // matched by `_`, so that a new variant has to be added
+            // either here or above.
+            EvaluatorError::StackOverflow(_)
+            | EvaluatorError::StackUnderflow(_)
+            | EvaluatorError::OddArgCount { .. }
+            | EvaluatorError::ImproperList
+            | EvaluatorError::CannotBeReferenced
+            | EvaluatorError::ArgIndex(_)
+            | EvaluatorError::Io(_)
+            | EvaluatorError::Load(_)
+            | EvaluatorError::EqualBudget(_)
+            | EvaluatorError::HeapExhausted(_)
+            | EvaluatorError::IndexOutOfBounds { .. }
+            | EvaluatorError::InvalidUtf8 { .. }
+            | EvaluatorError::Read(_)
+            | EvaluatorError::Regex(_)
+            | EvaluatorError::ClosedStream
+            | EvaluatorError::Interrupted => (),
         }
     }
 }
@@ -1,8 +1,11 @@
+use crate::backtrace::CallFrame;
+use crate::gc::{roots, write_barrier};
 use crate::prelude::*;
+use crate::shadowing;
 use crate::stack::StackUnderflowError;
 use crate::types::pointer_tagging::{ObjectTag, PointerTag};
 use crate::types::ConversionError;
-use std::{collections::HashMap, convert, fmt};
+use std::{convert, fmt, ops::Try};
 
 lazy_static! {
     static ref FUNCTION_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"function") };
@@ -34,13 +37,23 @@ impl Function {
         body: List,
         env: GcRef<Namespace>,
     ) -> Result<Function, ConversionError> {
+        let stack_frame_length = Function::count_stack_frame_length(arglist)?;
+        // Parameters are checked once, when the function is made,
+        // rather than every time they're bound.
+        for arg in arglist {
+            let s: GcRef<Symbol> = arg.maybe_into().unwrap();
+            if !(s == *REST || s == *OPTIONAL || s == *KEY) {
+                shadowing::check("parameter", s, env);
+            }
+        }
         Ok(Function {
             gc_marking: GcMark::default(),
             name: None,
             arglist,
             body: FunctionBody::Source(body),
-            stack_frame_length: Function::count_stack_frame_length(arglist)?,
+            stack_frame_length,
             env,
+            documentation: None,
         })
     }
     pub fn make_special_form(
@@ -56,6 +69,7 @@ impl Function {
             body: FunctionBody::SpecialForm(body),
             stack_frame_length: Function::count_stack_frame_length(arglist)?,
             env,
+            documentation: None,
         })
     }
     pub fn make_builtin(
@@ -71,6 +85,34 @@ impl Function {
             body: FunctionBody::Builtin(body),
             stack_frame_length: Function::count_stack_frame_length(arglist)?,
             env,
+            documentation: None,
+        })
+    }
+    /// A function defined in Rust which closes over `captured`.
+    /// Each call runs `body` on `captured`, with the arguments bound
+    /// according to `arglist` as for any other builtin. Unlike the
+    /// bodies of builtins, `body` may itself capture state, but any
+    /// `Object`s it needs must be passed in `captured` so that they
+    /// are seen by the garbage collector.
+    pub fn make_closure<F>(
+        arglist: List,
+        captured: Vec<Object>,
+        body: F,
+    ) -> Result<Function, ConversionError>
+    where
+        F: Fn(&[Object]) -> Object + 'static,
+    {
+        Ok(Function {
+            gc_marking: GcMark::default(),
+            name: None,
+            arglist,
+            body: FunctionBody::Closure {
+                body: Box::new(body),
+                captured,
+            },
+            stack_frame_length: Function::count_stack_frame_length(arglist)?,
+            env: symbol_lookup::default_global_env(),
+            documentation: None,
         })
     }
     pub fn with_name(self, name: GcRef<Symbol>) -> Function {
@@ -79,17 +121,97 @@ impl Function {
             ..self
         }
     }
+    pub fn name(&self) -> Option<GcRef<Symbol>> {
+        self.name
+    }
+    pub fn with_documentation(self, documentation: Object) -> Function {
+        Function {
+            documentation: Some(documentation),
+            ..self
+        }
+    }
+    pub fn set_documentation(&mut self, documentation: Object) {
+        if let Some(old) = self.documentation.replace(documentation) {
+            write_barrier(old);
+        }
+    }
+    pub fn documentation(&self) -> Option<Object> {
+        self.documentation
+    }
+    /// The arglist this function was defined with, like
+    /// `(x &optional y)`.
+    pub fn arglist(&self) -> List {
+        self.arglist
+    }
+    /// The forms which make up this function's body, or `None` for
+    /// functions defined in Rust.
+    pub fn body(&self) -> Option<List> {
+        match self.body {
+            FunctionBody::Source(body) => Some(body),
+            FunctionBody::Builtin(_)
+            | FunctionBody::SpecialForm(_)
+            | FunctionBody::Closure { .. } => None,
+        }
+    }
+    /// The environment this function closes over.
+    pub fn env(&self) -> GcRef<Namespace> {
+        self.env
+    }
+    /// True for functions defined in Rust, including special forms.
+    pub fn is_builtin(&self) -> bool {
+        match self.body {
+            FunctionBody::Source(_) => false,
+            FunctionBody::Builtin(_)
+            | FunctionBody::SpecialForm(_)
+            | FunctionBody::Closure { .. } => true,
+        }
+    }
+    /// True for functions which receive their arguments unevaluated.
+    pub fn is_special_form(&self) -> bool {
+        match self.body {
+            FunctionBody::SpecialForm(_) => true,
+            FunctionBody::Source(_) | FunctionBody::Builtin(_) | FunctionBody::Closure { .. } => {
+                false
+            }
+        }
+    }
     pub fn call(&self, args: List) -> Object {
         let args = if self.should_evaluate_args() {
+            // Evaluating an argument may run a collection, so the
+            // arguments evaluated so far have to be rooted.
             let mut evaled_args = List::nil();
+            let mut n_rooted = 0;
+            let mut error = None;
             for a in args {
-                evaled_args = evaled_args.push(a.evaluate()?);
+                match a.evaluate().into_result() {
+                    Ok(arg) => {
+                        evaled_args = evaled_args.push(arg);
+                        roots::push_temporary_root(Object::from(evaled_args));
+                        n_rooted += 1;
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
             }
-            evaled_args.reverse()
+            let evaled_args = evaled_args.reverse();
+            roots::pop_temporary_roots(n_rooted);
+            if let Some(e) = error {
+                return Object::loud_error(e);
+            }
+            evaled_args
         } else {
             args
         };
 
+        self.apply(args)
+    }
+    /// Call this function with `args` as its arguments, without
+    /// evaluating them first, for builtins which call functions they
+    /// were passed.
+    pub fn apply(&self, args: List) -> Object {
+        let _frame = CallFrame::enter(self.name, args.count());
         let env = self.build_env(args)?;
         let res = symbol_lookup::with_env(env, || {
             let mut o = self.body.evaluate()?;
@@ -118,12 +240,12 @@ impl Function {
         }
     }
     fn build_env(&self, mut args: List) -> Result<GcRef<Namespace>, EvaluatorError> {
-        use crate::stack::{end_stack_frame, push, ref_top};
-
         let mut arg_type = ArgType::Mandatory;
         let mut n_args: usize = 0;
-        let mut stack_frame_length = 0;
-        let mut symbol_lookup_buf = Vec::new();
+        // Nothing is pushed until every argument has been matched to
+        // its parameter, so a bad call leaves the stack untouched.
+        let mut symbols = Vec::new();
+        let mut values = Vec::new();
 
         {
             let mut iter = self.arglist;
@@ -142,18 +264,12 @@ impl Function {
                 match arg_type {
                     ArgType::Mandatory => {
                         if let Some(o) = args.next() {
-                            if let Err(e) = push(o) {
-                                end_stack_frame(stack_frame_length)?;
-                                return Err(e.into());
-                            } else {
-                                n_args += 1;
-                                stack_frame_length += 1;
-                            }
+                            n_args += 1;
+                            symbols.push(arg_sym);
+                            values.push(o);
                         } else {
-                            end_stack_frame(stack_frame_length)?;
                             return Err(EvaluatorError::bad_args_count(self.arglist, n_args));
                         }
-                        symbol_lookup_buf.push((arg_sym, ref_top()));
                     }
                     ArgType::Optional => {
                         let (o, narg) = if let Some(o) = args.next() {
@@ -161,71 +277,48 @@ impl Function {
                         } else {
                             (Object::uninitialized(), 0)
                         };
-                        if let Err(e) = push(o) {
-                            end_stack_frame(stack_frame_length)?;
-                            return Err(e.into());
-                        } else {
-                            n_args += narg;
-                            stack_frame_length += 1;
-                        }
-                        symbol_lookup_buf.push((arg_sym, ref_top()));
+                        n_args += narg;
+                        symbols.push(arg_sym);
+                        values.push(o);
                     }
                     ArgType::Rest => {
-                        if let Err(e) = push(Object::from(args)) {
-                            end_stack_frame(stack_frame_length)?;
-                            return Err(e.into());
-                        } else {
-                            n_args += args.count();
-                            stack_frame_length += 1;
-                            args = List::nil();
-                        }
-                        symbol_lookup_buf.push((arg_sym, ref_top()));
+                        n_args += args.count();
+                        symbols.push(arg_sym);
+                        values.push(Object::from(args));
+                        args = List::nil();
                     }
                     ArgType::Key => {
-                        let mut pairs = HashMap::new();
-                        'keys: loop {
-                            let key = if let Some(k) = args.next() {
-                                k
-                            } else {
-                                break 'keys;
-                            };
-                            let key = match key.try_convert_into() {
-                                Ok(k) => k,
-                                Err(e) => {
-                                    end_stack_frame(stack_frame_length)?;
-                                    return Err(e.into());
-                                }
-                            };
+                        let mut pairs: Vec<(Keyword, Object)> = Vec::new();
+                        while let Some(key) = args.next() {
+                            let key = Keyword::try_convert_from(key)?;
                             let val = if let Some(v) = args.next() {
                                 v
                             } else {
-                                end_stack_frame(stack_frame_length)?;
-                                return Err(EvaluatorError::UnaccompaniedKey { key });
+                                return Err(EvaluatorError::UnaccompaniedKey { key: key.symbol() });
                             };
-                            pairs.insert(key, val);
-                        }
-                        let s = arg_sym.with_colon_in_front();
-                        let v = pairs.get(&s).cloned().unwrap_or_else(Object::uninitialized);
-                        debug!("keyword pair {} -> {}", s, v);
-                        if let Err(e) = push(v) {
-                            end_stack_frame(stack_frame_length)?;
-                            return Err(e.into());
+                            pairs.push((key, val));
                         }
-                        stack_frame_length += 1;
-                        symbol_lookup_buf.push((arg_sym, ref_top()));
+                        // If a key is supplied more than once, the
+                        // leftmost value wins.
+                        let value_of = |sym: GcRef<Symbol>| {
+                            pairs
+                                .iter()
+                                .find(|(k, _)| k.name() == (*sym).as_ref())
+                                .map(|&(_, v)| v)
+                                .unwrap_or_else(Object::uninitialized)
+                        };
+                        let v = value_of(arg_sym);
+                        debug!("keyword pair {} -> {}", arg_sym, v);
+                        symbols.push(arg_sym);
+                        values.push(v);
 
                         for sym in iter {
                             debug!("{} is in the arglist while parsing keyword args", sym);
                             let sym: GcRef<Symbol> = sym.try_convert_into().unwrap();
-                            let s: GcRef<Symbol> = sym.with_colon_in_front();
-                            let v = pairs.get(&s).cloned().unwrap_or_else(Object::uninitialized);
-                            debug!("keyword pair {} -> {}", s, v);
-                            if let Err(e) = push(v) {
-                                end_stack_frame(stack_frame_length)?;
-                                return Err(e.into());
-                            }
-                            stack_frame_length += 1;
-                            symbol_lookup_buf.push((sym, ref_top()));
+                            let v = value_of(sym);
+                            debug!("keyword pair {} -> {}", sym, v);
+                            symbols.push(sym);
+                            values.push(v);
                         }
                         break 'args;
                     }
@@ -233,6 +326,8 @@ impl Function {
             }
         }
 
+        let refs = stack::push_frame(&values)?;
+        let symbol_lookup_buf: Vec<_> = symbols.into_iter().zip(refs).collect();
         Ok(Namespace::create_stack_env(&symbol_lookup_buf, self.env))
     }
     fn end_stack_frame(&self) -> Result<(), StackUnderflowError> {
@@ -249,12 +344,17 @@ pub struct Function {
     body: FunctionBody,
     stack_frame_length: usize,
     env: GcRef<Namespace>,
+    documentation: Option<Object>,
 }
 
 enum FunctionBody {
     Source(List),
     Builtin(&'static Fn() -> Object),
     SpecialForm(&'static Fn() -> Object),
+    Closure {
+        body: Box<Fn(&[Object]) -> Object>,
+        captured: Vec<Object>,
+    },
 }
 
 impl fmt::Display for FunctionBody {
@@ -263,6 +363,7 @@ impl fmt::Display for FunctionBody {
             FunctionBody::Source(ref l) => write!(f, "{}", l),
             FunctionBody::Builtin(_) => write!(f, "COMPILED BUILTIN"),
             FunctionBody::SpecialForm(_) => write!(f, "SPECIAL FORM"),
+            FunctionBody::Closure { .. } => write!(f, "COMPILED CLOSURE"),
         }
     }
 }
@@ -278,6 +379,10 @@ impl Evaluate for FunctionBody {
                 res
             }
             FunctionBody::Builtin(b) | FunctionBody::SpecialForm(b) => b()?,
+            FunctionBody::Closure {
+                ref body,
+                ref captured,
+            } => body(captured)?,
         }
     }
 }
@@ -295,17 +400,34 @@ impl GarbageCollected for Function {
     fn my_marking(&self) -> &GcMark {
         &self.gc_marking
     }
-    fn gc_mark_children(&mut self, mark: usize) {
+    fn gc_mark_children(&mut self, mark: bool) {
         if let Some(symref) = self.name {
             symref.gc_mark(mark);
         }
         if let Some(c) = <GcRef<Cons>>::maybe_from(self.arglist) {
             c.gc_mark(mark);
         }
-        if let FunctionBody::Source(b) = self.body {
-            if let Some(c) = <GcRef<Cons>>::maybe_from(b) {
-                c.gc_mark(mark);
+        match self.body {
+            FunctionBody::Source(b) => {
+                if let Some(c) = <GcRef<Cons>>::maybe_from(b) {
+                    c.gc_mark(mark);
+                }
+            }
+            FunctionBody::Closure { ref captured, .. } => {
+                for obj in captured {
+                    obj.gc_mark(mark);
+                }
             }
+            FunctionBody::Builtin(_) | FunctionBody::SpecialForm(_) => (),
+        }
+        if let Some(doc) = self.documentation {
+            doc.gc_mark(mark);
+        }
+        // Stack environments may refer to stack frames which have
+        // since been popped, so only heap environments, like those
+        // of lambdas closed over `let` bindings, are safe to mark.
+        if !self.env.needs_clone() {
+            self.env.gc_mark(mark);
         }
     }
 }
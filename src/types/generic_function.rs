@@ -0,0 +1,110 @@
+//! Generic functions are `Native` payloads holding the methods
+//! `defmethod` has added to them. Each method specializes each of its
+//! parameters either on a type name, as returned by `type-of`, or on
+//! nothing, in which case it accepts any argument. A method which
+//! specializes none of its parameters is the generic function's
+//! fallback. Generic functions print as `[generic-function NAME]`.
+
+use super::native::Payload;
+use crate::gc::write_barrier;
+use crate::prelude::*;
+use std::fmt;
+
+lazy_static! {
+    static ref GENERIC_FUNCTION_TYPE_NAME: GcRef<Symbol> =
+        { symbol_lookup::make_symbol(b"generic-function") };
+}
+
+struct Method {
+    /// One entry per parameter; `None` accepts any type.
+    specializers: Vec<Option<GcRef<Symbol>>>,
+    function: GcRef<Function>,
+}
+
+impl Method {
+    fn is_applicable(&self, args: &[Object]) -> bool {
+        self.specializers.len() == args.len()
+            && self
+                .specializers
+                .iter()
+                .zip(args)
+                .all(|(s, arg)| s.map_or(true, |s| s == arg.type_name()))
+    }
+}
+
+pub struct GenericFunction {
+    name: GcRef<Symbol>,
+    methods: Vec<Method>,
+}
+
+impl GenericFunction {
+    pub fn new(name: GcRef<Symbol>) -> GenericFunction {
+        GenericFunction {
+            name,
+            methods: Vec::new(),
+        }
+    }
+    /// The type name of `Native`s holding a `GenericFunction`.
+    pub fn type_name() -> GcRef<Symbol> {
+        *GENERIC_FUNCTION_TYPE_NAME
+    }
+    pub fn name(&self) -> GcRef<Symbol> {
+        self.name
+    }
+    /// Add a method, replacing any existing method with the same
+    /// specializers.
+    pub fn add_method(
+        &mut self,
+        specializers: Vec<Option<GcRef<Symbol>>>,
+        function: GcRef<Function>,
+    ) {
+        self.methods.retain(|m| {
+            let keep = m.specializers != specializers;
+            if !keep {
+                write_barrier(Object::from(m.function));
+            }
+            keep
+        });
+        self.methods.push(Method {
+            specializers,
+            function,
+        });
+    }
+    /// The most specific method applicable to `args`. Of two
+    /// applicable methods, the more specific is the one which
+    /// specializes the leftmost parameter the other leaves
+    /// unspecialized.
+    pub fn dispatch(&self, args: &[Object]) -> Option<GcRef<Function>> {
+        self.methods
+            .iter()
+            .filter(|m| m.is_applicable(args))
+            .max_by_key(|m| {
+                m.specializers
+                    .iter()
+                    .map(Option::is_some)
+                    .collect::<Vec<bool>>()
+            })
+            .map(|m| m.function)
+    }
+}
+
+impl Payload for GenericFunction {
+    fn type_name(&self) -> GcRef<Symbol> {
+        GenericFunction::type_name()
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        self.name.gc_mark(mark);
+        for method in &self.methods {
+            for specializer in method.specializers.iter().flatten() {
+                specializer.gc_mark(mark);
+            }
+            method.function.gc_mark(mark);
+        }
+    }
+}
+
+impl fmt::Display for GenericFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[generic-function {}]", self.name)
+    }
+}
@@ -0,0 +1,157 @@
+//! Measurements of the graph of objects reachable from an `Object`,
+//! for debugging memory use and for putting limits on untrusted
+//! data. Both walk the graph with an explicit stack rather than by
+//! recursion, and both tolerate cycles.
+//!
+//! Functions are treated as leaves: following their environments
+//! would reach most of the global namespace. So are weak references,
+//! which don't keep their targets alive.
+
+use crate::prelude::*;
+use crate::types::error::Error;
+use crate::types::ExpandedObject;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+/// The heap-allocated objects reachable from some `Object`, as
+/// measured by `size`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct ObjectSize {
+    /// The number of distinct heap-allocated objects. Numbers and
+    /// other immediates are not counted, since they are stored
+    /// inline.
+    pub objects: usize,
+    /// The bytes allocated for those objects.
+    pub bytes: usize,
+}
+
+/// The objects `obj` refers to, each paired with how much deeper it
+/// is than `obj`. A list's elements are one level deeper than the
+/// list, but its tail is the same list, so `(1 2 3)` has depth 1 and
+/// `((1))` has depth 2. References and heap objects are transparent
+/// boxes.
+fn children(obj: Object) -> Vec<(Object, usize)> {
+    match obj.expand_quiet() {
+        ExpandedObject::Cons(c) => vec![(c.car, 1), (c.cdr, 0)],
+        ExpandedObject::Reference(r) => vec![(*r, 0)],
+        ExpandedObject::HeapObject(h) => vec![(**h, 0)],
+        ExpandedObject::Vector(v) => v.elements().iter().map(|&obj| (obj, 1)).collect(),
+        ExpandedObject::HashTable(h) => h
+            .pairs()
+            .into_iter()
+            .flat_map(|(key, value)| vec![(key, 1), (value, 1)])
+            .collect(),
+        ExpandedObject::Structure(s) => s.slots().iter().map(|&obj| (obj, 1)).collect(),
+        ExpandedObject::Symbol(s) => vec![(s.plist(), 1)],
+        ExpandedObject::Namespace(n) => n
+            .bindings()
+            .into_iter()
+            .map(|(_, value)| (value, 1))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The bytes allocated for `obj` itself, or `None` if `obj` is not
+/// a heap-allocated object.
+fn allocated_size(obj: Object) -> Option<usize> {
+    match obj.expand_quiet() {
+        ExpandedObject::Float(_) | ExpandedObject::Immediate(_) | ExpandedObject::Reference(_) => {
+            None
+        }
+        ExpandedObject::Symbol(s) => Some(s.allocated_size()),
+        ExpandedObject::String(s) => Some(s.allocated_size()),
+        ExpandedObject::Cons(_) => Some(mem::size_of::<Cons>()),
+        ExpandedObject::Namespace(_) => Some(mem::size_of::<Namespace>()),
+        ExpandedObject::HeapObject(_) => Some(mem::size_of::<HeapObject>()),
+        ExpandedObject::Vector(v) => {
+            Some(mem::size_of::<Vector>() + v.len() * mem::size_of::<Object>())
+        }
+        ExpandedObject::HashTable(h) => {
+            Some(mem::size_of::<HashTable>() + h.len() * 2 * mem::size_of::<Object>())
+        }
+        ExpandedObject::Ratio(_) => Some(mem::size_of::<Ratio>()),
+        ExpandedObject::Structure(s) => {
+            Some(mem::size_of::<Structure>() + s.slots().len() * mem::size_of::<Object>())
+        }
+        ExpandedObject::Bytes(b) => Some(mem::size_of::<Bytes>() + b.len()),
+        ExpandedObject::WeakRef(_) => Some(mem::size_of::<WeakRef>()),
+        ExpandedObject::Native(_) => Some(mem::size_of::<Native>()),
+        ExpandedObject::Function(_) => Some(mem::size_of::<Function>()),
+        ExpandedObject::QuietError(_) => Some(mem::size_of::<Error>()),
+    }
+}
+
+/// Count the distinct heap-allocated objects reachable from `obj`,
+/// including `obj` itself.
+pub fn size(obj: Object) -> ObjectSize {
+    let mut size = ObjectSize::default();
+    let mut seen = HashSet::new();
+    let mut to_visit = vec![obj];
+    while let Some(obj) = to_visit.pop() {
+        if !seen.insert(obj) {
+            continue;
+        }
+        if let Some(bytes) = allocated_size(obj) {
+            size.objects += 1;
+            size.bytes += bytes;
+        }
+        to_visit.extend(children(obj).into_iter().map(|(child, _)| child));
+    }
+    size
+}
+
+/// How deeply nested `obj` is; see `children`. Atoms have depth 0.
+/// An edge which would close a cycle contributes nothing, so a
+/// circular list is as deep as one trip around it.
+pub fn depth(obj: Object) -> usize {
+    // `finished` maps each object whose children have all been
+    // measured to its depth; `in_progress` holds the objects on the
+    // path from `obj` to the one being visited.
+    let mut finished: HashMap<Object, usize> = HashMap::new();
+    let mut in_progress = HashSet::new();
+    let mut to_visit = vec![(obj, false)];
+    while let Some((obj, children_done)) = to_visit.pop() {
+        if children_done {
+            let depth = children(obj)
+                .into_iter()
+                .map(|(child, extra)| extra + finished.get(&child).cloned().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            in_progress.remove(&obj);
+            finished.insert(obj, depth);
+        } else if !finished.contains_key(&obj) && in_progress.insert(obj) {
+            to_visit.push((obj, true));
+            for (child, _) in children(obj) {
+                to_visit.push((child, false));
+            }
+        }
+    }
+    finished[&obj]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gc::GarbageCollected;
+
+    #[test]
+    fn circular_lists() {
+        let mut last = Cons::allocate(Cons::new(Object::from(3i32), Object::nil()));
+        let list = Cons::allocate(Cons::new(
+            Object::from(1i32),
+            Object::from(Cons::allocate(Cons::new(
+                Object::from(2i32),
+                Object::from(last),
+            ))),
+        ));
+        last.cdr = Object::from(list);
+
+        assert_eq!(size(Object::from(list)).objects, 3);
+        assert_eq!(depth(Object::from(list)), 1);
+
+        let nested = Cons::allocate(Cons::new(Object::from(list), Object::nil()));
+        assert_eq!(size(Object::from(nested)).objects, 4);
+        assert_eq!(depth(Object::from(nested)), 2);
+    }
+}
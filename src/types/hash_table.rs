@@ -0,0 +1,297 @@
+//! Hash tables map keys to values, comparing keys either with `eql`
+//! or with `equal`. Keys are hashed consistently with their test, so
+//! `1` and `1.0` share a bucket under either, and under `equal` so do
+//! two lists or strings with the same contents.
+
+use super::pointer_tagging::{ObjectTag, PointerTag};
+use super::ExpandedObject;
+use crate::gc::write_barrier;
+use crate::prelude::*;
+use std::collections::hash_map::{DefaultHasher, HashMap};
+use std::hash::{Hash, Hasher};
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref HASH_TABLE_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"hash-table") };
+    static ref EQL: GcRef<Symbol> = { symbol_lookup::make_symbol(b"eql") };
+    static ref EQUAL: GcRef<Symbol> = { symbol_lookup::make_symbol(b"equal") };
+}
+
+/// `equal` hashes at most this many conses of a key, so that hashing
+/// a long list is cheap. Lists which share a prefix this long will
+/// collide, but still compare correctly.
+const EQUAL_HASH_MAX_CONSES: usize = 16;
+
+/// How a `HashTable` compares its keys.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Test {
+    Eql,
+    Equal,
+}
+
+impl Test {
+    pub fn name(self) -> GcRef<Symbol> {
+        match self {
+            Test::Eql => *EQL,
+            Test::Equal => *EQUAL,
+        }
+    }
+    /// The `Test` named `name`, if any.
+    pub fn from_name(name: GcRef<Symbol>) -> Option<Test> {
+        if name == *EQL {
+            Some(Test::Eql)
+        } else if name == *EQUAL {
+            Some(Test::Equal)
+        } else {
+            None
+        }
+    }
+    fn compare(self, lhs: Object, rhs: Object) -> bool {
+        match self {
+            Test::Eql => deref(lhs).eql(deref(rhs)),
+            Test::Equal => lhs.equal(rhs),
+        }
+    }
+    fn hash(self, key: Object) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match self {
+            Test::Eql => hash_eql(key, &mut hasher),
+            Test::Equal => hash_equal(key, &mut hasher),
+        }
+        hasher.finish()
+    }
+}
+
+/// Keys are stored dereferenced, since a `Reference` may point into
+/// a stack frame which will soon be popped.
+fn deref(mut obj: Object) -> Object {
+    while let Some(r) = Reference::maybe_from(obj) {
+        obj = *r;
+    }
+    obj
+}
+
+fn hash_eql<H: Hasher>(obj: Object, state: &mut H) {
+    let obj = deref(obj);
+    if let Some(n) = PhoebeNumber::maybe_from(obj) {
+        // `eql` numbers compare by value, and every `i32` is exactly
+        // representable as an `f64`. Adding `0.0` turns `-0.0` into
+        // `0.0`, which it is equal to.
+        (f64::from(n) + 0.0).to_bits().hash(state);
+    } else {
+        obj.into_raw().hash(state);
+    }
+}
+
+fn hash_equal<H: Hasher>(obj: Object, state: &mut H) {
+    let mut obj = obj;
+    let mut conses = 0;
+    loop {
+        match deref(obj).expand_quiet() {
+            ExpandedObject::Cons(c) => {
+                if conses == EQUAL_HASH_MAX_CONSES {
+                    return;
+                }
+                conses += 1;
+                hash_equal(c.car, state);
+                obj = c.cdr;
+            }
+            ExpandedObject::String(s) => return s.as_str().hash(state),
+            ExpandedObject::HeapObject(h) => obj = **h,
+            _ => return hash_eql(obj, state),
+        }
+    }
+}
+
+pub struct HashTable {
+    gc_marking: GcMark,
+    test: Test,
+    count: usize,
+    buckets: HashMap<u64, Vec<(Object, Object)>>,
+}
+
+impl HashTable {
+    pub fn new(test: Test) -> HashTable {
+        HashTable {
+            gc_marking: GcMark::default(),
+            test,
+            count: 0,
+            buckets: HashMap::new(),
+        }
+    }
+    pub fn test(&self) -> Test {
+        self.test
+    }
+    pub fn len(&self) -> usize {
+        self.count
+    }
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    pub fn get(&self, key: Object) -> Option<Object> {
+        let test = self.test;
+        self.buckets
+            .get(&test.hash(key))?
+            .iter()
+            .find(|&&(k, _)| test.compare(k, key))
+            .map(|&(_, v)| v)
+    }
+    /// A reference to the value associated with `key`, which `setf`
+    /// can assign through. If `key` is not present, it is first
+    /// associated with `nil`. The reference is only valid until a key
+    /// is next added to or removed from the table.
+    pub fn ref_value(&mut self, key: Object) -> Reference {
+        let key = deref(key);
+        let test = self.test;
+        let bucket = self.buckets.entry(test.hash(key)).or_insert_with(Vec::new);
+        let idx = match bucket.iter().position(|&(k, _)| test.compare(k, key)) {
+            Some(idx) => idx,
+            None => {
+                bucket.push((key, Object::nil()));
+                self.count += 1;
+                bucket.len() - 1
+            }
+        };
+        Reference::from(&mut bucket[idx].1)
+    }
+    /// Associate `value` with `key`, returning the value previously
+    /// associated with it, if any.
+    pub fn insert(&mut self, key: Object, value: Object) -> Option<Object> {
+        let key = deref(key);
+        let test = self.test;
+        let bucket = self.buckets.entry(test.hash(key)).or_insert_with(Vec::new);
+        if let Some(pair) = bucket.iter_mut().find(|&&mut (k, _)| test.compare(k, key)) {
+            let old = ::std::mem::replace(&mut pair.1, value);
+            write_barrier(old);
+            return Some(old);
+        }
+        bucket.push((key, value));
+        self.count += 1;
+        None
+    }
+    /// Remove `key` from the table, returning the value which was
+    /// associated with it, if any.
+    pub fn remove(&mut self, key: Object) -> Option<Object> {
+        let test = self.test;
+        let hash = test.hash(key);
+        let (value, now_empty) = {
+            let bucket = self.buckets.get_mut(&hash)?;
+            let idx = bucket.iter().position(|&(k, _)| test.compare(k, key))?;
+            let (key, value) = bucket.swap_remove(idx);
+            write_barrier(key);
+            write_barrier(value);
+            (value, bucket.is_empty())
+        };
+        if now_empty {
+            self.buckets.remove(&hash);
+        }
+        self.count -= 1;
+        Some(value)
+    }
+    pub fn clear(&mut self) {
+        for (key, value) in self.buckets.drain().flat_map(|(_, b)| b) {
+            write_barrier(key);
+            write_barrier(value);
+        }
+        self.count = 0;
+    }
+    /// Every key in the table, paired with its value, in no
+    /// particular order.
+    pub fn pairs(&self) -> Vec<(Object, Object)> {
+        self.buckets
+            .values()
+            .flat_map(|b| b.iter().cloned())
+            .collect()
+    }
+}
+
+impl GarbageCollected for HashTable {
+    type ConvertFrom = HashTable;
+    fn alloc_one_and_initialize(h: HashTable) -> ::std::ptr::NonNull<HashTable> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, h) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        for bucket in self.buckets.values() {
+            for &(key, value) in bucket {
+                key.gc_mark(mark);
+                value.gc_mark(mark);
+            }
+        }
+    }
+}
+
+impl fmt::Display for HashTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[hash-table {} {}]", self.test.name(), self.len())
+    }
+}
+
+impl fmt::Debug for HashTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[hash-table {:?} {{", self.test)?;
+        for (i, (key, value)) in self.pairs().into_iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}: {:?}", key, value)?;
+        }
+        write!(f, "}}]")
+    }
+}
+
+impl convert::From<GcRef<HashTable>> for Object {
+    fn from(h: GcRef<HashTable>) -> Object {
+        Object::from_raw(ObjectTag::HashTable.tag(h.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<HashTable> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::HashTable
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *HASH_TABLE_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<HashTable> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<HashTable> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut HashTable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let nonnull = 0xdead_beef as *mut HashTable;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+    #[test]
+    fn numbers_hash_by_value() {
+        let mut table = HashTable::new(Test::Eql);
+        assert_eq!(table.insert(Object::from(1), Object::from(2)), None);
+        assert_eq!(table.get(Object::from(1.0)), Some(Object::from(2)));
+        assert_eq!(
+            table.insert(Object::from(1.0), Object::from(3)),
+            Some(Object::from(2))
+        );
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.remove(Object::from(1)), Some(Object::from(3)));
+        assert!(table.is_empty());
+    }
+}
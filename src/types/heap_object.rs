@@ -1,3 +1,4 @@
+use crate::gc::pool::Pool;
 use crate::prelude::*;
 use crate::types::pointer_tagging::{ObjectTag, PointerTag};
 use std::{convert, fmt, ops};
@@ -5,6 +6,7 @@ use std::{convert, fmt, ops};
 lazy_static! {
     static ref HEAP_OBJECT_TYPE_NAME: GcRef<Symbol> =
         { symbol_lookup::make_symbol(b"heap-object") };
+    static ref HEAP_OBJECT_POOL: Pool<HeapObject> = { Pool::default() };
 }
 
 #[derive(Debug)]
@@ -50,17 +52,22 @@ impl ops::DerefMut for HeapObject {
 impl GarbageCollected for HeapObject {
     type ConvertFrom = HeapObject;
     fn alloc_one_and_initialize(h: HeapObject) -> ::std::ptr::NonNull<HeapObject> {
-        use std::alloc::{Alloc, Global};
         use std::ptr;
-        let nn = Global.alloc_one().unwrap();
+        let nn = HEAP_OBJECT_POOL.alloc();
         let p = nn.as_ptr();
         unsafe { ptr::write(p, h) };
         nn
     }
+    unsafe fn deallocate(obj: GcRef<HeapObject>) {
+        use std::ptr::{self, NonNull};
+        let nn: NonNull<HeapObject> = obj.into();
+        ptr::drop_in_place(nn.as_ptr());
+        HEAP_OBJECT_POOL.free(nn);
+    }
     fn my_marking(&self) -> &GcMark {
         &self.gc_marking
     }
-    fn gc_mark_children(&mut self, mark: usize) {
+    fn gc_mark_children(&mut self, mark: bool) {
         self.val.gc_mark(mark)
     }
 }
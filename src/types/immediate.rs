@@ -16,6 +16,7 @@ pub enum Immediate {
     Integer(i32),
     UnsignedInt(usize),
     SpecialMarker(SpecialMarker),
+    Character(char),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -37,6 +38,32 @@ impl fmt::Display for SpecialMarker {
     }
 }
 
+/// Characters which print by name rather than as themselves, like
+/// `#\Space`. The reader accepts these names in any case.
+const CHARACTER_NAMES: &[(char, &str)] = &[
+    (' ', "Space"),
+    ('\n', "Newline"),
+    ('\t', "Tab"),
+    ('\r', "Return"),
+    ('\0', "Nul"),
+];
+
+/// The name `c` prints as, if it has one.
+pub fn character_name(c: char) -> Option<&'static str> {
+    CHARACTER_NAMES
+        .iter()
+        .find(|&&(named, _)| named == c)
+        .map(|&(_, name)| name)
+}
+
+/// The character named `name`, ignoring case.
+pub fn character_from_name(name: &str) -> Option<char> {
+    CHARACTER_NAMES
+        .iter()
+        .find(|&&(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|&(c, _)| c)
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(u64)]
 pub enum ImmediateTag {
@@ -44,6 +71,7 @@ pub enum ImmediateTag {
     Integer,
     UnsignedInt,
     SpecialMarker,
+    Character,
 }
 
 impl FromUnchecked<Object> for SpecialMarker {
@@ -95,12 +123,28 @@ impl FromUnchecked<Object> for Immediate {
             Immediate::UnsignedInt(usize::from_unchecked(obj))
         } else if SpecialMarker::is_type(obj) {
             Immediate::SpecialMarker(SpecialMarker::from_unchecked(obj))
+        } else if char::is_type(obj) {
+            Immediate::Character(char::from_unchecked(obj))
         } else {
             panic!("Immediate::from_unchecked on a non-Immediate value")
         }
     }
 }
 
+impl Immediate {
+    /// The name of the type of the value `self` holds - for example,
+    /// `Immediate::Integer`s are of type `integer`.
+    pub fn type_name(self) -> GcRef<Symbol> {
+        match self {
+            Immediate::Bool(_) => bool::type_name(),
+            Immediate::Integer(_) => i32::type_name(),
+            Immediate::UnsignedInt(_) => usize::type_name(),
+            Immediate::SpecialMarker(_) => SpecialMarker::type_name(),
+            Immediate::Character(_) => char::type_name(),
+        }
+    }
+}
+
 impl FromObject for Immediate {
     type Tag = ObjectTag;
     fn associated_tag() -> ObjectTag {
@@ -118,6 +162,7 @@ impl convert::From<Immediate> for Object {
             Immediate::Integer(n) => ImmediateTag::Integer.tag(u64::from(n as u32)),
             Immediate::UnsignedInt(n) => ImmediateTag::UnsignedInt.tag(n as u64),
             Immediate::SpecialMarker(s) => ImmediateTag::SpecialMarker.tag(u64::from(s as u32)),
+            Immediate::Character(c) => ImmediateTag::Character.tag(u64::from(c as u32)),
         })
     }
 }
@@ -164,6 +209,18 @@ impl convert::From<SpecialMarker> for Object {
     }
 }
 
+impl convert::From<char> for Immediate {
+    fn from(c: char) -> Immediate {
+        Immediate::Character(c)
+    }
+}
+
+impl convert::From<char> for Object {
+    fn from(c: char) -> Object {
+        Object::from_raw(ImmediateTag::Character.tag(u64::from(c as u32)))
+    }
+}
+
 impl convert::From<SpecialMarker> for Immediate {
     fn from(s: SpecialMarker) -> Immediate {
         Immediate::SpecialMarker(s)
@@ -183,6 +240,10 @@ impl fmt::Display for Immediate {
             Immediate::Integer(n) => write!(f, "{}", n),
             Immediate::UnsignedInt(n) => write!(f, "{}", n),
             Immediate::SpecialMarker(s) => write!(f, "{}", s),
+            Immediate::Character(c) => match character_name(c) {
+                Some(name) => write!(f, "#\\{}", name),
+                None => write!(f, "#\\{}", c),
+            },
         }
     }
 }
@@ -1,6 +1,7 @@
+use crate::gc::roots;
 use crate::prelude::*;
 use crate::types::pointer_tagging;
-use std::{cmp, convert, fmt, iter, mem};
+use std::{cmp, convert, fmt, iter};
 
 lazy_static! {
     static ref LIST_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"list") };
@@ -69,8 +70,7 @@ impl List {
                 }
                 List::Cons(c) => {
                     let mut copy = c;
-                    let &mut Cons { ref mut cdr, .. } = copy.as_mut();
-                    let next = mem::replace(cdr, prev);
+                    let next = copy.set_cdr(prev);
                     prev = Object::from(c);
                     self = List::from_unchecked(next);
                 }
@@ -78,14 +78,28 @@ impl List {
         }
     }
     pub fn backwards_list_from<I>(iter: I) -> List
+    where
+        I: iter::IntoIterator<Item = Object>,
+    {
+        let (list, n_rooted) = List::rooted_backwards_list_from(iter);
+        roots::pop_temporary_roots(n_rooted);
+        list
+    }
+    /// Like `backwards_list_from`, but leaves each cons registered as
+    /// a temporary root, returning how many there are for the caller
+    /// to pop once the list is reachable some other way.
+    fn rooted_backwards_list_from<I>(iter: I) -> (List, usize)
     where
         I: iter::IntoIterator<Item = Object>,
     {
         let mut head = Object::nil();
+        let mut n_rooted = 0;
         for el in iter {
             head = Object::from(Cons::allocate(Cons::new(el, head)));
+            roots::push_temporary_root(head);
+            n_rooted += 1;
         }
-        unsafe { List::from_unchecked(head) }
+        (unsafe { List::from_unchecked(head) }, n_rooted)
     }
 }
 
@@ -94,8 +108,11 @@ where
     Object: convert::From<O>,
 {
     fn from_iter<T: iter::IntoIterator<Item = O>>(iter: T) -> List {
-        let backwards = List::backwards_list_from(iter.into_iter().map(Object::from));
-        unsafe { backwards.nreverse() }
+        let (backwards, n_rooted) =
+            List::rooted_backwards_list_from(iter.into_iter().map(Object::from));
+        let list = unsafe { backwards.nreverse() };
+        roots::pop_temporary_roots(n_rooted);
+        list
     }
 }
 
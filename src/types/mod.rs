@@ -2,18 +2,36 @@ use self::pointer_tagging::*;
 use crate::prelude::*;
 use std::{convert, default, fmt, ops};
 
+pub mod bytes;
 pub mod cons;
 pub mod conversions;
+mod copy;
+pub mod equality;
 pub mod error;
 pub mod function;
+pub mod generic_function;
+pub mod graph;
+pub mod hash_table;
 pub mod heap_object;
 pub mod immediate;
 pub mod list;
 pub mod namespace;
+pub mod native;
 pub mod number;
+pub mod pathname;
 mod pointer_tagging;
+pub mod ratio;
 pub mod reference;
+pub mod regex;
+pub mod stream;
+pub mod string;
+pub mod structure;
 pub mod symbol;
+pub mod timestamp;
+pub mod userdata;
+pub mod vector;
+pub mod visit;
+pub mod weak_ref;
 
 /// Every Phoebe value is represented by an `Object`. `Object`s are
 /// NaN-boxed, and the non-`f64` values are pointer-tagged using
@@ -45,15 +63,23 @@ impl Object {
     /// should be passed to `allocate::deallocate` - heap objects will
     /// return `true` if their `gc_marking` does not match `mark` and
     /// by-value objects will always return `false`.
-    pub fn should_dealloc(self, mark: usize) -> bool {
+    pub fn should_dealloc(self, mark: bool) -> bool {
         match self.expand_quiet() {
             ExpandedObject::Float(_)
             | ExpandedObject::Immediate(_)
             | ExpandedObject::Reference(_) => false,
             ExpandedObject::Cons(c) => c.should_dealloc(mark),
             ExpandedObject::Symbol(s) => s.should_dealloc(mark),
+            ExpandedObject::String(s) => s.should_dealloc(mark),
             ExpandedObject::Namespace(n) => n.should_dealloc(mark),
             ExpandedObject::HeapObject(h) => h.should_dealloc(mark),
+            ExpandedObject::Vector(v) => v.should_dealloc(mark),
+            ExpandedObject::HashTable(h) => h.should_dealloc(mark),
+            ExpandedObject::Ratio(r) => r.should_dealloc(mark),
+            ExpandedObject::Structure(s) => s.should_dealloc(mark),
+            ExpandedObject::Bytes(b) => b.should_dealloc(mark),
+            ExpandedObject::WeakRef(w) => w.should_dealloc(mark),
+            ExpandedObject::Native(n) => n.should_dealloc(mark),
             ExpandedObject::Function(func) => func.should_dealloc(mark),
             ExpandedObject::QuietError(e) => e.should_dealloc(mark),
         }
@@ -61,18 +87,84 @@ impl Object {
     /// Used by the garbage collector - if `self` is a heap object,
     /// this method derefs and marks it so that it will not be
     /// deallocated. For by-value objects, this is a no-op.
-    pub fn gc_mark(self, mark: usize) {
+    pub fn gc_mark(self, mark: bool) {
         match self.expand_quiet() {
             ExpandedObject::Float(_) | ExpandedObject::Immediate(_) => (),
             ExpandedObject::Reference(r) => (*r).gc_mark(mark),
             ExpandedObject::Cons(c) => c.gc_mark(mark),
             ExpandedObject::Symbol(s) => s.gc_mark(mark),
+            ExpandedObject::String(s) => s.gc_mark(mark),
             ExpandedObject::Namespace(n) => n.gc_mark(mark),
             ExpandedObject::HeapObject(h) => h.gc_mark(mark),
+            ExpandedObject::Vector(v) => v.gc_mark(mark),
+            ExpandedObject::HashTable(h) => h.gc_mark(mark),
+            ExpandedObject::Ratio(r) => r.gc_mark(mark),
+            ExpandedObject::Structure(s) => s.gc_mark(mark),
+            ExpandedObject::Bytes(b) => b.gc_mark(mark),
+            ExpandedObject::WeakRef(w) => w.gc_mark(mark),
+            ExpandedObject::Native(n) => n.gc_mark(mark),
             ExpandedObject::Function(func) => func.gc_mark(mark),
             ExpandedObject::QuietError(e) => e.gc_mark(mark),
         }
     }
+    /// Used by the allocator to give a new heap object the mark
+    /// `white`. For by-value objects, this is a no-op.
+    pub(crate) fn whiten(self, white: bool) {
+        match self.expand_quiet() {
+            ExpandedObject::Float(_)
+            | ExpandedObject::Immediate(_)
+            | ExpandedObject::Reference(_) => (),
+            ExpandedObject::Cons(c) => c.whiten(white),
+            ExpandedObject::Symbol(s) => s.whiten(white),
+            ExpandedObject::String(s) => s.whiten(white),
+            ExpandedObject::Namespace(n) => n.whiten(white),
+            ExpandedObject::HeapObject(h) => h.whiten(white),
+            ExpandedObject::Vector(v) => v.whiten(white),
+            ExpandedObject::HashTable(h) => h.whiten(white),
+            ExpandedObject::Ratio(r) => r.whiten(white),
+            ExpandedObject::Structure(s) => s.whiten(white),
+            ExpandedObject::Bytes(b) => b.whiten(white),
+            ExpandedObject::WeakRef(w) => w.whiten(white),
+            ExpandedObject::Native(n) => n.whiten(white),
+            ExpandedObject::Function(func) => func.whiten(white),
+            ExpandedObject::QuietError(e) => e.whiten(white),
+        }
+    }
+    /// Used by the garbage collector to mark the children of a heap
+    /// object which has already been marked itself.
+    pub fn gc_mark_children(self, mark: bool) {
+        match self.expand_quiet() {
+            ExpandedObject::Float(_)
+            | ExpandedObject::Immediate(_)
+            | ExpandedObject::Reference(_) => (),
+            ExpandedObject::Cons(mut c) => c.gc_mark_children(mark),
+            ExpandedObject::Symbol(mut s) => s.gc_mark_children(mark),
+            ExpandedObject::String(mut s) => s.gc_mark_children(mark),
+            ExpandedObject::Namespace(mut n) => n.gc_mark_children(mark),
+            ExpandedObject::HeapObject(mut h) => h.gc_mark_children(mark),
+            ExpandedObject::Vector(mut v) => v.gc_mark_children(mark),
+            ExpandedObject::HashTable(mut h) => h.gc_mark_children(mark),
+            ExpandedObject::Ratio(mut r) => r.gc_mark_children(mark),
+            ExpandedObject::Structure(mut s) => s.gc_mark_children(mark),
+            ExpandedObject::Bytes(mut b) => b.gc_mark_children(mark),
+            ExpandedObject::WeakRef(mut w) => w.gc_mark_children(mark),
+            ExpandedObject::Native(mut n) => n.gc_mark_children(mark),
+            ExpandedObject::Function(mut func) => func.gc_mark_children(mark),
+            ExpandedObject::QuietError(mut e) => e.gc_mark_children(mark),
+        }
+    }
+    /// True for heap objects which keep their children in Rust
+    /// collections that a mutator may reallocate without holding any
+    /// lock, so that the collector can only mark their children while
+    /// the mutators are stopped.
+    pub(crate) fn children_may_move(self) -> bool {
+        match self.expand_quiet() {
+            ExpandedObject::Vector(_)
+            | ExpandedObject::HashTable(_)
+            | ExpandedObject::Native(_) => true,
+            _ => false,
+        }
+    }
     /// This object represents the boolean `false`, or the null-pointer.
     pub fn nil() -> Self {
         Object::from(Immediate::from(false))
@@ -102,6 +194,33 @@ impl Object {
         !self.undefinedp()
     }
 
+    /// The name of `self`'s type, as returned by the `FromObject`
+    /// impl for that type. `Reference`s report the type of the
+    /// `Object` they point to, and structures the name of the type
+    /// `defstruct` defined for them, native objects the name their payload
+    /// gives, and keywords `keyword`.
+    pub fn type_name(self) -> GcRef<Symbol> {
+        match self.expand_quiet() {
+            ExpandedObject::Float(_) => f64::type_name(),
+            ExpandedObject::Immediate(i) => i.type_name(),
+            ExpandedObject::Reference(r) => (*r).type_name(),
+            ExpandedObject::Symbol(s) if s.is_keyword() => Keyword::type_name(),
+            ExpandedObject::Symbol(_) => <GcRef<Symbol>>::type_name(),
+            ExpandedObject::String(_) => <GcRef<PhoebeString>>::type_name(),
+            ExpandedObject::Cons(_) => <GcRef<Cons>>::type_name(),
+            ExpandedObject::Namespace(_) => <GcRef<Namespace>>::type_name(),
+            ExpandedObject::HeapObject(_) => <GcRef<HeapObject>>::type_name(),
+            ExpandedObject::Vector(_) => <GcRef<Vector>>::type_name(),
+            ExpandedObject::HashTable(_) => <GcRef<HashTable>>::type_name(),
+            ExpandedObject::Ratio(_) => <GcRef<Ratio>>::type_name(),
+            ExpandedObject::Structure(s) => s.name(),
+            ExpandedObject::Bytes(_) => <GcRef<Bytes>>::type_name(),
+            ExpandedObject::WeakRef(_) => <GcRef<WeakRef>>::type_name(),
+            ExpandedObject::Native(n) => n.payload().type_name(),
+            ExpandedObject::Function(_) => <GcRef<Function>>::type_name(),
+            ExpandedObject::QuietError(_) => <GcRef<Error>>::type_name(),
+        }
+    }
     pub fn eql(self, other: Object) -> bool {
         if let (Some(n), Some(m)) = (
             number::PhoebeNumber::maybe_from(self),
@@ -113,14 +232,8 @@ impl Object {
         }
     }
     pub fn equal(self, other: Object) -> bool {
-        match (self.expand_quiet(), other.expand_quiet()) {
-            (ExpandedObject::Reference(r), _) => other.equal(*r),
-            (_, ExpandedObject::Reference(r)) => self.equal(*r),
-            (ExpandedObject::Cons(a), ExpandedObject::Cons(b)) => *a == *b,
-            (ExpandedObject::HeapObject(r), _) => other.equal(**r),
-            (_, ExpandedObject::HeapObject(r)) => self.equal(**r),
-            _ => self.eql(other),
-        }
+        equality::equal_within(self, other, equality::Budget::unlimited())
+            .expect("An unlimited comparison exceeded its budget")
     }
 }
 
@@ -166,10 +279,18 @@ impl fmt::Display for ExpandedObject {
             ExpandedObject::Float(n) => write!(f, "{}", n),
             ExpandedObject::Reference(r) => write!(f, "{}", r),
             ExpandedObject::Symbol(s) => write!(f, "{}", *s),
+            ExpandedObject::String(s) => write!(f, "{}", *s),
             ExpandedObject::Immediate(i) => write!(f, "{}", i),
             ExpandedObject::Cons(c) => write!(f, "{}", c),
             ExpandedObject::Namespace(n) => write!(f, "{}", n),
             ExpandedObject::HeapObject(h) => write!(f, "{}", h),
+            ExpandedObject::Vector(v) => write!(f, "{}", *v),
+            ExpandedObject::HashTable(h) => write!(f, "{}", *h),
+            ExpandedObject::Ratio(r) => write!(f, "{}", *r),
+            ExpandedObject::Structure(s) => write!(f, "{}", *s),
+            ExpandedObject::Bytes(b) => write!(f, "{}", *b),
+            ExpandedObject::WeakRef(w) => write!(f, "{}", *w),
+            ExpandedObject::Native(n) => write!(f, "{}", *n),
             ExpandedObject::Function(func) => write!(f, "{}", func),
             ExpandedObject::QuietError(e) => write!(f, "{}", e),
         }
@@ -182,10 +303,18 @@ impl fmt::Debug for ExpandedObject {
             ExpandedObject::Float(n) => write!(f, "{:?}", n),
             ExpandedObject::Reference(r) => write!(f, "{:?}", r),
             ExpandedObject::Symbol(s) => write!(f, "{:?}", *s),
+            ExpandedObject::String(s) => write!(f, "{:?}", *s),
             ExpandedObject::Immediate(i) => write!(f, "{:?}", i),
             ExpandedObject::Cons(c) => write!(f, "{:?}", *c),
             ExpandedObject::Namespace(n) => write!(f, "{:?}", *n),
             ExpandedObject::HeapObject(h) => write!(f, "{:?}", *h),
+            ExpandedObject::Vector(v) => write!(f, "{:?}", *v),
+            ExpandedObject::HashTable(h) => write!(f, "{:?}", *h),
+            ExpandedObject::Ratio(r) => write!(f, "{:?}", *r),
+            ExpandedObject::Structure(s) => write!(f, "{:?}", *s),
+            ExpandedObject::Bytes(b) => write!(f, "{:?}", *b),
+            ExpandedObject::WeakRef(w) => write!(f, "{:?}", *w),
+            ExpandedObject::Native(n) => write!(f, "{:?}", *n),
             ExpandedObject::Function(func) => write!(f, "{:?}", *func),
             ExpandedObject::QuietError(e) => write!(f, "{:?}", *e),
         }
@@ -212,12 +341,28 @@ impl convert::TryFrom<Object> for ExpandedObject {
             ExpandedObject::Immediate(unsafe { obj.into_unchecked() })
         } else if <GcRef<Symbol>>::is_type(obj) {
             ExpandedObject::Symbol(unsafe { obj.into_unchecked() })
+        } else if <GcRef<PhoebeString>>::is_type(obj) {
+            ExpandedObject::String(unsafe { obj.into_unchecked() })
         } else if Reference::is_type(obj) {
             ExpandedObject::Reference(unsafe { obj.into_unchecked() })
         } else if <GcRef<Namespace>>::is_type(obj) {
             ExpandedObject::Namespace(unsafe { obj.into_unchecked() })
         } else if <GcRef<HeapObject>>::is_type(obj) {
             ExpandedObject::HeapObject(unsafe { obj.into_unchecked() })
+        } else if <GcRef<Vector>>::is_type(obj) {
+            ExpandedObject::Vector(unsafe { obj.into_unchecked() })
+        } else if <GcRef<HashTable>>::is_type(obj) {
+            ExpandedObject::HashTable(unsafe { obj.into_unchecked() })
+        } else if <GcRef<Ratio>>::is_type(obj) {
+            ExpandedObject::Ratio(unsafe { obj.into_unchecked() })
+        } else if <GcRef<Structure>>::is_type(obj) {
+            ExpandedObject::Structure(unsafe { obj.into_unchecked() })
+        } else if <GcRef<Bytes>>::is_type(obj) {
+            ExpandedObject::Bytes(unsafe { obj.into_unchecked() })
+        } else if <GcRef<WeakRef>>::is_type(obj) {
+            ExpandedObject::WeakRef(unsafe { obj.into_unchecked() })
+        } else if <GcRef<Native>>::is_type(obj) {
+            ExpandedObject::Native(unsafe { obj.into_unchecked() })
         } else if <GcRef<Function>>::is_type(obj) {
             ExpandedObject::Function(unsafe { obj.into_unchecked() })
         } else if <GcRef<Error>>::is_type(obj) {
@@ -245,9 +390,17 @@ pub enum ExpandedObject {
     Immediate(Immediate),
     Reference(Reference),
     Symbol(GcRef<Symbol>),
+    String(GcRef<PhoebeString>),
     Cons(GcRef<Cons>),
     Namespace(GcRef<Namespace>),
     HeapObject(GcRef<HeapObject>),
+    Vector(GcRef<Vector>),
+    HashTable(GcRef<HashTable>),
+    Ratio(GcRef<Ratio>),
+    Structure(GcRef<Structure>),
+    Bytes(GcRef<Bytes>),
+    WeakRef(GcRef<WeakRef>),
+    Native(GcRef<Native>),
     Function(GcRef<Function>),
     QuietError(GcRef<Error>),
 }
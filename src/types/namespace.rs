@@ -1,28 +1,123 @@
 use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::gc::pool::{Pool, POOL_CAPACITY};
+use crate::gc::write_barrier;
 use crate::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
-use std::sync::RwLock;
-use std::{convert, fmt, iter};
+use std::sync::{Mutex, RwLock, RwLockWriteGuard};
+use std::{convert, fmt, iter, mem};
 
 lazy_static! {
     static ref NAMESPACE_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"namespace") };
+    /// Every function call makes a stack `Namespace`, so their memory
+    /// is pooled.
+    static ref NAMESPACE_POOL: Pool<Namespace> = { Pool::default() };
+    /// The emptied tables of deallocated stack `Namespace`s, which
+    /// `create_stack_env` reuses rather than growing a new `HashMap`.
+    static ref STACK_TABLES: Mutex<Vec<HashMap<GcRef<Symbol>, Reference>>> =
+        { Mutex::new(Vec::new()) };
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct NamespaceRef(*mut Namespace);
 
+type Cells = HashMap<GcRef<Symbol>, GcRef<HeapObject>>;
+
+/// New cells holding the values of `table`'s. See
+/// `Namespace::copy_bindings`.
+fn copy_cells(table: &Cells) -> Cells {
+    table
+        .iter()
+        .map(|(&sym, cell)| (sym, HeapObject::allocate(HeapObject::around(***cell))))
+        .collect()
+}
+
+/// Make `table` bind the same symbols to the same values as `copy`,
+/// reusing its own cells where it has them. See
+/// `Namespace::restore_bindings`.
+fn restore_cells(table: &mut Cells, copy: &Cells) {
+    table.retain(|sym, &mut cell| {
+        let keep = copy.contains_key(sym);
+        if !keep {
+            write_barrier(Object::from(cell));
+        }
+        keep
+    });
+    for (&sym, cell) in copy {
+        let mut own = *(table
+            .entry(sym)
+            .or_insert_with(|| HeapObject::allocate(HeapObject::around(Object::default()))));
+        write_barrier(mem::replace(&mut **own, ***cell));
+    }
+}
+
+/// The value of `cell`, which has just been removed from a table.
+/// Other namespaces may share the cell, so it's the cell rather than
+/// the value which the collector mustn't miss.
+fn remove_cell(cell: GcRef<HeapObject>) -> Object {
+    write_barrier(Object::from(cell));
+    **cell
+}
+
+/// Signal an `ImportConflict` if any of `imports` would replace a
+/// binding in `table`. See `Namespace::import_from`.
+fn check_import(
+    table: &Cells,
+    imports: &[(GcRef<Symbol>, GcRef<HeapObject>)],
+    alias: bool,
+    from: GcRef<Namespace>,
+) -> Result<(), EvaluatorError> {
+    for &(sym, cell) in imports {
+        if let Some(&existing) = table.get(&sym) {
+            let same = existing == cell || (!alias && **existing == **cell);
+            if !same {
+                return Err(EvaluatorError::ImportConflict {
+                    symbol: sym,
+                    namespace: from,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bind each of `imports` in `table`, once `check_import` has
+/// approved them.
+fn insert_imports(
+    table: &mut Cells,
+    imports: Vec<(GcRef<Symbol>, GcRef<HeapObject>)>,
+    alias: bool,
+) {
+    for (sym, cell) in imports {
+        if table.contains_key(&sym) {
+            continue;
+        }
+        let cell = if alias {
+            cell
+        } else {
+            HeapObject::allocate(HeapObject::around(**cell))
+        };
+        table.insert(sym, cell);
+    }
+}
+
 impl GcRef<Namespace> {
     pub fn clone_if_needed(self) -> GcRef<Namespace> {
         Namespace::allocate(match *self {
             Namespace::Heap {
                 parent: Some(p),
                 ref table,
+                ref exports,
+                ref uses,
+                ref functions,
                 name,
                 ..
             } => Namespace::Heap {
                 parent: Some(p.clone_if_needed()),
                 table: RwLock::new((*(table.read().unwrap())).clone()),
+                exports: RwLock::new((*(exports.read().unwrap())).clone()),
+                uses: RwLock::new((*(uses.read().unwrap())).clone()),
+                functions: RwLock::new((*(functions.read().unwrap())).clone()),
                 name,
                 gc_marking: GcMark::default(),
             },
@@ -45,6 +140,9 @@ impl GcRef<Namespace> {
                     gc_marking: GcMark::default(),
                     name: None,
                     table,
+                    exports: RwLock::new(HashSet::new()),
+                    uses: RwLock::new(Vec::new()),
+                    functions: RwLock::new(HashMap::new()),
                     parent,
                 }
             }
@@ -64,6 +162,16 @@ pub enum Namespace {
         gc_marking: GcMark,
         name: Option<Object>,
         table: RwLock<HashMap<GcRef<Symbol>, GcRef<HeapObject>>>,
+        /// The symbols which other namespaces may `import` from this
+        /// one.
+        exports: RwLock<HashSet<GcRef<Symbol>>>,
+        /// The namespaces whose exported bindings are visible in this
+        /// one, as if they were bound here, after its own bindings but
+        /// before its parent's. See `use_namespace`.
+        uses: RwLock<Vec<GcRef<Namespace>>>,
+        /// The function cells of this namespace's bindings, which are
+        /// only used in Lisp-2 mode. See the `lisp2` module.
+        functions: RwLock<HashMap<GcRef<Symbol>, GcRef<HeapObject>>>,
         parent: Option<GcRef<Namespace>>,
     },
     Stack {
@@ -79,11 +187,17 @@ impl Clone for Namespace {
             Namespace::Heap {
                 name,
                 ref table,
+                ref exports,
+                ref uses,
+                ref functions,
                 parent,
                 ..
             } => Namespace::Heap {
                 name,
                 table: RwLock::new((*(table.read().unwrap())).clone()),
+                exports: RwLock::new((*(exports.read().unwrap())).clone()),
+                uses: RwLock::new((*(uses.read().unwrap())).clone()),
+                functions: RwLock::new((*(functions.read().unwrap())).clone()),
                 parent,
                 gc_marking: GcMark::default(),
             },
@@ -129,6 +243,9 @@ impl iter::FromIterator<(GcRef<Symbol>, Object)> for Namespace {
             gc_marking: GcMark::default(),
             name: None,
             table,
+            exports: RwLock::new(HashSet::new()),
+            uses: RwLock::new(Vec::new()),
+            functions: RwLock::new(HashMap::new()),
             parent: None,
         }
     }
@@ -140,12 +257,78 @@ impl Default for Namespace {
             gc_marking: GcMark::default(),
             name: None,
             table: RwLock::new(HashMap::new()),
+            exports: RwLock::new(HashSet::new()),
+            uses: RwLock::new(Vec::new()),
+            functions: RwLock::new(HashMap::new()),
             parent: None,
         }
     }
 }
 
+/// The bindings of a heap `Namespace`, locked for writing. See
+/// `Namespace::update`.
+pub struct Bindings<'a> {
+    table: RwLockWriteGuard<'a, HashMap<GcRef<Symbol>, GcRef<HeapObject>>>,
+}
+
+impl<'a> Bindings<'a> {
+    /// The value bound to `sym` in this namespace. Unlike
+    /// `Namespace::get_sym_ref`, this does not search parent
+    /// namespaces.
+    pub fn get(&self, sym: GcRef<Symbol>) -> Option<Object> {
+        self.table.get(&sym).map(|h| ***h)
+    }
+    /// Bind `sym` to `value` in this namespace, creating the binding
+    /// if it does not already exist.
+    pub fn set(&mut self, sym: GcRef<Symbol>, value: Object) {
+        let mut h = *(self
+            .table
+            .entry(sym)
+            .or_insert_with(|| HeapObject::allocate(HeapObject::around(Object::default()))));
+        write_barrier(mem::replace(&mut **h, value));
+    }
+    /// Remove `sym`'s binding from this namespace, returning the value
+    /// it was bound to.
+    pub fn remove(&mut self, sym: GcRef<Symbol>) -> Option<Object> {
+        self.table.remove(&sym).map(remove_cell)
+    }
+}
+
 impl Namespace {
+    /// Calls `fun` while holding the lock on this namespace's
+    /// bindings, so that other threads cannot look up or create
+    /// bindings in it until every change `fun` makes is in place.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn update<F, T>(&self, fun: F) -> T
+    where
+        F: FnOnce(&mut Bindings) -> T,
+    {
+        match *self {
+            Namespace::Heap { ref table, .. } => fun(&mut Bindings {
+                table: table.write().unwrap(),
+            }),
+            Namespace::Stack { .. } => panic!("Attempt to update a stack namespace"),
+        }
+    }
+    /// A snapshot of the symbols bound in this namespace and their
+    /// values, not including those of parent namespaces.
+    pub fn bindings(&self) -> Vec<(GcRef<Symbol>, Object)> {
+        match *self {
+            Namespace::Heap { ref table, .. } => table
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(&sym, h)| (sym, ***h))
+                .collect(),
+            Namespace::Stack { ref table, .. } => table
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(&sym, r)| (sym, **r))
+                .collect(),
+        }
+    }
     pub fn contains_stack_ref(&self, find_me: Reference) -> bool {
         match *self {
             Namespace::Stack { ref table, .. } => {
@@ -197,9 +380,14 @@ impl Namespace {
         pairs: &[(GcRef<Symbol>, Reference)],
         parent: GcRef<Namespace>,
     ) -> GcRef<Namespace> {
-        let nmspc: Namespace = pairs.iter().cloned().collect();
+        let mut table = STACK_TABLES.lock().unwrap().pop().unwrap_or_default();
+        table.extend(pairs.iter().cloned());
 
-        Namespace::allocate(nmspc.with_parent(parent))
+        Namespace::allocate(Namespace::Stack {
+            gc_marking: GcMark::default(),
+            table: RwLock::new(table),
+            parent: Some(parent),
+        })
     }
 
     pub fn parent(&self) -> Option<GcRef<Namespace>> {
@@ -207,6 +395,14 @@ impl Namespace {
             Namespace::Stack { parent, .. } | Namespace::Heap { parent, .. } => parent,
         }
     }
+    /// The name of a heap `Namespace`, if it has one. Stack
+    /// `Namespace`s are never named.
+    pub fn name(&self) -> Option<Object> {
+        match *self {
+            Namespace::Heap { name, .. } => name,
+            Namespace::Stack { .. } => None,
+        }
+    }
     pub fn with_parent(self, parent: GcRef<Namespace>) -> Namespace {
         match self {
             Namespace::Stack { table, .. } => Namespace::Stack {
@@ -214,10 +410,20 @@ impl Namespace {
                 table,
                 parent: Some(parent),
             },
-            Namespace::Heap { name, table, .. } => Namespace::Heap {
+            Namespace::Heap {
+                name,
+                table,
+                exports,
+                uses,
+                functions,
+                ..
+            } => Namespace::Heap {
                 gc_marking: GcMark::default(),
                 name,
                 table,
+                exports,
+                uses,
+                functions,
                 parent: Some(parent),
             },
         }
@@ -259,18 +465,278 @@ impl Namespace {
     }
     pub fn get_sym_ref(&self, sym: GcRef<Symbol>) -> Option<Reference> {
         match *self {
-            Namespace::Heap { ref table, .. } => table
+            Namespace::Heap {
+                ref table,
+                ref uses,
+                ..
+            } => {
+                let own = table.read().unwrap().get(&sym).map(|&h| Reference::from(h));
+                own.or_else(|| {
+                    uses.read()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|n| n.get_exported_ref(sym))
+                        .next()
+                })
+                .or_else(|| self.parent().and_then(|n| n.get_sym_ref(sym)))
+            }
+            Namespace::Stack { ref table, .. } => table
                 .read()
                 .unwrap()
                 .get(&sym)
-                .map(|&h| Reference::from(h))
+                .cloned()
                 .or_else(|| self.parent().and_then(|n| n.get_sym_ref(sym))),
-            Namespace::Stack { ref table, .. } => table
+        }
+    }
+    /// `sym`'s binding in this namespace, if it exports `sym` and has
+    /// bound it. Doesn't search parent or used namespaces, so a
+    /// namespace never re-exports the bindings it `use`s.
+    fn get_exported_ref(&self, sym: GcRef<Symbol>) -> Option<Reference> {
+        match *self {
+            Namespace::Heap {
+                ref table,
+                ref exports,
+                ..
+            } if exports.read().unwrap().contains(&sym) => {
+                table.read().unwrap().get(&sym).map(|&h| Reference::from(h))
+            }
+            _ => None,
+        }
+    }
+
+    /// `sym`'s function cell in this namespace, or else in the first
+    /// namespace it uses which exports one, or else in its nearest
+    /// parent which has one. Stack `Namespace`s have no function
+    /// cells. See the `lisp2` module.
+    pub fn get_function_ref(&self, sym: GcRef<Symbol>) -> Option<Reference> {
+        match *self {
+            Namespace::Heap {
+                ref functions,
+                ref uses,
+                ..
+            } => {
+                let own = functions
+                    .read()
+                    .unwrap()
+                    .get(&sym)
+                    .map(|&h| Reference::from(h));
+                own.or_else(|| {
+                    uses.read()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|n| n.get_exported_function_ref(sym))
+                        .next()
+                })
+                .or_else(|| self.parent().and_then(|n| n.get_function_ref(sym)))
+            }
+            Namespace::Stack { .. } => self.parent().and_then(|n| n.get_function_ref(sym)),
+        }
+    }
+
+    /// Like `get_exported_ref`, but for `sym`'s function cell.
+    fn get_exported_function_ref(&self, sym: GcRef<Symbol>) -> Option<Reference> {
+        match *self {
+            Namespace::Heap {
+                ref functions,
+                ref exports,
+                ..
+            } if exports.read().unwrap().contains(&sym) => functions
                 .read()
                 .unwrap()
                 .get(&sym)
-                .cloned()
-                .or_else(|| self.parent().and_then(|n| n.get_sym_ref(sym))),
+                .map(|&h| Reference::from(h)),
+            _ => None,
+        }
+    }
+
+    /// Like `make_sym_ref`, but for `sym`'s function cell.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn make_function_ref(&self, sym: GcRef<Symbol>) -> Reference {
+        match *self {
+            Namespace::Heap { ref functions, .. } => {
+                let p = *(functions.write().unwrap().entry(sym).or_insert_with(|| {
+                    HeapObject::allocate(HeapObject::around(Object::default()))
+                }));
+                p.into()
+            }
+            Namespace::Stack { .. } => panic!("Attempt to insert into a stack namespace"),
+        }
+    }
+
+    /// Remove `sym`'s function cell from this namespace, but not from
+    /// its parents, returning the function it held.
+    pub fn remove_function(&self, sym: GcRef<Symbol>) -> Option<Object> {
+        match *self {
+            Namespace::Heap { ref functions, .. } => {
+                functions.write().unwrap().remove(&sym).map(remove_cell)
+            }
+            Namespace::Stack { .. } => None,
+        }
+    }
+
+    /// Make the exported bindings of `used` visible in this namespace
+    /// without `nref`, after any bindings of its own. Namespaces used
+    /// earlier take precedence over those used later. Using the same
+    /// namespace twice, or a namespace from within itself, does
+    /// nothing.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn use_namespace(&self, used: GcRef<Namespace>) {
+        match *self {
+            Namespace::Heap { ref uses, .. } => {
+                let mut uses = uses.write().unwrap();
+                if !::std::ptr::eq(self, &*used) && !uses.contains(&used) {
+                    uses.push(used);
+                }
+            }
+            Namespace::Stack { .. } => panic!("Attempt to use a namespace from a stack namespace"),
+        }
+    }
+
+    /// Undo `use_namespace(used)`, if this namespace uses `used`.
+    pub fn unuse_namespace(&self, used: GcRef<Namespace>) {
+        if let Namespace::Heap { ref uses, .. } = *self {
+            uses.write().unwrap().retain(|&n| n != used);
+        }
+    }
+
+    /// Remove `sym`'s binding from this namespace, but not from its
+    /// parents, returning the value it was bound to. Any binding of
+    /// `sym` it shadowed becomes visible again.
+    ///
+    /// Removing a binding from a stack `Namespace` leaves its value on
+    /// the stack until the frame is popped, but nothing can refer to
+    /// it by name.
+    pub fn remove(&self, sym: GcRef<Symbol>) -> Option<Object> {
+        match *self {
+            Namespace::Heap { .. } => self.update(|b| b.remove(sym)),
+            Namespace::Stack { ref table, .. } => table.write().unwrap().remove(&sym).map(|r| *r),
+        }
+    }
+
+    /// Remove the innermost binding of `sym` visible from this
+    /// namespace, its own or else its nearest parent's, returning the
+    /// value it was bound to. Bindings visible through
+    /// `use_namespace` belong to the namespace which exports them, so
+    /// they aren't removed, and neither are the bindings they shadow.
+    pub fn unbind(&self, sym: GcRef<Symbol>) -> Option<Object> {
+        if let Some(value) = self.remove(sym) {
+            return Some(value);
+        }
+        if let Namespace::Heap { ref uses, .. } = *self {
+            if uses
+                .read()
+                .unwrap()
+                .iter()
+                .any(|n| n.get_exported_ref(sym).is_some())
+            {
+                return None;
+            }
+        }
+        self.parent().and_then(|p| p.unbind(sym))
+    }
+
+    /// Allow other namespaces to `import_from` this one's binding of
+    /// `sym`, whether or not it has been bound yet.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn export(&self, sym: GcRef<Symbol>) {
+        match *self {
+            Namespace::Heap { ref exports, .. } => {
+                exports.write().unwrap().insert(sym);
+            }
+            Namespace::Stack { .. } => panic!("Attempt to export from a stack namespace"),
+        }
+    }
+    /// The symbols this namespace exports, sorted by name. Stack
+    /// `Namespace`s export nothing.
+    pub fn exports(&self) -> Vec<GcRef<Symbol>> {
+        let mut syms: Vec<GcRef<Symbol>> = match *self {
+            Namespace::Heap { ref exports, .. } => {
+                exports.read().unwrap().iter().cloned().collect()
+            }
+            Namespace::Stack { .. } => Vec::new(),
+        };
+        syms.sort_by(|a, b| (**a).as_ref().cmp((**b).as_ref()));
+        syms
+    }
+    /// Bind each of `syms` in this namespace to its binding in
+    /// `from`, which must export all of them, along with its function
+    /// cell if it has one. If `alias`, the bindings are shared, so
+    /// that `setf`ing either one changes both; otherwise this
+    /// namespace gets new bindings to copies of their current values.
+    ///
+    /// Symbols which are already bound in this namespace to the same
+    /// binding, or when copying to the same value, are left alone. If
+    /// any of `syms` isn't exported, or is already bound in this
+    /// namespace to something else, nothing is imported.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn import_from(
+        &self,
+        from: GcRef<Namespace>,
+        syms: &[GcRef<Symbol>],
+        alias: bool,
+    ) -> Result<(), EvaluatorError> {
+        let (cells, function_cells) = match *from {
+            Namespace::Heap {
+                ref table,
+                ref exports,
+                ref functions,
+                ..
+            } => {
+                let exports = exports.read().unwrap();
+                let mut table = table.write().unwrap();
+                let functions = functions.read().unwrap();
+                let mut cells = Vec::with_capacity(syms.len());
+                let mut function_cells = Vec::new();
+                for &sym in syms {
+                    if !exports.contains(&sym) {
+                        return Err(EvaluatorError::NotExported {
+                            symbol: sym,
+                            namespace: from,
+                        });
+                    }
+                    let cell = *(table.entry(sym).or_insert_with(|| {
+                        HeapObject::allocate(HeapObject::around(Object::default()))
+                    }));
+                    cells.push((sym, cell));
+                    if let Some(&cell) = functions.get(&sym) {
+                        function_cells.push((sym, cell));
+                    }
+                }
+                (cells, function_cells)
+            }
+            Namespace::Stack { .. } => {
+                return match syms.first() {
+                    Some(&symbol) => Err(EvaluatorError::NotExported {
+                        symbol,
+                        namespace: from,
+                    }),
+                    None => Ok(()),
+                };
+            }
+        };
+
+        // `from`'s locks are released before taking this one's, so
+        // that two namespaces importing from each other at once can't
+        // deadlock.
+        match *self {
+            Namespace::Heap {
+                ref table,
+                ref functions,
+                ..
+            } => {
+                let mut table = table.write().unwrap();
+                let mut functions = functions.write().unwrap();
+                check_import(&table, &cells, alias, from)?;
+                check_import(&functions, &function_cells, alias, from)?;
+                insert_imports(&mut table, cells, alias);
+                insert_imports(&mut functions, function_cells, alias);
+                Ok(())
+            }
+            Namespace::Stack { .. } => panic!("Attempt to import into a stack namespace"),
         }
     }
 
@@ -278,18 +744,91 @@ impl Namespace {
     /// parent envs. It is called by
     /// `symbol_lookup::make_from_[default_]global_namespace`.
     pub fn make_sym_ref(&mut self, sym: GcRef<Symbol>) -> Reference {
+        self.make_sym_cell(sym).into()
+    }
+
+    /// The `HeapObject` which holds `sym`'s binding in this
+    /// namespace, as `make_sym_ref`, for holders of a `Reference`
+    /// which need to keep its binding alive.
+    pub(crate) fn make_sym_cell(&self, sym: GcRef<Symbol>) -> GcRef<HeapObject> {
         use std::default::Default;
 
         match *self {
-            Namespace::Heap { ref mut table, .. } => {
-                let p = *(table.write().unwrap().entry(sym).or_insert_with(|| {
-                    HeapObject::allocate(HeapObject::around(Object::default()))
-                }));
-                p.into()
+            Namespace::Heap { ref table, .. } => {
+                *(table
+                    .write()
+                    .unwrap()
+                    .entry(sym)
+                    .or_insert_with(|| HeapObject::allocate(HeapObject::around(Object::default()))))
             }
             Namespace::Stack { .. } => panic!("Attempt to insert into a stack namespace"),
         }
     }
+
+    /// A new heap namespace with the same bindings as this one, but
+    /// in cells of its own, so that assigning to a binding in one
+    /// doesn't change the other. The values themselves, and the
+    /// parent namespace, are shared rather than copied.
+    ///
+    /// Panics if `self` is a stack `Namespace`.
+    pub fn copy_bindings(&self) -> Namespace {
+        match *self {
+            Namespace::Heap {
+                name,
+                ref table,
+                ref exports,
+                ref uses,
+                ref functions,
+                parent,
+                ..
+            } => Namespace::Heap {
+                gc_marking: GcMark::default(),
+                name,
+                table: RwLock::new(copy_cells(&table.read().unwrap())),
+                exports: RwLock::new(exports.read().unwrap().clone()),
+                uses: RwLock::new(uses.read().unwrap().clone()),
+                functions: RwLock::new(copy_cells(&functions.read().unwrap())),
+                parent,
+            },
+            Namespace::Stack { .. } => panic!("Attempt to copy a stack namespace"),
+        }
+    }
+
+    /// Undo every change made to this namespace's bindings since
+    /// `copy` was made from it by `copy_bindings`. Bindings made
+    /// since are removed, and those which `copy` has keep their
+    /// cells, so references to them stay valid.
+    ///
+    /// Panics if either is a stack `Namespace`.
+    pub fn restore_bindings(&self, copy: &Namespace) {
+        match (self, copy) {
+            (
+                Namespace::Heap {
+                    ref table,
+                    ref exports,
+                    ref uses,
+                    ref functions,
+                    ..
+                },
+                Namespace::Heap {
+                    table: ref copy_table,
+                    exports: ref copy_exports,
+                    uses: ref copy_uses,
+                    functions: ref copy_functions,
+                    ..
+                },
+            ) => {
+                restore_cells(&mut table.write().unwrap(), &copy_table.read().unwrap());
+                restore_cells(
+                    &mut functions.write().unwrap(),
+                    &copy_functions.read().unwrap(),
+                );
+                *exports.write().unwrap() = copy_exports.read().unwrap().clone();
+                *uses.write().unwrap() = copy_uses.read().unwrap().clone();
+            }
+            _ => panic!("Attempt to restore a stack namespace"),
+        }
+    }
 }
 
 impl fmt::Display for Namespace {
@@ -307,13 +846,26 @@ impl fmt::Display for Namespace {
 impl GarbageCollected for Namespace {
     type ConvertFrom = Namespace;
     fn alloc_one_and_initialize(n: Namespace) -> ::std::ptr::NonNull<Namespace> {
-        use std::alloc::{Alloc, Global};
         use std::ptr;
-        let nn = Global.alloc_one::<Namespace>().unwrap();
+        let nn = NAMESPACE_POOL.alloc();
         let p = nn.as_ptr();
         unsafe { ptr::write(p, n) };
         nn
     }
+    unsafe fn deallocate(obj: GcRef<Namespace>) {
+        use std::ptr::{self, NonNull};
+        let nn: NonNull<Namespace> = obj.into();
+        if let Namespace::Stack { ref mut table, .. } = *nn.as_ptr() {
+            let mut table = mem::replace(table.get_mut().unwrap(), HashMap::new());
+            table.clear();
+            let mut tables = STACK_TABLES.lock().unwrap();
+            if tables.len() < POOL_CAPACITY {
+                tables.push(table);
+            }
+        }
+        ptr::drop_in_place(nn.as_ptr());
+        NAMESPACE_POOL.free(nn);
+    }
     fn my_marking(&self) -> &GcMark {
         match *self {
             Namespace::Heap { ref gc_marking, .. } | Namespace::Stack { ref gc_marking, .. } => {
@@ -321,10 +873,13 @@ impl GarbageCollected for Namespace {
             }
         }
     }
-    fn gc_mark_children(&mut self, mark: usize) {
+    fn gc_mark_children(&mut self, mark: bool) {
         match *self {
             Namespace::Heap {
                 ref mut table,
+                ref exports,
+                ref uses,
+                ref functions,
                 parent,
                 ..
             } => {
@@ -335,6 +890,16 @@ impl GarbageCollected for Namespace {
                         p.gc_mark(mark);
                     }
                 }
+                for sym in exports.read().unwrap().iter() {
+                    sym.clone().gc_mark(mark);
+                }
+                for used in uses.read().unwrap().iter() {
+                    used.clone().gc_mark(mark);
+                }
+                for (sym, heapobj) in functions.read().unwrap().iter() {
+                    sym.clone().gc_mark(mark);
+                    heapobj.clone().gc_mark(mark);
+                }
             }
             Namespace::Stack {
                 ref mut table,
@@ -0,0 +1,142 @@
+//! Native objects wrap an arbitrary Rust value, for types like streams
+//! whose state can't be represented by other `Object`s. The wrapped
+//! value implements `Payload`, which names its type and prints it,
+//! and can be recovered with `Native::downcast_ref` or
+//! `Native::downcast_mut`.
+
+use super::pointer_tagging::{self, ExtendedHeader, ExtendedTag, ObjectTag, PointerTag};
+use crate::prelude::*;
+use std::any::Any;
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref NATIVE_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"native") };
+}
+
+/// Lets `Native` downcast its payload. Implemented for every `Any`
+/// type, so `Payload` implementors needn't implement it themselves.
+pub trait AsAny {
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+}
+
+/// A Rust value which can be wrapped in a `Native`. `Display` is how
+/// Phoebe prints it.
+pub trait Payload: AsAny + fmt::Display + 'static {
+    /// The name `type-of` returns.
+    fn type_name(&self) -> GcRef<Symbol>;
+    /// Payloads which hold onto `Object`s must mark them here.
+    fn gc_mark_children(&mut self, _mark: bool) {}
+}
+
+#[repr(C)]
+pub struct Native {
+    header: ExtendedHeader,
+    gc_marking: GcMark,
+    payload: Box<Payload>,
+}
+
+impl Native {
+    pub fn new<P: Payload>(payload: P) -> Native {
+        Native {
+            header: ExtendedHeader::new(ExtendedTag::Native),
+            gc_marking: GcMark::default(),
+            payload: Box::new(payload),
+        }
+    }
+    pub fn payload(&self) -> &Payload {
+        &*self.payload
+    }
+    /// The payload, if it is a `P`.
+    pub fn downcast_ref<P: Payload>(&self) -> Option<&P> {
+        // Deref first: `Box<Payload>` is itself `AsAny`.
+        (*self.payload).as_any().downcast_ref()
+    }
+    /// The payload, if it is a `P`.
+    pub fn downcast_mut<P: Payload>(&mut self) -> Option<&mut P> {
+        (*self.payload).as_any_mut().downcast_mut()
+    }
+}
+
+impl GarbageCollected for Native {
+    type ConvertFrom = Native;
+    fn alloc_one_and_initialize(n: Native) -> ::std::ptr::NonNull<Native> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, n) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        self.payload.gc_mark_children(mark);
+    }
+}
+
+impl fmt::Display for Native {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.payload)
+    }
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[native {}]", self.payload)
+    }
+}
+
+impl convert::From<GcRef<Native>> for Object {
+    fn from(n: GcRef<Native>) -> Object {
+        Object::from_raw(ObjectTag::Extended.tag(n.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<Native> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Extended
+    }
+    fn is_type(obj: Object) -> bool {
+        unsafe { pointer_tagging::extended_tag_of(obj.0) == Some(ExtendedTag::Native) }
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *NATIVE_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<Native> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<Native> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut Native)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::timestamp::Timestamp;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            // Extended objects are told apart by their header, so this
+            // must point to a real `Native`.
+            let mut x = Native::new(Timestamp::from_nanos(0));
+            let nonnull = &mut x as *mut Native;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert!(<GcRef<Native>>::is_type(obj));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
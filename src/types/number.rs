@@ -1,6 +1,7 @@
 use crate::prelude::*;
 use crate::symbol_lookup::make_symbol;
 use crate::types::pointer_tagging;
+use crate::types::ratio::Ratio;
 use std::{cmp, convert, ops};
 
 lazy_static! {
@@ -10,6 +11,10 @@ lazy_static! {
 #[derive(Clone, Copy)]
 pub enum PhoebeNumber {
     Integer(i32),
+    /// A numerator and denominator in lowest terms, with the
+    /// denominator greater than 1. Construct these with
+    /// `PhoebeNumber::ratio`, which normalizes them.
+    Ratio(i32, i32),
     Float(f64),
 }
 
@@ -30,10 +35,79 @@ fn try_flatten_float(f: f64) -> PhoebeNumber {
     }
 }
 
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a.abs()
+}
+
+fn i128_fits_in_an_int(n: i128) -> bool {
+    n <= i128::from(::std::i32::MAX) && n >= i128::from(::std::i32::MIN)
+}
+
+/// Combine `lhs` and `rhs` with `exact` if both are integers or
+/// ratios, or else with `inexact` on their values as floats.
+fn combine(
+    lhs: PhoebeNumber,
+    rhs: PhoebeNumber,
+    exact: fn((i128, i128), (i128, i128)) -> PhoebeNumber,
+    inexact: fn(f64, f64) -> PhoebeNumber,
+) -> PhoebeNumber {
+    if let (Some(l), Some(r)) = (lhs.as_fraction(), rhs.as_fraction()) {
+        exact(l, r)
+    } else {
+        inexact(f64::from(lhs), f64::from(rhs))
+    }
+}
+
 impl PhoebeNumber {
+    /// The exact quotient of `numerator` and `denominator`: an
+    /// `Integer` if it is one, or else a `Ratio` in lowest
+    /// terms. Quotients with no exact representation, because
+    /// `denominator` is zero or the reduced fraction doesn't fit in
+    /// `i32`s, are `Float`s instead.
+    pub fn ratio(numerator: i128, denominator: i128) -> PhoebeNumber {
+        if denominator == 0 {
+            return PhoebeNumber::Float(numerator as f64 / 0.0);
+        }
+        let mut divisor = gcd(numerator, denominator);
+        if denominator < 0 {
+            divisor = -divisor;
+        }
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+        if !(i128_fits_in_an_int(numerator) && i128_fits_in_an_int(denominator)) {
+            PhoebeNumber::Float(numerator as f64 / denominator as f64)
+        } else if denominator == 1 {
+            PhoebeNumber::Integer(numerator as i32)
+        } else {
+            PhoebeNumber::Ratio(numerator as i32, denominator as i32)
+        }
+    }
+    /// `self` as a numerator and a positive denominator, unless it's
+    /// a `Float`.
+    fn as_fraction(self) -> Option<(i128, i128)> {
+        match self {
+            PhoebeNumber::Integer(n) => Some((i128::from(n), 1)),
+            PhoebeNumber::Ratio(n, d) => Some((i128::from(n), i128::from(d))),
+            PhoebeNumber::Float(_) => None,
+        }
+    }
     pub fn recip(self) -> Self {
-        let recip = 1.0 / (f64::from(self));
-        try_flatten_float(recip)
+        if let Some((n, d)) = self.as_fraction() {
+            PhoebeNumber::ratio(d, n)
+        } else {
+            try_flatten_float(1.0 / (f64::from(self)))
+        }
+    }
+    pub fn abs(self) -> Self {
+        if self < PhoebeNumber::from(0) {
+            -self
+        } else {
+            self
+        }
     }
     pub fn try_flatten(self) -> Self {
         if let PhoebeNumber::Float(f) = self {
@@ -46,8 +120,8 @@ impl PhoebeNumber {
 
 impl cmp::PartialEq for PhoebeNumber {
     fn eq(&self, rhs: &PhoebeNumber) -> bool {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs == rhs
+        if let (Some((a, b)), Some((c, d))) = (self.as_fraction(), rhs.as_fraction()) {
+            a * d == c * b
         } else {
             f64::from(*self) == f64::from(*rhs)
         }
@@ -56,50 +130,25 @@ impl cmp::PartialEq for PhoebeNumber {
 
 impl cmp::PartialOrd for PhoebeNumber {
     fn partial_cmp(&self, rhs: &PhoebeNumber) -> Option<cmp::Ordering> {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs.partial_cmp(&rhs)
+        // Denominators are always positive, so cross-multiplying
+        // preserves the order.
+        if let (Some((a, b)), Some((c, d))) = (self.as_fraction(), rhs.as_fraction()) {
+            (a * d).partial_cmp(&(c * b))
         } else {
             f64::from(*self).partial_cmp(&f64::from(*rhs))
         }
     }
-    fn lt(&self, rhs: &PhoebeNumber) -> bool {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs < rhs
-        } else {
-            f64::from(*self) < f64::from(*rhs)
-        }
-    }
-    fn le(&self, rhs: &PhoebeNumber) -> bool {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs <= rhs
-        } else {
-            f64::from(*self) <= f64::from(*rhs)
-        }
-    }
-    fn gt(&self, rhs: &PhoebeNumber) -> bool {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs > rhs
-        } else {
-            f64::from(*self) > f64::from(*rhs)
-        }
-    }
-    fn ge(&self, rhs: &PhoebeNumber) -> bool {
-        if let (Some(lhs), Some(rhs)) = (i32::maybe_from(*self), i32::maybe_from(*rhs)) {
-            lhs >= rhs
-        } else {
-            f64::from(*self) >= f64::from(*rhs)
-        }
-    }
 }
 
 impl ops::Add for PhoebeNumber {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        if let (Some(l), Some(r)) = (i32::maybe_from(self), i32::maybe_from(other)) {
-            (l + r).into()
-        } else {
-            (f64::from(self) + f64::from(other)).into()
-        }
+        combine(
+            self,
+            other,
+            |(a, b), (c, d)| PhoebeNumber::ratio(a * d + c * b, b * d),
+            |l, r| PhoebeNumber::Float(l + r),
+        )
     }
 }
 
@@ -112,11 +161,12 @@ impl ops::AddAssign for PhoebeNumber {
 impl ops::Sub for PhoebeNumber {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
-        if let (Some(l), Some(r)) = (i32::maybe_from(self), i32::maybe_from(other)) {
-            (l - r).into()
-        } else {
-            (f64::from(self) - f64::from(other)).into()
-        }
+        combine(
+            self,
+            other,
+            |(a, b), (c, d)| PhoebeNumber::ratio(a * d - c * b, b * d),
+            |l, r| PhoebeNumber::Float(l - r),
+        )
     }
 }
 
@@ -129,11 +179,12 @@ impl ops::SubAssign for PhoebeNumber {
 impl ops::Mul for PhoebeNumber {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
-        if let (Some(l), Some(r)) = (i32::maybe_from(self), i32::maybe_from(other)) {
-            (l * r).into()
-        } else {
-            (f64::from(self) * f64::from(other)).into()
-        }
+        combine(
+            self,
+            other,
+            |(a, b), (c, d)| PhoebeNumber::ratio(a * c, b * d),
+            |l, r| PhoebeNumber::Float(l * r),
+        )
     }
 }
 
@@ -146,7 +197,12 @@ impl ops::MulAssign for PhoebeNumber {
 impl ops::Div for PhoebeNumber {
     type Output = Self;
     fn div(self, other: Self) -> Self {
-        Self::from(f64::from(self) / f64::from(other)).try_flatten()
+        combine(
+            self,
+            other,
+            |(a, b), (c, d)| PhoebeNumber::ratio(a * d, b * c),
+            |l, r| PhoebeNumber::Float(l / r).try_flatten(),
+        )
     }
 }
 
@@ -159,8 +215,8 @@ impl ops::DivAssign for PhoebeNumber {
 impl ops::Neg for PhoebeNumber {
     type Output = Self;
     fn neg(self) -> Self {
-        if let Some(n) = i32::maybe_from(self) {
-            PhoebeNumber::from(-n)
+        if let Some((n, d)) = self.as_fraction() {
+            PhoebeNumber::ratio(-n, d)
         } else {
             PhoebeNumber::from(-(f64::from(self)))
         }
@@ -173,6 +229,8 @@ impl MaybeFrom<Object> for PhoebeNumber {
             Some(PhoebeNumber::Float(f))
         } else if let Some(n) = i32::maybe_from(obj) {
             Some(PhoebeNumber::Integer(n))
+        } else if let Some(r) = <GcRef<Ratio>>::maybe_from(obj) {
+            Some(PhoebeNumber::Ratio(r.numerator(), r.denominator()))
         } else if let Some(reference) = Reference::maybe_from(obj) {
             PhoebeNumber::maybe_from(*reference)
         } else {
@@ -202,7 +260,7 @@ impl FromObject for PhoebeNumber {
     }
 
     fn is_type(obj: Object) -> bool {
-        f64::is_type(obj) || i32::is_type(obj)
+        f64::is_type(obj) || i32::is_type(obj) || <GcRef<Ratio>>::is_type(obj)
     }
 }
 
@@ -228,6 +286,7 @@ impl convert::From<PhoebeNumber> for f64 {
         match n {
             PhoebeNumber::Float(f) => f,
             PhoebeNumber::Integer(i) => f64::from(i),
+            PhoebeNumber::Ratio(n, d) => f64::from(n) / f64::from(d),
         }
     }
 }
@@ -246,10 +305,10 @@ impl convert::From<i32> for PhoebeNumber {
 
 impl convert::From<PhoebeNumber> for Object {
     fn from(n: PhoebeNumber) -> Object {
-        if let Some(n) = i32::maybe_from(n) {
-            Object::from(n)
-        } else {
-            Object::from(f64::from(n))
+        match n {
+            PhoebeNumber::Integer(n) => Object::from(n),
+            PhoebeNumber::Ratio(n, d) => Object::from(Ratio::allocate(Ratio::new(n, d))),
+            PhoebeNumber::Float(f) => Object::from(f),
         }
     }
 }
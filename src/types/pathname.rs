@@ -0,0 +1,68 @@
+//! Pathnames are `Native` payloads wrapping a `PathBuf`, so that file
+//! paths can be built and taken apart without splicing strings. They
+//! print as `[pathname "src/lib.rs"]`. Builtins which take a path,
+//! like `load` and `open`, accept either a pathname or a string.
+
+use super::native::Payload;
+use crate::prelude::*;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref PATHNAME_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"pathname") };
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pathname(PathBuf);
+
+impl Pathname {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Pathname {
+        Pathname(path.into())
+    }
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+    /// `self` followed by `other`, unless `other` is absolute, in
+    /// which case just `other`.
+    pub fn join<P: AsRef<Path>>(&self, other: P) -> Pathname {
+        Pathname(self.0.join(other))
+    }
+    /// `self` without its last component, or `None` if it is a root
+    /// or empty.
+    pub fn parent(&self) -> Option<Pathname> {
+        self.0.parent().map(Pathname::new)
+    }
+    /// The last component, or `None` if it is a root or ends in `..`.
+    pub fn file_name(&self) -> Option<String> {
+        self.0
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+    /// The path as a string. Any parts which aren't valid UTF-8 are
+    /// replaced with U+FFFD.
+    pub fn to_string_lossy(&self) -> String {
+        self.0.to_string_lossy().into_owned()
+    }
+}
+
+/// The path `obj` designates, if it is a pathname or a string.
+pub fn path_designated_by(obj: Object) -> Result<PathBuf, GcRef<Error>> {
+    if let Some(string) = <GcRef<PhoebeString>>::maybe_from(obj) {
+        return Ok(PathBuf::from(string.as_str()));
+    }
+    <GcRef<Native>>::maybe_from(obj)
+        .and_then(|n| n.downcast_ref::<Pathname>().map(|p| p.0.clone()))
+        .ok_or_else(|| Error::type_error(*PATHNAME_TYPE_NAME))
+}
+
+impl Payload for Pathname {
+    fn type_name(&self) -> GcRef<Symbol> {
+        *PATHNAME_TYPE_NAME
+    }
+}
+
+impl fmt::Display for Pathname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[pathname {:?}]", self.0)
+    }
+}
@@ -37,19 +37,27 @@ pub trait PointerTag: Into<u64> + Copy {
 
 /// There could conceivably be up to 16 variants of `ObjectTag`, as
 /// `Object` leaves 4 bits of tag between the NaN marker and the 48
-/// bit integer immediate.
+/// bit integer immediate. Rather than spend them all, less common
+/// heap types share `ObjectTag::Extended` and are distinguished by
+/// an `ExtendedTag` stored in the object itself.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(u64)]
 pub enum ObjectTag {
     Cons,
     Symbol,
-    // String,
+    String,
     Function,
     Error,
     Namespace,
     Immediate,
     Reference,
     HeapObject,
+    Vector,
+    HashTable,
+    Ratio,
+    Structure,
+    /// A pointer to an object which begins with an `ExtendedHeader`.
+    Extended,
 }
 
 impl convert::From<ObjectTag> for u64 {
@@ -70,6 +78,46 @@ impl PointerTag for ObjectTag {
     }
 }
 
+/// The type of an extended heap object. Unlike the other tags, this
+/// isn't stored in the bits of the `Object` but in the first word of
+/// the object it points to, so there's room for as many as we like.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u64)]
+pub enum ExtendedTag {
+    Bytes,
+    WeakRef,
+    Native,
+}
+
+/// Every extended heap object must be `#[repr(C)]` and have one of
+/// these as its first field.
+#[derive(Debug)]
+#[repr(C)]
+pub struct ExtendedHeader {
+    tag: ExtendedTag,
+}
+
+impl ExtendedHeader {
+    pub fn new(tag: ExtendedTag) -> ExtendedHeader {
+        ExtendedHeader { tag }
+    }
+    pub fn tag(&self) -> ExtendedTag {
+        self.tag
+    }
+}
+
+/// The `ExtendedTag` of the object `ptr` points to, if it is tagged
+/// `ObjectTag::Extended`. Unlike the rest of this module, this reads
+/// through `ptr`, which must therefore point to a live object.
+pub unsafe fn extended_tag_of(ptr: u64) -> Option<ExtendedTag> {
+    if ObjectTag::Extended.is_of_type(ptr) {
+        let header = ObjectTag::Extended.untag(ptr) as *const ExtendedHeader;
+        Some((*header).tag())
+    } else {
+        None
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(u64)]
 pub enum Floatp {
@@ -133,6 +181,9 @@ mod test {
         let as_a_symbol = ObjectTag::Symbol.tag(num);
         assert_eq!(ObjectTag::Symbol.untag(as_a_symbol), num);
 
+        let as_a_string = ObjectTag::String.tag(num);
+        assert_eq!(ObjectTag::String.untag(as_a_string), num);
+
         let as_a_namespace = ObjectTag::Namespace.tag(num);
         assert_eq!(ObjectTag::Namespace.untag(as_a_namespace), num);
 
@@ -144,5 +195,32 @@ mod test {
 
         let as_a_heap_object = ObjectTag::HeapObject.tag(num);
         assert_eq!(ObjectTag::HeapObject.untag(as_a_heap_object), num);
+
+        let as_a_vector = ObjectTag::Vector.tag(num);
+        assert_eq!(ObjectTag::Vector.untag(as_a_vector), num);
+
+        let as_a_hash_table = ObjectTag::HashTable.tag(num);
+        assert_eq!(ObjectTag::HashTable.untag(as_a_hash_table), num);
+
+        let as_a_ratio = ObjectTag::Ratio.tag(num);
+        assert_eq!(ObjectTag::Ratio.untag(as_a_ratio), num);
+
+        let as_a_structure = ObjectTag::Structure.tag(num);
+        assert_eq!(ObjectTag::Structure.untag(as_a_structure), num);
+
+        let as_extended = ObjectTag::Extended.tag(num);
+        assert_eq!(ObjectTag::Extended.untag(as_extended), num);
+    }
+    #[test]
+    fn extended_tags() {
+        let header = ExtendedHeader::new(ExtendedTag::WeakRef);
+        let ptr = &header as *const ExtendedHeader as u64;
+        unsafe {
+            assert_eq!(
+                extended_tag_of(ObjectTag::Extended.tag(ptr)),
+                Some(ExtendedTag::WeakRef)
+            );
+            assert_eq!(extended_tag_of(ObjectTag::Structure.tag(ptr)), None);
+        }
     }
 }
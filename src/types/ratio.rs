@@ -0,0 +1,98 @@
+//! Ratios are exact rational numbers, like `1/3`. They're always
+//! stored in lowest terms with a positive denominator greater than 1,
+//! so a ratio is never equal to an integer. Arithmetic on ratios
+//! happens in `PhoebeNumber`; `Ratio` is only how they're stored on
+//! the heap.
+
+use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::prelude::*;
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref RATIO_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"ratio") };
+}
+
+#[derive(Debug)]
+pub struct Ratio {
+    gc_marking: GcMark,
+    numerator: i32,
+    denominator: i32,
+}
+
+impl Ratio {
+    /// `numerator` and `denominator` must already be in lowest terms,
+    /// with `denominator > 1`. `PhoebeNumber::ratio` normalizes
+    /// arbitrary fractions.
+    pub fn new(numerator: i32, denominator: i32) -> Ratio {
+        debug_assert!(denominator > 1);
+        Ratio {
+            gc_marking: GcMark::default(),
+            numerator,
+            denominator,
+        }
+    }
+    pub fn numerator(&self) -> i32 {
+        self.numerator
+    }
+    pub fn denominator(&self) -> i32 {
+        self.denominator
+    }
+}
+
+impl GarbageCollected for Ratio {
+    type ConvertFrom = Ratio;
+    fn alloc_one_and_initialize(r: Ratio) -> ::std::ptr::NonNull<Ratio> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, r) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, _: bool) {}
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl convert::From<GcRef<Ratio>> for Object {
+    fn from(r: GcRef<Ratio>) -> Object {
+        Object::from_raw(ObjectTag::Ratio.tag(r.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<Ratio> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Ratio
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *RATIO_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<Ratio> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<Ratio> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut Ratio)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let nonnull = 0xdead_beef as *mut Ratio;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::gc::write_barrier;
 use crate::prelude::*;
-use std::{borrow, convert, fmt, ops};
+use std::{borrow, convert, fmt, mem, ops};
 
 lazy_static! {
     static ref REFERENCE_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"reference") };
@@ -9,6 +10,18 @@ lazy_static! {
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Reference(GcRef<Object>);
 
+impl Reference {
+    /// Store `value` in the referenced slot, returning the value it
+    /// replaces. Slots which may already be reachable should be
+    /// written this way rather than through `DerefMut`, so that the
+    /// collector can't miss the old value; see `gc::write_barrier`.
+    pub fn set(&mut self, value: Object) -> Object {
+        let old = mem::replace(&mut **self, value);
+        write_barrier(old);
+        old
+    }
+}
+
 impl<'any> convert::From<&'any mut Object> for Reference {
     fn from(r: &mut Object) -> Reference {
         Reference(unsafe { GcRef::from_ptr(r as *mut Object) })
@@ -0,0 +1,44 @@
+//! Regexes are `Native` payloads wrapping a pattern compiled by the
+//! `regex` crate, whose documentation describes the syntax. They
+//! print as `[regex "a+b"]`.
+
+use super::native::Payload;
+use crate::prelude::*;
+use std::fmt;
+
+lazy_static! {
+    static ref REGEX_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"regex") };
+}
+
+pub struct Regex(::regex::Regex);
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, ::regex::Error> {
+        Ok(Regex(::regex::Regex::new(pattern)?))
+    }
+    /// The text of the leftmost match in `text` and of each of its
+    /// capture groups, with `None` for groups which didn't
+    /// participate, or `None` if there is no match.
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Vec<Option<&'t str>>> {
+        self.0
+            .captures(text)
+            .map(|caps| caps.iter().map(|m| m.map(|m| m.as_str())).collect())
+    }
+    /// `text` with every match replaced by `replacement`, in which
+    /// `$1` or `${name}` stand for capture groups.
+    pub fn replace_all(&self, text: &str, replacement: &str) -> String {
+        self.0.replace_all(text, replacement).into_owned()
+    }
+}
+
+impl Payload for Regex {
+    fn type_name(&self) -> GcRef<Symbol> {
+        *REGEX_TYPE_NAME
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[regex {:?}]", self.0.as_str())
+    }
+}
@@ -0,0 +1,226 @@
+//! Streams are `Native` payloads which read bytes from or write bytes
+//! to a file, a string, or one of the thread's standard streams. They
+//! print as `[stream path/to/file]`, or `[stream string]` for string
+//! streams, with ` closed` appended once closed.
+
+use super::native::Payload;
+use crate::prelude::*;
+use crate::printer::ColumnTracker;
+use crate::reader;
+use crate::standard_streams::{with_standard_error, with_standard_input, with_standard_output};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::iter::Peekable;
+use std::path::Path;
+use std::{fmt, mem};
+
+lazy_static! {
+    static ref STREAM_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"stream") };
+    static ref INPUT_STREAM: GcRef<Symbol> = { symbol_lookup::make_symbol(b"input-stream") };
+    static ref OUTPUT_STREAM: GcRef<Symbol> = { symbol_lookup::make_symbol(b"output-stream") };
+}
+
+/// How `Stream::open` opens a file.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Input,
+    /// Create the file, or truncate it if it exists.
+    Output,
+    /// Create the file, or write to its end if it exists.
+    Append,
+}
+
+enum Input {
+    /// This thread's standard input, as installed by the REPL.
+    Standard,
+    Bytes(Peekable<Box<Iterator<Item = u8>>>),
+}
+
+enum Output {
+    /// This thread's standard output, as installed by the REPL.
+    Standard,
+    /// This thread's standard error, as installed by the REPL.
+    StandardError,
+    /// Accumulates what's written to it until it's retrieved by
+    /// `Stream::take_output_string`.
+    String(ColumnTracker<Vec<u8>>),
+    File(ColumnTracker<BufWriter<File>>),
+}
+
+pub struct Stream {
+    /// Printed to identify the stream.
+    description: String,
+    input: Option<Input>,
+    output: Option<Output>,
+    open: bool,
+}
+
+impl Stream {
+    fn new(description: &str, input: Option<Input>, output: Option<Output>) -> Stream {
+        Stream {
+            description: description.to_owned(),
+            input,
+            output,
+            open: true,
+        }
+    }
+    pub fn standard_input() -> Stream {
+        Stream::new("standard-input", Some(Input::Standard), None)
+    }
+    pub fn standard_output() -> Stream {
+        Stream::new("standard-output", None, Some(Output::Standard))
+    }
+    pub fn standard_error() -> Stream {
+        Stream::new("error-output", None, Some(Output::StandardError))
+    }
+    /// An input stream which reads the bytes of `string`.
+    pub fn from_string(string: &str) -> Stream {
+        let bytes: Box<Iterator<Item = u8>> = Box::new(string.as_bytes().to_vec().into_iter());
+        Stream::new("string", Some(Input::Bytes(bytes.peekable())), None)
+    }
+    /// An output stream whose contents are retrieved by
+    /// `take_output_string`.
+    pub fn string_output() -> Stream {
+        let output = Output::String(ColumnTracker::new(Vec::new()));
+        Stream::new("string", None, Some(output))
+    }
+    pub fn open(path: &Path, direction: Direction) -> io::Result<Stream> {
+        let description = path.to_string_lossy();
+        Ok(match direction {
+            Direction::Input => {
+                // Reading stops at the first error, as it would at
+                // the end of the file.
+                let bytes = BufReader::new(File::open(path)?)
+                    .bytes()
+                    .scan((), |_, b| b.ok());
+                let bytes: Box<Iterator<Item = u8>> = Box::new(bytes);
+                Stream::new(&description, Some(Input::Bytes(bytes.peekable())), None)
+            }
+            Direction::Output | Direction::Append => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(direction == Direction::Output)
+                    .append(direction == Direction::Append)
+                    .open(path)?;
+                let output = Output::File(ColumnTracker::new(BufWriter::new(file)));
+                Stream::new(&description, None, Some(output))
+            }
+        })
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    /// Flush any buffered output and release the file, if any.
+    /// Closing a stream twice does nothing.
+    pub fn close(&mut self) -> io::Result<()> {
+        if let Some(Output::File(ref mut file)) = self.output {
+            file.flush()?;
+        }
+        self.open = false;
+        self.input = None;
+        self.output = None;
+        Ok(())
+    }
+    fn check_open(&self) -> Result<(), EvaluatorError> {
+        if self.open {
+            Ok(())
+        } else {
+            Err(EvaluatorError::ClosedStream)
+        }
+    }
+    /// Call `fun` on this stream's input.
+    pub fn with_input<F, T>(&mut self, fun: F) -> Result<T, EvaluatorError>
+    where
+        F: FnOnce(&mut Iterator<Item = u8>) -> T,
+    {
+        self.check_open()?;
+        match self.input {
+            Some(Input::Standard) => Ok(with_standard_input(fun)),
+            Some(Input::Bytes(ref mut bytes)) => Ok(fun(bytes)),
+            None => Err(ConversionError::wanted(*INPUT_STREAM).into()),
+        }
+    }
+    /// Read one form from this stream, returning `None` at the end of
+    /// input. The standard input can't be peeked at between reads, so
+    /// reading from it consumes the byte which ends each form.
+    pub fn read_form(&mut self) -> Result<Option<Object>, EvaluatorError> {
+        self.check_open()?;
+        match self.input {
+            Some(Input::Standard) => Ok(with_standard_input(|input| {
+                reader::read(&mut input.peekable())
+            })?),
+            Some(Input::Bytes(ref mut bytes)) => Ok(reader::read(bytes)?),
+            None => Err(ConversionError::wanted(*INPUT_STREAM).into()),
+        }
+    }
+    /// Call `fun` on this stream's output.
+    pub fn with_output<F, T>(&mut self, fun: F) -> Result<T, EvaluatorError>
+    where
+        F: FnOnce(&mut ColumnTracker<Write>) -> T,
+    {
+        self.check_open()?;
+        match self.output {
+            Some(Output::Standard) => Ok(with_standard_output(fun)),
+            Some(Output::StandardError) => {
+                // The standard error doesn't track its column, so
+                // `fun` writes to a buffer which is then copied to it.
+                let mut buffer = ColumnTracker::new(Vec::new());
+                let result = fun(&mut buffer);
+                with_standard_error(|e| e.write_all(buffer.get_mut()))?;
+                Ok(result)
+            }
+            Some(Output::String(ref mut string)) => Ok(fun(string)),
+            Some(Output::File(ref mut file)) => Ok(fun(file)),
+            None => Err(ConversionError::wanted(*OUTPUT_STREAM).into()),
+        }
+    }
+    /// Everything written to a string output stream since it was made
+    /// or this was last called, or `None` if this isn't a string
+    /// output stream.
+    pub fn take_output_string(&mut self) -> Option<String> {
+        match self.output {
+            Some(Output::String(ref mut string)) => {
+                let bytes = mem::replace(string.get_mut(), Vec::new());
+                Some(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Payload for Stream {
+    fn type_name(&self) -> GcRef<Symbol> {
+        *STREAM_TYPE_NAME
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[stream {}", self.description)?;
+        if !self.open {
+            write!(f, " closed")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn string_streams() {
+        let mut input = Stream::from_string("(1 2) foo");
+        let form = input.read_form().unwrap().unwrap();
+        assert_eq!(format!("{}", form), "(1 2)");
+        assert!(input.with_output(|_| ()).is_err());
+
+        let mut output = Stream::string_output();
+        output.with_output(|o| write!(o, "bar")).unwrap().unwrap();
+        assert_eq!(output.take_output_string().unwrap(), "bar");
+        assert_eq!(output.take_output_string().unwrap(), "");
+        output.close().unwrap();
+        assert_eq!(format!("{}", output), "[stream string closed]");
+        assert!(output.with_output(|_| ()).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Phoebe's strings are immutable sequences of UTF-8 text. Like a
+//! `Symbol`, a `PhoebeString` stores its bytes inline, directly after
+//! its header, so each string is a single allocation.
+
+use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::prelude::*;
+use crate::symbol_lookup::make_symbol;
+use std::alloc::{Alloc, Global, Layout};
+use std::ptr::NonNull;
+use std::{cmp, convert, fmt, hash, mem, ptr, slice, str};
+
+lazy_static! {
+    static ref STRING_TYPE_NAME: GcRef<Symbol> = { make_symbol(b"string") };
+}
+
+/// Allocate a `PhoebeString` containing `text`.
+pub fn make_string(text: &str) -> GcRef<PhoebeString> {
+    PhoebeString::allocate(text as *const str)
+}
+
+pub struct PhoebeString {
+    gc_marking: GcMark,
+    length: usize,
+    head: u8,
+}
+
+impl hash::Hash for PhoebeString {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: hash::Hasher,
+    {
+        self.as_str().hash(state);
+    }
+}
+
+impl cmp::PartialEq for PhoebeString {
+    fn eq(&self, other: &PhoebeString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl cmp::Eq for PhoebeString {}
+
+impl GarbageCollected for PhoebeString {
+    /// As for `Symbol`, this must be a **valid** `*const str`, which
+    /// need only live until `alloc_one_and_initialize` returns.
+    type ConvertFrom = *const str;
+
+    fn alloc_one_and_initialize(text: *const str) -> NonNull<PhoebeString> {
+        use std::default::Default;
+
+        let text = unsafe { &*text };
+
+        let layout = PhoebeString::make_layout(text.len());
+
+        #[cfg_attr(feature = "cargo-clippy", allow(cast_ptr_alignment))]
+        let pointer = unsafe { Global.alloc(layout) }.unwrap().as_ptr() as *mut PhoebeString;
+        let string_ref = unsafe { &mut *pointer };
+        string_ref.gc_marking = GcMark::default();
+        string_ref.length = text.len();
+        unsafe {
+            ptr::copy_nonoverlapping(text.as_ptr(), string_ref.pointer_mut(), text.len());
+        }
+        unsafe { NonNull::new_unchecked(pointer) }
+    }
+    unsafe fn deallocate(obj: GcRef<Self>) {
+        let p = obj.into_ptr();
+        let layout = (&*p).my_layout();
+        Global.dealloc(NonNull::new_unchecked(p as *mut u8), layout);
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, _: bool) {}
+}
+
+impl PhoebeString {
+    pub fn as_str(&self) -> &str {
+        // `alloc_one_and_initialize` only accepts valid UTF-8, and
+        // strings are never mutated.
+        unsafe { str::from_utf8_unchecked(slice::from_raw_parts(self.pointer(), self.len())) }
+    }
+    /// The length of this string in bytes, as for `str::len`.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The number of bytes allocated for this string, including its
+    /// text.
+    pub fn allocated_size(&self) -> usize {
+        self.my_layout().size()
+    }
+    fn my_layout(&self) -> Layout {
+        PhoebeString::make_layout(self.len())
+    }
+    fn make_layout(len: usize) -> Layout {
+        // A `PhoebeString` has room for one byte of text inline, so
+        // an empty string is allocated with one byte to spare.
+        Layout::from_size_align(
+            mem::size_of::<PhoebeString>() + len.max(1) - 1,
+            mem::align_of::<PhoebeString>(),
+        )
+        .unwrap()
+    }
+    fn pointer(&self) -> *const u8 {
+        (&self.head) as *const u8
+    }
+    fn pointer_mut(&mut self) -> *mut u8 {
+        (&mut self.head) as *mut u8
+    }
+}
+
+impl convert::AsRef<str> for PhoebeString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Strings print readably, surrounded by double quotes and with any
+/// double quotes or backslashes in them escaped, so that the reader
+/// can read them back in.
+impl fmt::Display for PhoebeString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.as_str().chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{}", c)?,
+                _ => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+impl fmt::Debug for PhoebeString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[string {}]", self)
+    }
+}
+
+impl convert::From<GcRef<PhoebeString>> for Object {
+    fn from(s: GcRef<PhoebeString>) -> Object {
+        Object::from_raw(ObjectTag::String.tag(s.into_ptr() as u64))
+    }
+}
+
+impl<'a> convert::From<&'a str> for Object {
+    fn from(s: &'a str) -> Object {
+        Object::from(make_string(s))
+    }
+}
+
+impl convert::From<String> for Object {
+    fn from(s: String) -> Object {
+        Object::from(make_string(&s))
+    }
+}
+
+impl convert::From<GcRef<PhoebeString>> for String {
+    fn from(s: GcRef<PhoebeString>) -> String {
+        s.as_str().to_owned()
+    }
+}
+
+impl FromObject for GcRef<PhoebeString> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::String
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *STRING_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<PhoebeString> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<PhoebeString> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut PhoebeString)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let nonnull = 0xdead_beef as *mut PhoebeString;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+    #[test]
+    fn print_readably() {
+        let s = make_string("say \"hi\" \\ bye");
+        assert_eq!(s.as_str(), "say \"hi\" \\ bye");
+        assert_eq!(format!("{}", *s), "\"say \\\"hi\\\" \\\\ bye\"");
+        assert_eq!(String::from(make_string("")), "");
+    }
+}
@@ -0,0 +1,130 @@
+//! Structures are instances of the record types `defstruct` defines:
+//! a type name, the names of the type's slots, and a vector holding
+//! each slot's value. Every instance of a type shares the same list of
+//! slot names. They print as `#S(point :x 1 :y 2)`.
+
+use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::gc::write_barrier;
+use crate::prelude::*;
+use std::{convert, fmt, mem};
+
+lazy_static! {
+    static ref STRUCTURE_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"structure") };
+}
+
+#[derive(Debug)]
+pub struct Structure {
+    gc_marking: GcMark,
+    name: GcRef<Symbol>,
+    slot_names: List,
+    slots: Vec<Object>,
+}
+
+impl Structure {
+    /// A structure of type `name` whose slots, named by `slot_names`,
+    /// hold `slots`. `slot_names` and `slots` must be the same
+    /// length.
+    pub fn new(name: GcRef<Symbol>, slot_names: List, slots: Vec<Object>) -> Structure {
+        debug_assert_eq!(slot_names.count(), slots.len());
+        Structure {
+            gc_marking: GcMark::default(),
+            name,
+            slot_names,
+            slots,
+        }
+    }
+    /// The name of this structure's type, which `type-of` returns.
+    pub fn name(&self) -> GcRef<Symbol> {
+        self.name
+    }
+    pub fn slot_names(&self) -> List {
+        self.slot_names
+    }
+    pub fn slots(&self) -> &[Object] {
+        &self.slots
+    }
+    pub fn slots_mut(&mut self) -> &mut [Object] {
+        &mut self.slots
+    }
+    /// Store `value` in the slot at `index`, returning its old value.
+    /// Unlike writing through `slots_mut`, this is safe while the
+    /// collector is marking; see `gc::write_barrier`.
+    pub fn set_slot(&mut self, index: usize, value: Object) -> Object {
+        let old = mem::replace(&mut self.slots[index], value);
+        write_barrier(old);
+        old
+    }
+    /// A reference to the slot at `index`, which `setf` can assign
+    /// through.
+    pub fn ref_slot(&mut self, index: usize) -> Reference {
+        Reference::from(&mut self.slots[index])
+    }
+}
+
+impl GarbageCollected for Structure {
+    type ConvertFrom = Structure;
+    fn alloc_one_and_initialize(s: Structure) -> ::std::ptr::NonNull<Structure> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, s) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        self.name.gc_mark(mark);
+        Object::from(self.slot_names).gc_mark(mark);
+        for obj in &self.slots {
+            obj.gc_mark(mark);
+        }
+    }
+}
+
+impl fmt::Display for Structure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#S({}", self.name)?;
+        for (name, value) in self.slot_names.zip(self.slots.iter()) {
+            write!(f, " :{} {}", name, value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl convert::From<GcRef<Structure>> for Object {
+    fn from(s: GcRef<Structure>) -> Object {
+        Object::from_raw(ObjectTag::Structure.tag(s.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<Structure> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Structure
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *STRUCTURE_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<Structure> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<Structure> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut Structure)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let nonnull = 0xdead_beef as *mut Structure;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::gc::write_barrier;
 use crate::prelude::*;
 use crate::symbol_lookup::make_symbol;
 use std::alloc::{Alloc, Global, Layout};
@@ -7,14 +8,42 @@ use std::{convert, fmt, hash, mem, ptr, slice, str};
 
 lazy_static! {
     static ref SYMBOL_TYPE_NAME: GcRef<Symbol> = { make_symbol(b"symbol") };
+    static ref KEYWORD_TYPE_NAME: GcRef<Symbol> = { make_symbol(b"keyword") };
 }
 
+/// A symbol's name is stored inline, starting at `head`, so `head`
+/// must be the last field; `repr(C)` keeps the compiler from
+/// reordering it.
+#[repr(C)]
 pub struct Symbol {
     gc_marking: GcMark,
+    /// The property list, alternating indicators and values, as in
+    /// `(color red size 3)`.
+    plist: Object,
+    /// Set by `make_symbol` when interning a name like `:foo`.
+    /// Uninterned symbols are never keywords, even if their names
+    /// start with a colon.
+    keyword: bool,
+    /// Set by `make_symbol`, for symbols which Rust code may hold
+    /// anywhere, so the intern table must never drop them.
+    permanent: bool,
+    /// Boxed, since most symbols never have any.
+    metadata: Option<Box<Metadata>>,
     length: usize,
     head: u8,
 }
 
+/// Per-symbol data which is rarely present, allocated the first time
+/// any of it is set.
+#[derive(Default)]
+struct Metadata {
+    /// Usually a docstring, but any object will do.
+    documentation: Option<Object>,
+    /// Where the symbol was defined, in whatever form the definer
+    /// chose, like `("init.phoebe" 12 1)`.
+    source_location: Option<Object>,
+}
+
 impl hash::Hash for Symbol {
     fn hash<H>(&self, state: &mut H)
     where
@@ -44,6 +73,12 @@ impl GarbageCollected for Symbol {
         let pointer = unsafe { Global.alloc(layout) }.unwrap().as_ptr() as *mut Symbol;
         let sym_ref = unsafe { &mut *pointer };
         sym_ref.gc_marking = GcMark::default();
+        sym_ref.plist = Object::nil();
+        sym_ref.keyword = false;
+        sym_ref.permanent = false;
+        // The memory is uninitialized, so assigning would drop
+        // garbage.
+        unsafe { ptr::write(&mut sym_ref.metadata, None) };
         sym_ref.length = text.len();
         unsafe {
             ptr::copy_nonoverlapping(text.as_ptr(), sym_ref.pointer_mut(), text.len());
@@ -53,30 +88,122 @@ impl GarbageCollected for Symbol {
     unsafe fn deallocate(obj: GcRef<Self>) {
         let p = obj.into_ptr();
         ptr::drop_in_place((&mut *p).as_mut() as *mut [u8]);
+        ptr::drop_in_place(&mut (*p).metadata);
         let layout = (&*p).my_layout();
         Global.dealloc(NonNull::new_unchecked(p as *mut u8), layout);
     }
     fn my_marking(&self) -> &GcMark {
         &self.gc_marking
     }
-    fn gc_mark_children(&mut self, _: usize) {}
+    fn gc_mark_children(&mut self, mark: bool) {
+        self.plist.gc_mark(mark);
+        if let Some(ref metadata) = self.metadata {
+            for obj in metadata
+                .documentation
+                .iter()
+                .chain(&metadata.source_location)
+            {
+                obj.gc_mark(mark);
+            }
+        }
+    }
 }
 
 impl Symbol {
-    pub fn with_colon_in_front(&self) -> GcRef<Symbol> {
-        let mut vec = Vec::with_capacity(self.len() + 1);
-        vec.push(b':');
-        vec.extend_from_slice(self.as_ref());
-        symbol_lookup::make_symbol(&vec)
+    /// `true` iff interning `name` should make a keyword: it starts
+    /// with a colon and isn't just `:`.
+    pub fn names_keyword(name: &[u8]) -> bool {
+        name.len() > 1 && name[0] == b':'
+    }
+    /// Only `make_symbol` should call this, right after allocating an
+    /// interned symbol whose name `names_keyword`.
+    pub(crate) fn make_keyword(&mut self) {
+        debug_assert!(Symbol::names_keyword(self.as_ref()));
+        self.keyword = true;
+    }
+    pub fn is_keyword(&self) -> bool {
+        self.keyword
+    }
+    /// Only `make_symbol` should call this, while it holds
+    /// `SYMBOLS_HEAP`.
+    pub(crate) fn make_permanent(&mut self) {
+        self.permanent = true;
+    }
+    /// `true` iff the intern table should keep this symbol even if
+    /// nothing else refers to it: it was interned by Rust code, or
+    /// dropping it would lose its plist, documentation or source
+    /// location.
+    pub fn is_pinned(&self) -> bool {
+        self.permanent || !self.plist.nilp() || self.metadata.is_some()
     }
     fn is_self_evaluating(&self) -> bool {
-        // The symbols `:` and `&` are *not* self-evaluating, but any
-        // other symbols which start with `&` or `:` are.
-        (self.len() > 1) && self.as_ref()[0] == b':' || self.as_ref()[0] == b'&'
+        // Keywords are self-evaluating, as are any symbols which
+        // start with `&`, like `&optional`.
+        self.is_keyword() || self.as_ref().first() == Some(&b'&')
+    }
+    pub fn plist(&self) -> Object {
+        self.plist
+    }
+    pub fn set_plist(&mut self, plist: Object) {
+        write_barrier(mem::replace(&mut self.plist, plist));
+    }
+    /// The cons in the plist whose car is the value stored under
+    /// `indicator`, compared with `eq`.
+    fn plist_cell(&self, indicator: Object) -> Option<GcRef<Cons>> {
+        let mut rest = self.plist;
+        while let Some(c) = <GcRef<Cons>>::maybe_from(rest) {
+            let value = <GcRef<Cons>>::maybe_from(c.cdr)?;
+            if c.car == indicator {
+                return Some(value);
+            }
+            rest = value.cdr;
+        }
+        None
+    }
+    /// The value stored under `indicator` in the plist.
+    pub fn get(&self, indicator: Object) -> Option<Object> {
+        self.plist_cell(indicator).map(|c| c.car)
+    }
+    /// Store `value` under `indicator` in the plist, replacing any
+    /// existing value.
+    pub fn put(&mut self, indicator: Object, value: Object) {
+        match self.plist_cell(indicator) {
+            Some(mut c) => {
+                c.set_car(value);
+            }
+            None => {
+                let tail = Object::from(Cons::allocate(Cons::new(value, self.plist)));
+                self.plist = Object::from(Cons::allocate(Cons::new(indicator, tail)));
+            }
+        }
+    }
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        self.metadata.get_or_insert_with(Box::default)
+    }
+    pub fn documentation(&self) -> Option<Object> {
+        self.metadata.as_ref().and_then(|m| m.documentation)
+    }
+    pub fn set_documentation(&mut self, documentation: Object) {
+        if let Some(old) = self.metadata_mut().documentation.replace(documentation) {
+            write_barrier(old);
+        }
+    }
+    pub fn source_location(&self) -> Option<Object> {
+        self.metadata.as_ref().and_then(|m| m.source_location)
+    }
+    pub fn set_source_location(&mut self, location: Object) {
+        if let Some(old) = self.metadata_mut().source_location.replace(location) {
+            write_barrier(old);
+        }
     }
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+    /// The number of bytes allocated for this symbol, including its
+    /// name.
+    pub fn allocated_size(&self) -> usize {
+        self.my_layout().size()
+    }
     fn my_layout(&self) -> Layout {
         Symbol::make_layout(self.len())
     }
@@ -149,6 +276,56 @@ impl FromUnchecked<Object> for GcRef<Symbol> {
     }
 }
 
+/// A `Symbol` which is a keyword, like `:foo`. Keywords share
+/// `ObjectTag::Symbol`, and are distinguished by a flag in their
+/// header, so every keyword is also a `GcRef<Symbol>`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Keyword(GcRef<Symbol>);
+
+impl Keyword {
+    pub fn symbol(self) -> GcRef<Symbol> {
+        self.0
+    }
+    /// This keyword's name without its leading colon, which is the
+    /// name of the `&key` argument it supplies.
+    pub fn name(&self) -> &[u8] {
+        &AsRef::<[u8]>::as_ref(&*self.0)[1..]
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", *self.0)
+    }
+}
+
+impl convert::From<Keyword> for Object {
+    fn from(k: Keyword) -> Object {
+        Object::from(k.0)
+    }
+}
+
+impl FromObject for Keyword {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Symbol
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *KEYWORD_TYPE_NAME
+    }
+    fn is_type(obj: Object) -> bool {
+        <GcRef<Symbol>>::is_type(obj)
+            && unsafe { <GcRef<Symbol>>::from_unchecked(obj) }.is_keyword()
+    }
+}
+
+impl FromUnchecked<Object> for Keyword {
+    unsafe fn from_unchecked(obj: Object) -> Keyword {
+        debug_assert!(Self::is_type(obj));
+        Keyword(GcRef::from_unchecked(obj))
+    }
+}
+
 impl Evaluate for Symbol {
     fn evaluate(&self) -> Object {
         let gc_r = unsafe { GcRef::from_ptr(self as *const Self as *mut Self) };
@@ -179,4 +356,27 @@ mod test {
             crate::symbol_lookup::make_symbol(b"symbol")
         );
     }
+    #[test]
+    fn keywords() {
+        let keyword = Object::from(make_symbol(b":keyword-test"));
+        assert_eq!(
+            Keyword::maybe_from(keyword).unwrap().name(),
+            b"keyword-test"
+        );
+        assert!(Keyword::maybe_from(Object::from(make_symbol(b"keyword-test"))).is_none());
+        assert!(Keyword::maybe_from(Object::from(make_symbol(b":"))).is_none());
+        let uninterned = crate::symbol_lookup::make_uninterned_symbol(b":keyword-test");
+        assert!(!uninterned.is_keyword());
+    }
+    #[test]
+    fn metadata() {
+        let mut sym = make_symbol(b"metadata-test");
+        assert!(sym.documentation().is_none());
+        assert!(sym.source_location().is_none());
+        sym.set_documentation(Object::from(1));
+        assert_eq!(sym.documentation(), Some(Object::from(1)));
+        assert!(sym.source_location().is_none());
+        sym.set_source_location(Object::from(2));
+        assert_eq!(sym.source_location(), Some(Object::from(2)));
+    }
 }
@@ -0,0 +1,143 @@
+//! Timestamps are `Native` payloads holding a moment in time as
+//! nanoseconds since the Unix epoch, 1970-01-01T00:00:00Z. They print
+//! in UTC as `[timestamp 2018-07-04T12:00:00.000000000Z]`.
+
+use super::native::Payload;
+use crate::prelude::*;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref TIMESTAMP_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"timestamp") };
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const SECONDS_PER_DAY: i128 = 86_400;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Timestamp {
+    nanos: i128,
+}
+
+/// A `Timestamp` broken down into its UTC calendar date and time of
+/// day.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DecodedTime {
+    pub year: i64,
+    /// From 1 to 12.
+    pub month: u32,
+    /// From 1 to 31.
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanosecond: u32,
+}
+
+impl Timestamp {
+    pub fn now() -> Timestamp {
+        let nanos = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(e) => -(e.duration().as_nanos() as i128),
+        };
+        Timestamp { nanos }
+    }
+    pub fn from_nanos(nanos: i128) -> Timestamp {
+        Timestamp { nanos }
+    }
+    pub fn from_seconds(seconds: f64) -> Timestamp {
+        Timestamp {
+            nanos: (seconds * NANOS_PER_SECOND as f64).round() as i128,
+        }
+    }
+    /// Nanoseconds since the Unix epoch.
+    pub fn nanos(self) -> i128 {
+        self.nanos
+    }
+    /// Seconds since the Unix epoch.
+    pub fn seconds(self) -> f64 {
+        self.nanos as f64 / NANOS_PER_SECOND as f64
+    }
+    /// The seconds from `earlier` to `self`, which are negative if
+    /// `earlier` is in fact later.
+    pub fn seconds_since(self, earlier: Timestamp) -> f64 {
+        (self.nanos - earlier.nanos) as f64 / NANOS_PER_SECOND as f64
+    }
+    pub fn decode(self) -> DecodedTime {
+        let seconds = self.nanos.div_euclid(NANOS_PER_SECOND);
+        let nanosecond = self.nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let second_of_day = seconds.rem_euclid(SECONDS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days as i64);
+        DecodedTime {
+            year,
+            month,
+            day,
+            hour: second_of_day / 3600,
+            minute: second_of_day / 60 % 60,
+            second: second_of_day % 60,
+            nanosecond,
+        }
+    }
+    /// This moment in ISO 8601 format, in UTC, like
+    /// `2018-07-04T12:00:00.000000000Z`.
+    pub fn iso8601(self) -> String {
+        let d = self.decode();
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+            d.year, d.month, d.day, d.hour, d.minute, d.second, d.nanosecond,
+        )
+    }
+}
+
+/// The year, month and day of the proleptic Gregorian calendar which
+/// is `days` days after 1970-01-01. This is Howard Hinnant's
+/// `civil_from_days`, which counts in 400-year eras beginning on
+/// March 1st so that leap days fall at the end of each year.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+impl Payload for Timestamp {
+    fn type_name(&self) -> GcRef<Symbol> {
+        *TIMESTAMP_TYPE_NAME
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[timestamp {}]", self.iso8601())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(17_716), (2018, 7, 4));
+    }
+    #[test]
+    fn decode_before_the_epoch() {
+        let t = Timestamp::from_nanos(-1);
+        assert_eq!(t.iso8601(), "1969-12-31T23:59:59.999999999Z");
+    }
+}
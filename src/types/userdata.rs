@@ -0,0 +1,116 @@
+//! Userdata lets an embedding application pass its own Rust values
+//! through Phoebe code. A `Userdata` is a `Native` payload which owns
+//! an arbitrary `Send` value along with the type name `type-of`
+//! returns for it, and optionally a finalizer, which runs when the
+//! garbage collector frees it, and a printer. Without a printer, it
+//! prints as its type name in brackets, like `[file-handle]`.
+
+use super::native::Payload;
+use crate::prelude::*;
+use std::any::Any;
+use std::{convert, fmt};
+
+type Finalizer = Box<FnMut(&mut Any) + Send>;
+type Printer = Box<Fn(&Any, &mut fmt::Formatter) -> fmt::Result + Send>;
+
+pub struct Userdata {
+    type_name: GcRef<Symbol>,
+    value: Box<Any + Send>,
+    finalizer: Option<Finalizer>,
+    printer: Option<Printer>,
+}
+
+impl Userdata {
+    pub fn new<T: Any + Send>(type_name: &str, value: T) -> Userdata {
+        Userdata {
+            type_name: symbol_lookup::make_symbol(type_name.as_bytes()),
+            value: Box::new(value),
+            finalizer: None,
+            printer: None,
+        }
+    }
+    /// Run `finalizer` on the value when this userdata is freed.
+    /// The garbage collector runs in its own thread, so `finalizer`
+    /// may be called from any thread.
+    pub fn with_finalizer<T, F>(mut self, mut finalizer: F) -> Userdata
+    where
+        T: Any,
+        F: FnMut(&mut T) + Send + 'static,
+    {
+        let finalizer = move |value: &mut Any| {
+            if let Some(value) = value.downcast_mut() {
+                finalizer(value);
+            }
+        };
+        self.finalizer = Some(Box::new(finalizer));
+        self
+    }
+    /// Print this userdata by calling `printer` on the value.
+    pub fn with_printer<T, F>(mut self, printer: F) -> Userdata
+    where
+        T: Any,
+        F: Fn(&T, &mut fmt::Formatter) -> fmt::Result + Send + 'static,
+    {
+        let printer = move |value: &Any, f: &mut fmt::Formatter| match value.downcast_ref() {
+            Some(value) => printer(value, f),
+            None => Ok(()),
+        };
+        self.printer = Some(Box::new(printer));
+        self
+    }
+    pub fn type_name(&self) -> GcRef<Symbol> {
+        self.type_name
+    }
+    /// The value, if it is a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+    /// The value, if it is a `T`.
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.value.downcast_mut()
+    }
+}
+
+/// Call `f` on the value `obj` holds, if `obj` is a userdata holding
+/// a `T`.
+pub fn with_userdata<T, F, R>(obj: Object, f: F) -> Option<R>
+where
+    T: Any,
+    F: FnOnce(&mut T) -> R,
+{
+    let mut native = <GcRef<Native>>::maybe_from(obj)?;
+    let userdata = native.downcast_mut::<Userdata>()?;
+    userdata.downcast_mut().map(f)
+}
+
+impl Drop for Userdata {
+    fn drop(&mut self) {
+        if let Some(mut finalizer) = self.finalizer.take() {
+            finalizer(&mut *self.value);
+        }
+    }
+}
+
+impl Payload for Userdata {
+    fn type_name(&self) -> GcRef<Symbol> {
+        self.type_name
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        self.type_name.gc_mark(mark);
+    }
+}
+
+impl fmt::Display for Userdata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.printer {
+            Some(ref printer) => printer(&*self.value, f),
+            None => write!(f, "[{}]", self.type_name),
+        }
+    }
+}
+
+impl convert::From<Userdata> for Object {
+    fn from(userdata: Userdata) -> Object {
+        Object::from(Native::allocate(Native::new(userdata)))
+    }
+}
@@ -0,0 +1,127 @@
+//! Vectors are growable, one-dimensional arrays of `Object`s with
+//! constant-time indexing. They read and print as `#(1 2 3)`.
+
+use super::pointer_tagging::{ObjectTag, PointerTag};
+use crate::gc::write_barrier;
+use crate::prelude::*;
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref VECTOR_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"vector") };
+}
+
+#[derive(Debug)]
+pub struct Vector {
+    gc_marking: GcMark,
+    elements: Vec<Object>,
+}
+
+impl Vector {
+    pub fn new(elements: Vec<Object>) -> Vector {
+        Vector {
+            gc_marking: GcMark::default(),
+            elements,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+    pub fn elements(&self) -> &[Object] {
+        &self.elements
+    }
+    pub fn elements_mut(&mut self) -> &mut [Object] {
+        &mut self.elements
+    }
+    /// A reference to the element at `index`, which `setf` can
+    /// assign through. The reference is only valid until the vector
+    /// next grows or shrinks.
+    pub fn ref_element(&mut self, index: usize) -> Result<Reference, EvaluatorError> {
+        let length = self.len();
+        self.elements
+            .get_mut(index)
+            .map(Reference::from)
+            .ok_or(EvaluatorError::IndexOutOfBounds { index, length })
+    }
+    pub fn push(&mut self, obj: Object) {
+        self.elements.push(obj);
+    }
+    pub fn pop(&mut self) -> Option<Object> {
+        let popped = self.elements.pop();
+        if let Some(obj) = popped {
+            write_barrier(obj);
+        }
+        popped
+    }
+}
+
+impl GarbageCollected for Vector {
+    type ConvertFrom = Vector;
+    fn alloc_one_and_initialize(v: Vector) -> ::std::ptr::NonNull<Vector> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, v) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    fn gc_mark_children(&mut self, mark: bool) {
+        for obj in &self.elements {
+            obj.gc_mark(mark);
+        }
+    }
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#(")?;
+        for (i, obj) in self.elements.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", obj)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl convert::From<GcRef<Vector>> for Object {
+    fn from(v: GcRef<Vector>) -> Object {
+        Object::from_raw(ObjectTag::Vector.tag(v.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<Vector> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Vector
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *VECTOR_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<Vector> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<Vector> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut Vector)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let nonnull = 0xdead_beef as *mut Vector;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
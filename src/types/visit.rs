@@ -0,0 +1,113 @@
+//! A stable way for code outside this crate to handle each kind of
+//! `Object`, without matching on `ExpandedObject`, whose variants
+//! follow Phoebe's internal representation and change along with it.
+//!
+//! Every method of `ObjectVisitor` but `visit_other` has a default
+//! which calls `visit_other`, so a visitor need only implement the
+//! kinds it cares about, and kinds added to Phoebe later reach
+//! `visit_other` rather than breaking existing visitors.
+
+use crate::prelude::*;
+use crate::types::immediate::SpecialMarker;
+use crate::types::ExpandedObject;
+
+pub trait ObjectVisitor {
+    type Output;
+
+    /// Called for every object without a more specific method.
+    fn visit_other(&mut self, obj: Object) -> Self::Output;
+
+    fn visit_float(&mut self, f: f64) -> Self::Output {
+        self.visit_other(Object::from(f))
+    }
+    fn visit_integer(&mut self, n: i64) -> Self::Output {
+        self.visit_other(Object::from(n as i32))
+    }
+    fn visit_ratio(&mut self, ratio: &Ratio) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(ratio) }))
+    }
+    /// `t` and `nil`. Note that `nil` is also the empty list.
+    fn visit_bool(&mut self, b: bool) -> Self::Output {
+        self.visit_other(Object::from(b))
+    }
+    fn visit_character(&mut self, c: char) -> Self::Output {
+        self.visit_other(Object::from(c))
+    }
+    fn visit_symbol(&mut self, sym: &Symbol) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(sym) }))
+    }
+    fn visit_string(&mut self, string: &PhoebeString) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(string) }))
+    }
+    fn visit_cons(&mut self, cons: &Cons) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(cons) }))
+    }
+    fn visit_vector(&mut self, vector: &Vector) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(vector) }))
+    }
+    fn visit_bytes(&mut self, bytes: &Bytes) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(bytes) }))
+    }
+    fn visit_weak_ref(&mut self, weak_ref: &WeakRef) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(weak_ref) }))
+    }
+    fn visit_native(&mut self, native: &Native) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(native) }))
+    }
+    fn visit_hash_table(&mut self, table: &HashTable) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(table) }))
+    }
+    fn visit_structure(&mut self, structure: &Structure) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(structure) }))
+    }
+    fn visit_namespace(&mut self, namespace: &Namespace) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(namespace) }))
+    }
+    fn visit_function(&mut self, function: &Function) -> Self::Output {
+        self.visit_other(Object::from(unsafe { gc_ref(function) }))
+    }
+    fn visit_error(&mut self, error: &Error) -> Self::Output {
+        self.visit_other(Object::quiet_error(unsafe { gc_ref(error) }))
+    }
+}
+
+/// Recover the `GcRef` to an object `Object::visit` passed by
+/// reference. Unsafe unless `obj` came from `Object::visit`.
+unsafe fn gc_ref<T>(obj: &T) -> GcRef<T> {
+    GcRef::from_ptr(obj as *const T as *mut T)
+}
+
+impl Object {
+    /// Call the method of `visitor` for `self`'s kind. References are
+    /// followed, so the visitor sees the object they refer to.
+    pub fn visit<V>(self, visitor: &mut V) -> V::Output
+    where
+        V: ObjectVisitor,
+    {
+        match self.expand_quiet() {
+            ExpandedObject::Float(f) => visitor.visit_float(f),
+            ExpandedObject::Immediate(Immediate::Bool(b)) => visitor.visit_bool(b),
+            ExpandedObject::Immediate(Immediate::Integer(n)) => visitor.visit_integer(i64::from(n)),
+            ExpandedObject::Immediate(Immediate::UnsignedInt(n)) => visitor.visit_integer(n as i64),
+            ExpandedObject::Immediate(Immediate::Character(c)) => visitor.visit_character(c),
+            ExpandedObject::Immediate(Immediate::SpecialMarker(SpecialMarker::Uninitialized)) => {
+                visitor.visit_other(self)
+            }
+            ExpandedObject::Ratio(r) => visitor.visit_ratio(&r),
+            ExpandedObject::Reference(r) => (*r).visit(visitor),
+            ExpandedObject::HeapObject(h) => (**h).visit(visitor),
+            ExpandedObject::Symbol(s) => visitor.visit_symbol(&s),
+            ExpandedObject::String(s) => visitor.visit_string(&s),
+            ExpandedObject::Cons(c) => visitor.visit_cons(&c),
+            ExpandedObject::Vector(v) => visitor.visit_vector(&v),
+            ExpandedObject::Bytes(b) => visitor.visit_bytes(&b),
+            ExpandedObject::WeakRef(w) => visitor.visit_weak_ref(&w),
+            ExpandedObject::Native(n) => visitor.visit_native(&n),
+            ExpandedObject::HashTable(h) => visitor.visit_hash_table(&h),
+            ExpandedObject::Structure(s) => visitor.visit_structure(&s),
+            ExpandedObject::Namespace(n) => visitor.visit_namespace(&n),
+            ExpandedObject::Function(f) => visitor.visit_function(&f),
+            ExpandedObject::QuietError(e) => visitor.visit_error(&e),
+        }
+    }
+}
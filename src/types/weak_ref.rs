@@ -0,0 +1,111 @@
+//! Weak references hold onto an object without keeping it alive. The
+//! garbage collector doesn't trace through them, and clears any whose
+//! target it is about to deallocate, after which the weak reference
+//! holds nothing. They print as `[weak-ref (1 2)]`, or `[weak-ref]`
+//! once cleared.
+
+use super::pointer_tagging::{self, ExtendedHeader, ExtendedTag, ObjectTag, PointerTag};
+use crate::prelude::*;
+use std::{convert, fmt};
+
+lazy_static! {
+    static ref WEAK_REF_TYPE_NAME: GcRef<Symbol> = { symbol_lookup::make_symbol(b"weak-ref") };
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct WeakRef {
+    header: ExtendedHeader,
+    gc_marking: GcMark,
+    target: Option<Object>,
+}
+
+impl WeakRef {
+    /// A weak reference to `target`. `target` must not be a
+    /// `Reference`, since those may point into a stack frame which
+    /// will soon be popped.
+    pub fn new(target: Object) -> WeakRef {
+        debug_assert!(!Reference::is_type(target));
+        WeakRef {
+            header: ExtendedHeader::new(ExtendedTag::WeakRef),
+            gc_marking: GcMark::default(),
+            target: Some(target),
+        }
+    }
+    /// The target, or `None` if it has been collected.
+    pub fn target(&self) -> Option<Object> {
+        self.target
+    }
+    /// Called by the garbage collector before deallocating the
+    /// target.
+    pub fn clear(&mut self) {
+        self.target = None;
+    }
+}
+
+impl GarbageCollected for WeakRef {
+    type ConvertFrom = WeakRef;
+    fn alloc_one_and_initialize(w: WeakRef) -> ::std::ptr::NonNull<WeakRef> {
+        use std::alloc::{Alloc, Global};
+        use std::ptr;
+        let nn = Global.alloc_one().unwrap();
+        let p = nn.as_ptr();
+        unsafe { ptr::write(p, w) };
+        nn
+    }
+    fn my_marking(&self) -> &GcMark {
+        &self.gc_marking
+    }
+    /// The target is deliberately not marked.
+    fn gc_mark_children(&mut self, _: bool) {}
+}
+
+impl fmt::Display for WeakRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.target {
+            Some(target) => write!(f, "[weak-ref {}]", target),
+            None => write!(f, "[weak-ref]"),
+        }
+    }
+}
+
+impl convert::From<GcRef<WeakRef>> for Object {
+    fn from(w: GcRef<WeakRef>) -> Object {
+        Object::from_raw(ObjectTag::Extended.tag(w.into_ptr() as u64))
+    }
+}
+
+impl FromObject for GcRef<WeakRef> {
+    type Tag = ObjectTag;
+    fn associated_tag() -> ObjectTag {
+        ObjectTag::Extended
+    }
+    fn is_type(obj: Object) -> bool {
+        unsafe { pointer_tagging::extended_tag_of(obj.0) == Some(ExtendedTag::WeakRef) }
+    }
+    fn type_name() -> GcRef<Symbol> {
+        *WEAK_REF_TYPE_NAME
+    }
+}
+
+impl FromUnchecked<Object> for GcRef<WeakRef> {
+    unsafe fn from_unchecked(obj: Object) -> GcRef<WeakRef> {
+        debug_assert!(Self::is_type(obj));
+        GcRef::from_ptr(Self::associated_tag().untag(obj.0) as *mut WeakRef)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn tag_and_untag() {
+        unsafe {
+            let mut x = WeakRef::new(Object::nil());
+            let nonnull = &mut x as *mut WeakRef;
+            let obj = Object::from(GcRef::from_ptr(nonnull));
+            assert!(<GcRef<WeakRef>>::is_type(obj));
+            assert_eq!(GcRef::from_ptr(nonnull), GcRef::from_unchecked(obj));
+        }
+    }
+}
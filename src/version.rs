@@ -0,0 +1,50 @@
+//! Information about this build of Phoebe, used by the REPL's banner
+//! and exposed to Phoebe code as `(version)`,
+//! `(lisp-implementation-version)` and `*features*`.
+
+use std::fmt;
+
+/// The crate version, as listed in `Cargo.toml`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    /// Always includes `phoebe` and the capabilities every build has,
+    /// like `threads`, followed by any cargo features this build was
+    /// compiled with.
+    pub features: Vec<&'static str>,
+}
+
+/// Describes this build of Phoebe. Scripts and libraries see the
+/// same information as `(version)`, `(lisp-implementation-version)`
+/// and `*features*`.
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: VERSION,
+        features: enabled_features(),
+    }
+}
+
+/// Cargo features are added to this list as Phoebe grows them, each
+/// guarded by `cfg!(feature = "...")`.
+fn enabled_features() -> Vec<&'static str> {
+    // Any number of threads may evaluate at once, sharing the default
+    // global namespace.
+    let mut features = vec!["phoebe", "threads"];
+    if cfg!(feature = "line-editing") {
+        features.push("line-editing");
+    }
+    features
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Phoebe {} (features: {})",
+            self.version,
+            self.features.join(" ")
+        )
+    }
+}
@@ -0,0 +1,143 @@
+//! Watchpoints run a callback whenever `setf`, or one of its
+//! relatives like `incf` and `push`, stores a value in a watched
+//! global binding, for debugging changes to global state. Watchers
+//! are Phoebe functions, added with `watch` or the `watch` builtin,
+//! or Rust closures, added with `watch_with`. Each is called with
+//! the binding's symbol, its old value and its new value, after the
+//! new value has been stored.
+//!
+//! Assignments made while a watcher is running don't run watchers,
+//! so a watcher may `setf` the binding it watches.
+
+use crate::gc::Rooted;
+use crate::prelude::*;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::{Arc, Mutex};
+
+type Callback = Arc<Fn(GcRef<Symbol>, Object, Object) + Send + Sync>;
+
+#[derive(Clone)]
+enum Watcher {
+    Function(GcRef<Function>),
+    Callback(Callback),
+}
+
+struct Watch {
+    symbol: GcRef<Symbol>,
+    /// The watched binding, which is kept alive so that its memory
+    /// can't be reused for another binding.
+    cell: GcRef<HeapObject>,
+    watchers: Vec<Watcher>,
+}
+
+/// The number of entries in `WATCHES`, so that `notify` needn't lock
+/// it when nothing is watched.
+static WATCH_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+lazy_static! {
+    /// Maps references to watched bindings to their watchers.
+    static ref WATCHES: Mutex<HashMap<Reference, Watch>> = { Mutex::new(HashMap::new()) };
+}
+
+thread_local! {
+    /// True while this thread is running a watcher.
+    static IN_WATCHER: Cell<bool> = { Cell::new(false) };
+}
+
+fn add_watcher(sym: GcRef<Symbol>, watcher: Watcher) {
+    let cell = symbol_lookup::global_env().make_sym_cell(sym);
+    let mut watches = WATCHES.lock().unwrap();
+    watches
+        .entry(Reference::from(cell))
+        .or_insert_with(|| {
+            WATCH_COUNT.fetch_add(1, Ordering::Relaxed);
+            Watch {
+                symbol: sym,
+                cell,
+                watchers: Vec::new(),
+            }
+        })
+        .watchers
+        .push(watcher);
+}
+
+/// Call `function` whenever `setf` stores into `sym`'s binding in
+/// the current global namespace, creating the binding if there isn't
+/// one.
+pub fn watch(sym: GcRef<Symbol>, function: GcRef<Function>) {
+    add_watcher(sym, Watcher::Function(function));
+}
+
+/// Like `watch`, but call a Rust closure.
+pub fn watch_with<F>(sym: GcRef<Symbol>, callback: F)
+where
+    F: Fn(GcRef<Symbol>, Object, Object) + Send + Sync + 'static,
+{
+    add_watcher(sym, Watcher::Callback(Arc::new(callback)));
+}
+
+/// Remove every watcher of the binding `sym` names in the current
+/// global namespace, returning whether there were any.
+pub fn unwatch(sym: GcRef<Symbol>) -> bool {
+    let place = match symbol_lookup::get_from_global_namespace(sym) {
+        Some(place) => place,
+        None => return false,
+    };
+    let removed = WATCHES.lock().unwrap().remove(&place).is_some();
+    if removed {
+        WATCH_COUNT.fetch_sub(1, Ordering::Relaxed);
+    }
+    removed
+}
+
+/// Called by `setf` and its relatives after storing `new` through
+/// `place`, which held `old`, to run the watchers of the binding
+/// `place` refers to. Returns `new`, or the first error a watcher
+/// signals.
+pub(crate) fn notify(place: Reference, old: Object, new: Object) -> Object {
+    if WATCH_COUNT.load(Ordering::Relaxed) == 0 || IN_WATCHER.with(Cell::get) {
+        return new;
+    }
+    let (symbol, watchers) = match WATCHES.lock().unwrap().get(&place) {
+        Some(watch) => (watch.symbol, watch.watchers.clone()),
+        None => return new,
+    };
+
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            IN_WATCHER.with(|w| w.set(false));
+        }
+    }
+    IN_WATCHER.with(|w| w.set(true));
+    let _restore = Restore;
+
+    // Nothing else may refer to the old value any more.
+    let old = Rooted::new(old);
+    for watcher in watchers {
+        match watcher {
+            Watcher::Function(f) => {
+                let args: List = [Object::from(symbol), *old, new].iter().cloned().collect();
+                f.apply(args)?;
+            }
+            Watcher::Callback(callback) => callback(symbol, *old, new),
+        }
+    }
+    new
+}
+
+/// Mark every watched binding and its watchers. Called by
+/// `gc::roots::gc_mark_roots`.
+pub(crate) fn gc_mark_watches(m: bool) {
+    for watch in WATCHES.lock().unwrap().values() {
+        watch.symbol.gc_mark(m);
+        watch.cell.gc_mark(m);
+        for watcher in &watch.watchers {
+            if let Watcher::Function(f) = *watcher {
+                f.gc_mark(m);
+            }
+        }
+    }
+}
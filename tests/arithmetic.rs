@@ -28,15 +28,41 @@ fn subtraction() {
 #[test]
 fn division() {
     test_pairs! {
-        "(/ 5)" => "0.2";
+        "(/ 5)" => "1/5";
         "(/ .2)" => "5";
         "(/ 20 2 2)" => "5";
-        "(/ 10 2 2)" => "2.5";
+        "(/ 10 2 2)" => "5/2";
         "(/ .5 .5)" => "1";
         "(/ 12.2 4.4)" => "2.7727272727272725";
     }
 }
 
+#[test]
+fn ratios() {
+    test_pairs! {
+        "1/3" => "1/3";
+        "2/4" => "1/2";
+        "-6/3" => "-2";
+        "(type-of 1/3)" => "ratio";
+        "(/ 1 3)" => "1/3";
+        "(/ -4 6)" => "-2/3";
+        "(/ 4 -6)" => "-2/3";
+        "(+ 1/3 2/3)" => "1";
+        "(+ 1/2 1/3)" => "5/6";
+        "(- 1/2 1)" => "-1/2";
+        "(* 2/3 3/4)" => "1/2";
+        "(/ 1/2 1/4)" => "2";
+        "(/ 2/3)" => "3/2";
+        "(+ 1/2 .25)" => "0.75";
+        "(= 1/2 .5)" => "t";
+        "(= 2/4 1/2)" => "t";
+        "(< 1/3 1/2 1)" => "t";
+        "(max 1/3 1/4)" => "1/3";
+        "(abs -1/3)" => "1/3";
+        "(+ 2147483647 1)" => "2147483648";
+    }
+}
+
 #[test]
 fn multiplication() {
     test_pairs! {
@@ -47,3 +73,32 @@ fn multiplication() {
         "(* 1.8 2)" => "3.6";
     }
 }
+
+#[test]
+fn comparison() {
+    test_pairs! {
+        "(< 1 2 3)" => "t";
+        "(< 1 3 2)" => "nil";
+        "(< 1 1)" => "nil";
+        "(<= 1 1 2)" => "t";
+        "(> 3 2.5 1)" => "t";
+        "(> 3 3)" => "nil";
+        "(>= 3 3 -1)" => "t";
+        "(<)" => "t";
+        "(< 1)" => "t";
+    }
+}
+
+#[test]
+fn min_max_and_abs() {
+    test_pairs! {
+        "(min 3)" => "3";
+        "(min 3 1 2)" => "1";
+        "(min 2 1.5)" => "1.5";
+        "(max 3 1 2)" => "3";
+        "(max -1 -2.5)" => "-1";
+        "(abs -3)" => "3";
+        "(abs 2.5)" => "2.5";
+        "(abs -0.5)" => "0.5";
+    }
+}
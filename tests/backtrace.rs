@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::backtrace::{backtrace, Backtrace, Frame};
+use phoebe::gc::GarbageCollected;
+use phoebe::symbol_lookup::{add_to_global, make_symbol};
+use phoebe::types::function::Function;
+use phoebe::types::list::List;
+use phoebe::types::Object;
+use std::sync::{Arc, Mutex};
+
+fn frame(name: &str, arg_count: usize) -> Frame {
+    Frame {
+        name: Some(name.to_owned()),
+        arg_count,
+    }
+}
+
+#[test]
+fn backtrace_of_nested_calls() {
+    let snapshot = Arc::new(Mutex::new(Backtrace::default()));
+    let taken = snapshot.clone();
+    let name = make_symbol(b"take-backtrace");
+    let take_backtrace = Function::make_closure(List::nil(), Vec::new(), move |_| {
+        *taken.lock().unwrap() = backtrace();
+        Object::nil()
+    })
+    .unwrap()
+    .with_name(name);
+    add_to_global(name, Object::from(Function::allocate(take_backtrace)));
+
+    test_pairs! {
+        "(defun inner (a b) (take-backtrace))" => "[function inner]";
+        "(defun outer (x) (inner x 2))" => "[function outer]";
+        "(outer 1)" => "nil";
+    }
+    let snapshot = snapshot.lock().unwrap().clone();
+    assert_eq!(
+        &snapshot.frames[..],
+        &[
+            frame("take-backtrace", 0),
+            frame("inner", 2),
+            frame("outer", 1)
+        ]
+    );
+    assert_eq!(
+        format!("{}", snapshot),
+        "  0: take-backtrace with 0 arguments\n  1: inner with 2 arguments\n  2: outer with 1 argument\n"
+    );
+    // Once they've returned, they're gone.
+    assert!(backtrace().frames.is_empty());
+}
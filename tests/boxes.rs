@@ -0,0 +1,30 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn box_and_unbox() {
+    test_pairs! {
+        "(box 1)" => "#S(box :value 1)";
+        "(type-of (box 1))" => "box";
+        "(box-p (box 1))" => "t";
+        "(box-p 1)" => "nil";
+        "(defvar box-test (box (quote a)))" => "#S(box :value a)";
+        "(unbox box-test)" => "a";
+        "(set-box! box-test 2)" => "2";
+        "(unbox box-test)" => "2";
+        "(incf (unbox box-test))" => "3";
+        "box-test" => "#S(box :value 3)";
+        "(catch-error (unbox 1) e e)" => "Expected a value of type box.";
+    }
+}
+
+#[test]
+fn boxes_are_shared() {
+    test_pairs! {
+        "(defvar shared-box (box 0))" => "#S(box :value 0)";
+        "(defun shared-box-incr (b) (set-box! b (+ (unbox b) 1)))" => "[function shared-box-incr]";
+        "(shared-box-incr shared-box)" => "1";
+        "(shared-box-incr shared-box)" => "2";
+        "(unbox shared-box)" => "2";
+    }
+}
@@ -0,0 +1,40 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn make_bytes_and_bytes() {
+    test_pairs! {
+        "(make-bytes 2)" => "[bytes 00 00]";
+        "(make-bytes 3 255)" => "[bytes ff ff ff]";
+        "(bytes 1 16 171)" => "[bytes 01 10 ab]";
+        "(bytes)" => "[bytes]";
+        "(type-of (bytes))" => "bytes";
+        "(length (make-bytes 4))" => "4";
+        "(catch-error (bytes 256) e e)" => "Expected a value of type byte.";
+    }
+}
+
+#[test]
+fn byte_ref_and_setf() {
+    test_pairs! {
+        "(defvar byte-ref-test (bytes 1 2 3))" => "[bytes 01 02 03]";
+        "(byte-ref byte-ref-test 0)" => "1";
+        "(setf (byte-ref byte-ref-test 0) 255)" => "255";
+        "(byte-ref byte-ref-test 0)" => "255";
+        "(incf (byte-ref byte-ref-test 1) 10)" => "12";
+        "byte-ref-test" => "[bytes ff 0c 03]";
+        "(catch-error (byte-ref byte-ref-test 3) e e)" =>
+            "The index 3 is out of bounds for a vector of length 3";
+    }
+}
+
+#[test]
+fn string_conversions() {
+    test_pairs! {
+        "(string-to-bytes \"hi\")" => "[bytes 68 69]";
+        "(bytes-to-string (bytes 104 105))" => "\"hi\"";
+        "(bytes-to-string (string-to-bytes \"λ\"))" => "\"λ\"";
+        "(catch-error (bytes-to-string (bytes 104 255)) e e)" =>
+            "The bytes are not valid UTF-8 past byte 1";
+    }
+}
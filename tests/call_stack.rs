@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn deep_recursion() {
+    test_pairs! {
+        "(defun count-down (n) (if (= n 0) 'done (count-down (- n 1))))" => "[function count-down]";
+        "(count-down 200)" => "done";
+        "(catch-error (count-down 100000) e (error-name e))" => "stack-overflow-error";
+        // Still usable once that's unwound.
+        "(count-down 200)" => "done";
+    }
+}
@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn character_literals() {
+    test_pairs! {
+        "#\\a" => "#\\a";
+        "#\\(" => "#\\(";
+        "#\\ " => "#\\Space";
+        "#\\space" => "#\\Space";
+        "#\\Newline" => "#\\Newline";
+        "#\\λ" => "#\\λ";
+        "(list #\\a #\\b)" => "(#\\a #\\b)";
+        "(type-of #\\a)" => "character";
+    }
+}
+
+#[test]
+fn character_builtins() {
+    test_pairs! {
+        "(char-code #\\a)" => "97";
+        "(char-code #\\λ)" => "955";
+        "(code-char 65)" => "#\\A";
+        "(code-char 10)" => "#\\Newline";
+        "(code-char -1)" => "nil";
+        "(code-char 55296)" => "nil";
+        "(char= #\\a #\\a)" => "t";
+        "(char= #\\a #\\a #\\b)" => "nil";
+        "(eql #\\a (code-char 97))" => "t";
+    }
+}
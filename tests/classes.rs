@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn define_and_instantiate() {
+    test_pairs! {
+        "(defclass class-point () (x y))" => "#S(class :name class-point :superclass nil :slots (x y))";
+        "(defvar class-pt (make-instance (quote class-point) :x 1))" => "#S(class-point :x 1 :y nil)";
+        "(type-of class-pt)" => "class-point";
+        "(slot-value class-pt (quote x))" => "1";
+        "(setf (slot-value class-pt (quote y)) 2)" => "2";
+        "(incf (slot-value class-pt (quote x)))" => "2";
+        "class-pt" => "#S(class-point :x 2 :y 2)";
+        "(eq (class-of class-pt) (find-class (quote class-point)))" => "t";
+        "(class-of 1)" => "nil";
+        "(find-class (quote no-such-class))" => "nil";
+        "(catch-error (slot-value class-pt (quote z)) e e)" =>
+            "#S(class-point :x 2 :y 2) has no slot named z";
+        "(catch-error (make-instance (quote class-point) :z 1) e (quote caught))" => "caught";
+        "(catch-error (make-instance (quote no-such-class)) e e)" => "Expected a value of type class.";
+    }
+}
+
+#[test]
+fn single_inheritance() {
+    test_pairs! {
+        "(defclass class-animal () (name legs))" =>
+            "#S(class :name class-animal :superclass nil :slots (name legs))";
+        "(class-slots (defclass class-dog (class-animal) (breed legs)))" => "(name legs breed)";
+        "(make-instance (quote class-dog) :name (quote rex) :legs 4)" =>
+            "#S(class-dog :name rex :legs 4 :breed nil)";
+        "(subclassp (quote class-dog) (quote class-animal))" => "t";
+        "(subclassp (quote class-animal) (quote class-dog))" => "nil";
+        "(subclassp (quote class-dog) (quote class-dog))" => "t";
+        "(catch-error (defclass class-mutt (class-dog class-animal) ()) e (quote caught))" => "caught";
+    }
+}
+
+#[test]
+fn slot_value_on_structures() {
+    test_pairs! {
+        "(defstruct class-struct a)" => "class-struct";
+        "(slot-value (make-class-struct :a 1) (quote a))" => "1";
+    }
+}
@@ -0,0 +1,40 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn define_and_signal_conditions() {
+    test_pairs! {
+        "(define-condition cond-file-error () (path))" =>
+            "#S(class :name cond-file-error :superclass #S(class :name condition :superclass nil :slots nil) :slots (path))";
+        "(defvar cond-e (make-condition (quote cond-file-error) :path (quote foo)))" =>
+            "#S(cond-file-error :path foo)";
+        "(error-name cond-e)" => "cond-file-error";
+        "(slot-value (error-payload cond-e) (quote path))" => "foo";
+        "(catch-error (throw cond-e) e (error-name e))" => "cond-file-error";
+        "(error-payload (error (quote plain) 1))" => "1";
+        "(error-payload (type-error (quote integer)))" => "nil";
+        "(class-name (defclass cond-not-a-condition () ()))" => "cond-not-a-condition";
+        "(catch-error (make-condition (quote cond-not-a-condition)) e e)" =>
+            "Expected a value of type condition.";
+    }
+}
+
+#[test]
+fn handler_case() {
+    test_pairs! {
+        "(define-condition cond-io-error () ())" =>
+            "#S(class :name cond-io-error :superclass #S(class :name condition :superclass nil :slots nil) :slots nil)";
+        "(class-slots (define-condition cond-missing (cond-io-error) (path)))" => "(path)";
+        "(handler-case 1 (t () 2))" => "1";
+        "(handler-case (throw (make-condition (quote cond-missing) :path (quote bar))) \
+           (type-error () (quote wrong)) \
+           (cond-io-error (e) (slot-value (error-payload e) (quote path))))" => "bar";
+        "(handler-case (throw (make-condition (quote cond-io-error))) \
+           (cond-missing () (quote too-specific)) \
+           (condition () (quote caught)))" => "caught";
+        "(handler-case (+ 1 (quote a)) (type-error (e) (error-name e)))" => "type-error";
+        "(catch-error (handler-case (throw (make-condition (quote cond-io-error))) \
+                        (cond-missing () (quote too-specific))) \
+           e (error-name e))" => "cond-io-error";
+    }
+}
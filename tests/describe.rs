@@ -0,0 +1,40 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::Interpreter;
+
+fn run(input: &str) -> String {
+    let (output, error) = run_repl(&mut Interpreter::new(), input.as_bytes());
+    assert!(error.is_empty());
+    output
+}
+
+#[test]
+fn describe_objects() {
+    assert_eq!(run("(describe 1)"), "1 is of type integer.\nnil\n");
+    assert_eq!(
+        run("(defun describe-fn (x) x)
+(set-documentation 'describe-fn '(returns x))
+(describe describe-fn)"),
+        "[function describe-fn]\n(returns x)\n\
+         [function describe-fn] is of type function.\n  \
+         Name: describe-fn\n  \
+         Arglist: (x)\n  \
+         Documentation: (returns x)\n\
+         nil\n"
+    );
+    assert_eq!(
+        run("(describe (make-namespace :name describe-ns :contents ((b 1) (a 2))))"),
+        "[namespace describe-ns] is of type namespace.\n  \
+         Name: describe-ns\n  \
+         Bindings: a b\n\
+         nil\n"
+    );
+    assert_eq!(
+        run("(describe (error 'describe-error 'payload))"),
+        "describe-error: payload is of type error.\n  \
+         Kind: describe-error\n  \
+         Payload: payload\n\
+         nil\n"
+    );
+}
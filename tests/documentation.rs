@@ -0,0 +1,38 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::Interpreter;
+
+#[test]
+fn document_variables_and_functions() {
+    test_pairs! {
+        "(defvar documented-var 1 (the documented variable))" => "1";
+        "(documentation 'documented-var)" => "(the documented variable)";
+        "(defvar documented-var 2 (still documented))" => "1";
+        "(documentation 'documented-var)" => "(still documented)";
+        "(defun documented-fn (x) x)" => "[function documented-fn]";
+        "(documentation 'documented-fn)" => "nil";
+        "(set-documentation 'documented-fn '(returns x))" => "(returns x)";
+        "(documentation 'documented-fn)" => "(returns x)";
+        "(documentation 'undocumented)" => "nil";
+    }
+    assert!(Interpreter::new()
+        .query("documented-fn")
+        .contains(r#""documentation":"(returns x)""#));
+}
+
+#[test]
+fn docstrings() {
+    test_pairs! {
+        "(defun docstring-fn (x) \"Returns x.\" x)" => "[function docstring-fn]";
+        "(documentation 'docstring-fn)" => "\"Returns x.\"";
+        "(docstring-fn 1)" => "1";
+        "(defun only-a-string () \"Not documentation.\")" => "[function only-a-string]";
+        "(documentation 'only-a-string)" => "nil";
+        "(only-a-string)" => "\"Not documentation.\"";
+        "(function-body (lambda (x) \"Adds one to x.\" (+ x 1)))" => "((+ x 1))";
+        "(type-of (setf (symbol-function 'docstring-lambda) (lambda () \"Returns one.\" 1)))" =>
+            "function";
+        "(documentation 'docstring-lambda)" => "\"Returns one.\"";
+    }
+}
@@ -0,0 +1,46 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn eq_is_identity() {
+    test_pairs! {
+        "(eq (quote eq-is-identity) (quote eq-is-identity))" => "t";
+        "(eq 1 1)" => "t";
+        "(eq 1 1.0)" => "nil";
+        "(eq (list 1 2) (list 1 2))" => "nil";
+        "(defvar eq-is-identity-list (list 1 2))" => "(1 2)";
+        "(eq eq-is-identity-list eq-is-identity-list)" => "t";
+    }
+}
+
+#[test]
+fn eql_compares_numbers() {
+    test_pairs! {
+        "(eql 1 1.0)" => "t";
+        "(eql 2 1.0)" => "nil";
+        "(eql (list 1) (list 1))" => "nil";
+    }
+}
+
+#[test]
+fn equal_compares_structure() {
+    test_pairs! {
+        "(equal (list 1 2 3) (list 1 2 3))" => "t";
+        "(equal (list 1 (list 2 3)) (list 1.0 (list 2 3)))" => "t";
+        "(equal (list 1 2) (list 1 2 3))" => "nil";
+    }
+}
+
+#[test]
+fn equal_within_a_budget() {
+    use phoebe::repl::test_utilities::run_repl;
+    use phoebe::types::equality::Budget;
+    use phoebe::Interpreter;
+
+    let mut interpreter =
+        Interpreter::new().with_equal_budget(Budget::unlimited().with_max_comparisons(8));
+    let input: &[u8] = b"(equal (list 1 2) (list 1 2))
+(catch-error (equal (list 1 2 3 4 5) (list 1 2 3 4 5)) e (quote over-budget))";
+    let (output, _) = run_repl(&mut interpreter, input);
+    assert_eq!(output, "t\nover-budget\n");
+}
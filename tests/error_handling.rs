@@ -51,3 +51,33 @@ fn catch_an_error() {
        (quote caught-an-error))" => "caught-an-error";
     }
 }
+
+#[test]
+fn assertions() {
+    test_pairs! {
+        "(assert (= 1 1))" => "nil";
+        "(catch-error (assert (= 1 2) 'oops) e e)" => "The assertion (= 1 2) failed: oops";
+        "(catch-error (assert nil) e e)" => "The assertion nil failed: nil";
+        "(defvar assertion-message 'from-a-variable)" => "from-a-variable";
+        "(let ((x 2)) (catch-error (assert (= x 3) assertion-message) e e))" =>
+            "The assertion (= x 3) failed: from-a-variable";
+    }
+}
+
+#[test]
+fn debug_assertions() {
+    use phoebe::repl::test_utilities::run_repl;
+    use phoebe::Interpreter;
+
+    let program: &[u8] = b"(catch-error (debug-assert (print 'checked)) e e)
+(catch-error (debug-assert nil 'oops) e e)";
+
+    let mut interpreter = Interpreter::new();
+    assert_eq!(run_repl(&mut interpreter, program).0, "nil\nnil\n");
+
+    interpreter.set_debug_mode(true);
+    assert_eq!(
+        run_repl(&mut interpreter, program).0,
+        "checked\nnil\nThe assertion nil failed: oops\n"
+    );
+}
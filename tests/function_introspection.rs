@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn function_introspection() {
+    test_pairs! {
+        "(defun introspected-fn (x &optional y) (+ x y))" => "[function introspected-fn]";
+        "(function-name introspected-fn)" => "introspected-fn";
+        "(function-arglist introspected-fn)" => "(x &optional y)";
+        "(function-body introspected-fn)" => "((+ x y))";
+        "(function-name (lambda (x) x))" => "nil";
+        "(function-name cons)" => "cons";
+        "(function-arglist cons)" => "(first second)";
+        "(function-body cons)" => "nil";
+        "(let ((introspected-x 1)) (defun introspected-closure () introspected-x))" =>
+            "[function introspected-closure]";
+        "(function-environment introspected-closure)" => "[namespace ANONYMOUS]";
+        "(catch-error (function-name 1) e e)" => "Expected a value of type function.";
+    }
+}
@@ -0,0 +1,59 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn identity() {
+    test_pairs! {
+        "(identity 3)" => "3";
+        "(identity (quote (a b)))" => "(a b)";
+    }
+}
+
+#[test]
+fn constantly() {
+    test_pairs! {
+        "(defvar always-five (constantly 5))" => "[function ANONYMOUS]";
+        "(always-five)" => "5";
+        "(always-five 1 2 3)" => "5";
+        "((constantly (quote (x y))) nil)" => "(x y)";
+        "(type-of (constantly 1))" => "function";
+    }
+}
+
+#[test]
+fn complement() {
+    test_pairs! {
+        "(defvar not-null (complement null))" => "[function ANONYMOUS]";
+        "(not-null nil)" => "nil";
+        "(not-null 3)" => "t";
+        "((complement eq) 1 1)" => "nil";
+        "((complement eq) 1 2)" => "t";
+        "((complement (lambda (x y) (< x y))) 3 2)" => "t";
+        "((complement (complement null)) nil)" => "t";
+    }
+}
+
+#[test]
+fn compose() {
+    test_pairs! {
+        "(defun compose-add-one (x) (+ x 1))" => "[function compose-add-one]";
+        "(defun compose-double (x) (* x 2))" => "[function compose-double]";
+        "((compose compose-add-one compose-double) 5)" => "11";
+        "((compose compose-double compose-add-one) 5)" => "12";
+        "((compose null list))" => "t";
+        "((compose compose-add-one +) 1 2 3)" => "7";
+        "((compose) 4)" => "4";
+    }
+}
+
+#[test]
+fn partial() {
+    test_pairs! {
+        "(defvar partial-add-ten (partial + 10))" => "[function ANONYMOUS]";
+        "(partial-add-ten 5)" => "15";
+        "(partial-add-ten)" => "10";
+        "((partial list 1 2) 3 4)" => "(1 2 3 4)";
+        "((partial (lambda (x y) (- x y)) 10) 3)" => "7";
+        "((partial list (quote a)) (quote b))" => "(a b)";
+    }
+}
@@ -0,0 +1,195 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::gc;
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::types::userdata::Userdata;
+use phoebe::types::Object;
+use phoebe::Interpreter;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+fn finalized_userdata(counter: &Arc<AtomicUsize>) -> Object {
+    let counter = counter.clone();
+    Object::from(
+        Userdata::new("finalized-handle", ()).with_finalizer(move |_: &mut ()| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }),
+    )
+}
+
+#[test]
+fn tune_pause_and_collect() {
+    // From here on, only explicit collections free anything.
+    gc::set_threshold(usize::max_value());
+    assert_eq!(gc::threshold(), usize::max_value());
+
+    let finalized = Arc::new(AtomicUsize::new(0));
+    finalized_userdata(&finalized);
+    // The userdata may not have reached the collector before the
+    // first pass started.
+    while finalized.load(Ordering::SeqCst) == 0 {
+        gc::collect();
+    }
+    assert_eq!(gc::threshold(), usize::max_value());
+
+    let pause = gc::pause();
+    finalized_userdata(&finalized);
+    let passes = gc::gc_count();
+    let collector = thread::spawn(gc::collect);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(gc::gc_count(), passes);
+    assert_eq!(finalized.load(Ordering::SeqCst), 1);
+
+    drop(pause);
+    collector.join().unwrap();
+    assert!(gc::gc_count() > passes);
+}
+
+#[test]
+fn gc_builtin() {
+    test_pairs! {
+        "(defvar gc-survivor (list 1 2 3))" => "(1 2 3)";
+        "(length (defvar gc-counts (gc)))" => "2";
+        // Objects freed, then objects remaining. The other test may
+        // already have collected any garbage.
+        "(type-of (pop gc-counts))" => "integer";
+        "(< 0 (pop gc-counts))" => "t";
+        "gc-survivor" => "(1 2 3)";
+    }
+}
+
+#[test]
+fn stats_and_room() {
+    gc::collect();
+    let stats = gc::stats();
+    assert!(stats.collections > 0);
+    assert!(stats.objects_allocated >= stats.objects_live);
+
+    let (output, _) = run_repl(&mut Interpreter::new(), b"(room)");
+    assert_eq!(output.lines().count(), 8);
+    assert!(output.starts_with("Objects allocated: "));
+    assert!(output.ends_with("seconds\nnil\n"));
+}
+
+#[test]
+fn collection_callback() {
+    let (sender, receiver) = mpsc::channel();
+    gc::on_collection(move |summary| {
+        let _ = sender.send(*summary);
+    });
+    let collected = gc::collect();
+    let summary = receiver.recv().unwrap();
+    gc::clear_on_collection();
+
+    assert!(summary.mark > Duration::from_secs(0));
+    let stats = gc::stats();
+    assert!(stats.total_mark >= summary.mark);
+    assert!(stats.total_sweep >= summary.sweep);
+    assert!(stats.total_pause >= collected.pause);
+}
+
+#[test]
+fn rooted_values_survive() {
+    let finalized = Arc::new(AtomicUsize::new(0));
+    let handle = finalized_userdata(&finalized);
+    root!(handle);
+    for _ in 0..3 {
+        gc::collect();
+    }
+    assert_eq!(finalized.load(Ordering::SeqCst), 0);
+    drop(handle);
+    while finalized.load(Ordering::SeqCst) == 0 {
+        gc::collect();
+    }
+}
+
+#[test]
+fn objects_moved_while_marking_survive() {
+    // Keep passes running while objects move from one box to another,
+    // so that some are moved after their old box has been marked.
+    let done = Arc::new(AtomicBool::new(false));
+    let collector = {
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                gc::collect();
+            }
+        })
+    };
+    test_pairs! {
+        "(unbox (defvar barrier-from (box (list 1 2 3))))" => "(1 2 3)";
+        "(unbox (defvar barrier-to (box nil)))" => "nil";
+        "(defun move-between-boxes (n)
+           (if (= n 0)
+               (unbox barrier-to)
+             (set-box! barrier-to (unbox barrier-from))
+             (set-box! barrier-from (list 1 2 3))
+             (move-between-boxes (- n 1))))" => "[function move-between-boxes]";
+        "(move-between-boxes 200)" => "(1 2 3)";
+    }
+    done.store(true, Ordering::SeqCst);
+    collector.join().unwrap();
+}
+
+#[test]
+fn temporaries_survive_collection() {
+    // Reading a nested form, and evaluating `psetf` and `let`, hold
+    // objects only in Rust while allocating or evaluating more.
+    let done = Arc::new(AtomicBool::new(false));
+    let collector = {
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                gc::collect();
+            }
+        })
+    };
+    test_pairs! {
+        "'((a) (b) ((c)) #((d) e))" => "((a) (b) ((c)) #((d) e))";
+        "(defvar temp-a nil)" => "nil";
+        "(defvar temp-b nil)" => "nil";
+        "(defun fill-temporaries (n)
+           (if (= n 0)
+               (let ((a (list temp-a)) (b (list temp-b)))
+                 (list a b))
+             (psetf temp-a (list (list n) n)
+                    temp-b (list (list n) n))
+             (fill-temporaries (- n 1))))" => "[function fill-temporaries]";
+        "(fill-temporaries 200)" => "((((1) 1)) (((1) 1)))";
+    }
+    done.store(true, Ordering::SeqCst);
+    collector.join().unwrap();
+}
+
+#[test]
+fn growing_containers_survive_marking() {
+    // Vectors and hash tables reallocate their storage as they grow,
+    // which must not happen while the collector reads it.
+    let done = Arc::new(AtomicBool::new(false));
+    let collector = {
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                gc::collect();
+            }
+        })
+    };
+    test_pairs! {
+        "(defvar growing-vector (vector))" => "#()";
+        "(defvar growing-table (make-hash-table))" => "[hash-table eql 0]";
+        "(defun grow-containers (n)
+           (if (= n 0)
+               (list (length growing-vector) (hash-table-count growing-table))
+             (vector-push (list n) growing-vector)
+             (setf (gethash n growing-table) (list n))
+             (grow-containers (- n 1))))" => "[function grow-containers]";
+        "(grow-containers 300)" => "(300 300)";
+        "(aref growing-vector 0)" => "(300)";
+        "(gethash 1 growing-table)" => "(1)";
+    }
+    done.store(true, Ordering::SeqCst);
+    collector.join().unwrap();
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn dispatch_on_argument_types() {
+    test_pairs! {
+        "(defgeneric describe-thing (x))" => "[function describe-thing]";
+        "(defmethod describe-thing ((x integer)) (quote an-integer))" => "[function describe-thing]";
+        "(defmethod describe-thing ((x symbol)) (quote a-symbol))" => "[function describe-thing]";
+        "(describe-thing 1)" => "an-integer";
+        "(describe-thing (quote foo))" => "a-symbol";
+        "(catch-error (describe-thing 1.5) e e)" =>
+            "No method of describe-thing is applicable to the arguments (1.5)";
+        "(defmethod describe-thing (x) (quote something))" => "[function describe-thing]";
+        "(describe-thing 1.5)" => "something";
+        "(describe-thing 1)" => "an-integer";
+        "(defmethod describe-thing ((x integer)) (+ x 1))" => "[function describe-thing]";
+        "(describe-thing 1)" => "2";
+    }
+}
+
+#[test]
+fn more_specific_methods_win() {
+    test_pairs! {
+        "(defmethod combine ((a integer) b) (quote integer-first))" => "[function combine]";
+        "(defmethod combine (a (b integer)) (quote integer-second))" => "[function combine]";
+        "(defmethod combine ((a integer) (b integer)) (+ a b))" => "[function combine]";
+        "(defmethod combine ((a t) (b t)) (quote neither))" => "[function combine]";
+        "(combine 1 2)" => "3";
+        "(combine 1 (quote x))" => "integer-first";
+        "(combine (quote x) 1)" => "integer-second";
+        "(combine (quote x) (quote y))" => "neither";
+    }
+}
+
+#[test]
+fn dispatch_on_structure_types() {
+    test_pairs! {
+        "(defstruct generic-circle radius)" => "generic-circle";
+        "(defstruct generic-square side)" => "generic-square";
+        "(defgeneric area (shape))" => "[function area]";
+        "(defmethod area ((s generic-circle)) (* 3 (generic-circle-radius s) (generic-circle-radius s)))" =>
+            "[function area]";
+        "(defmethod area ((s generic-square)) (* (generic-square-side s) (generic-square-side s)))" =>
+            "[function area]";
+        "(area (make-generic-circle :radius 2))" => "12";
+        "(area (make-generic-square :side 3))" => "9";
+    }
+}
+
+#[test]
+fn plist_entries_dont_clobber_methods() {
+    test_pairs! {
+        "(put (quote clobbered-area) (quote generic-function) 1)" => "1";
+        "(defmethod clobbered-area ((x integer)) (* x x))" => "[function clobbered-area]";
+        "(clobbered-area 3)" => "9";
+        "(get (quote clobbered-area) (quote generic-function))" => "1";
+        "(put (quote clobbered-area) (quote generic-function) 2)" => "2";
+        "(defmethod clobbered-area ((x float)) (quote a-float))" => "[function clobbered-area]";
+        "(clobbered-area 1.5)" => "a-float";
+        "(clobbered-area 4)" => "16";
+    }
+}
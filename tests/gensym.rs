@@ -0,0 +1,43 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::gensym::{gensym_counter, make_gensym, make_gensym_with_prefix, prefix_for};
+use phoebe::symbol_lookup::make_symbol;
+
+#[test]
+fn counter_counts_gensyms() {
+    let before = gensym_counter();
+    let sym = make_gensym_with_prefix(b"counter-test");
+    assert!(gensym_counter() > before);
+    assert!(format!("{}", sym).starts_with("counter-test-"));
+    test_pairs! {
+        "(type-of (gensym-counter))" => "integer";
+    }
+}
+
+#[test]
+fn prefixes_per_namespace() {
+    test_pairs! {
+        "(make-namespace :name gensym-prefix-ns)" => "[namespace gensym-prefix-ns]";
+        "(gensym-prefix gensym-prefix-ns)" => "GENSYM";
+        "(set-gensym-prefix (quote gensym-prefix-test) gensym-prefix-ns)" => "gensym-prefix-test";
+        "(gensym-prefix gensym-prefix-ns)" => "gensym-prefix-test";
+        "(gensym-prefix)" => "GENSYM";
+    }
+    assert_eq!(
+        prefix_for(make_symbol(b"gensym-prefix-ns")),
+        b"gensym-prefix-test"
+    );
+}
+
+#[test]
+fn gensyms_are_uninterned() {
+    let sym = make_gensym();
+    assert!(sym != make_symbol(format!("{}", sym).as_bytes()));
+    test_pairs! {
+        "(eq (gensym) (gensym))" => "nil";
+        "(type-of (gensym))" => "symbol";
+        "(type-of (defvar gensym-test-sym (gensym (quote gensym-builtin-test))))" => "symbol";
+        "(eq gensym-test-sym (intern (symbol-name gensym-test-sym)))" => "nil";
+    }
+}
@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn eql_tables() {
+    test_pairs! {
+        "(defvar eql-table (make-hash-table))" => "[hash-table eql 0]";
+        "(type-of eql-table)" => "hash-table";
+        "(setf (gethash 'a eql-table) 1)" => "1";
+        "(setf (gethash 2 eql-table) 'two)" => "two";
+        "(gethash 'a eql-table)" => "1";
+        "(gethash 2.0 eql-table)" => "two";
+        "(gethash 'b eql-table)" => "nil";
+        "(gethash 'b eql-table 'missing)" => "missing";
+        "(setf (gethash (list 1) eql-table) 'list)" => "list";
+        "(gethash (list 1) eql-table)" => "nil";
+        "(hash-table-count eql-table)" => "3";
+        "(setf (gethash 'a eql-table) 10)" => "10";
+        "(hash-table-count eql-table)" => "3";
+        "(gethash 'a eql-table)" => "10";
+    }
+}
+
+#[test]
+fn equal_tables() {
+    test_pairs! {
+        "(defvar equal-table (make-hash-table :test 'equal))" => "[hash-table equal 0]";
+        "(setf (gethash (list 1 2) equal-table) 'list)" => "list";
+        "(setf (gethash \"key\" equal-table) 'string)" => "string";
+        "(gethash (list 1 2) equal-table)" => "list";
+        "(gethash \"key\" equal-table)" => "string";
+        "(gethash (list 1 2 3) equal-table)" => "nil";
+        "(catch-error (make-hash-table :test 'eq) e (type-of e))" => "error";
+    }
+}
+
+#[test]
+fn remhash_and_maphash() {
+    test_pairs! {
+        "(defvar remhash-table (make-hash-table))" => "[hash-table eql 0]";
+        "(setf (gethash 1 remhash-table) 10)" => "10";
+        "(setf (gethash 2 remhash-table) 20)" => "20";
+        "(defvar remhash-sum 0)" => "0";
+        "(maphash (lambda (k v) (incf remhash-sum (+ k v))) remhash-table)" => "nil";
+        "remhash-sum" => "33";
+        "(remhash 1 remhash-table)" => "t";
+        "(remhash 1 remhash-table)" => "nil";
+        "(hash-table-count remhash-table)" => "1";
+        "(gethash 1 remhash-table)" => "nil";
+        "(gethash 2 remhash-table)" => "20";
+    }
+}
@@ -0,0 +1,25 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::gc;
+
+// The limit applies to every thread, so this test has a process to
+// itself.
+#[test]
+fn heap_limit() {
+    assert_eq!(gc::heap_limit(), None);
+    test_pairs! {
+        "(defun hog (depth) \
+           (if (= depth 0) \
+             (list 1 2 3 4 5 6 7 8) \
+             (list (hog (- depth 1)) (hog (- depth 1)))))" => "[function hog]";
+    }
+    gc::set_heap_limit(Some(gc::stats().objects_live + 20_000));
+    test_pairs! {
+        "(catch-error (defvar heap-hog (hog 14)) e (error-name e))" => "heap-exhausted-error";
+        // Once the hog is garbage, there's room again.
+        "(length (hog 2))" => "2";
+    }
+    gc::set_heap_limit(None);
+    assert_eq!(gc::heap_limit(), None);
+}
@@ -0,0 +1,17 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn incf_and_decf_modify_places() {
+    test_pairs! {
+        "(defvar incf-counter 0)" => "0";
+        "(incf incf-counter)" => "1";
+        "(incf incf-counter 10)" => "11";
+        "(decf incf-counter)" => "10";
+        "(decf incf-counter (+ 2 3))" => "5";
+        "(incf incf-counter 0.5)" => "5.5";
+        "incf-counter" => "5.5";
+        "(defvar incf-symbol (quote foo))" => "foo";
+        "(catch-error (incf incf-symbol) e (quote caught))" => "caught";
+    }
+}
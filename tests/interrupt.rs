@@ -0,0 +1,48 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::interrupt::interrupt_handle;
+use phoebe::session::SessionError;
+use phoebe::symbol_lookup::make_symbol;
+use phoebe::watchpoints::watch_with;
+use phoebe::Session;
+
+#[test]
+fn interrupts_are_catchable() {
+    test_pairs! {
+        "(defvar interrupt-trigger 0)" => "0";
+    }
+    // Interrupt this thread partway through evaluating a form, when
+    // it assigns to `interrupt-trigger`.
+    let handle = interrupt_handle();
+    watch_with(make_symbol(b"interrupt-trigger"), move |_, _, _| {
+        handle.interrupt()
+    });
+    test_pairs! {
+        "(catch-error (let ((x 0)) (setf interrupt-trigger 1) (+ x 2)) e (error-name e))"
+            => "interrupted-error";
+        "(+ 1 2)" => "3";
+    }
+}
+
+#[test]
+fn interrupts_stop_evaluation() {
+    let mut session = Session::new();
+    interrupt_handle().interrupt();
+    match session.eval_str("(+ 1 2)") {
+        Err(SessionError::Eval(message)) => assert_eq!(message, "Evaluation was interrupted"),
+        other => panic!(
+            "Expected an interrupt, found {:?}",
+            other.map(|o| format!("{}", *o))
+        ),
+    }
+    assert_eq!(format!("{}", *session.eval_str("(+ 1 2)").unwrap()), "3");
+}
+
+#[test]
+fn the_repl_ignores_stale_interrupts() {
+    interrupt_handle().interrupt();
+    test_pairs! {
+        "(+ 1 2)" => "3";
+    }
+}
@@ -16,3 +16,13 @@ fn define_and_call() {
         "(define-and-call-fn :y 2 :z 3)" => "2";
     }
 }
+
+#[test]
+fn keys_must_be_keywords() {
+    test_pairs! {
+        "(defun keys-must-be-keywords-fn (&key x) x)" => "[function keys-must-be-keywords-fn]";
+        "(keys-must-be-keywords-fn :x 1 :x 2)" => "1";
+        "(catch-error (keys-must-be-keywords-fn (quote x) 1) e e)" =>
+            "Expected a value of type keyword.";
+    }
+}
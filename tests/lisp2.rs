@@ -0,0 +1,35 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::Interpreter;
+
+#[test]
+fn separate_function_and_value_cells() {
+    let mut interpreter = Interpreter::new().with_lisp2(true);
+    let program: &[u8] = b"(defun lisp2-both (x) (+ x 1))
+(defvar lisp2-both 10)
+(lisp2-both lisp2-both)
+(let ((list 1)) (list list))
+(funcall (function lisp2-both) 2)
+(funcall (symbol-function (quote lisp2-both)) 3)
+(setf (symbol-function (quote lisp2-added)) (lambda () 4))
+(lisp2-added)
+(boundp lisp2-added)
+(fboundp lisp2-added)
+(catch-error (lisp2-undefined) e (error-name e))";
+    assert_eq!(
+        run_repl(&mut interpreter, program).0,
+        "[function lisp2-both]\n10\n11\n(1)\n3\n4\n[function ANONYMOUS]\n4\nnil\nt\nundefined-function-error\n"
+    );
+}
+
+#[test]
+fn off_by_default() {
+    test_pairs! {
+        "(defun lisp1-both (x) (+ x 1))" => "[function lisp1-both]";
+        "(defvar lisp1-both 10)" => "[function lisp1-both]";
+        "(funcall (function lisp1-both) 2)" => "3";
+        "(funcall (symbol-function (quote lisp1-both)) 3)" => "4";
+    }
+}
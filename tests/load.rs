@@ -0,0 +1,62 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::load::{FormErrorKind, LoadError, Loader, Location};
+
+const SOURCE_WITH_ERRORS: &str = "(defvar load-errors-a 1)
+)
+(defvar load-errors-b 2)
+  (load-errors-this-function-does-not-exist)
+(defvar load-errors-c 3)
+";
+
+#[test]
+fn load_evaluates_every_form() {
+    let mut source: &[u8] = b"(defvar load-every-form-a 1)\n(defvar load-every-form-b 2)\n";
+    Loader::new().load("every-form", &mut source).unwrap();
+    test_pairs! {
+        "load-every-form-a" => "1";
+        "load-every-form-b" => "2";
+    }
+}
+
+#[test]
+fn load_recovers_from_errors() {
+    let mut source = SOURCE_WITH_ERRORS.as_bytes();
+    let errors = match Loader::new().load("recover", &mut source) {
+        Err(LoadError::Forms(errors)) => errors,
+        other => panic!("Expected form errors but found {:?}", other),
+    };
+    assert_eq!(errors.file, "recover");
+    assert_eq!(errors.errors.len(), 2);
+    assert_eq!(errors.errors[0].location, Location { line: 2, column: 1 });
+    match errors.errors[0].kind {
+        FormErrorKind::Read(_) => (),
+        ref other => panic!("Expected a reader error but found {:?}", other),
+    }
+    assert_eq!(errors.errors[1].location, Location { line: 4, column: 3 });
+    match errors.errors[1].kind {
+        FormErrorKind::Eval(_) => (),
+        ref other => panic!("Expected an evaluation error but found {:?}", other),
+    }
+    test_pairs! {
+        "load-errors-b" => "2";
+        "load-errors-c" => "3";
+    }
+}
+
+#[test]
+fn load_can_fail_fast() {
+    let mut source: &[u8] = b"(defvar load-fail-fast-a 1)\n)\n(defvar load-fail-fast-b 2)\n";
+    match Loader::new()
+        .with_fail_fast(true)
+        .load("fail-fast", &mut source)
+    {
+        Err(LoadError::Forms(ref errors)) if errors.errors.len() == 1 => (),
+        other => panic!("Expected a single form error but found {:?}", other),
+    }
+    test_pairs! {
+        "load-fail-fast-a" => "1";
+        "(boundp load-fail-fast-b)" => "nil";
+    }
+}
@@ -0,0 +1,48 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::test_input_output_pairs;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn load_a_file() {
+    let path = env::temp_dir().join("phoebe-load-a-file.phoebe");
+    File::create(&path)
+        .unwrap()
+        .write_all(b"(defvar load-a-file-a 1)\n(defun load-a-file-fn (x) x)\n")
+        .unwrap();
+
+    let load = format!("(load (quote {}))", path.display());
+    test_input_output_pairs(&[
+        (&load, "t\n"),
+        ("load-a-file-a", "1\n"),
+        ("(load-a-file-fn 2)", "2\n"),
+    ])
+    .unwrap();
+}
+
+#[test]
+fn load_a_pathname() {
+    let dir = env::temp_dir();
+    File::create(dir.join("phoebe-load-a-pathname.phoebe"))
+        .unwrap()
+        .write_all(b"(defvar load-a-pathname-a 1)\n")
+        .unwrap();
+
+    let load = format!(
+        "(load (path-join \"{}\" \"phoebe-load-a-pathname.phoebe\"))",
+        dir.display()
+    );
+    test_input_output_pairs(&[(&load, "t\n"), ("load-a-pathname-a", "1\n")]).unwrap();
+}
+
+#[test]
+fn load_a_missing_file() {
+    let path = env::temp_dir().join("phoebe-load-a-missing-file.phoebe");
+    let load = format!(
+        "(catch-error (load (quote {})) e (quote caught))",
+        path.display()
+    );
+    test_input_output_pairs(&[(&load, "caught\n")]).unwrap();
+}
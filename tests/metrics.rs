@@ -0,0 +1,66 @@
+#[macro_use]
+extern crate phoebe;
+
+use std::thread;
+
+#[test]
+fn metrics_describe_the_runtime() {
+    test_pairs! {
+        "(type-of (runtime-stats))" => "cons";
+    }
+    let metrics = phoebe::metrics();
+    assert!(phoebe::metrics::healthy());
+    assert!(metrics.symbols_interned > 0);
+    assert!(metrics.threads_with_stacks > 0);
+
+    let exported = format!("{}", metrics);
+    assert_eq!(exported.lines().count(), 8);
+    assert!(exported.contains(&format!(
+        "phoebe_symbols_interned {}",
+        metrics.symbols_interned
+    )));
+}
+
+#[test]
+fn stack_usage_has_a_high_water_mark() {
+    test_pairs! {
+        "(defun count-down (n) (if (= n 0) 'done (count-down (- n 1))))" => "[function count-down]";
+        "(count-down 50)" => "done";
+    }
+    let usage = phoebe::metrics::stack_usage()
+        .into_iter()
+        .find(|u| {
+            u.thread.as_ref().map(String::as_str) == Some("stack_usage_has_a_high_water_mark")
+        })
+        .unwrap();
+    assert!(usage.peak >= 50);
+    assert!(usage.depth < usage.peak);
+    assert!(usage.peak <= usage.capacity);
+    assert!(phoebe::metrics().stack_peak >= usage.peak);
+}
+
+#[test]
+fn exited_threads_release_their_stacks_and_roots() {
+    let short_lived = || {
+        phoebe::metrics::stack_usage()
+            .into_iter()
+            .any(|u| u.thread.as_ref().map(String::as_str) == Some("short-lived"))
+    };
+    let has_roots = |id| phoebe::metrics::threads_with_roots().contains(&id);
+    let id = thread::Builder::new()
+        .name("short-lived".to_owned())
+        .spawn(move || {
+            test_pairs! {
+                "(+ 1 2)" => "3";
+            }
+            assert!(short_lived());
+            let id = thread::current().id();
+            assert!(has_roots(id));
+            id
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(!short_lived());
+    assert!(!has_roots(id));
+}
@@ -27,3 +27,80 @@ fn with_contents() {
         "(nref with-contents two)" => "1";
     }
 }
+
+#[test]
+fn export_and_import() {
+    test_pairs! {
+        "(make-namespace :name import-lib :contents ((shared 1) (private 2)))"
+            => "[namespace import-lib]";
+        "(with-namespace import-lib (export shared later))" => "(shared later)";
+        "(namespace-exports import-lib)" => "(later shared)";
+        "(catch-error (import import-lib :symbols (private)) e (error-name e))"
+            => "not-exported-error";
+        "(import import-lib :symbols (shared))" => "(shared)";
+        "shared" => "1";
+        "(setf (nref import-lib shared) 3)" => "3";
+        "shared" => "1";
+        "(catch-error (import import-lib :symbols (shared)) e e)"
+            => "Importing shared from [namespace import-lib] conflicts with an existing binding";
+    }
+}
+
+#[test]
+fn import_aliases() {
+    test_pairs! {
+        "(make-namespace :name alias-lib :contents ((aliased 1)))"
+            => "[namespace alias-lib]";
+        "(with-namespace alias-lib (export aliased))" => "(aliased)";
+        "(import alias-lib :alias t)" => "(aliased)";
+        "(setf (nref alias-lib aliased) 2)" => "2";
+        "aliased" => "2";
+        "(setf aliased 3)" => "3";
+        "(nref alias-lib aliased)" => "3";
+        "(import alias-lib :alias t)" => "(aliased)";
+    }
+}
+
+#[test]
+fn use_namespace() {
+    test_pairs! {
+        "(make-namespace :name used-lib :contents ((used-fn 1) (unused-fn 2)))"
+            => "[namespace used-lib]";
+        "(with-namespace used-lib (export used-fn))" => "(used-fn)";
+        "(make-namespace :name user-ns :parent t)" => "[namespace user-ns]";
+        "(with-namespace user-ns (use-namespace used-lib))" => "[namespace used-lib]";
+        "(with-namespace user-ns used-fn)" => "1";
+        "(with-namespace user-ns (boundp unused-fn))" => "nil";
+        "(with-namespace user-ns (setf used-fn 3))" => "3";
+        "(nref used-lib used-fn)" => "3";
+        "(with-namespace user-ns (defvar used-fn 4))" => "4";
+        "(with-namespace user-ns used-fn)" => "4";
+        "(nref used-lib used-fn)" => "3";
+    }
+}
+
+#[test]
+fn delete_namespace() {
+    test_pairs! {
+        "(make-namespace :name deleted-ns)" => "[namespace deleted-ns]";
+        "(set-gensym-prefix (quote deleted-prefix) deleted-ns)" => "deleted-prefix";
+        "(defvar deleted-ns-ref (weak-ref deleted-ns))" => "[weak-ref [namespace deleted-ns]]";
+        "(delete-namespace deleted-ns)" => "t";
+        "(boundp deleted-ns)" => "nil";
+    }
+    assert_eq!(
+        phoebe::gensym::prefix_for(phoebe::symbol_lookup::make_symbol(b"deleted-ns")),
+        phoebe::gensym::DEFAULT_PREFIX
+    );
+    // Two complete passes, since one may have been marking when the
+    // namespace was allocated.
+    let start = phoebe::metrics().gc_count;
+    while phoebe::metrics().gc_count < start + 2 {
+        test_pairs! {
+            "(length (make-bytes 64))" => "64";
+        }
+    }
+    test_pairs! {
+        "(weak-ref-value deleted-ns-ref)" => "nil";
+    }
+}
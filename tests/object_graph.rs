@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn object_size() {
+    test_pairs! {
+        "(object-size 1)" => "0";
+        "(object-size (list 1 2 3))" => "3";
+        "(object-size (list (quote a) (quote a)))" => "3";
+        "(defvar shared-tail (list 1 2))" => "(1 2)";
+        "(object-size (cons shared-tail shared-tail))" => "3";
+        "(object-size (make-namespace :contents ((a 1) (b 2))))" => "1";
+        "(< (object-size (list 1 2 3)) (object-size (list 1 2 3) :bytes t))" => "t";
+    }
+}
+
+#[test]
+fn object_depth() {
+    test_pairs! {
+        "(object-depth 1)" => "0";
+        "(object-depth nil)" => "0";
+        "(object-depth (list 1 2 3))" => "1";
+        "(object-depth (list 1 (list 2 (list 3))))" => "3";
+        "(object-depth (cons (list (list 1)) 2))" => "3";
+    }
+}
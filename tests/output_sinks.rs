@@ -0,0 +1,22 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::{run_repl, SharedBuf};
+use phoebe::Interpreter;
+
+#[test]
+fn debug_writes_to_repl_output() {
+    let (output, error) = run_repl(&mut Interpreter::new(), b"(debug 3)");
+    assert!(error.is_empty());
+    assert_eq!(output, "[immediate 3]\n3\n");
+}
+
+#[test]
+fn debug_writes_to_configured_sink() {
+    let sink = SharedBuf::default();
+    let mut interpreter = Interpreter::new().with_output(sink.clone());
+    let (output, error) = run_repl(&mut interpreter, b"(debug 3)");
+
+    assert!(error.is_empty());
+    assert_eq!(output, "3\n");
+    assert_eq!(sink.contents(), "[immediate 3]\n");
+}
@@ -0,0 +1,25 @@
+extern crate phoebe;
+
+#[test]
+fn parse_numbers() {
+    use phoebe::repl::test_utilities::run_repl;
+    use phoebe::Interpreter;
+
+    // Symbols like `-42` would be read as numbers, so the text to
+    // parse comes from `read-line` instead.
+    let lines: &[u8] = b"-42\nff\n  12abc\nabc\n12abc\n12\n1.5e2\n-0.25x\nx\n";
+    let mut interpreter = Interpreter::new().with_input(lines);
+    let input: &[u8] = b"(parse-integer (read-line))
+(parse-integer (read-line) :radix 16)
+(parse-integer (read-line) :junk-allowed t)
+(parse-integer (read-line) :junk-allowed t)
+(catch-error (parse-integer (read-line)) e 'caught)
+(catch-error (parse-integer (read-line) :radix 37) e 'caught)
+(parse-float (read-line))
+(parse-float (read-line) :junk-allowed t)
+(catch-error (parse-float (read-line)) e 'caught)";
+    assert_eq!(
+        run_repl(&mut interpreter, input).0,
+        "-42\n255\n12\nnil\ncaught\ncaught\n150\n-0.25\ncaught\n"
+    );
+}
@@ -0,0 +1,21 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn build_and_take_apart() {
+    test_pairs! {
+        "(defvar pathnames-p (path-join \"src\" \"builtins\" \"mod.rs\"))" => "[pathname \"src/builtins/mod.rs\"]";
+        "(type-of pathnames-p)" => "pathname";
+        "(pathnamep pathnames-p)" => "t";
+        "(pathnamep \"src\")" => "nil";
+        "(path->string pathnames-p)" => "\"src/builtins/mod.rs\"";
+        "(path-filename pathnames-p)" => "\"mod.rs\"";
+        "(path-parent pathnames-p)" => "[pathname \"src/builtins\"]";
+        "(path-parent (path-parent (path-parent pathnames-p)))" => "[pathname \"\"]";
+        "(path-parent \"/\")" => "nil";
+        "(path-filename \"a/..\")" => "nil";
+        "(path-join \"a\" \"/b\")" => "[pathname \"/b\"]";
+        "(pathname \"a/b\")" => "[pathname \"a/b\"]";
+        "(catch-error (path-parent 1) e e)" => "Expected a value of type pathname.";
+    }
+}
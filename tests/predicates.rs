@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn not_and_null() {
+    test_pairs! {
+        "(not nil)" => "t";
+        "(not t)" => "nil";
+        "(not 0)" => "nil";
+        "(null nil)" => "t";
+        "(null (list))" => "t";
+        "(null (list 1))" => "nil";
+    }
+}
@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn print_and_terpri() {
+    test_pairs! {
+        "(print 1)" => "1\n1";
+        "(list (print 1) (terpri) (print 2))" => "1\n2\n(1 nil 2)";
+    }
+}
+
+#[test]
+fn fresh_line() {
+    test_pairs! {
+        "(fresh-line)" => "nil";
+        "(list (print 1) (fresh-line) (fresh-line))" => "1\n(1 t nil)";
+    }
+}
+
+#[test]
+fn results_start_on_a_fresh_line() {
+    test_pairs! {
+        "(terpri)" => "\nnil";
+        "(list (print 1) (terpri))" => "1\n(1 nil)";
+    }
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn delay_and_force() {
+    test_pairs! {
+        "(defvar delay-count 0)" => "0";
+        "(defvar delay-p (delay (incf delay-count)))" => "#S(promise :forced nil :value [function ANONYMOUS])";
+        "delay-count" => "0";
+        "(type-of delay-p)" => "promise";
+        "(promise-p delay-p)" => "t";
+        "(force delay-p)" => "1";
+        "(force delay-p)" => "1";
+        "delay-count" => "1";
+        "delay-p" => "#S(promise :forced t :value 1)";
+        "(force 5)" => "5";
+    }
+}
+
+#[test]
+fn delay_closes_over_its_environment() {
+    test_pairs! {
+        "(defun delay-adder (n) (let ((m n)) (delay (+ m 1))))" => "[function delay-adder]";
+        "(force (delay-adder 2))" => "3";
+        "(defun lazy-sum (n) (let ((m n)) (delay (if (= m 0) 0 (+ m (force (lazy-sum (- m 1))))))))" =>
+            "[function lazy-sum]";
+        "(force (lazy-sum 3))" => "6";
+    }
+}
+
+#[test]
+fn errors_leave_the_promise_unforced() {
+    test_pairs! {
+        "(defvar delay-fails (delay (throw (error (quote delay-failed)))))" =>
+            "#S(promise :forced nil :value [function ANONYMOUS])";
+        "(catch-error (force delay-fails) e (quote caught))" => "caught";
+        "(promise-forced delay-fails)" => "nil";
+    }
+}
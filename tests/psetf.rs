@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::symbol_lookup::make_symbol;
+use phoebe::types::Object;
+use phoebe::Interpreter;
+
+#[test]
+fn psetf_assigns_in_parallel() {
+    test_pairs! {
+        "(defvar psetf-a 1)" => "1";
+        "(defvar psetf-b 2)" => "2";
+        "(psetf psetf-a psetf-b psetf-b psetf-a)" => "nil";
+        "(list psetf-a psetf-b)" => "(2 1)";
+        "(catch-error (psetf psetf-a) e (quote caught))" => "caught";
+    }
+}
+
+#[test]
+fn update_globals_from_rust() {
+    let mut interpreter = Interpreter::new();
+    let old = interpreter.update(|env| {
+        env.set(make_symbol(b"update-x"), Object::from(1i32));
+        env.set(make_symbol(b"update-y"), Object::from(2i32));
+        env.get(make_symbol(b"update-x"))
+    });
+    assert_eq!(old, Some(Object::from(1i32)));
+    test_pairs! {
+        "(list update-x update-y)" => "(1 2)";
+    }
+}
@@ -0,0 +1,19 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn push_and_pop_modify_places() {
+    test_pairs! {
+        "(defvar push-stack nil)" => "nil";
+        "(push 1 push-stack)" => "(1)";
+        "(push (+ 1 1) push-stack)" => "(2 1)";
+        "push-stack" => "(2 1)";
+        "(pop push-stack)" => "2";
+        "push-stack" => "(1)";
+        "(pop push-stack)" => "1";
+        "(pop push-stack)" => "nil";
+        "push-stack" => "nil";
+        "(defvar push-number 1)" => "1";
+        "(catch-error (pop push-number) e 'caught)" => "caught";
+    }
+}
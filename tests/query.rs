@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::load::Loader;
+use phoebe::Interpreter;
+
+#[test]
+fn query_symbols() {
+    test_pairs! {
+        "(defun query-fn (x &optional y) x)" => "[function query-fn]";
+        "(defvar query-var 1)" => "1";
+    }
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        interpreter.query("cons"),
+        r#"{"name":"cons","type":"builtin","arglist":"(first second)","documentation":null,"location":null}"#
+    );
+    assert_eq!(
+        interpreter.query("query-fn"),
+        r#"{"name":"query-fn","type":"function","arglist":"(x &optional y)","documentation":null,"location":null}"#
+    );
+    assert!(interpreter
+        .query("quote")
+        .contains(r#""type":"special-form""#));
+    assert!(interpreter
+        .query("query-var")
+        .contains(r#""type":"variable","arglist":null"#));
+    assert!(interpreter
+        .query("query-nothing")
+        .contains(r#""type":"unbound""#));
+}
+
+#[test]
+fn query_source_locations() {
+    let mut source: &[u8] = b"(defvar located-var 1)
+
+(defun located-fn (x)
+  \"Returns x.\"
+  x)
+";
+    Loader::new().load("located.phoebe", &mut source).unwrap();
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        interpreter.query("located-fn"),
+        r#"{"name":"located-fn","type":"function","arglist":"(x)","documentation":"Returns x.","location":"located.phoebe:3"}"#
+    );
+    assert!(interpreter
+        .query("located-var")
+        .ends_with(r#""location":"located.phoebe:1"}"#));
+}
@@ -0,0 +1,25 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn random_integers() {
+    test_pairs! {
+        "(random 1)" => "0";
+        "(type-of (random 10))" => "integer";
+    }
+}
+
+#[test]
+fn random_floats() {
+    test_pairs! {
+        "(type-of (random 1.0))" => "float";
+    }
+}
+
+#[test]
+fn random_needs_a_positive_limit() {
+    test_pairs! {
+        "(catch-error (random 0) e (quote caught))" => "caught";
+        "(catch-error (random -1.0) e (quote caught))" => "caught";
+    }
+}
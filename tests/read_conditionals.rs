@@ -0,0 +1,29 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn conditionals_in_lists() {
+    test_pairs! {
+        "(list 1 #+phoebe 2 3)" => "(1 2 3)";
+        "(list 1 #-phoebe 2 3)" => "(1 3)";
+        "(list 1 #+read-conditionals-missing-feature 2)" => "(1)";
+        "(list #-read-conditionals-missing-feature 1)" => "(1)";
+    }
+}
+
+#[test]
+fn skipped_forms_are_not_evaluated() {
+    test_pairs! {
+        "#-phoebe (this-function-does-not-exist) 1" => "1";
+        "#+phoebe 2" => "2";
+    }
+}
+
+#[test]
+fn feature_expressions() {
+    test_pairs! {
+        "(list #+(and phoebe (not read-conditionals-missing-feature)) 1)" => "(1)";
+        "(list #+(or read-conditionals-missing-feature phoebe) 2)" => "(2)";
+        "(list #+(and) 3 #+(or) 4)" => "(3)";
+    }
+}
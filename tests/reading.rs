@@ -0,0 +1,69 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::{gc, Interpreter};
+use std::io::{self, Read};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn read_from_repl_input() {
+    test_pairs! {
+        "(read-char)x" => "120";
+        "(list (read-char) (read-char))ab" => "(97 98)";
+        "(read-line)" => "nil";
+    }
+}
+
+#[test]
+fn read_from_configured_input() {
+    let mut interpreter = Interpreter::new().with_input(&b"hello world\nabc"[..]);
+    let (output, error) = run_repl(
+        &mut interpreter,
+        b"(read-line) (read-char) (read-line) (read-line)",
+    );
+
+    assert!(error.is_empty());
+    assert_eq!(output, "hello world\n97\nbc\nnil\n");
+}
+
+/// Input which arrives a chunk at a time from another thread.
+struct ChannelInput {
+    chunks: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Read for ChannelInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            match self.chunks.recv() {
+                Ok(chunk) => self.pending = chunk,
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn collect_while_waiting_for_input() {
+    let (send, receive) = channel();
+    let reader = thread::spawn(move || {
+        let mut interpreter = Interpreter::new().with_input(ChannelInput {
+            chunks: receive,
+            pending: Vec::new(),
+        });
+        run_repl(&mut interpreter, b"(read-line)").0
+    });
+    // Give the reader time to start waiting. A pass has to be able to
+    // finish while it does.
+    thread::sleep(Duration::from_millis(100));
+    gc::collect();
+    send.send(b"typed\n".to_vec()).unwrap();
+    assert_eq!(reader.join().unwrap(), "typed\n");
+}
@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn compile_and_match() {
+    test_pairs! {
+        "(defvar regex-re (regex \"(\\\\w+)@(\\\\w+)\\\\.com\"))" => "[regex \"(\\\\w+)@(\\\\w+)\\\\.com\"]";
+        "(type-of regex-re)" => "regex";
+        "(regex-match regex-re \"mail bob@example.com now\")" => "(\"bob@example.com\" \"bob\" \"example\")";
+        "(regex-match regex-re \"no address\")" => "nil";
+        "(regex-match \"(a)(x)?b\" \"cab\")" => "(\"ab\" \"a\" nil)";
+        "(catch-error (regex \"(\") e (error-name e))" => "regex-error";
+        "(catch-error (regex-match 1 \"a\") e e)" => "Expected a value of type regex.";
+    }
+}
+
+#[test]
+fn replace() {
+    test_pairs! {
+        "(regex-replace \"a+\" \"caaat and aardvark\" \"A\")" => "\"cAt And ArdvArk\"";
+        "(regex-replace (regex \"(\\\\w+) (\\\\w+)\") \"hello world\" \"$2 $1\")" => "\"world hello\"";
+    }
+}
@@ -0,0 +1,32 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::Interpreter;
+
+#[test]
+fn previous_results_in_lisp1() {
+    let mut interpreter = Interpreter::new();
+    let program: &[u8] = b"(+ 1 2)
+(+ 3 4)
+(list ** (* 2 5))
+***
+(* 2 3)";
+    assert_eq!(
+        run_repl(&mut interpreter, program).0,
+        "3\n7\n(3 10)\n3\n6\n"
+    );
+}
+
+#[test]
+fn previous_results_in_lisp2() {
+    let mut interpreter = Interpreter::new().with_lisp2(true);
+    let program: &[u8] = b"(+ 1 2)
+(car 1)
+(+ 3 4)
+(list * ** ***)
+(* 2 3)";
+    assert_eq!(
+        run_repl(&mut interpreter, program).0,
+        "3\n7\n(7 3 nil)\n6\n"
+    );
+}
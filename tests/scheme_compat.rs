@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn scheme_compat() {
+    test_pairs! {
+        "'#t" => "#t";
+        "(catch-error (require 'no-such-module) e 'caught)" => "caught";
+        "(require 'scheme-compat)" => "t";
+        "(require 'scheme-compat)" => "nil";
+        "#t" => "t";
+        "#f" => "nil";
+        "(define scheme-x 1)" => "1";
+        "(define scheme-x 2)" => "2";
+        "(set! scheme-x (+ scheme-x 1))" => "3";
+        "(define (scheme-square n) (begin 1 (* n n)))" => "[function scheme-square]";
+        "(scheme-square scheme-x)" => "9";
+        "(begin (display 'hello) #t)" => "hello\nt";
+    }
+}
+
+#[test]
+fn unknown_module_errors_keep_their_name() {
+    test_pairs! {
+        "(defvar unknown-module (catch-error (require 'a-module-nobody-wrote) e e))" =>
+            "There is no module named a-module-nobody-wrote";
+        "(length (gc))" => "2";
+        "(length (gc))" => "2";
+        "unknown-module" => "There is no module named a-module-nobody-wrote";
+    }
+}
@@ -0,0 +1,65 @@
+extern crate phoebe;
+
+use phoebe::reader::{forms, ReaderError};
+use phoebe::session::SessionError;
+use phoebe::{Interpreter, Session};
+
+#[test]
+fn eval_forms_and_strings() {
+    let mut session = Session::new();
+    let value = session
+        .eval_str(
+            "(defvar session-x 2) (defun session-double (n) (* n 2)) (session-double session-x)",
+        )
+        .unwrap();
+    assert_eq!(format!("{}", *value), "4");
+
+    let form = forms(b"(list session-x 3)".iter().cloned())
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(format!("{}", *session.eval_form(form).unwrap()), "(2 3)");
+    assert_eq!(format!("{}", *session.eval_str("").unwrap()), "nil");
+
+    match session.eval_str("(+ 1") {
+        Err(SessionError::Read(ReaderError::UnclosedList)) => (),
+        other => panic!(
+            "Expected an unclosed list, found {:?}",
+            other.map(|o| format!("{}", *o))
+        ),
+    }
+    match session.eval_str("(session-double (quote foo))") {
+        Err(SessionError::Eval(_)) => (),
+        other => panic!(
+            "Expected an error, found {:?}",
+            other.map(|o| format!("{}", *o))
+        ),
+    }
+}
+
+#[test]
+fn run_repls_against_streams() {
+    let mut session = Session::from(Interpreter::new().with_debug_mode(true));
+    session.eval_str("(defvar session-repl-y 5)").unwrap();
+
+    let mut input: &[u8] = b"(+ session-repl-y 1)";
+    let mut output = Vec::new();
+    let mut error = Vec::new();
+    session
+        .run_repl(&mut input, &mut output, &mut error, false)
+        .unwrap();
+    assert_eq!(output, b"6\n");
+    assert!(error.is_empty());
+}
+
+#[test]
+fn returned_values_stay_rooted() {
+    let mut session = Session::new();
+    // The inner list isn't the last object allocated.
+    let kept = session.eval_str("(car (list (list 1) 2))").unwrap();
+    for _ in 0..3 {
+        phoebe::gc::collect();
+    }
+    session.eval_str("(list 3 4)").unwrap();
+    assert_eq!(format!("{}", *kept), "(1)");
+}
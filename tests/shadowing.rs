@@ -0,0 +1,37 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::Interpreter;
+
+#[test]
+fn warn_about_shadowed_bindings() {
+    let mut interpreter = Interpreter::new().with_shadowing_warnings(true);
+    let program: &[u8] = b"(defvar shadowed-global 1)
+(let ((shadowed-global 2)) shadowed-global)
+(let ((list 3)) list)
+(defun shadows-a-parameter (x &optional shadowed-global) x)
+(let ((not-shadowing 4)) not-shadowing)
+(make-namespace :name shadowing-ns)
+(with-namespace shadowing-ns (defvar shadowed-global 5))";
+    let (output, error) = run_repl(&mut interpreter, program);
+    assert_eq!(
+        output,
+        "1\n2\n3\n[function shadows-a-parameter]\n4\n[namespace shadowing-ns]\n5\n"
+    );
+    assert_eq!(
+        error,
+        "WARNING: let binding of shadowed-global shadows an existing binding of shadowed-global
+WARNING: let binding of list shadows the builtin list
+WARNING: parameter binding of shadowed-global shadows an existing binding of shadowed-global
+WARNING: defvar binding of shadowed-global shadows an existing binding of shadowed-global
+"
+    );
+}
+
+#[test]
+fn no_warnings_by_default() {
+    let mut interpreter = Interpreter::new();
+    let (output, error) = run_repl(&mut interpreter, b"(let ((list 1)) list)");
+    assert_eq!(output, "1\n");
+    assert_eq!(error, "");
+}
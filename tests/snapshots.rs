@@ -0,0 +1,81 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::symbol_lookup::with_fresh_global_env;
+use phoebe::Interpreter;
+
+#[test]
+fn restore_a_snapshot() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        run_repl(&mut interpreter, b"(defvar snapshot-kept 1)").0,
+        "1\n"
+    );
+    let snapshot = interpreter.snapshot();
+    assert_eq!(
+        run_repl(
+            &mut interpreter,
+            b"(defvar snapshot-added 2)
+(setf snapshot-kept 3)
+(defun car (x) x)
+(car (quote (4 5)))"
+        )
+        .0,
+        "2\n3\n[function car]\n(4 5)\n"
+    );
+    snapshot.restore();
+    assert_eq!(
+        run_repl(
+            &mut interpreter,
+            b"snapshot-kept
+(boundp snapshot-added)
+(car (quote (4 5)))"
+        )
+        .0,
+        "1\nnil\n4\n"
+    );
+}
+
+#[test]
+fn fresh_global_env() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        run_repl(&mut interpreter, b"(defvar fresh-kept 1)").0,
+        "1\n"
+    );
+    let sandboxed = with_fresh_global_env(|| {
+        run_repl(
+            &mut interpreter,
+            b"(setf fresh-kept 2)
+(defvar fresh-added 3)
+(list fresh-kept fresh-added)",
+        )
+        .0
+    });
+    assert_eq!(sandboxed, "2\n3\n(2 3)\n");
+    assert_eq!(
+        run_repl(&mut interpreter, b"(list fresh-kept (boundp fresh-added))").0,
+        "(1 nil)\n"
+    );
+}
+
+#[test]
+fn restore_a_setf_binding() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        run_repl(&mut interpreter, b"(defvar snapshot-restored (quote old))").0,
+        "old\n"
+    );
+    let snapshot = interpreter.snapshot();
+    assert_eq!(
+        run_repl(
+            &mut interpreter,
+            b"(setf snapshot-restored (quote new))
+snapshot-restored"
+        )
+        .0,
+        "new\nnew\n"
+    );
+    snapshot.restore();
+    assert_eq!(run_repl(&mut interpreter, b"snapshot-restored").0, "old\n");
+}
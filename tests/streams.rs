@@ -0,0 +1,111 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::test_input_output_pairs;
+
+#[test]
+fn string_streams() {
+    test_pairs! {
+        "(defvar string-in (make-string-input-stream \"(1 2) foo\"))" => "[stream string]";
+        "(type-of string-in)" => "stream";
+        "(streamp string-in)" => "t";
+        "(streamp 1)" => "nil";
+        "(read-from-stream string-in)" => "(1 2)";
+        "(read-from-stream string-in)" => "foo";
+        "(read-from-stream string-in)" => "nil";
+        "(read-from-stream string-in (quote eof))" => "eof";
+        "(defvar string-out (make-string-output-stream))" => "[stream string]";
+        "(write-to-stream string-out \"a string \")" => "\"a string \"";
+        "(write-to-stream string-out (list 1 \"two\"))" => "(1 \"two\")";
+        "(get-output-stream-string string-out)" => "\"a string (1 \\\"two\\\")\"";
+        "(get-output-stream-string string-out)" => "\"\"";
+    }
+}
+
+#[test]
+fn printer_and_reader_accept_streams() {
+    test_pairs! {
+        "(defvar print-out (make-string-output-stream))" => "[stream string]";
+        "(print (quote a) print-out)" => "a";
+        "(terpri print-out)" => "nil";
+        "(fresh-line print-out)" => "nil";
+        "(print 1 print-out)" => "1";
+        "(fresh-line print-out)" => "t";
+        "(get-output-stream-string print-out)" => "\"a\n1\n\"";
+        "(defvar read-in (make-string-input-stream \"first line\nxy\"))" => "[stream string]";
+        "(read-line read-in)" => "first line";
+        "(read-char read-in)" => "120";
+        "(read-line read-in)" => "y";
+        "(read-char read-in)" => "nil";
+    }
+}
+
+#[test]
+fn closed_and_one_way_streams() {
+    test_pairs! {
+        "(defvar closed-out (make-string-output-stream))" => "[stream string]";
+        "(close closed-out)" => "t";
+        "closed-out" => "[stream string closed]";
+        "(catch-error (print 1 closed-out) e e)" => "The stream is closed";
+        "(catch-error (read-char (make-string-output-stream)) e e)" =>
+            "Expected a value of type input-stream.";
+        "(catch-error (print 1 (make-string-input-stream \"\")) e e)" =>
+            "Expected a value of type output-stream.";
+        "(catch-error (print 1 2) e e)" => "Expected a value of type stream.";
+        "(catch-error (read-from-stream (make-string-input-stream \"(1\")) e e)" =>
+            "A list went unclosed";
+    }
+}
+
+#[test]
+fn standard_streams() {
+    test_pairs! {
+        "*standard-output*" => "[stream standard-output]";
+        "(print 1 *standard-output*)" => "1\n1";
+    }
+}
+
+#[test]
+fn files() {
+    let path = std::env::temp_dir().join(format!("phoebe-streams-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    test_input_output_pairs(&[
+        (
+            &format!("(defvar file-out (open \"{}\" :direction :output))", path),
+            &format!("[stream {}]\n", path),
+        ),
+        ("(write-to-stream file-out (quote (1 2)))", "(1 2)\n"),
+        (
+            "(write-to-stream file-out (bytes 32 104 105))",
+            "[bytes 20 68 69]\n",
+        ),
+        ("(close file-out)", "t\n"),
+        (
+            &format!(
+                "(defvar file-append (open \"{}\" :direction :append))",
+                path
+            ),
+            &format!("[stream {}]\n", path),
+        ),
+        ("(write-to-stream file-append \" 3\")", "\" 3\"\n"),
+        ("(close file-append)", "t\n"),
+        (
+            &format!("(defvar file-in (open \"{}\"))", path),
+            &format!("[stream {}]\n", path),
+        ),
+        ("(read-from-stream file-in)", "(1 2)\n"),
+        ("(read-from-stream file-in)", "hi\n"),
+        ("(read-from-stream file-in)", "3\n"),
+        ("(read-from-stream file-in)", "nil\n"),
+        (
+            "(catch-error (open \"file-that-does-not-exist\") e (quote failed))",
+            "failed\n",
+        ),
+        (
+            &format!("(catch-error (open \"{}\" :direction :sideways) e e)", path),
+            "Expected a value of type direction.\n",
+        ),
+    ])
+    .unwrap();
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,33 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn strings_are_self_evaluating() {
+    test_pairs! {
+        "\"hello world\"" => "\"hello world\"";
+        "\"\"" => "\"\"";
+        "(type-of \"hello\")" => "string";
+        "(list \"a\" \"b ; not a comment\")" => "(\"a\" \"b ; not a comment\")";
+        "(quote \"quoted\")" => "\"quoted\"";
+    }
+}
+
+#[test]
+fn escapes() {
+    test_pairs! {
+        "\"say \\\"hi\\\"\"" => "\"say \\\"hi\\\"\"";
+        "\"back\\\\slash\"" => "\"back\\\\slash\"";
+    }
+}
+
+#[test]
+fn string_equality() {
+    test_pairs! {
+        "(defvar string-eq-test \"abc\")" => "\"abc\"";
+        "(eq string-eq-test string-eq-test)" => "t";
+        "(eq \"abc\" \"abc\")" => "nil";
+        "(equal \"abc\" \"abc\")" => "t";
+        "(equal \"abc\" \"abd\")" => "nil";
+        "(equal (list \"a\" 1) (list \"a\" 1))" => "t";
+    }
+}
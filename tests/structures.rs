@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn define_and_construct() {
+    test_pairs! {
+        "(defstruct point x y)" => "point";
+        "(defvar defstruct-p (make-point :x 1 :y 2))" => "#S(point :x 1 :y 2)";
+        "(make-point :y 3)" => "#S(point :x nil :y 3)";
+        "(type-of defstruct-p)" => "point";
+        "(point-p defstruct-p)" => "t";
+        "(point-p 1)" => "nil";
+        "(point-x defstruct-p)" => "1";
+        "(point-y defstruct-p)" => "2";
+        "make-point" => "[function make-point]";
+    }
+}
+
+#[test]
+fn setf_accessors() {
+    test_pairs! {
+        "(defstruct setf-test a b)" => "setf-test";
+        "(defvar setf-test-s (make-setf-test :a 1))" => "#S(setf-test :a 1 :b nil)";
+        "(setf (setf-test-b setf-test-s) 'two)" => "two";
+        "(incf (setf-test-a setf-test-s))" => "2";
+        "setf-test-s" => "#S(setf-test :a 2 :b two)";
+    }
+}
+
+#[test]
+fn accessors_check_types() {
+    test_pairs! {
+        "(defstruct type-check-a x)" => "type-check-a";
+        "(defstruct type-check-b x)" => "type-check-b";
+        "(catch-error (type-check-a-x (make-type-check-b :x 1)) e e)" =>
+            "Expected a value of type type-check-a.";
+        "(type-check-b-p (make-type-check-a))" => "nil";
+    }
+}
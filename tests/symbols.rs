@@ -0,0 +1,62 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn symbol_name() {
+    test_pairs! {
+        "(symbol-name (quote symbol-name-test))" => "symbol-name-test";
+        "(eq (symbol-name (quote symbol-name-test)) (quote symbol-name-test))" => "t";
+    }
+}
+
+#[test]
+fn intern_and_make_symbol() {
+    test_pairs! {
+        "(eq (intern (quote intern-test)) (quote intern-test))" => "t";
+        "(make-symbol (quote make-symbol-test))" => "make-symbol-test";
+        "(eq (make-symbol (quote make-symbol-test)) (quote make-symbol-test))" => "nil";
+        "(type-of (make-symbol (quote make-symbol-test)))" => "symbol";
+        "(eq (intern (symbol-name (make-symbol (quote make-symbol-test)))) (quote make-symbol-test))" => "t";
+    }
+}
+
+#[test]
+fn property_lists() {
+    test_pairs! {
+        "(symbol-plist 'plist-sym)" => "nil";
+        "(get 'plist-sym 'color)" => "nil";
+        "(get 'plist-sym 'color 'none)" => "none";
+        "(put 'plist-sym 'color 'red)" => "red";
+        "(put 'plist-sym 'size 3)" => "3";
+        "(get 'plist-sym 'color)" => "red";
+        "(put 'plist-sym 'color 'blue)" => "blue";
+        "(symbol-plist 'plist-sym)" => "(size 3 color blue)";
+        "(get (make-symbol 'plist-sym) 'color)" => "nil";
+    }
+}
+
+#[test]
+fn conversions() {
+    test_pairs! {
+        "(number-to-string 42)" => "42";
+        "(number-to-string -1.5)" => "-1.5";
+        "(type-of (number-to-string 42))" => "symbol";
+        "(parse-integer (number-to-string 42))" => "42";
+        "(symbol-to-string 'conversion-sym)" => "conversion-sym";
+        "(eq (symbol-to-string (make-symbol 'conversion-sym)) 'conversion-sym)" => "t";
+        "(eq (string-to-symbol (symbol-to-string 'conversion-sym)) 'conversion-sym)" => "t";
+    }
+}
+
+#[test]
+fn keywords() {
+    test_pairs! {
+        ":keyword-test" => ":keyword-test";
+        "(eq :keyword-test (quote :keyword-test))" => "t";
+        "(type-of :keyword-test)" => "keyword";
+        "(keywordp :keyword-test)" => "t";
+        "(keywordp (quote keyword-test))" => "nil";
+        "(keywordp (quote :))" => "nil";
+        "(keywordp (make-symbol :keyword-test))" => "nil";
+    }
+}
@@ -68,3 +68,50 @@ fn many_threads_at_once() {
         handle.join().expect("A thread errored");
     }
 }
+
+#[test]
+fn shared_globals() {
+    use std::thread::spawn;
+    const NUMBER_OF_THREADS: usize = 8;
+    const INCREMENTS: usize = 50;
+
+    test_input_output_pairs(&[
+        (
+            "(make-namespace :name shared-globals-ns)",
+            "[namespace shared-globals-ns]\n",
+        ),
+        (
+            "(with-namespace shared-globals-ns (defglobal shared-counter 0))",
+            "0\n",
+        ),
+        ("(defglobal shared-counter 10)", "0\n"),
+        (
+            "(defun shared-incf () \
+             (let ((old shared-counter)) \
+             (if (compare-and-swap-global (quote shared-counter) old (+ old 1)) \
+             old \
+             (shared-incf))))",
+            "[function shared-incf]\n",
+        ),
+    ])
+    .unwrap();
+
+    let handles: Vec<_> = (0..NUMBER_OF_THREADS)
+        .map(|_| {
+            spawn(|| {
+                for _ in 0..INCREMENTS {
+                    test_input_output_pairs(&[("(type-of (shared-incf))", "integer\n")]).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("A thread errored");
+    }
+
+    test_input_output_pairs(&[(
+        "shared-counter",
+        &format!("{}\n", NUMBER_OF_THREADS * INCREMENTS),
+    )])
+    .unwrap();
+}
@@ -0,0 +1,18 @@
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::{run_repl, SharedBuf};
+use phoebe::Interpreter;
+
+#[test]
+fn time_reports_to_standard_error_and_returns_the_value() {
+    let sink = SharedBuf::default();
+    let mut interpreter = Interpreter::new().with_error_output(sink.clone());
+    let (output, _) = run_repl(&mut interpreter, b"(time (list 1 2 3))");
+
+    assert_eq!(output, "(1 2 3)\n");
+    let report = sink.contents();
+    assert!(report.starts_with("Evaluation took "));
+    assert!(report.contains(" seconds of real time, "));
+    assert!(report.contains(" allocations and "));
+    assert!(report.ends_with(" garbage collections.\n"));
+}
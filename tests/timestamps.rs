@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn make_and_format_timestamps() {
+    test_pairs! {
+        "(defvar timestamp-t (seconds-to-timestamp 1530705600.5))" =>
+            "[timestamp 2018-07-04T12:00:00.500000000Z]";
+        "(type-of timestamp-t)" => "timestamp";
+        "(format-time timestamp-t)" => "\"2018-07-04T12:00:00.500000000Z\"";
+        "(decode-time timestamp-t)" => "(2018 7 4 12 0 0 500000000)";
+        "(timestamp-seconds timestamp-t)" => "1530705600.5";
+        "(decode-time (seconds-to-timestamp -1))" => "(1969 12 31 23 59 59 0)";
+        "(catch-error (format-time 1) e e)" => "Expected a value of type timestamp.";
+    }
+}
+
+#[test]
+fn time_differences() {
+    test_pairs! {
+        "(time-difference (seconds-to-timestamp 90) (seconds-to-timestamp 30))" => "60";
+        "(time-difference (seconds-to-timestamp 30) (seconds-to-timestamp 90))" => "-60";
+        "(type-of (defvar timestamp-start (now)))" => "timestamp";
+        "(>= (time-difference (now) timestamp-start) 0)" => "t";
+    }
+}
@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn type_of() {
+    test_pairs! {
+        "(type-of 1)" => "integer";
+        "(type-of 1.5)" => "float";
+        "(type-of nil)" => "boolean";
+        "(type-of (quote type-of-test))" => "symbol";
+        "(type-of (list 1 2))" => "cons";
+        "(type-of (lambda (x) x))" => "function";
+        "(type-of (error (quote type-of-error)))" => "error";
+        "(type-of (make-namespace))" => "namespace";
+    }
+}
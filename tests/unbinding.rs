@@ -0,0 +1,47 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn fboundp_makunbound_and_fmakunbound() {
+    test_pairs! {
+        "(defvar unbind-var 1)" => "1";
+        "(defun unbind-fn () 2)" => "[function unbind-fn]";
+        "(fboundp unbind-fn)" => "t";
+        "(fboundp unbind-var)" => "nil";
+        "(fboundp unbind-nothing)" => "nil";
+        "(fmakunbound unbind-var)" => "unbind-var";
+        "(boundp unbind-var)" => "t";
+        "(makunbound unbind-var)" => "unbind-var";
+        "(boundp unbind-var)" => "nil";
+        "(fmakunbound unbind-fn)" => "unbind-fn";
+        "(fboundp unbind-fn)" => "nil";
+        "(boundp unbind-fn)" => "nil";
+        "(makunbound unbind-nothing)" => "unbind-nothing";
+    }
+}
+
+#[test]
+fn unbind_reveals_shadowed_bindings() {
+    test_pairs! {
+        "(defvar unbind-shadowed 1)" => "1";
+        "(let ((unbind-shadowed 2)) (unbind unbind-shadowed) unbind-shadowed)" => "1";
+        "(defun unbind-arg (unbind-shadowed) (unbind unbind-shadowed) unbind-shadowed)"
+            => "[function unbind-arg]";
+        "(unbind-arg 3)" => "1";
+        "(defun unbind-only-arg (unbind-lonely) (unbind unbind-lonely) unbind-lonely)"
+            => "[function unbind-only-arg]";
+        "(catch-error (unbind-only-arg 4) e (error-name e))" => "unbound-symbol-error";
+        "unbind-shadowed" => "1";
+        "(unbind unbind-shadowed)" => "unbind-shadowed";
+        "(boundp unbind-shadowed)" => "nil";
+    }
+}
+
+#[test]
+fn makunbound_in_a_namespace() {
+    test_pairs! {
+        "(make-namespace :name unbind-ns :contents ((unbind-ns-var 1)))" => "[namespace unbind-ns]";
+        "(makunbound unbind-ns-var unbind-ns)" => "unbind-ns-var";
+        "(nref unbind-ns unbind-ns-var)" => "UNINITIALIZED";
+    }
+}
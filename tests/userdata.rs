@@ -0,0 +1,47 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::symbol_lookup::{add_to_global, make_symbol};
+use phoebe::types::userdata::{with_userdata, Userdata};
+use phoebe::types::Object;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Handle(u32);
+
+#[test]
+fn pass_userdata_through_phoebe() {
+    let plain = Object::from(Userdata::new("host-handle", Handle(1)));
+    add_to_global(make_symbol(b"userdata-plain"), plain);
+    let printed = Object::from(
+        Userdata::new("host-handle", Handle(2))
+            .with_printer(|h: &Handle, f| write!(f, "[host-handle {}]", h.0)),
+    );
+    add_to_global(make_symbol(b"userdata-printed"), printed);
+
+    test_pairs! {
+        "userdata-plain" => "[host-handle]";
+        "userdata-printed" => "[host-handle 2]";
+        "(type-of userdata-plain)" => "host-handle";
+        "(list userdata-plain userdata-printed)" => "([host-handle] [host-handle 2])";
+    }
+
+    assert_eq!(with_userdata(printed, |h: &mut Handle| h.0), Some(2));
+    assert_eq!(with_userdata(printed, |h: &mut u32| *h), None);
+    assert_eq!(with_userdata(Object::nil(), |h: &mut Handle| h.0), None);
+    with_userdata(plain, |h: &mut Handle| h.0 = 5);
+    assert_eq!(with_userdata(plain, |h: &mut Handle| h.0), Some(5));
+}
+
+#[test]
+fn finalizers_run_when_dropped() {
+    let finalized = Arc::new(AtomicUsize::new(0));
+    let counter = finalized.clone();
+    let userdata =
+        Userdata::new("finalized-handle", Handle(7)).with_finalizer(move |h: &mut Handle| {
+            counter.fetch_add(h.0 as usize, Ordering::SeqCst);
+        });
+    assert_eq!(userdata.downcast_ref::<Handle>().map(|h| h.0), Some(7));
+    drop(userdata);
+    assert_eq!(finalized.load(Ordering::SeqCst), 7);
+}
@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn vector_literals() {
+    test_pairs! {
+        "#(1 2 3)" => "#(1 2 3)";
+        "#()" => "#()";
+        "#(a (b c) #(d))" => "#(a (b c) #(d))";
+        "(type-of #(1))" => "vector";
+    }
+}
+
+#[test]
+fn make_vector_and_vector() {
+    test_pairs! {
+        "(make-vector 3)" => "#(nil nil nil)";
+        "(make-vector 2 (quote x))" => "#(x x)";
+        "(vector 1 (+ 1 1) 3)" => "#(1 2 3)";
+        "(vector)" => "#()";
+    }
+}
+
+#[test]
+fn aref_and_setf() {
+    test_pairs! {
+        "(defvar aref-test (vector 1 2 3))" => "#(1 2 3)";
+        "(aref aref-test 0)" => "1";
+        "(aref aref-test 2)" => "3";
+        "(setf (aref aref-test 1) (quote two))" => "two";
+        "aref-test" => "#(1 two 3)";
+        "(catch-error (aref aref-test 3) e e)" =>
+            "The index 3 is out of bounds for a vector of length 3";
+    }
+}
+
+#[test]
+fn push_pop_and_length() {
+    test_pairs! {
+        "(defvar vector-push-test (vector))" => "#()";
+        "(vector-push (quote a) vector-push-test)" => "0";
+        "(vector-push (quote b) vector-push-test)" => "1";
+        "vector-push-test" => "#(a b)";
+        "(length vector-push-test)" => "2";
+        "(vector-pop vector-push-test)" => "b";
+        "(length vector-push-test)" => "1";
+        "(length (list 1 2 3))" => "3";
+        "(length nil)" => "0";
+        "(length \"hello\")" => "5";
+    }
+}
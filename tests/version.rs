@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::repl::test_utilities::run_repl;
+use phoebe::{version_info, Interpreter};
+
+#[test]
+fn implementation_version() {
+    let info = version_info();
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    phoebe::repl::test_utilities::test_input_output_pairs(&[(
+        "(lisp-implementation-version)",
+        &format!("{}\n", info.version),
+    )])
+    .unwrap();
+}
+
+#[test]
+fn version_alist() {
+    let info = version_info();
+    phoebe::repl::test_utilities::test_input_output_pairs(&[(
+        "(version)",
+        &format!(
+            "((version . {}) (features {}))\n",
+            info.version,
+            info.features.join(" ")
+        ),
+    )])
+    .unwrap();
+}
+
+#[test]
+fn features_include_phoebe() {
+    test_pairs! {
+        "*features*" => "(phoebe threads)";
+    }
+}
+
+#[test]
+fn banner_is_printed_once() {
+    let mut interpreter = Interpreter::new().with_banner(true);
+    let info = version_info();
+    let expected_result = format!("{}\n", info.version);
+    let expected_banner = format!("{}\n", info);
+
+    for expected in &[expected_banner + &expected_result, expected_result] {
+        let (output, error) = run_repl(&mut interpreter, b"(lisp-implementation-version)");
+        assert!(error.is_empty());
+        assert_eq!(output, *expected);
+    }
+}
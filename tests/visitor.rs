@@ -0,0 +1,62 @@
+extern crate phoebe;
+
+use phoebe::reader::read;
+use phoebe::types::cons::Cons;
+use phoebe::types::visit::ObjectVisitor;
+use phoebe::types::Object;
+use std::str;
+
+/// Renders objects as JSON: lists as arrays, symbols as strings, and
+/// anything else as `null`.
+struct Json;
+
+impl ObjectVisitor for Json {
+    type Output = String;
+    fn visit_other(&mut self, _: Object) -> String {
+        "null".to_owned()
+    }
+    fn visit_integer(&mut self, n: i64) -> String {
+        n.to_string()
+    }
+    fn visit_float(&mut self, f: f64) -> String {
+        f.to_string()
+    }
+    fn visit_bool(&mut self, b: bool) -> String {
+        if b { "true" } else { "[]" }.to_owned()
+    }
+    fn visit_symbol(&mut self, sym: &phoebe::types::symbol::Symbol) -> String {
+        format!("\"{}\"", str::from_utf8(sym.as_ref()).unwrap())
+    }
+    fn visit_cons(&mut self, cons: &Cons) -> String {
+        let mut elements = vec![cons.car.visit(self)];
+        let mut rest = cons.cdr;
+        while let Some(next) = rest.visit(&mut NextCons) {
+            elements.push(next.0.visit(self));
+            rest = next.1;
+        }
+        format!("[{}]", elements.join(","))
+    }
+}
+
+/// Splits a cons into its car and cdr, and is `None` for anything
+/// else.
+struct NextCons;
+
+impl ObjectVisitor for NextCons {
+    type Output = Option<(Object, Object)>;
+    fn visit_other(&mut self, _: Object) -> Self::Output {
+        None
+    }
+    fn visit_cons(&mut self, cons: &Cons) -> Self::Output {
+        Some((cons.car, cons.cdr))
+    }
+}
+
+#[test]
+fn visit_objects_from_outside_the_crate() {
+    let input = b"(1 2.5 (foo t) nil)";
+    let obj = read(&mut input.iter().cloned().peekable())
+        .unwrap()
+        .unwrap();
+    assert_eq!(obj.visit(&mut Json), r#"[1,2.5,["foo",true],[]]"#);
+}
@@ -0,0 +1,53 @@
+#[macro_use]
+extern crate phoebe;
+
+use phoebe::symbol_lookup::make_symbol;
+use phoebe::watchpoints::watch_with;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn phoebe_watchers() {
+    test_pairs! {
+        "(defvar watched-var 1)" => "1";
+        "(defvar watched-log nil)" => "nil";
+        "(watch (quote watched-var) \
+           (lambda (sym old new) (setf watched-log (list sym old new))))" => "watched-var";
+        "(setf watched-var 2)" => "2";
+        "watched-log" => "(watched-var 1 2)";
+        "(incf watched-var)" => "3";
+        "watched-log" => "(watched-var 2 3)";
+        "(unwatch (quote watched-var))" => "t";
+        "(setf watched-var 4)" => "4";
+        "watched-log" => "(watched-var 2 3)";
+        "(unwatch (quote watched-var))" => "nil";
+    }
+}
+
+#[test]
+fn watcher_errors_are_signaled() {
+    test_pairs! {
+        "(defvar guarded-var 1)" => "1";
+        "(watch (quote guarded-var) (lambda (sym old new) (throw (error (quote read-only) sym))))"
+            => "guarded-var";
+        "(catch-error (setf guarded-var 2) e (error-name e))" => "read-only";
+        "guarded-var" => "2";
+        "(unwatch (quote guarded-var))" => "t";
+    }
+}
+
+#[test]
+fn rust_watchers() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    test_pairs! {
+        "(defvar rust-watched 1)" => "1";
+    }
+    let log = seen.clone();
+    watch_with(make_symbol(b"rust-watched"), move |sym, old, new| {
+        log.lock().unwrap().push(format!("{} {} {}", sym, old, new));
+    });
+    test_pairs! {
+        "(setf rust-watched 2)" => "2";
+        "(let ((rust-watched 5)) (setf rust-watched 6))" => "6";
+    }
+    assert_eq!(*seen.lock().unwrap(), vec!["rust-watched 1 2".to_string()]);
+}
@@ -0,0 +1,33 @@
+#[macro_use]
+extern crate phoebe;
+
+#[test]
+fn weak_ref_value() {
+    test_pairs! {
+        "(weak-ref-value (weak-ref 5))" => "5";
+        "(type-of (weak-ref 5))" => "weak-ref";
+        "(defvar weak-ref-target (list 1 2))" => "(1 2)";
+        "(weak-ref weak-ref-target)" => "[weak-ref (1 2)]";
+        "(eq (weak-ref-value (weak-ref weak-ref-target)) weak-ref-target)" => "t";
+        "(catch-error (weak-ref-value 5) e e)" => "Expected a value of type weak-ref.";
+    }
+}
+
+#[test]
+fn cleared_when_target_is_collected() {
+    test_pairs! {
+        "(defvar weak-ref-collected (let ((target (list 1 2))) (weak-ref target)))" => "[weak-ref (1 2)]";
+    }
+    // Two complete passes, since one may have been marking when the
+    // target was allocated.
+    let start = phoebe::metrics().gc_count;
+    while phoebe::metrics().gc_count < start + 2 {
+        test_pairs! {
+            "(length (make-bytes 64))" => "64";
+        }
+    }
+    test_pairs! {
+        "(weak-ref-value weak-ref-collected)" => "nil";
+        "weak-ref-collected" => "[weak-ref]";
+    }
+}